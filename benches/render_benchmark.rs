@@ -0,0 +1,150 @@
+//! Baseline timings for rendering and intersecting, run with `cargo bench`.
+//! The rendered canvases are only passed to `black_box`, no image is written.
+use std::f64::consts::PI;
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rustic_ray::{
+    shapes::{Plane, Sphere},
+    Camera, Point, Ray, RayBatch, Transformation, Vector, World,
+};
+
+/// A camera of `size` by `size` pixels looking at the default world.
+fn camera(size: usize) -> Camera {
+    let mut c = Camera::new(size, size, PI / 3.0);
+    c.transform = Transformation::view_transform(
+        Point::new(0.0, 1.5, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    c
+}
+
+fn bench_render_small(c: &mut Criterion) {
+    let world = World::default();
+    let camera = camera(100);
+
+    c.bench_function("render_small", |b| {
+        b.iter(|| black_box(camera.render(&world)))
+    });
+}
+
+fn bench_render_medium(c: &mut Criterion) {
+    let world = World::default();
+    let camera = camera(400);
+
+    c.bench_function("render_medium", |b| {
+        b.iter(|| black_box(camera.render(&world)))
+    });
+}
+
+fn bench_render_parallel_vs_serial(c: &mut Criterion) {
+    let world = World::default();
+    let camera = camera(200);
+    let mut group = c.benchmark_group("render_parallel_vs_serial");
+
+    group.bench_function("serial", |b| b.iter(|| black_box(camera.render(&world))));
+    group.bench_function("parallel", |b| {
+        b.iter(|| black_box(camera.render_parallel_with_progress(&world, 1, |_| ())))
+    });
+    group.finish();
+}
+
+fn bench_intersect_many_objects(c: &mut Criterion) {
+    let mut world = World::new();
+    for i in 0..10 {
+        for j in 0..10 {
+            let mut s = Sphere::new();
+            s.transform = Transformation::new()
+                .scale(0.4, 0.4, 0.4)
+                .translate(i as f64 - 4.5, j as f64 - 4.5, 0.0)
+                .build();
+            world.add_object(Box::new(s));
+        }
+    }
+    let rays: Vec<Ray> = (0..100)
+        .map(|k| {
+            let (x, y) = ((k % 10) as f64 - 4.7, (k / 10) as f64 - 4.3);
+            Ray::new(Point::new(x, y, -5.0), Vector::new(0.0, 0.0, 1.0))
+        })
+        .collect();
+    let mut group = c.benchmark_group("intersect_many_objects");
+
+    for name in ["linear", "kd_tree"] {
+        if name == "kd_tree" {
+            world.build_kd_tree();
+        }
+        group.bench_with_input(BenchmarkId::from_parameter(name), &rays, |b, rays| {
+            b.iter(|| {
+                for r in rays {
+                    black_box(world.intersect_world(*r));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_intersect_batch(c: &mut Criterion) {
+    let mut world = World::default();
+    world.add_object(Box::new(Plane::new()));
+    let camera = camera(64);
+    let rays: Vec<Ray> = (0..64)
+        .flat_map(|y| (0..64).map(move |x| (x, y)))
+        .map(|(x, y)| camera.ray_for_pixel(x, y))
+        .collect();
+    let mut group = c.benchmark_group("intersect_batch");
+
+    for size in [4, 8, 16, 64] {
+        let batches: Vec<RayBatch> = rays
+            .chunks(size)
+            .map(|chunk| chunk.iter().copied().collect())
+            .collect();
+
+        group.bench_with_input(BenchmarkId::new("single", size), &rays, |b, rays| {
+            b.iter(|| {
+                for r in rays {
+                    black_box(world.intersect_world(*r));
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("batch", size), &batches, |b, batches| {
+            b.iter(|| {
+                for batch in batches {
+                    black_box(world.intersect_batch(batch));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_matrix_mul(c: &mut Criterion) {
+    let a = Transformation::new()
+        .rotate_x(PI / 4.0)
+        .scale(2.0, 3.0, 4.0)
+        .translate(1.0, -2.0, 3.0)
+        .build();
+    let b = Transformation::new()
+        .rotate_y(PI / 3.0)
+        .shear(1.0, 0.0, 0.0, 0.0, 0.0, 1.0)
+        .build();
+
+    c.bench_function("matrix_mul", |bench| {
+        bench.iter(|| black_box(black_box(a) * black_box(b)))
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .sample_size(10)
+        .measurement_time(Duration::from_secs(10));
+    targets = bench_render_small,
+        bench_render_medium,
+        bench_render_parallel_vs_serial,
+        bench_intersect_many_objects,
+        bench_intersect_batch,
+        bench_matrix_mul
+}
+criterion_main!(benches);
@@ -17,17 +17,17 @@ pub fn new_world() -> World {
         .build();
 
     let mut floor = Plane::new();
-    floor.material.pattern = Some(Box::new(pattern));
+    floor.material.pattern = Box::new(pattern);
     world.add_object(Box::new(floor));
 
     let mut middle = Sphere::new();
-    middle.material.color = Color::from_u8(255, 242, 0);
+    middle.material.set_color(Color::from_u8(255, 242, 0));
     middle.material.diffuse = 0.7;
     middle.material.specular = 0.3;
     // world.add_object(Box::new(middle));
 
     let mut cube = Cube::new();
-    cube.material.color = Color::from_u8(255, 242, 0);
+    cube.material.set_color(Color::from_u8(255, 242, 0));
     cube.transform = Transformation::new()
         .scale(0.55, 0.55, 1.5)
         .rotate_z(PI / 4.0)
@@ -49,14 +49,14 @@ pub fn new_world() -> World {
             .translate(1.5, 0.5, -1.0)
             .build(),
     );
-    right.material.color = Color::new(0.1, 0.15, 0.1);
+    right.material.set_color(Color::new(0.1, 0.15, 0.1));
     right.material.diffuse = 0.7;
     right.material.specular = 0.3;
     right.material.transparency = 0.8;
 
     let mut left = Sphere::new();
     left.set_transform(Transformation::new().translate(-0.5, 0.5, -1.0).build());
-    left.material.color = Color::new(0.1, 0.1, 0.1);
+    left.material.set_color(Color::new(0.1, 0.1, 0.1));
     left.material.diffuse = 0.7;
     left.material.specular = 0.3;
     left.material.transparency = 0.8;
@@ -72,7 +72,7 @@ pub fn new_world() -> World {
             .translate(-1.75, 0.5, -3.0)
             .build(),
     );
-    right1.material.color = Color::new(0.1, 0.15, 0.1);
+    right1.material.set_color(Color::new(0.1, 0.15, 0.1));
     right1.material.diffuse = 0.7;
     right1.material.specular = 0.3;
     right1.material.transparency = 0.8;
@@ -85,7 +85,7 @@ pub fn new_world() -> World {
             .translate(-2.5, 0.5, -3.0)
             .build(),
     );
-    left1.material.color = Color::new(0.1, 0.1, 0.1);
+    left1.material.set_color(Color::new(0.1, 0.1, 0.1));
     left1.material.diffuse = 0.7;
     left1.material.specular = 0.3;
     left1.material.transparency = 0.8;
@@ -7,7 +7,7 @@ use crate::{elapsed, Opts};
 pub fn render(serialized_world: &str, camera: Camera, opts: &Opts) {
     elapsed!(
         let canvas = if opts.parallel {
-            camera.render_parallel(serialized_world, opts.batch_size)
+            camera.render_parallel(&World::from_str(serialized_world), opts.batch_size)
         } else {
             camera.render(&World::from_str(serialized_world))
         };
@@ -1,11 +1,15 @@
 use std::f64::consts::PI;
+use std::sync::{
+    mpsc::{self, Receiver},
+    Arc,
+};
 
 use clap::Clap;
 use macroquad::prelude::*;
 
-use rustic_ray::{Camera, Point, Transformation, Vector};
+use rustic_ray::{Camera, Canvas, Point, Transformation, Vector, World};
 
-use crate::{elapsed, load_world, Opts};
+use crate::{load_world, Opts};
 
 pub struct View {
     pub texture: Texture2D,
@@ -16,6 +20,15 @@ pub struct View {
     pub opts: Opts,
     pub serialized_world: String,
     pub need_update: bool,
+    /// Canvases of the preview being rendered, refined over time.
+    preview: Receiver<Canvas>,
+}
+
+/// Start rendering a preview of the world seen by `camera`.
+fn start_preview(camera: &Camera, serialized_world: &str) -> Receiver<Canvas> {
+    let (tx, rx) = mpsc::channel();
+    camera.start_preview(Arc::new(World::from_str(serialized_world)), tx);
+    rx
 }
 
 impl View {
@@ -40,7 +53,8 @@ impl View {
 
         let serialized_world = load_world(opts.input.as_deref());
 
-        let canvas = camera.render_parallel(&serialized_world, opts.batch_size);
+        let preview = start_preview(&camera, &serialized_world);
+        let canvas = preview.recv().unwrap();
         let bytes = canvas.canvas_to_rgba_buffer();
 
         let InternalGlContext {
@@ -58,20 +72,24 @@ impl View {
             opts,
             serialized_world,
             need_update: false,
+            preview,
         }
     }
 
     pub fn update(&mut self) {
-        if !self.need_update {
-            return;
-        }
+        if self.need_update {
+            let mut camera = Camera::new(self.opts.hsize, self.opts.vsize, self.fov);
+            camera.transform = Transformation::view_transform(self.from, self.to, self.up);
 
-        let mut camera = Camera::new(self.opts.hsize, self.opts.vsize, self.fov);
-        camera.transform = Transformation::view_transform(self.from, self.to, self.up);
+            // dropping the receiver of the old preview stops it
+            self.preview = start_preview(&camera, &self.serialized_world);
+            self.need_update = false;
+        }
 
-        elapsed!(
-            let canvas = camera.render_parallel(&self.serialized_world, self.opts.batch_size);
-        );
+        let canvas = match self.preview.try_iter().last() {
+            Some(canvas) => canvas,
+            None => return,
+        };
         let bytes = canvas.canvas_to_rgba_buffer();
 
         let InternalGlContext {
@@ -86,7 +104,6 @@ impl View {
                 height: canvas.height as u16,
             },
         );
-        self.need_update = false;
     }
 
     pub fn width(&self) -> f32 {
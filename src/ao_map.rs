@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, SeedableRng};
+use uuid::Uuid;
+
+use crate::{ambient_occlusion, shapes::Shape, Point, World, EPSILON};
+
+/// Number of cells along each of `u` and `v` of a baked map.
+pub const DEFAULT_AO_MAP_RESOLUTION: usize = 16;
+/// Average number of surface points sampled for every cell of an object.
+const POINTS_PER_CELL: usize = 4;
+
+/// Ambient occlusion of the objects of a static scene computed once ahead of
+/// rendering, stored by the texture coordinates of the surface.
+///
+/// The `(u, v)` coordinates of every object with a texture mapping, such as a
+/// [`crate::shapes::Sphere`], are split into a grid of cells, each holding
+/// the average [`ambient_occlusion`] of the points sampled on the surface in
+/// it. Objects without texture coordinates, and objects inside groups, are
+/// not baked. Parts of a surface sharing texture coordinates, like the faces
+/// of a [`crate::shapes::Cube`], share their cells. The occlusion is baked
+/// on the outside of the surfaces, so it is not right for the inside of a
+/// closed object such as a room.
+///
+/// Set [`World::ao_map`] to the map to use it in place of firing rays at
+/// every hit when [`World::ambient_occlusion`] is set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AoMap {
+    /// Number of cells along each of `u` and `v`.
+    pub resolution: usize,
+    values: HashMap<(Uuid, usize, usize), f64>,
+}
+
+impl AoMap {
+    /// Create an empty map with `resolution` cells along each of `u` and `v`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `resolution` is 0.
+    pub fn new(resolution: usize) -> Self {
+        assert!(resolution > 0, "an AO map needs at least one cell");
+        AoMap {
+            resolution,
+            values: HashMap::new(),
+        }
+    }
+
+    /// Bake the occlusion of the objects of `world`, firing `samples` rays
+    /// from every point sampled on their surfaces. The points are picked
+    /// with the seed of [`World::ambient_occlusion`], so the same world gives
+    /// the same map.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `resolution` is 0.
+    pub fn bake(world: &World, samples: usize, resolution: usize) -> Self {
+        let mut map = AoMap::new(resolution);
+        let seed = world.ambient_occlusion.map_or(0, |settings| settings.seed);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let points = resolution * resolution * POINTS_PER_CELL;
+
+        for object in world.objects() {
+            if object.local_uv_at(Point::new(0.0, 0.0, 0.0)).is_none() {
+                continue;
+            }
+
+            let mut sums: HashMap<(usize, usize), (f64, usize)> = HashMap::new();
+            for _ in 0..points {
+                let (point, normal) = object.sample_surface(&mut rng);
+                let cell = map.cell(object, point);
+                let over_point = point + normal * EPSILON;
+                let factor = ambient_occlusion(world, over_point, normal, samples);
+                let sum = sums.entry(cell).or_insert((0.0, 0));
+                sum.0 += factor;
+                sum.1 += 1;
+            }
+
+            for ((x, y), (total, count)) in sums {
+                map.values.insert((object.id(), x, y), total / count as f64);
+            }
+        }
+
+        map
+    }
+
+    /// The baked occlusion of the shape with `shape_id` at the texture
+    /// coordinates `u` and `v`, or `None` when it was not baked there.
+    pub fn get(&self, shape_id: Uuid, u: f64, v: f64) -> Option<f64> {
+        if self.resolution == 0 {
+            return None;
+        }
+        let (x, y) = (self.index(u), self.index(v));
+        self.values.get(&(shape_id, x, y)).copied()
+    }
+
+    /// Number of cells holding a baked value.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether no value was baked.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    fn index(&self, t: f64) -> usize {
+        ((t.clamp(0.0, 1.0) * self.resolution as f64) as usize).min(self.resolution - 1)
+    }
+
+    /// The cell of the world space `point` on the top level `object`.
+    fn cell(&self, object: &dyn Shape, point: Point) -> (usize, usize) {
+        let object_point = object.transform().inverse() * point;
+        let (u, v) = object
+            .local_uv_at(object_point)
+            .expect("only objects with texture coordinates are baked");
+        (self.index(u), self.index(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shapes::{Plane, Sphere},
+        AmbientOcclusion, Color, PointLight, Ray, Transformation, Vector,
+    };
+
+    /// A ball resting on a floor.
+    fn ball_on_floor() -> (World, Uuid) {
+        let mut w = World::new();
+        w.add_object(Box::new(Plane::new()));
+        let mut ball = Sphere::new();
+        ball.transform = Transformation::new().translate(0.0, 1.0, 0.0).build();
+        let id = ball.id();
+        w.add_object(Box::new(ball));
+        w.ambient_occlusion = Some(AmbientOcclusion::new(64, 10.0));
+        (w, id)
+    }
+
+    #[test]
+    fn baked_occlusion_matches_firing_rays() {
+        let (mut w, id) = ball_on_floor();
+        w.ao_map = Some(w.ambient_occlusion_precompute(64));
+
+        // from the top of the ball, which is open, to its side and bottom,
+        // which the floor blocks more and more of
+        for angle in [0.3, 1.2, 1.8, 2.5] {
+            let normal = Vector::new(f64::sin(angle), f64::cos(angle), 0.0);
+            let point = Point::new(normal.x, 1.0 + normal.y, normal.z) + normal * EPSILON;
+            let (u, v) = Sphere::new().uv_at(Point::new(normal.x, normal.y, normal.z));
+
+            let exact = ambient_occlusion(&w, point, normal, 1024);
+            let baked = w.sample_ao(id, u, v);
+
+            assert!((baked - exact).abs() < 0.1, "{} {} {}", angle, baked, exact);
+        }
+    }
+
+    #[test]
+    fn shapes_without_texture_coordinates_are_not_baked() {
+        let (w, id) = ball_on_floor();
+        let map = AoMap::bake(&w, 4, 4);
+        let floor = w.get_object(0).unwrap().id();
+
+        assert!(!map.is_empty());
+        assert!(map.len() <= 16);
+        assert_eq!(map.get(floor, 0.5, 0.5), None);
+        assert!(map.get(id, 0.5, 0.5).is_some());
+    }
+
+    #[test]
+    fn shading_uses_the_baked_occlusion() {
+        let (mut w, _) = ball_on_floor();
+        w.light = Some(PointLight::new(
+            Point::new(0.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        // the side of the ball close to the floor
+        let r = Ray::new(Point::new(0.0, 0.3, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        // no rays are fired at the hit, so only the baked map darkens it
+        w.ambient_occlusion = Some(AmbientOcclusion::new(0, 10.0));
+        let open = w.color_at(r, 1);
+        w.ao_map = Some(w.ambient_occlusion_precompute(32));
+        let occluded = w.color_at(r, 1);
+
+        assert!(occluded.red < open.red - 0.01);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one cell")]
+    fn a_map_without_cells() {
+        AoMap::new(0);
+    }
+
+    #[test]
+    fn a_map_changed_to_no_cells_has_no_values() {
+        let (w, ball) = ball_on_floor();
+        let mut map = AoMap::bake(&w, 4, 2);
+        map.resolution = 0;
+
+        assert_eq!(map.get(ball, 0.5, 0.5), None);
+    }
+}
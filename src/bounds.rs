@@ -0,0 +1,219 @@
+use crate::{Matrix, Point, Ray, EPSILON};
+
+use serde::{Deserialize, Serialize};
+
+/// An axis aligned box given by its `min` and `max` corners. Bounding the
+/// parts of a shape lets a ray skip every part inside a box it misses.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct Bounds {
+    /// The corner with the smallest coordinates.
+    pub min: Point,
+    /// The corner with the largest coordinates.
+    pub max: Point,
+}
+
+impl Bounds {
+    /// Create a new box between the corners `min` and `max`.
+    pub fn new(min: Point, max: Point) -> Self {
+        Bounds { min, max }
+    }
+
+    /// Create a box containing nothing, which grows to fit the first point
+    /// added to it.
+    pub fn empty() -> Self {
+        Bounds {
+            min: Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    /// Create a box containing all of space, used for shapes like planes that
+    /// have no bounds.
+    pub fn infinite() -> Self {
+        Bounds {
+            min: Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            max: Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        }
+    }
+
+    /// Whether the box contains nothing, like a box made by [`Bounds::empty`].
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    /// Whether every side of the box is at a finite position.
+    pub fn is_finite(&self) -> bool {
+        [self.min, self.max]
+            .iter()
+            .all(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite())
+    }
+
+    /// The smallest box containing all of the `points`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Bounds, Point};
+    ///
+    /// let b = Bounds::from_points(&[Point::new(1.0, -2.0, 0.0), Point::new(-1.0, 3.0, 0.5)]);
+    ///
+    /// assert_eq!(b.min, Point::new(-1.0, -2.0, 0.0));
+    /// assert_eq!(b.max, Point::new(1.0, 3.0, 0.5));
+    /// ```
+    pub fn from_points(points: &[Point]) -> Self {
+        let mut bounds = Bounds::empty();
+        for point in points {
+            bounds.add_point(*point);
+        }
+        bounds
+    }
+
+    /// Grow the box to contain `point`.
+    pub fn add_point(&mut self, point: Point) {
+        self.min = Point::new(
+            self.min.x.min(point.x),
+            self.min.y.min(point.y),
+            self.min.z.min(point.z),
+        );
+        self.max = Point::new(
+            self.max.x.max(point.x),
+            self.max.y.max(point.y),
+            self.max.z.max(point.z),
+        );
+    }
+
+    /// The smallest box containing both this box and `other`.
+    pub fn merge(&self, other: &Bounds) -> Bounds {
+        let mut bounds = *self;
+        bounds.add_point(other.min);
+        bounds.add_point(other.max);
+        bounds
+    }
+
+    /// The axis aligned box containing this box after it is moved by
+    /// `transform`. A box with an infinite side stays infinite, as a rotation
+    /// can swing the unbounded side in any direction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Bounds, Point, Transformation};
+    ///
+    /// let b = Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+    /// let moved = b.transform(Transformation::new().translate(2.0, 0.0, 0.0).build());
+    ///
+    /// assert_eq!(moved.min, Point::new(1.0, -1.0, -1.0));
+    /// assert_eq!(moved.max, Point::new(3.0, 1.0, 1.0));
+    /// ```
+    pub fn transform(&self, transform: Matrix) -> Bounds {
+        if self.is_empty() {
+            return *self;
+        }
+        if !self.is_finite() {
+            return Bounds::infinite();
+        }
+
+        let mut bounds = Bounds::empty();
+        for &x in &[self.min.x, self.max.x] {
+            for &y in &[self.min.y, self.max.y] {
+                for &z in &[self.min.z, self.max.z] {
+                    bounds.add_point(transform * Point::new(x, y, z));
+                }
+            }
+        }
+        bounds
+    }
+
+    /// The point in the middle of the box.
+    pub fn centroid(&self) -> Point {
+        Point::new(
+            (self.min.x + self.max.x) / 2.0,
+            (self.min.y + self.max.y) / 2.0,
+            (self.min.z + self.max.z) / 2.0,
+        )
+    }
+
+    /// Find where `ray` enters and leaves the box, if it does. The box is
+    /// padded by [`EPSILON`] so rays grazing a flat box still hit it.
+    pub fn intersect(&self, ray: Ray) -> Option<(f64, f64)> {
+        let mut t_min = f64::NEG_INFINITY;
+        let mut t_max = f64::INFINITY;
+
+        for (origin, direction, lo, hi) in [
+            (ray.origin.x, ray.direction.x, self.min.x, self.max.x),
+            (ray.origin.y, ray.direction.y, self.min.y, self.max.y),
+            (ray.origin.z, ray.direction.z, self.min.z, self.max.z),
+        ] {
+            if direction.abs() < f64::EPSILON {
+                if origin < lo - EPSILON || origin > hi + EPSILON {
+                    return None;
+                }
+                continue;
+            }
+
+            let t0 = (lo - EPSILON - origin) / direction;
+            let t1 = (hi + EPSILON - origin) / direction;
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+        }
+
+        if t_min <= t_max {
+            Some((t_min, t_max))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vector;
+
+    #[test]
+    fn merging_two_boxes() {
+        let a = Bounds::new(Point::new(-1.0, 0.0, 0.0), Point::new(1.0, 1.0, 1.0));
+        let b = Bounds::new(Point::new(0.0, -2.0, 0.5), Point::new(3.0, 0.5, 0.5));
+        let m = a.merge(&b);
+
+        assert_eq!(m.min, Point::new(-1.0, -2.0, 0.0));
+        assert_eq!(m.max, Point::new(3.0, 1.0, 1.0));
+        assert_eq!(m.centroid(), Point::new(1.0, -0.5, 0.5));
+    }
+
+    #[test]
+    fn transforming_a_box() {
+        use crate::Transformation;
+        use std::f64::consts::PI;
+
+        let b = Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let rotated = b.transform(Transformation::new().rotate_y(PI / 4.0).build());
+
+        assert!((rotated.max.x - 2.0_f64.sqrt()).abs() < EPSILON);
+        assert!((rotated.min.z + 2.0_f64.sqrt()).abs() < EPSILON);
+        assert_eq!(rotated.max.y, 1.0);
+
+        let moved = Transformation::new().translate(1.0, 0.0, 0.0).build();
+        assert!(!Bounds::infinite().transform(moved).is_finite());
+        assert!(Bounds::empty().transform(moved).is_empty());
+    }
+
+    #[test]
+    fn a_ray_intersects_a_box() {
+        let b = Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let r = Ray::new(Point::new(0.5, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let (t0, t1) = b.intersect(r).expect("No intersection");
+
+        assert!((t0 - 4.0).abs() < 2.0 * EPSILON);
+        assert!((t1 - 6.0).abs() < 2.0 * EPSILON);
+    }
+
+    #[test]
+    fn a_ray_misses_a_box() {
+        let b = Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let r = Ray::new(Point::new(2.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert!(b.intersect(r).is_none());
+        assert!(Bounds::empty().intersect(r).is_none());
+    }
+}
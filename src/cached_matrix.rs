@@ -0,0 +1,120 @@
+use std::cell::OnceCell;
+
+use crate::Matrix;
+
+#[cfg(test)]
+thread_local! {
+    /// Number of inverses found by [`CachedMatrix::inverse`] on this thread.
+    static INVERSIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// A [`Matrix`] that finds its inverse the first time it is asked for and
+/// keeps it until the matrix is replaced with [`CachedMatrix::set`].
+#[derive(Debug, Clone)]
+pub struct CachedMatrix {
+    forward: Matrix,
+    inverse: OnceCell<Matrix>,
+}
+
+impl CachedMatrix {
+    /// Creates a `CachedMatrix` for `m`, without finding its inverse yet.
+    pub fn new(m: Matrix) -> Self {
+        CachedMatrix {
+            forward: m,
+            inverse: OnceCell::new(),
+        }
+    }
+
+    /// The matrix itself.
+    pub fn matrix(&self) -> &Matrix {
+        &self.forward
+    }
+
+    /// The inverse of the matrix, found on the first call and reused after.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{CachedMatrix, Transformation};
+    ///
+    /// let m = Transformation::new().translate(1.0, 2.0, 3.0).build();
+    /// let cached = CachedMatrix::new(m);
+    ///
+    /// assert_eq!(*cached.inverse(), m.inverse());
+    /// ```
+    pub fn inverse(&self) -> &Matrix {
+        self.inverse.get_or_init(|| {
+            #[cfg(test)]
+            INVERSIONS.with(|n| n.set(n.get() + 1));
+            self.forward.inverse()
+        })
+    }
+
+    /// Replace the matrix with `m`, dropping the inverse of the old one.
+    pub fn set(&mut self, m: Matrix) {
+        self.forward = m;
+        self.inverse = OnceCell::new();
+    }
+}
+
+impl From<Matrix> for CachedMatrix {
+    fn from(m: Matrix) -> Self {
+        CachedMatrix::new(m)
+    }
+}
+
+impl PartialEq for CachedMatrix {
+    fn eq(&self, other: &CachedMatrix) -> bool {
+        self.forward == other.forward
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Transformation;
+
+    fn inversions() -> usize {
+        INVERSIONS.with(|n| n.get())
+    }
+
+    #[test]
+    fn the_inverse_is_found_at_most_once() {
+        let m = Transformation::new().scale(2.0, 2.0, 2.0).build();
+        let cached = CachedMatrix::new(m);
+        let before = inversions();
+
+        let first = *cached.inverse();
+        let second = *cached.inverse();
+
+        assert_eq!(inversions() - before, 1);
+        assert_eq!(first, second);
+        assert_eq!(first, m.inverse());
+    }
+
+    #[test]
+    fn the_inverse_is_not_found_until_asked_for() {
+        let before = inversions();
+        let cached = CachedMatrix::new(Transformation::new().rotate_x(1.0).build());
+
+        assert_eq!(inversions(), before);
+        assert_eq!(
+            *cached.matrix(),
+            Transformation::new().rotate_x(1.0).build()
+        );
+    }
+
+    #[test]
+    fn setting_a_new_matrix_drops_the_old_inverse() {
+        let mut cached = CachedMatrix::new(Transformation::new().translate(1.0, 0.0, 0.0).build());
+        let old = *cached.inverse();
+        let m = Transformation::new().translate(0.0, 5.0, 0.0).build();
+        let before = inversions();
+
+        cached.set(m);
+
+        assert_eq!(*cached.inverse(), m.inverse());
+        assert_ne!(*cached.inverse(), old);
+        assert_eq!(inversions() - before, 1);
+    }
+}
@@ -1,15 +1,92 @@
+use std::f64::consts::PI;
+use std::fs;
 use std::io::Write;
+use std::path::Path;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc::Sender, Arc};
+use std::thread::{self, JoinHandle};
 
-#[allow(unused_imports)]
-use crate::Color;
-use crate::{Canvas, Matrix, Point, Ray, World, IDENTITY};
+use crate::{
+    canvas::fill_parallel, Bounds, Canvas, Color, Matrix, Point, Ray, Transformation, Vector,
+    World, EPSILON, IDENTITY,
+};
 
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Rays per pixel of the passes of [`Camera::start_preview`].
+const PREVIEW_SAMPLES: [usize; 4] = [1, 4, 16, 64];
+
+/// Rows rendered so far by [`Camera::render_resumable`], saved to the
+/// checkpoint file.
+#[derive(Serialize, Deserialize)]
+struct Checkpoint {
+    hsize: usize,
+    vsize: usize,
+    rows: Vec<Vec<Color>>,
+}
+
+/// How a [`Camera`] projects the world onto its canvas.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum Projection {
+    /// Rays spread out from the camera through the canvas, so far objects
+    /// look smaller. `fov` is the field of view, the angle the camera sees
+    /// across the longer side of the canvas.
+    Perspective { fov: f64 },
+    /// Rays start on the canvas and all go in the direction the camera looks
+    /// at, so objects keep their size at any distance and parallel lines stay
+    /// parallel. The canvas spans `half_width` and `half_height` world units
+    /// on each side of the center; the pixels are sized to fit the width.
+    Orthographic { half_width: f64, half_height: f64 },
+    /// Rays go out from the camera in every direction, spread evenly in
+    /// angle over the canvas whatever its size. The columns go once around
+    /// the camera from behind on the left to behind on the right, and the
+    /// rows from straight up at the top to straight down at the bottom, the
+    /// equirectangular image shown by panorama and VR viewers.
+    Panoramic,
+    /// Rays spread out from the camera over a circle in the middle of the
+    /// canvas, as through a fisheye lens. The circle fills the shorter side
+    /// of the canvas and its edge sees `max_angle` away from the direction
+    /// the camera looks at, with `mapping` spacing the angles in between.
+    /// Pixels outside of the circle are rendered black.
+    Fisheye {
+        max_angle: f64,
+        mapping: FisheyeProjection,
+    },
+}
+
+/// How a [`Projection::Fisheye`] lens spaces the angles of the rays from the
+/// center of its circle, with `r` the distance from the center of the
+/// circle, `f` the focal length and `θ` the angle of the ray from the
+/// direction the camera looks at.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum FisheyeProjection {
+    /// `r = f·θ`, the angle grows evenly with the distance from the center.
+    Equidistant,
+    /// `r = 2f·tan(θ/2)`, keeping the shapes of small objects.
+    Stereographic,
+    /// `r = 2f·sin(θ/2)`, keeping the areas of objects.
+    Equisolid,
+}
+
+impl FisheyeProjection {
+    /// The angle from the center of a ray at `radius` from the center of the
+    /// circle, where the edge of the circle is at 1.0 and sees `max_angle`.
+    pub fn angle(&self, radius: f64, max_angle: f64) -> f64 {
+        match self {
+            FisheyeProjection::Equidistant => radius * max_angle,
+            FisheyeProjection::Stereographic => 2.0 * (radius * (max_angle / 2.0).tan()).atan(),
+            FisheyeProjection::Equisolid => {
+                2.0 * (radius * (max_angle / 2.0).sin()).min(1.0).asin()
+            }
+        }
+    }
+}
 
 /// Encapsulates the view and provides an interface for rendering the world
 /// onto a [`Canvas`]. The [`Canvas`] is exactly one unit in front of the
 /// `Camera`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Camera {
     /// Horizontal size of the canvas.
     pub hsize: usize,
@@ -17,6 +94,7 @@ pub struct Camera {
     pub vsize: usize,
     /// Camera transformation matrix.
     pub transform: Matrix,
+    projection: Projection,
     half_width: f64,
     half_height: f64,
     pixel_size: f64,
@@ -29,16 +107,44 @@ impl Camera {
     /// When the field of view is small, the view will be "zoomed in". Magnifying
     /// a smaller area of the scene.
     pub fn new(hsize: usize, vsize: usize, field_of_view: f64) -> Camera {
-        let half_view = (field_of_view / 2.0).tan();
-        let aspect = hsize as f64 / vsize as f64;
+        Camera::with_projection(hsize, vsize, Projection::Perspective { fov: field_of_view })
+    }
 
-        let mut half_width = half_view * aspect;
-        let mut half_height = half_view;
+    /// Construct a `Camera` with a canvas of `hsize` by `vsize` pixels using
+    /// the given `projection`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Camera, Projection};
+    ///
+    /// let c = Camera::with_projection(
+    ///     200,
+    ///     100,
+    ///     Projection::Orthographic { half_width: 4.0, half_height: 2.0 },
+    /// );
+    ///
+    /// assert_eq!(c.ray_for_pixel(0, 0).direction, c.ray_for_pixel(199, 99).direction);
+    /// ```
+    pub fn with_projection(hsize: usize, vsize: usize, projection: Projection) -> Camera {
+        let (half_width, half_height) = match projection {
+            Projection::Perspective { fov } => {
+                let half_view = (fov / 2.0).tan();
+                let aspect = hsize as f64 / vsize as f64;
 
-        if aspect >= 1.0 {
-            half_width = half_view;
-            half_height = half_view / aspect;
-        }
+                if aspect >= 1.0 {
+                    (half_view, half_view / aspect)
+                } else {
+                    (half_view * aspect, half_view)
+                }
+            }
+            Projection::Orthographic {
+                half_width,
+                half_height,
+            } => (half_width, half_height),
+            Projection::Panoramic => (PI, PI / 2.0),
+            Projection::Fisheye { max_angle, .. } => (max_angle, max_angle),
+        };
 
         let pixel_size = (half_width * 2.0) / hsize as f64;
 
@@ -46,18 +152,105 @@ impl Camera {
             hsize,
             vsize,
             transform: IDENTITY,
+            projection,
             half_width,
             half_height,
             pixel_size,
         }
     }
 
+    /// How the camera projects the world onto its canvas.
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    /// The angle in radians seen across a pixel, horizontally and vertically,
+    /// averaged over the canvas.
+    ///
+    /// Pixels of an orthographic camera see no angle, their rays being
+    /// parallel, and pixels of a fisheye camera are measured across the
+    /// circle of the lens.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::Camera;
+    /// use std::f64::consts::PI;
+    ///
+    /// let (width, height) = Camera::new(100, 100, PI / 2.0).pixel_size();
+    ///
+    /// assert!((width - PI / 200.0).abs() < 1e-9);
+    /// assert!((height - PI / 200.0).abs() < 1e-9);
+    /// ```
+    pub fn pixel_size(&self) -> (f64, f64) {
+        let (hsize, vsize) = (self.hsize as f64, self.vsize as f64);
+        match self.projection {
+            Projection::Perspective { .. } => (
+                2.0 * self.half_width.atan() / hsize,
+                2.0 * self.half_height.atan() / vsize,
+            ),
+            Projection::Orthographic { .. } => (0.0, 0.0),
+            Projection::Panoramic => (2.0 * PI / hsize, PI / vsize),
+            Projection::Fisheye { max_angle, .. } => {
+                let size = 2.0 * max_angle / hsize.min(vsize);
+                (size, size)
+            }
+        }
+    }
+
+    /// The solid angle in steradians seen by a pixel, averaged over the
+    /// canvas, so that it adds up to the solid angle seen by the whole
+    /// camera. For a perspective camera that is the pyramid through the
+    /// corners of the canvas, `4·asin(sin(a)·sin(b))` for the half angles
+    /// `a` and `b` across the width and the height.
+    ///
+    /// Pixels of an orthographic camera see no solid angle, and pixels of a
+    /// fisheye camera outside of the circle of the lens are not counted.
+    pub fn solid_angle_per_pixel(&self) -> f64 {
+        let pixels = (self.hsize * self.vsize) as f64;
+        match self.projection {
+            Projection::Perspective { .. } => {
+                let a = self.half_width.atan();
+                let b = self.half_height.atan();
+                4.0 * (a.sin() * b.sin()).asin() / pixels
+            }
+            Projection::Orthographic { .. } => 0.0,
+            Projection::Panoramic => 4.0 * PI / pixels,
+            Projection::Fisheye { max_angle, .. } => {
+                let radius = self.hsize.min(self.vsize) as f64 / 2.0;
+                2.0 * PI * (1.0 - max_angle.cos()) / (PI * radius * radius)
+            }
+        }
+    }
+
+    /// Returns a ray that starts at the camera and passes through the center
+    /// of the given `x` and `y` pixel on the canvas. The ray respects the
+    /// camera's `transform`, making it usable outside of `render` for custom
+    /// tracing loops such as picking an object under the cursor.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Camera, Point, Vector};
+    /// use std::f64::consts::PI;
+    ///
+    /// let c = Camera::new(201, 101, PI / 2.0);
+    /// let r = c.ray_for_pixel(100, 50);
+    ///
+    /// assert_eq!(r.origin, Point::new(0.0, 0.0, 0.0));
+    /// assert_eq!(r.direction, Vector::new(0.0, 0.0, -1.0));
+    /// ```
+    pub fn ray_for_pixel(&self, px: usize, py: usize) -> Ray {
+        self.ray_for_subpixel(px as f64 + 0.5, py as f64 + 0.5)
+    }
+
     /// Returns a ray that starts at the camera and passes through the given
-    /// `x` and `y` pixel on the canvas.
-    pub fn ray_for_pixel(&self, px: f64, py: f64) -> Ray {
-        // the offset from the edge of the canvas to the pixel's center
-        let x_offset = (px + 0.5) * self.pixel_size;
-        let y_offset = (py + 0.5) * self.pixel_size;
+    /// position on the canvas measured in pixels, where `(0.5, 0.5)` is the
+    /// center of the top left pixel.
+    fn ray_for_subpixel(&self, px: f64, py: f64) -> Ray {
+        // the offset from the edge of the canvas to the point on the canvas
+        let x_offset = px * self.pixel_size;
+        let y_offset = py * self.pixel_size;
 
         // the untransformed coordinates of the pixel in world space.
         // the camera looks toward -z, so +x is to the *left*.
@@ -67,11 +260,286 @@ impl Camera {
         // using the camera matrix, transform teh canvas point and the origin,
         // and then compute the ray's direction vector.
         // the canvas is at z: -1.
-        let pixel = self.transform.inverse() * Point::new(world_x, world_y, -1.0);
-        let origin = self.transform.inverse() * Point::new(0.0, 0.0, 0.0);
-        let direction = (pixel - origin).normalize();
+        let inverse = self.transform.inverse();
+        match self.projection {
+            Projection::Perspective { .. } => {
+                let pixel = inverse * Point::new(world_x, world_y, -1.0);
+                let origin = inverse * Point::new(0.0, 0.0, 0.0);
+                let direction = (pixel - origin).normalize();
+
+                Ray::new_unit(origin, direction)
+            }
+            Projection::Orthographic { .. } => {
+                let origin = inverse * Point::new(world_x, world_y, 0.0);
+                let direction = (inverse * Vector::new(0.0, 0.0, -1.0)).normalize();
+
+                Ray::new_unit(origin, direction)
+            }
+            Projection::Panoramic => {
+                // the angle around the camera, growing to the right, and the
+                // angle above the horizon
+                let azimuth = (px / self.hsize as f64 - 0.5) * 2.0 * PI;
+                let elevation = (0.5 - py / self.vsize as f64) * PI;
+                let local = Vector::new(
+                    -azimuth.sin() * elevation.cos(),
+                    elevation.sin(),
+                    -azimuth.cos() * elevation.cos(),
+                );
+                let origin = inverse * Point::new(0.0, 0.0, 0.0);
+                let direction = (inverse * local).normalize();
+
+                Ray::new_unit(origin, direction)
+            }
+            Projection::Fisheye { max_angle, mapping } => {
+                // the offset from the center of the circle, to the right and
+                // up, with the edge of the circle at 1.0
+                let (dx, dy) = self.fisheye_offset(px, py);
+                let radius = (dx * dx + dy * dy).sqrt();
+                let angle = mapping.angle(radius, max_angle);
+                let local = if radius == 0.0 {
+                    Vector::new(0.0, 0.0, -1.0)
+                } else {
+                    let spread = angle.sin() / radius;
+                    Vector::new(-dx * spread, dy * spread, -angle.cos())
+                };
+                let origin = inverse * Point::new(0.0, 0.0, 0.0);
+                let direction = (inverse * local).normalize();
+
+                Ray::new_unit(origin, direction)
+            }
+        }
+    }
+
+    /// Offset of the position on the canvas in pixels from the center of the
+    /// circle of a fisheye lens, to the right and up, with the edge of the
+    /// circle at a distance of 1.0.
+    fn fisheye_offset(&self, px: f64, py: f64) -> (f64, f64) {
+        let radius = self.hsize.min(self.vsize) as f64 / 2.0;
+        (
+            (px - self.hsize as f64 / 2.0) / radius,
+            (self.vsize as f64 / 2.0 - py) / radius,
+        )
+    }
+
+    /// The ray through the center of the pixel at `x` and `y` of the canvas,
+    /// `None` for pixels outside of the circle of a fisheye lens, which are
+    /// left black by every renderer.
+    pub(crate) fn visible_ray_for_pixel(&self, x: usize, y: usize) -> Option<Ray> {
+        self.visible_ray_for_subpixel(x as f64 + 0.5, y as f64 + 0.5)
+    }
+
+    /// The ray through the position on the canvas in pixels, see
+    /// `ray_for_subpixel`, `None` outside of the circle of a fisheye lens.
+    fn visible_ray_for_subpixel(&self, px: f64, py: f64) -> Option<Ray> {
+        if let Projection::Fisheye { .. } = self.projection {
+            let (dx, dy) = self.fisheye_offset(px, py);
+            if dx * dx + dy * dy > 1.0 {
+                return None;
+            }
+        }
+
+        Some(self.ray_for_subpixel(px, py))
+    }
+
+    /// The color of the pixel at `x` and `y` of the canvas, black for pixels
+    /// outside of the circle of a fisheye lens.
+    fn color_for_pixel(&self, world: &World, x: usize, y: usize) -> Color {
+        self.color_for_subpixel(world, x as f64 + 0.5, y as f64 + 0.5)
+    }
+
+    /// The color seen through the position on the canvas in pixels, see
+    /// `ray_for_subpixel`.
+    fn color_for_subpixel(&self, world: &World, px: f64, py: f64) -> Color {
+        match self.visible_ray_for_subpixel(px, py) {
+            Some(ray) => world.color_at(ray, 5),
+            None => Color::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    /// The colors of the pixels of the `width` by `height` tile of the
+    /// canvas with its top left corner at `x` and `y`, row by row.
+    pub(crate) fn render_tile(
+        &self,
+        world: &World,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Vec<Color> {
+        (y..y + height)
+            .flat_map(|py| (x..x + width).map(move |px| (px, py)))
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|(px, py)| self.color_for_pixel(world, px, py))
+            .collect()
+    }
+
+    /// The average color of `samples` rays through the pixel at `x` and
+    /// `y`, spread over a grid across the pixel. `samples` is rounded down
+    /// to a square number, with one ray through the center of the pixel.
+    fn supersampled_pixel(&self, world: &World, x: usize, y: usize, samples: usize) -> Color {
+        let n = ((samples as f64).sqrt() as usize).max(1);
+        let mut total = Color::new(0.0, 0.0, 0.0);
+        for i in 0..n {
+            for j in 0..n {
+                let px = x as f64 + (i as f64 + 0.5) / n as f64;
+                let py = y as f64 + (j as f64 + 0.5) / n as f64;
+                total = total + self.color_for_subpixel(world, px, py);
+            }
+        }
+
+        total * (1.0 / (n * n) as f64)
+    }
+
+    /// Indices of the objects in `world` that may be seen by the camera.
+    /// Objects whose bounding box lies completely outside the view frustum
+    /// can be skipped before rendering. The frustum is bounded by the near
+    /// plane through the camera and the four planes through the edges of the
+    /// canvas; the camera has no far plane. Objects without bounds, like
+    /// planes, are always kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::Sphere, Camera, Transformation, World};
+    /// use std::f64::consts::PI;
+    ///
+    /// let c = Camera::new(100, 100, PI / 2.0);
+    /// let mut w = World::new();
+    /// let mut front = Sphere::new();
+    /// front.transform = Transformation::new().translate(0.0, 0.0, -5.0).build();
+    /// let mut behind = Sphere::new();
+    /// behind.transform = Transformation::new().translate(0.0, 0.0, 5.0).build();
+    /// w.add_object(Box::new(front));
+    /// w.add_object(Box::new(behind));
+    ///
+    /// assert_eq!(c.frustum_cull(&w), vec![0]);
+    /// ```
+    pub fn frustum_cull(&self, world: &World) -> Vec<usize> {
+        let planes = self.frustum_planes();
+
+        world
+            .objects()
+            .enumerate()
+            .filter(|(_, object)| {
+                let bounds = object.parent_space_bounds();
+                !bounds.is_empty()
+                    && (!bounds.is_finite()
+                        || planes
+                            .iter()
+                            .all(|plane| Self::box_inside_plane(&bounds, plane)))
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The planes of the view frustum in world space as `[a, b, c, d]`, with
+    /// points inside the frustum where `a·x + b·y + c·z + d >= 0`.
+    fn frustum_planes(&self) -> [[f64; 4]; 5] {
+        // in camera space the camera looks toward -z and the canvas at z: -1
+        // spans half_width and half_height on each side. An orthographic view
+        // is a box of that size instead.
+        let (hw, hh) = (self.half_width, self.half_height);
+        let camera_planes = match self.projection {
+            Projection::Perspective { .. } => [
+                [0.0, 0.0, -1.0, 0.0],
+                [1.0, 0.0, -hw, 0.0],
+                [-1.0, 0.0, -hw, 0.0],
+                [0.0, 1.0, -hh, 0.0],
+                [0.0, -1.0, -hh, 0.0],
+            ],
+            Projection::Orthographic { .. } => [
+                [0.0, 0.0, -1.0, 0.0],
+                [1.0, 0.0, 0.0, hw],
+                [-1.0, 0.0, 0.0, hw],
+                [0.0, 1.0, 0.0, hh],
+                [0.0, -1.0, 0.0, hh],
+            ],
+            // a panorama or a fisheye lens may see in every direction, so
+            // nothing is outside
+            Projection::Panoramic | Projection::Fisheye { .. } => [[0.0, 0.0, 0.0, 1.0]; 5],
+        };
 
-        Ray::new(origin, direction)
+        // a point moves from world to camera space by the camera transform,
+        // so the planes move the other way by its transpose.
+        let m = self.transform;
+        let mut planes = [[0.0; 4]; 5];
+        for (plane, camera_plane) in planes.iter_mut().zip(camera_planes.iter()) {
+            for (col, value) in plane.iter_mut().enumerate() {
+                *value = (0..4).map(|row| camera_plane[row] * m[row][col]).sum();
+            }
+        }
+        planes
+    }
+
+    /// Whether any part of `bounds` is on the inside of `plane`, testing the
+    /// corner of the box furthest along the plane normal.
+    fn box_inside_plane(bounds: &Bounds, plane: &[f64; 4]) -> bool {
+        let pick = |n: f64, min: f64, max: f64| if n >= 0.0 { max } else { min };
+        let x = pick(plane[0], bounds.min.x, bounds.max.x);
+        let y = pick(plane[1], bounds.min.y, bounds.max.y);
+        let z = pick(plane[2], bounds.min.z, bounds.max.z);
+
+        plane[0] * x + plane[1] * y + plane[2] * z + plane[3] >= 0.0
+    }
+
+    /// The pixels of the canvas covered by the world space `bounds`, or
+    /// `None` when the box is empty or outside of the view, as the
+    /// rectangle from `x0`, `y0` included to `x1`, `y1` excluded.
+    pub(crate) fn pixel_rect(&self, bounds: &Bounds) -> Option<(usize, usize, usize, usize)> {
+        let whole_canvas = Some((0, 0, self.hsize, self.vsize));
+        if bounds.is_empty() {
+            return None;
+        }
+        if !bounds.is_finite() {
+            return whole_canvas;
+        }
+
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for i in 0..8 {
+            let corner = Point::new(
+                if i & 1 == 0 {
+                    bounds.min.x
+                } else {
+                    bounds.max.x
+                },
+                if i & 2 == 0 {
+                    bounds.min.y
+                } else {
+                    bounds.max.y
+                },
+                if i & 4 == 0 {
+                    bounds.min.z
+                } else {
+                    bounds.max.z
+                },
+            );
+            // the corner in camera space, projected onto the canvas at z: -1
+            let p = self.transform * corner;
+            let (x, y) = match self.projection {
+                Projection::Perspective { .. } if p.z < -EPSILON => (p.x / -p.z, p.y / -p.z),
+                Projection::Orthographic { .. } => (p.x, p.y),
+                _ => return whole_canvas,
+            };
+            // the opposite of `ray_for_subpixel`, +x is to the left
+            let px = (self.half_width - x) / self.pixel_size;
+            let py = (self.half_height - y) / self.pixel_size;
+            min_x = min_x.min(px);
+            min_y = min_y.min(py);
+            max_x = max_x.max(px);
+            max_y = max_y.max(py);
+        }
+
+        // a pixel of margin for the rays through the centers of the pixels
+        let x0 = (min_x.floor() - 1.0).max(0.0) as usize;
+        let y0 = (min_y.floor() - 1.0).max(0.0) as usize;
+        let x1 = ((max_x.ceil() + 1.0).max(0.0) as usize).min(self.hsize);
+        let y1 = ((max_y.ceil() + 1.0).max(0.0) as usize).min(self.vsize);
+        if x0 >= x1 || y0 >= y1 {
+            return None;
+        }
+        Some((x0, y0, x1, y1))
     }
 
     /// Uses the camera to render an image of the given world. The `render`
@@ -80,59 +548,282 @@ impl Camera {
     /// into the [`World`] using the `color_at` function of the [`World`] to get
     /// a [`Color`] for an object intersected by the [`Ray`] if there is one.
     pub fn render(&self, world: &World) -> Canvas {
+        self.render_with_progress(world, |_| ())
+    }
+
+    /// The cameras of a left and a right eye, moved `eye_separation / 2` to
+    /// each side of this camera, towards the left and the right edge of its
+    /// canvas. They look the same way as this camera, so their views are
+    /// parallel.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Camera, Point};
+    /// use std::f64::consts::PI;
+    ///
+    /// let (left, right) = Camera::new(11, 11, PI / 2.0).stereo_pair(0.1);
+    ///
+    /// // the left of the canvas is towards +x for a camera looking along -z
+    /// assert_eq!(left.ray_for_pixel(5, 5).origin, Point::new(0.05, 0.0, 0.0));
+    /// assert_eq!(right.ray_for_pixel(5, 5).origin, Point::new(-0.05, 0.0, 0.0));
+    /// ```
+    pub fn stereo_pair(&self, eye_separation: f64) -> (Camera, Camera) {
+        // the left of the canvas is towards +x in camera space
+        let left = (self.transform.inverse() * Vector::new(1.0, 0.0, 0.0)).normalize();
+        let eye = |offset: f64| {
+            let o = left * offset;
+            let mut camera = self.clone();
+            camera.transform =
+                self.transform * Transformation::new().translate(-o.x, -o.y, -o.z).build();
+            camera
+        };
+
+        (eye(eye_separation / 2.0), eye(-eye_separation / 2.0))
+    }
+
+    /// Render the world for the left and the right eye of a stereo pair, see
+    /// `stereo_pair`. The canvases can be put into a single image with
+    /// [`Canvas::side_by_side`].
+    pub fn render_stereo(&self, world: &World, eye_separation: f64) -> (Canvas, Canvas) {
+        let (left, right) = self.stereo_pair(eye_separation);
+        (left.render(world), right.render(world))
+    }
+
+    /// Render the world all around the camera into an equirectangular image,
+    /// using the size and the transformation of this camera with a
+    /// [`Projection::Panoramic`] projection.
+    pub fn render_panoramic(&self, world: &World) -> Canvas {
+        let mut panorama = Camera::with_projection(self.hsize, self.vsize, Projection::Panoramic);
+        panorama.transform = self.transform;
+        panorama.render(world)
+    }
+
+    /// Render the world through an equidistant fisheye lens seeing
+    /// `max_angle` away from the direction the camera looks at, using the
+    /// size and the transformation of this camera. Other lens mappings are
+    /// made with [`Camera::with_projection`] and [`Projection::Fisheye`].
+    pub fn render_fisheye(&self, world: &World, max_angle: f64) -> Canvas {
+        let projection = Projection::Fisheye {
+            max_angle,
+            mapping: FisheyeProjection::Equidistant,
+        };
+        let mut fisheye = Camera::with_projection(self.hsize, self.vsize, projection);
+        fisheye.transform = self.transform;
+        fisheye.render(world)
+    }
+
+    /// Render the world the same way as `render`, calling `on_progress` with
+    /// the fraction of the image done, from 0.0 to 1.0, after each row.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Camera, World};
+    /// use std::cell::Cell;
+    /// use std::f64::consts::PI;
+    ///
+    /// let c = Camera::new(4, 4, PI / 2.0);
+    /// let done = Cell::new(0.0);
+    /// c.render_with_progress(&World::default(), |fraction| done.set(fraction));
+    ///
+    /// assert_eq!(done.get(), 1.0);
+    /// ```
+    pub fn render_with_progress<F: Fn(f64)>(&self, world: &World, on_progress: F) -> Canvas {
         let mut canvas = Canvas::new(self.hsize, self.vsize);
 
         for y in 0..self.vsize {
             for x in 0..self.hsize {
-                let ray = self.ray_for_pixel(x as f64, y as f64);
-                let color = world.color_at(ray, 5);
+                let color = self.color_for_pixel(world, x, y);
 
                 canvas.write_pixel(x, y, color);
             }
+
+            on_progress((y + 1) as f64 / self.vsize as f64);
         }
 
         canvas
     }
 
-    pub fn render_parallel(&self, serialized_world: &str, n_lines: usize) -> Canvas {
-        let mut canvas = Canvas::new(self.vsize, self.hsize);
-        let n_chunks = self.hsize / n_lines;
+    /// Render the world the same way as `render`, saving the rendered rows to
+    /// the file at `checkpoint_path` every `interval_rows` rows. If the file
+    /// holds a checkpoint for a camera of the same size, the rows in it are
+    /// kept and rendering continues from the first missing row. The file is
+    /// removed once the image is complete.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checkpoint file can not be written.
+    pub fn render_resumable(
+        &self,
+        world: &World,
+        checkpoint_path: &Path,
+        interval_rows: usize,
+    ) -> Canvas {
+        let canvas =
+            self.render_rows_with_checkpoint(world, checkpoint_path, interval_rows, self.vsize);
+        // a missing file is fine, there is nothing left to resume either way
+        let _ = fs::remove_file(checkpoint_path);
 
-        let counter = AtomicUsize::new(0);
+        canvas
+    }
+
+    /// Render rows until `last_row`, resuming from and saving to the
+    /// checkpoint file at `checkpoint_path`.
+    fn render_rows_with_checkpoint(
+        &self,
+        world: &World,
+        checkpoint_path: &Path,
+        interval_rows: usize,
+        last_row: usize,
+    ) -> Canvas {
+        let mut rows = fs::read_to_string(checkpoint_path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<Checkpoint>(&contents).ok())
+            .filter(|c| c.hsize == self.hsize && c.vsize == self.vsize)
+            .map(|c| c.rows)
+            .unwrap_or_default();
+
+        for y in rows.len()..last_row.min(self.vsize) {
+            rows.push(
+                (0..self.hsize)
+                    .map(|x| self.color_for_pixel(world, x, y))
+                    .collect(),
+            );
+
+            if (y + 1) % interval_rows.max(1) == 0 || y + 1 == last_row {
+                let checkpoint = Checkpoint {
+                    hsize: self.hsize,
+                    vsize: self.vsize,
+                    rows,
+                };
+                fs::write(checkpoint_path, serde_json::to_string(&checkpoint).unwrap())
+                    .expect("Failed to write checkpoint");
+                rows = checkpoint.rows;
+            }
+        }
+
+        let mut canvas = Canvas::new(self.hsize, self.vsize);
+        for (y, row) in rows.into_iter().enumerate() {
+            for (x, color) in row.into_iter().enumerate() {
+                canvas.write_pixel(x, y, color);
+            }
+        }
 
         canvas
-            .chunks_mut(n_lines)
-            .enumerate()
-            .for_each(|(i, chunk)| {
-                let world_copy = World::from_str(serialized_world);
-
-                let start_line = i * n_lines;
-                for y in 0..chunk.len() / self.vsize {
-                    for x in 0..self.vsize {
-                        let ray = self.ray_for_pixel(x as f64, (y + start_line) as f64);
-                        let color = world_copy.color_at(ray, 5);
-
-                        let i = x + y * self.vsize;
-                        chunk[i] = color;
-                    }
+    }
+
+    /// Render the world in the background for an interactive preview,
+    /// sending a canvas to `tx` with 1 ray per pixel first and then
+    /// refining it with 4, 16 and 64 rays per pixel, each sent when it is
+    /// done. The rays of a pass are spread over a grid across every pixel,
+    /// smoothing the edges of objects more with every pass.
+    ///
+    /// Every pass renders on all cores. Dropping the receiver of `tx` stops
+    /// the preview once the pass being rendered is finished, after which the
+    /// returned thread ends.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Camera, World};
+    /// use std::f64::consts::PI;
+    /// use std::sync::{mpsc, Arc};
+    ///
+    /// let c = Camera::new(8, 8, PI / 2.0);
+    /// let (tx, rx) = mpsc::channel();
+    /// let handle = c.start_preview(Arc::new(World::default()), tx);
+    ///
+    /// assert_eq!(rx.iter().count(), 4);
+    /// handle.join().unwrap();
+    /// ```
+    pub fn start_preview(&self, world: Arc<World>, tx: Sender<Canvas>) -> JoinHandle<()> {
+        let camera = self.clone();
+
+        thread::spawn(move || {
+            for samples in PREVIEW_SAMPLES {
+                let canvas = Canvas::parallel_fill(camera.hsize, camera.vsize, |x, y| {
+                    camera.supersampled_pixel(&world, x, y, samples)
+                });
+
+                if tx.send(canvas).is_err() {
+                    // nobody is watching the preview anymore
+                    return;
                 }
+            }
+        })
+    }
 
-                counter.fetch_add(1, Ordering::Relaxed);
-                print!("\r{}/{}", counter.load(Ordering::Relaxed), n_chunks);
-                std::io::stdout().flush().unwrap();
-            });
+    /// Render the world on all cores, `n_lines` rows at a time. The threads
+    /// share the `world`. The number of finished batches is printed to the
+    /// standard output.
+    pub fn render_parallel(&self, world: &World, n_lines: usize) -> Canvas {
+        let n_chunks = self.vsize.div_ceil(n_lines);
+
+        let canvas = self.render_parallel_with_progress(world, n_lines, |fraction| {
+            let done = (fraction * n_chunks as f64).round() as usize;
+            print!("\r{}/{}", done, n_chunks);
+            std::io::stdout().flush().unwrap();
+        });
 
         println!();
 
         canvas
     }
+
+    /// Render the world the same way as `render_parallel`, calling
+    /// `on_progress` with the fraction of the image done, from 0.0 to 1.0,
+    /// after each batch of rows. The callback is always called on the thread
+    /// that called this function.
+    pub fn render_parallel_with_progress<F: Fn(f64)>(
+        &self,
+        world: &World,
+        n_lines: usize,
+        on_progress: F,
+    ) -> Canvas {
+        let mut canvas = Canvas::new(self.hsize, self.vsize);
+        let n_chunks = self.vsize.div_ceil(n_lines);
+
+        let counter = AtomicUsize::new(0);
+        let (sender, receiver) = crossbeam_channel::unbounded();
+
+        std::thread::scope(|scope| {
+            let canvas = &mut canvas;
+            let counter = &counter;
+
+            scope.spawn(move || {
+                canvas
+                    .chunks_mut(n_lines)
+                    .enumerate()
+                    .for_each(|(i, chunk)| {
+                        fill_parallel(chunk, self.hsize, i * n_lines, &|x, y| {
+                            self.color_for_pixel(world, x, y)
+                        });
+
+                        let done = counter.fetch_add(1, Ordering::Relaxed) + 1;
+                        sender.send(done).unwrap();
+                    });
+            });
+
+            // the sender is dropped when the rendering thread finishes which
+            // ends this loop
+            for done in receiver.iter() {
+                on_progress(done as f64 / n_chunks as f64);
+            }
+        });
+
+        canvas
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::cell::RefCell;
+    use std::env;
     use std::f64::consts::PI;
 
-    use crate::{float_eq, Color, Point, Transformation, Vector, World};
+    use crate::shapes::{Plane, Sphere};
+    use crate::{float_eq, Point, PointLight, Transformation, Vector};
 
     use super::*;
 
@@ -168,12 +859,52 @@ mod tests {
         assert!(float_eq(c.pixel_size, 0.01));
     }
 
+    #[test]
+    fn the_angular_size_of_a_pixel() {
+        let c = Camera::new(100, 100, PI / 2.0);
+        let (width, height) = c.pixel_size();
+
+        // 90° over 100 pixels is 0.9° each
+        assert!(float_eq(width, 0.0157));
+        assert!(float_eq(height, 0.0157));
+        assert_eq!(Camera::new(360, 180, PI).pixel_size().0, PI / 360.0);
+    }
+
+    #[test]
+    fn the_solid_angles_of_the_pixels_add_up_to_the_view() {
+        let c = Camera::new(100, 100, PI / 2.0);
+        let total = c.solid_angle_per_pixel() * 10_000.0;
+
+        // a pixel at distance d on the canvas one unit away sees its area
+        // shrunk by cos³ of its angle from the center, cos = 1 / d
+        let mut integrated = 0.0;
+        for y in 0..c.vsize {
+            for x in 0..c.hsize {
+                let d = c.ray_for_pixel(x, y).direction;
+                let cos = d.z.abs() / d.magnitude();
+                integrated += c.pixel_size * c.pixel_size * cos.powi(3);
+            }
+        }
+
+        assert!(
+            (total - integrated).abs() < 1e-3,
+            "{} {}",
+            total,
+            integrated
+        );
+        assert!(float_eq(total, 2.0 * PI / 3.0));
+        // the square view holds the cone of the field of view
+        assert!(total > 2.0 * PI * (1.0 - (PI / 4.0).cos()));
+        let panorama = Camera::with_projection(40, 20, Projection::Panoramic);
+        assert!(float_eq(panorama.solid_angle_per_pixel() * 800.0, 4.0 * PI));
+    }
+
     // Chapter 7 Making a Scene
     // Page 103
     #[test]
     fn constructing_a_ray_through_the_center_of_canvas() {
         let c = Camera::new(201, 101, PI / 2.0);
-        let r = c.ray_for_pixel(100.0, 50.0);
+        let r = c.ray_for_pixel(100, 50);
 
         assert_eq!(r.origin, Point::new(0.0, 0.0, 0.0));
         assert_eq!(r.direction, Vector::new(0.0, 0.0, -1.0));
@@ -184,7 +915,7 @@ mod tests {
     #[test]
     fn constructing_a_ray_through_a_corner_of_the_canvas() {
         let c = Camera::new(201, 101, PI / 2.0);
-        let r = c.ray_for_pixel(0.0, 0.0);
+        let r = c.ray_for_pixel(0, 0);
 
         assert_eq!(r.origin, Point::new(0.0, 0.0, 0.0));
         assert_eq!(r.direction, Vector::new(0.66519, 0.33259, -0.66851));
@@ -199,7 +930,7 @@ mod tests {
             .translate(0.0, -2.0, 5.0)
             .rotate_y(PI / 4.0)
             .build();
-        let r = c.ray_for_pixel(100., 50.0);
+        let r = c.ray_for_pixel(100, 50);
 
         assert_eq!(r.origin, Point::new(0.0, 2.0, -5.0));
         assert_eq!(
@@ -208,6 +939,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn a_ray_through_the_top_left_pixel_points_up_and_left() {
+        let c = Camera::new(201, 101, PI / 2.0);
+        let center = c.ray_for_pixel(100, 50);
+        let corner = c.ray_for_pixel(0, 0);
+
+        // rows grow downward so the center is offset below the top row
+        assert!(center.direction.y < corner.direction.y);
+        assert!(corner.direction.y > 0.0);
+        // the camera looks toward -z, so +x is to the left
+        assert!(corner.direction.x > 0.0);
+    }
+
+    #[test]
+    fn a_ray_at_the_edge_of_the_canvas_respects_the_field_of_view() {
+        let c = Camera::new(1001, 1, PI / 2.0);
+        let r = c.ray_for_pixel(0, 0);
+        let angle = r.direction.dot(Vector::new(0.0, 0.0, -1.0)).acos();
+
+        assert!((angle - PI / 4.0).abs() < 0.001);
+    }
+
     // Chapter 7 Making a Scene
     // Page 104
     #[test]
@@ -222,4 +975,518 @@ mod tests {
 
         assert_eq!(image.pixel_at(5, 5), Color::new(0.38066, 0.47583, 0.2855));
     }
+
+    #[test]
+    fn rendering_with_progress_reports_every_row() {
+        let w = World::default();
+        let mut c = Camera::new(11, 7, PI / 2.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        let calls = RefCell::new(Vec::new());
+        let image = c.render_with_progress(&w, |fraction| calls.borrow_mut().push(fraction));
+        let expected = c.render(&w);
+        let calls = calls.into_inner();
+
+        assert_eq!(calls.len(), 7);
+        assert!(calls[0] > 0.0);
+        assert_eq!(*calls.last().unwrap(), 1.0);
+        for y in 0..7 {
+            for x in 0..11 {
+                assert_eq!(image.pixel_at(x, y), expected.pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn rendering_in_parallel_with_progress() {
+        let w = World::default();
+        let mut c = Camera::new(11, 7, PI / 2.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        let calls = RefCell::new(Vec::new());
+        let image =
+            c.render_parallel_with_progress(&w, 2, |fraction| calls.borrow_mut().push(fraction));
+        let expected = c.render(&w);
+        let calls = calls.into_inner();
+
+        assert_eq!(calls.len(), 4);
+        assert!(calls[0] > 0.0);
+        assert_eq!(*calls.last().unwrap(), 1.0);
+        assert_eq!(image.width, 11);
+        assert_eq!(image.height, 7);
+        for y in 0..7 {
+            for x in 0..11 {
+                assert_eq!(image.pixel_at(x, y), expected.pixel_at(x, y));
+            }
+        }
+    }
+
+    fn sphere_at(x: f64, y: f64, z: f64) -> Box<Sphere> {
+        let mut s = Sphere::new();
+        s.transform = Transformation::new().translate(x, y, z).build();
+        Box::new(s)
+    }
+
+    #[test]
+    fn frustum_culling_skips_objects_behind_the_camera() {
+        let mut c = Camera::new(100, 50, PI / 2.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        let mut w = World::new();
+        w.add_object(sphere_at(0.0, 0.0, -10.0));
+        w.add_object(sphere_at(0.0, 0.0, 0.0));
+
+        assert_eq!(c.frustum_cull(&w), vec![1]);
+    }
+
+    #[test]
+    fn frustum_culling_keeps_partially_visible_objects() {
+        let c = Camera::new(100, 100, PI / 2.0);
+        let mut w = World::new();
+        // the right edge of the view at z: -5 is x: -5, the sphere reaches
+        // into it by half a unit.
+        w.add_object(sphere_at(-5.5, 0.0, -5.0));
+        w.add_object(sphere_at(-8.0, 0.0, -5.0));
+        w.add_object(sphere_at(0.0, 8.0, -5.0));
+        w.add_object(Box::new(Plane::new()));
+
+        assert_eq!(c.frustum_cull(&w), vec![0, 3]);
+    }
+
+    fn checkpoint_path(name: &str) -> std::path::PathBuf {
+        env::temp_dir().join(format!("rustic_ray_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn resuming_an_interrupted_render() {
+        let w = World::default();
+        let mut c = Camera::new(11, 10, PI / 2.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        let path = checkpoint_path("interrupted.json");
+
+        // stop half way as if the render had been killed
+        c.render_rows_with_checkpoint(&w, &path, 2, 5);
+        assert!(path.exists());
+
+        let image = c.render_resumable(&w, &path, 2);
+        let expected = c.render(&w);
+
+        assert!(!path.exists());
+        for y in 0..10 {
+            for x in 0..11 {
+                assert_eq!(image.pixel_at(x, y), expected.pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn resuming_keeps_the_rows_in_the_checkpoint() {
+        let w = World::default();
+        let c = Camera::new(3, 4, PI / 2.0);
+        let path = checkpoint_path("kept_rows.json");
+        let red = vec![Color::new(1.0, 0.0, 0.0); 3];
+        let checkpoint = Checkpoint {
+            hsize: 3,
+            vsize: 4,
+            rows: vec![red.clone(), red],
+        };
+        fs::write(&path, serde_json::to_string(&checkpoint).unwrap()).unwrap();
+
+        let image = c.render_resumable(&w, &path, 1);
+
+        assert_eq!(image.pixel_at(2, 1), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(image.pixel_at(2, 3), w.color_at(c.ray_for_pixel(2, 3), 5));
+    }
+
+    #[test]
+    fn a_missing_checkpoint_starts_fresh() {
+        let w = World::default();
+        let c = Camera::new(5, 5, PI / 2.0);
+        let path = checkpoint_path("missing.json");
+
+        let image = c.render_resumable(&w, &path, 2);
+        let expected = c.render(&w);
+
+        for y in 0..5 {
+            for x in 0..5 {
+                assert_eq!(image.pixel_at(x, y), expected.pixel_at(x, y));
+            }
+        }
+    }
+
+    fn orthographic_camera() -> Camera {
+        Camera::with_projection(
+            21,
+            11,
+            Projection::Orthographic {
+                half_width: 2.1,
+                half_height: 1.1,
+            },
+        )
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let mut c = orthographic_camera();
+        c.transform = Transformation::new().rotate_y(PI / 4.0).build();
+        let corner = c.ray_for_pixel(0, 0);
+        let center = c.ray_for_pixel(10, 5);
+
+        assert_eq!(corner.direction, center.direction);
+        assert_ne!(corner.origin, center.origin);
+        assert_eq!(center.origin, Point::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn orthographic_objects_keep_their_size_at_any_distance() {
+        let covered = |distance: f64| {
+            let mut w = World::new();
+            w.light = Some(PointLight::new(
+                Point::new(0.0, 0.0, 10.0),
+                Color::new(1.0, 1.0, 1.0),
+            ));
+            let mut s = Sphere::new();
+            s.transform = Transformation::new().translate(0.0, 0.0, -distance).build();
+            s.material.set_color(Color::new(1.0, 1.0, 1.0));
+            s.material.ambient = 1.0;
+            w.add_object(Box::new(s));
+
+            let image = orthographic_camera().render(&w);
+            (0..11)
+                .flat_map(|y| (0..21).map(move |x| (x, y)))
+                .filter(|(x, y)| image.pixel_at(*x, *y).red > 0.0)
+                .count()
+        };
+
+        assert!(covered(3.0) > 0);
+        assert_eq!(covered(3.0), covered(30.0));
+    }
+
+    #[test]
+    fn orthographic_frustum_culling_skips_objects_beside_the_view() {
+        let mut w = World::new();
+        let mut beside = Sphere::new();
+        beside.transform = Transformation::new().translate(4.0, 0.0, -100.0).build();
+        let mut far = Sphere::new();
+        far.transform = Transformation::new().translate(1.5, 0.0, -100.0).build();
+        w.add_object(Box::new(beside));
+        w.add_object(Box::new(far));
+
+        assert_eq!(orthographic_camera().frustum_cull(&w), vec![1]);
+    }
+
+    fn stereo_camera() -> Camera {
+        let mut c = Camera::new(21, 11, PI / 3.0);
+        c.transform = Transformation::view_transform(
+            Point::new(1.0, 1.5, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        c
+    }
+
+    #[test]
+    fn the_eyes_of_a_stereo_pair_are_apart_across_the_view() {
+        let c = stereo_camera();
+        let (left, right) = c.stereo_pair(0.2);
+        let center = c.ray_for_pixel(10, 5);
+        let (l, r) = (left.ray_for_pixel(10, 5), right.ray_for_pixel(10, 5));
+
+        assert!(float_eq((l.origin - r.origin).magnitude(), 0.2));
+        assert!(float_eq((l.origin - center.origin).magnitude(), 0.1));
+        assert!(float_eq((l.origin - r.origin).dot(center.direction), 0.0));
+        assert_eq!(l.direction, center.direction);
+        assert_eq!(r.direction, center.direction);
+        // the left eye is on the side of the left edge of the canvas
+        let to_left_edge = c.ray_for_pixel(0, 5).direction - center.direction;
+        assert!((l.origin - r.origin).dot(to_left_edge) > 0.0);
+    }
+
+    #[test]
+    fn the_eyes_of_a_stereo_pair_see_different_images() {
+        let w = World::default();
+        let (left, right) = stereo_camera().render_stereo(&w, 0.5);
+        let differs = (0..11)
+            .flat_map(|y| (0..21).map(move |x| (x, y)))
+            .any(|(x, y)| left.pixel_at(x, y) != right.pixel_at(x, y));
+
+        assert!(differs);
+        let both = Canvas::side_by_side(&left, &right);
+        assert_eq!((both.width, both.height), (42, 11));
+        assert_eq!(both.pixel_at(25, 4), right.pixel_at(4, 4));
+    }
+
+    #[test]
+    fn a_stereo_pair_without_separation_sees_the_same_image() {
+        let w = World::default();
+        let c = stereo_camera();
+        let (left, right) = c.render_stereo(&w, 0.0);
+        let mono = c.render(&w);
+
+        for y in 0..11 {
+            for x in 0..21 {
+                assert_eq!(left.pixel_at(x, y), right.pixel_at(x, y));
+                assert_eq!(left.pixel_at(x, y), mono.pixel_at(x, y));
+            }
+        }
+    }
+
+    /// Angle of `direction` around the y axis, 0 towards -z and growing
+    /// towards -x.
+    fn azimuth(direction: Vector) -> f64 {
+        (-direction.x).atan2(-direction.z)
+    }
+
+    #[test]
+    fn the_center_of_a_panorama_looks_forward() {
+        let mut c = Camera::with_projection(21, 11, Projection::Panoramic);
+        c.transform = Transformation::view_transform(
+            Point::new(1.0, 2.0, 3.0),
+            Point::new(4.0, 2.0, -1.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        let r = c.ray_for_pixel(10, 5);
+
+        assert_eq!(r.origin, Point::new(1.0, 2.0, 3.0));
+        assert_eq!(r.direction, Vector::new(0.6, 0.0, -0.8));
+    }
+
+    #[test]
+    fn the_edges_of_a_panorama_meet_behind_the_camera() {
+        let c = Camera::with_projection(21, 11, Projection::Panoramic);
+        let step = 2.0 * PI / 21.0;
+        let left = azimuth(c.ray_for_subpixel(0.0, 5.5).direction);
+        let right = azimuth(c.ray_for_subpixel(21.0, 5.5).direction);
+
+        assert!(float_eq(left.abs(), PI));
+        assert!(float_eq(right.abs(), PI));
+        assert_eq!(
+            c.ray_for_subpixel(0.0, 5.5).direction,
+            c.ray_for_subpixel(21.0, 5.5).direction
+        );
+        assert!(float_eq(
+            azimuth(c.ray_for_pixel(0, 5).direction),
+            -PI + step / 2.0
+        ));
+        assert!(float_eq(
+            azimuth(c.ray_for_pixel(20, 5).direction),
+            PI - step / 2.0
+        ));
+        // the rows span from straight up to straight down whatever the size
+        assert_eq!(
+            c.ray_for_subpixel(3.0, 0.0).direction,
+            Vector::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            c.ray_for_subpixel(3.0, 11.0).direction,
+            Vector::new(0.0, -1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn a_sphere_above_the_camera_is_at_the_top_of_a_panorama() {
+        let mut w = World::new();
+        let mut s = Sphere::new();
+        s.transform = Transformation::new().translate(0.0, 5.0, 0.0).build();
+        s.material.set_color(Color::new(1.0, 0.0, 0.0));
+        s.material.ambient = 1.0;
+        w.add_object(Box::new(s));
+        w.light = Some(PointLight::new(
+            Point::new(0.0, 0.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+
+        let image = Camera::new(21, 11, PI / 2.0).render_panoramic(&w);
+
+        assert_eq!((image.width, image.height), (21, 11));
+        assert!(image.pixel_at(10, 0).red > 0.0);
+        assert_eq!(image.pixel_at(10, 5), Color::new(0.0, 0.0, 0.0));
+        assert_eq!(image.pixel_at(10, 10), Color::new(0.0, 0.0, 0.0));
+    }
+
+    fn fisheye_camera(mapping: FisheyeProjection) -> Camera {
+        let projection = Projection::Fisheye {
+            max_angle: 0.4 * PI,
+            mapping,
+        };
+        Camera::with_projection(21, 21, projection)
+    }
+
+    /// Angle of a ray of an untransformed camera from the direction it looks.
+    fn angle_from_forward(r: Ray) -> f64 {
+        (-r.direction.z).clamp(-1.0, 1.0).acos()
+    }
+
+    #[test]
+    fn the_center_of_a_fisheye_looks_forward() {
+        let c = fisheye_camera(FisheyeProjection::Equidistant);
+
+        assert_eq!(
+            c.ray_for_pixel(10, 10).direction,
+            Vector::new(0.0, 0.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn the_edge_of_a_fisheye_circle_sees_the_max_angle() {
+        for mapping in [
+            FisheyeProjection::Equidistant,
+            FisheyeProjection::Stereographic,
+            FisheyeProjection::Equisolid,
+        ] {
+            let c = fisheye_camera(mapping);
+
+            assert!(float_eq(
+                angle_from_forward(c.ray_for_subpixel(21.0, 10.5)),
+                0.4 * PI
+            ));
+            assert!(float_eq(
+                angle_from_forward(c.ray_for_subpixel(10.5, 0.0)),
+                0.4 * PI
+            ));
+        }
+        // the right edge of the canvas sees to the right, towards -x
+        let right = fisheye_camera(FisheyeProjection::Equidistant).ray_for_subpixel(21.0, 10.5);
+        assert!(right.direction.x < 0.0);
+    }
+
+    #[test]
+    fn the_corners_outside_of_a_fisheye_circle_are_black() {
+        let mut w = World::new();
+        let mut room = Sphere::new();
+        room.transform = Transformation::new().scale(10.0, 10.0, 10.0).build();
+        room.material.ambient = 1.0;
+        w.add_object(Box::new(room));
+        w.light = Some(PointLight::new(
+            Point::new(0.0, 0.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+
+        let image = Camera::new(21, 21, PI / 2.0).render_fisheye(&w, PI / 2.0);
+
+        assert_eq!(image.pixel_at(0, 0), Color::new(0.0, 0.0, 0.0));
+        assert_eq!(image.pixel_at(20, 20), Color::new(0.0, 0.0, 0.0));
+        assert_eq!(image.pixel_at(0, 20), Color::new(0.0, 0.0, 0.0));
+        assert!(image.pixel_at(10, 10).red > 0.0);
+        assert!(image.pixel_at(0, 10).red > 0.0);
+    }
+
+    #[test]
+    fn every_renderer_leaves_the_corners_of_a_fisheye_black() {
+        let mut w = World::new();
+        let mut room = Sphere::new();
+        room.transform = Transformation::new().scale(10.0, 10.0, 10.0).build();
+        room.material.ambient = 1.0;
+        w.add_object(Box::new(room));
+        w.light = Some(PointLight::new(
+            Point::new(0.0, 0.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        let projection = Projection::Fisheye {
+            max_angle: PI / 2.0,
+            mapping: FisheyeProjection::Equidistant,
+        };
+        let c = Camera::with_projection(11, 11, projection);
+        let black = Color::new(0.0, 0.0, 0.0);
+
+        let job = crate::RenderJob::new(w.clone_deep(), c.clone(), vec![crate::RenderPass::Beauty]);
+        let cache = crate::IrradianceCache::new(4, 0.5);
+        for image in [
+            c.render(&w),
+            c.render_spectral(&w, 1),
+            w.render_with_irradiance_cache(&c, &cache),
+            job.run()[&crate::RenderPass::Beauty].clone(),
+        ] {
+            assert_eq!(image.pixel_at(0, 0), black);
+            assert!(image.pixel_at(5, 5).red > 0.0);
+        }
+        assert!(w.pick(&c, 0, 0).is_none());
+        assert!(w.pick(&c, 5, 5).is_some());
+        assert_eq!(crate::RayDebugger::trace_pixel(&c, &w, 0, 0).color, black);
+    }
+
+    #[test]
+    fn fisheye_mappings_space_the_angles_by_their_definition() {
+        let max = 0.4 * PI;
+        // half way from the center to the edge of the circle
+        let halfway =
+            |mapping| angle_from_forward(fisheye_camera(mapping).ray_for_subpixel(15.75, 10.5));
+
+        assert!(float_eq(halfway(FisheyeProjection::Equidistant), max / 2.0));
+        assert!(float_eq(
+            halfway(FisheyeProjection::Stereographic),
+            2.0 * (0.5 * (max / 2.0).tan()).atan()
+        ));
+        assert!(float_eq(
+            halfway(FisheyeProjection::Equisolid),
+            2.0 * (0.5 * (max / 2.0).sin()).asin()
+        ));
+    }
+
+    /// Sum of the differences of every channel of every pixel of `a` and `b`.
+    fn difference(a: &Canvas, b: &Canvas) -> f64 {
+        let mut total = 0.0;
+        for y in 0..a.height {
+            for x in 0..a.width {
+                let d = a.pixel_at(x, y) - b.pixel_at(x, y);
+                total += d.red.abs() + d.green.abs() + d.blue.abs();
+            }
+        }
+        total
+    }
+
+    #[test]
+    fn a_preview_is_refined_until_the_last_pass() {
+        use std::time::Duration;
+
+        let mut c = Camera::new(24, 24, PI / 3.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        let (tx, rx) = std::sync::mpsc::channel();
+        let handle = c.start_preview(Arc::new(World::default()), tx);
+
+        let first = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        // the first pass is a plain render with a ray through every center
+        assert_eq!(difference(&first, &c.render(&World::default())), 0.0);
+
+        let mut passes = vec![first];
+        passes.extend(rx.iter());
+        handle.join().unwrap();
+        assert_eq!(passes.len(), 4);
+
+        // every pass is closer to the last one, smoothing the edges of the
+        // spheres
+        let last = passes.last().unwrap();
+        let errors: Vec<f64> = passes.iter().map(|p| difference(p, last)).collect();
+        assert!(errors.windows(2).all(|e| e[1] <= e[0]), "{:?}", errors);
+        assert!(errors[0] > 0.0);
+    }
+
+    #[test]
+    fn stopping_a_preview_ends_its_thread() {
+        let c = Camera::new(16, 16, PI / 2.0);
+        let (tx, rx) = std::sync::mpsc::channel();
+        let handle = c.start_preview(Arc::new(World::default()), tx);
+
+        rx.recv().unwrap();
+        drop(rx);
+
+        assert!(handle.join().is_ok());
+    }
 }
@@ -1,14 +1,30 @@
+use std::io::{self, Write};
+
 use rayon::prelude::*;
 use rayon::slice::ChunksMut;
 
-use crate::Color;
+use crate::{Color, Vector};
 
 const MAXIMUM_PPM_LINE_LENGTH: usize = 70;
 
+/// Color the `pixels` of whole rows of a canvas `width` pixels wide, the first
+/// one being row `first_row`, with `f(x, y)`. Every pixel is evaluated on its
+/// own so the work is spread over all cores even within a single row.
+pub(crate) fn fill_parallel<F>(pixels: &mut [Color], width: usize, first_row: usize, f: &F)
+where
+    F: Fn(usize, usize) -> Color + Sync,
+{
+    pixels
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(i, pixel)| *pixel = f(i % width, first_row + i / width));
+}
+
 /// A grid of pixels. The size of the canvas is determined by its width and height.
 ///
 /// The pixels are stored in a linear 1D array indexing a pixel is done with
 /// this formula `index = x + y * width`.
+#[derive(Clone)]
 pub struct Canvas {
     pub width: usize,
     pub height: usize,
@@ -44,6 +60,28 @@ impl Canvas {
         }
     }
 
+    /// Creates a new canvas with the given `width` and `height` where each
+    /// pixel has the [`Color`] returned by `f(x, y)`. The pixels are
+    /// evaluated in parallel.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Canvas, Color};
+    ///
+    /// let c = Canvas::parallel_fill(4, 3, |x, y| Color::new(x as f64, y as f64, 0.0));
+    ///
+    /// assert_eq!(c.pixel_at(3, 2), Color::new(3.0, 2.0, 0.0));
+    /// ```
+    pub fn parallel_fill<F>(width: usize, height: usize, f: F) -> Canvas
+    where
+        F: Fn(usize, usize) -> Color + Sync,
+    {
+        let mut canvas = Canvas::new(width, height);
+        fill_parallel(&mut canvas.pixels, width, 0, &f);
+        canvas
+    }
+
     /// Output the canvas buffer to a string buffer in the PPM file format.
     ///
     /// Example
@@ -96,6 +134,212 @@ impl Canvas {
         buffer
     }
 
+    /// Count how many pixels have each byte value, from 0 to 255, in the
+    /// red, green and blue channels, converting the colors the same way as
+    /// `canvas_to_rgb_buffer`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Canvas, Color};
+    ///
+    /// let mut c = Canvas::new(2, 1);
+    /// c.write_pixel(1, 0, Color::new(1.0, 0.5, 0.0));
+    /// let [red, green, blue] = c.histogram();
+    ///
+    /// assert_eq!((red[0], red[255]), (1, 1));
+    /// assert_eq!((green[0], green[127]), (1, 1));
+    /// assert_eq!(blue[0], 2);
+    /// ```
+    pub fn histogram(&self) -> [[u32; 256]; 3] {
+        let mut histogram = [[0; 256]; 3];
+        for color in &self.pixels {
+            for (channel, value) in color.to_rgb_u8().iter().enumerate() {
+                histogram[channel][*value as usize] += 1;
+            }
+        }
+        histogram
+    }
+
+    /// Stretch the range of each channel so the darkest percent of the
+    /// pixels become black and the brightest percent become white, like the
+    /// auto contrast of image editors. A channel with a single value is left
+    /// as it is.
+    pub fn auto_levels(&mut self) {
+        if self.pixels.is_empty() {
+            return;
+        }
+
+        let channels: [fn(&mut Color) -> &mut f64; 3] =
+            [|c| &mut c.red, |c| &mut c.green, |c| &mut c.blue];
+
+        for channel in channels.iter() {
+            let mut values: Vec<f64> = self.pixels.iter_mut().map(|c| *channel(c)).collect();
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let percentile = |p: f64| values[((values.len() - 1) as f64 * p).round() as usize];
+            let (low, high) = (percentile(0.01), percentile(0.99));
+
+            if high <= low {
+                continue;
+            }
+            for color in self.pixels.iter_mut() {
+                let value = channel(color);
+                *value = ((*value - low) / (high - low)).clamp(0.0, 1.0);
+            }
+        }
+    }
+
+    /// Reduce the noise of the image with the non-local means filter.
+    ///
+    /// Every pixel is replaced by an average of the pixels up to
+    /// `search_radius` away, weighted by how much the square patches of
+    /// `patch_radius` around them look like the patch around the pixel. Noise
+    /// is averaged away while edges are kept, as the patches on the other side
+    /// of an edge look different. A larger `h` lets less similar patches count
+    /// more, smoothing more of the image.
+    ///
+    /// Buades et al. "A non-local algorithm for image denoising" (2005)
+    pub fn denoise_nlm(&self, h: f64, patch_radius: usize, search_radius: usize) -> Canvas {
+        let clamped = |x: isize, y: isize| {
+            let x = x.clamp(0, self.width as isize - 1) as usize;
+            let y = y.clamp(0, self.height as isize - 1) as usize;
+            self.pixels[y * self.width + x]
+        };
+        let (patch, search) = (patch_radius as isize, search_radius as isize);
+        let patch_size = ((2 * patch + 1) * (2 * patch + 1) * 3) as f64;
+
+        // mean squared difference between the patches around two pixels
+        let distance = |(x1, y1): (isize, isize), (x2, y2): (isize, isize)| {
+            let mut sum = 0.0;
+            for dy in -patch..=patch {
+                for dx in -patch..=patch {
+                    let a = clamped(x1 + dx, y1 + dy);
+                    let b = clamped(x2 + dx, y2 + dy);
+                    sum += (a.red - b.red).powi(2)
+                        + (a.green - b.green).powi(2)
+                        + (a.blue - b.blue).powi(2);
+                }
+            }
+            sum / patch_size
+        };
+
+        Canvas::parallel_fill(self.width, self.height, |x, y| {
+            let center = (x as isize, y as isize);
+            let mut total = Color::new(0.0, 0.0, 0.0);
+            let mut weights = 0.0;
+
+            for sy in -search..=search {
+                for sx in -search..=search {
+                    let other = (center.0 + sx, center.1 + sy);
+                    if other.0 < 0
+                        || other.1 < 0
+                        || other.0 >= self.width as isize
+                        || other.1 >= self.height as isize
+                    {
+                        continue;
+                    }
+                    let weight = (-distance(center, other) / (h * h)).exp();
+                    total = total + clamped(other.0, other.1) * weight;
+                    weights += weight;
+                }
+            }
+
+            total * (1.0 / weights)
+        })
+    }
+
+    /// Turn a canvas holding the `x`, `y` and `z` of normal vectors in its red,
+    /// green and blue channels into colors that can be stored in an image,
+    /// moving each axis from `-1..1` to `0..1` with `n * 0.5 + 0.5`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Canvas, Color, Vector};
+    ///
+    /// let mut c = Canvas::new(1, 1);
+    /// c.write_pixel(0, 0, Color::new(0.0, 1.0, 0.0));
+    /// let encoded = c.encode_normals();
+    ///
+    /// assert_eq!(encoded.pixel_at(0, 0), Color::new(0.5, 1.0, 0.5));
+    /// assert_eq!(encoded.decode_normals()[0][0], Vector::new(0.0, 1.0, 0.0));
+    /// ```
+    pub fn encode_normals(&self) -> Canvas {
+        self.map(|c| Color::new(c.red * 0.5 + 0.5, c.green * 0.5 + 0.5, c.blue * 0.5 + 0.5))
+    }
+
+    /// Read back the normal vectors of a canvas made by `encode_normals`, as
+    /// rows of vectors from the top of the canvas.
+    pub fn decode_normals(&self) -> Vec<Vec<Vector>> {
+        self.pixels
+            .chunks(self.width)
+            .map(|row| {
+                row.iter()
+                    .map(|c| {
+                        Vector::new(c.red * 2.0 - 1.0, c.green * 2.0 - 1.0, c.blue * 2.0 - 1.0)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Turn a canvas holding distances into a gray image, black at distance 0
+    /// and white at `max_depth` and beyond.
+    pub fn encode_depth(&self, max_depth: f64) -> Canvas {
+        self.map(|c| {
+            let value = (c.red / max_depth).clamp(0.0, 1.0);
+            Color::new(value, value, value)
+        })
+    }
+
+    /// Put the `left` and `right` canvases next to each other in a single
+    /// canvas, such as the two views of a stereo pair.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the canvases differ in height.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Canvas, Color};
+    ///
+    /// let left = Canvas::parallel_fill(2, 3, |_, _| Color::new(1.0, 0.0, 0.0));
+    /// let right = Canvas::parallel_fill(4, 3, |_, _| Color::new(0.0, 0.0, 1.0));
+    /// let both = Canvas::side_by_side(&left, &right);
+    ///
+    /// assert_eq!((both.width, both.height), (6, 3));
+    /// assert_eq!(both.pixel_at(1, 2), Color::new(1.0, 0.0, 0.0));
+    /// assert_eq!(both.pixel_at(2, 2), Color::new(0.0, 0.0, 1.0));
+    /// ```
+    pub fn side_by_side(left: &Canvas, right: &Canvas) -> Canvas {
+        assert_eq!(
+            left.height, right.height,
+            "canvases put side by side must have the same height"
+        );
+
+        let pixels = left
+            .pixels
+            .chunks(left.width)
+            .zip(right.pixels.chunks(right.width))
+            .flat_map(|(l, r)| l.iter().chain(r.iter()).copied())
+            .collect();
+        Canvas {
+            width: left.width + right.width,
+            height: left.height,
+            pixels,
+        }
+    }
+
+    /// A canvas of the same size with `f` applied to every pixel.
+    fn map<F: Fn(Color) -> Color>(&self, f: F) -> Canvas {
+        Canvas {
+            width: self.width,
+            height: self.height,
+            pixels: self.pixels.iter().map(|c| f(*c)).collect(),
+        }
+    }
+
     pub fn canvas_to_rgb_buffer(&self) -> Vec<u8> {
         let mut buffer = Vec::with_capacity(self.pixels.len() * 3);
         for color in &self.pixels {
@@ -112,6 +356,25 @@ impl Canvas {
         buffer
     }
 
+    /// Write the pixels to `fd` as raw bytes, three per pixel in red, green,
+    /// blue order, row by row from the top left. There is no header, so the
+    /// reader must already know the size of the image.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Canvas, Color};
+    ///
+    /// let canvas = Canvas::parallel_fill(2, 1, |x, _| Color::new(x as f64, 0.0, 1.0));
+    /// let mut bytes = Vec::new();
+    /// canvas.write_to_fd(&mut bytes).unwrap();
+    ///
+    /// assert_eq!(bytes, vec![0, 0, 255, 255, 0, 255]);
+    /// ```
+    pub fn write_to_fd(&self, fd: &mut impl Write) -> io::Result<()> {
+        fd.write_all(&self.canvas_to_rgb_buffer())
+    }
+
     /// Returns the [`Color`] of a pixel on the canvas at the specified `x` and
     /// `y` coordinates.
     ///
@@ -242,4 +505,186 @@ mod tests {
             split[6]
         );
     }
+
+    #[test]
+    fn a_parallel_fill_matches_a_serial_fill() {
+        use crate::{Camera, Point, Transformation, Vector, World};
+        use std::f64::consts::PI;
+
+        let w = World::default();
+        let mut c = Camera::new(21, 13, PI / 2.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        let parallel = Canvas::parallel_fill(21, 13, |x, y| w.color_at(c.ray_for_pixel(x, y), 5));
+        let serial = c.render(&w);
+
+        for y in 0..13 {
+            for x in 0..21 {
+                assert_eq!(parallel.pixel_at(x, y), serial.pixel_at(x, y));
+            }
+        }
+    }
+
+    /// Compare filling a large canvas pixel by pixel in parallel with filling
+    /// it row by row, both serially and with the rows spread over the cores,
+    /// run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn benchmark_parallel_fill_against_rows() {
+        use std::time::Instant;
+
+        let size = 2048;
+        let shade = |x: usize, y: usize| {
+            let (u, v) = (x as f64 / size as f64, y as f64 / size as f64);
+            let mut value = 0.0;
+            for i in 1..64 {
+                value += (u * i as f64).sin() * (v * i as f64).cos() / i as f64;
+            }
+            Color::new(value, u, v)
+        };
+
+        let start = Instant::now();
+        let mut serial = Canvas::new(size, size);
+        for y in 0..size {
+            for x in 0..size {
+                serial.write_pixel(x, y, shade(x, y));
+            }
+        }
+        let serial_time = start.elapsed();
+
+        // `chunks_mut` gives the rows to rayon, one row per task
+        let start = Instant::now();
+        let mut rows = Canvas::new(size, size);
+        rows.chunks_mut(1).enumerate().for_each(|(y, row)| {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = shade(x, y);
+            }
+        });
+        let rows_time = start.elapsed();
+
+        let start = Instant::now();
+        let pixels = Canvas::parallel_fill(size, size, shade);
+        let pixels_time = start.elapsed();
+
+        println!(
+            "serial rows: {:?}, parallel rows: {:?}, parallel pixels: {:?}",
+            serial_time, rows_time, pixels_time
+        );
+        for (x, y) in [(0, 0), (size / 3, size / 2), (size - 1, size - 1)] {
+            assert_eq!(serial.pixel_at(x, y), pixels.pixel_at(x, y));
+            assert_eq!(rows.pixel_at(x, y), pixels.pixel_at(x, y));
+        }
+    }
+
+    #[test]
+    fn the_histogram_of_a_white_canvas() {
+        let c = Canvas::parallel_fill(255, 257, |_, _| Color::new(1.0, 1.0, 1.0));
+        let histogram = c.histogram();
+
+        for channel in histogram.iter() {
+            assert_eq!(channel[255], 65535);
+            assert_eq!(channel.iter().sum::<u32>(), 65535);
+        }
+    }
+
+    #[test]
+    fn auto_levels_stretches_a_grey_range() {
+        let mut c = Canvas::parallel_fill(10, 10, |x, y| {
+            let v = 0.25 + 0.5 * (x + y * 10) as f64 / 99.0;
+            Color::new(v, v, v)
+        });
+        c.auto_levels();
+
+        let first = c.pixel_at(0, 0);
+        let last = c.pixel_at(9, 9);
+        assert_eq!(first, Color::new(0.0, 0.0, 0.0));
+        assert_eq!(last, Color::new(1.0, 1.0, 1.0));
+        let middle = c.pixel_at(5, 4).red;
+        assert!(middle > 0.45 && middle < 0.55);
+    }
+
+    #[test]
+    fn auto_levels_leaves_a_flat_channel() {
+        let mut c = Canvas::parallel_fill(4, 4, |x, _| Color::new(x as f64 / 3.0, 0.5, 0.0));
+        c.auto_levels();
+
+        assert_eq!(c.pixel_at(1, 2), Color::new(1.0 / 3.0, 0.5, 0.0));
+    }
+
+    #[test]
+    fn encoding_and_decoding_a_normal() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(1, 0, Color::new(0.0, 1.0, 0.0));
+        c.write_pixel(0, 0, Color::new(0.6, 0.0, -0.8));
+
+        let decoded = c.encode_normals().decode_normals();
+
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0][1], Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(decoded[0][0], Vector::new(0.6, 0.0, -0.8));
+    }
+
+    #[test]
+    fn encoding_depth_as_gray() {
+        let mut c = Canvas::new(3, 1);
+        c.write_pixel(0, 0, Color::new(0.0, 0.0, 0.0));
+        c.write_pixel(1, 0, Color::new(10.0, 10.0, 10.0));
+        c.write_pixel(
+            2,
+            0,
+            Color::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        );
+
+        let encoded = c.encode_depth(10.0);
+
+        assert_eq!(encoded.pixel_at(0, 0), Color::new(0.0, 0.0, 0.0));
+        assert_eq!(encoded.pixel_at(1, 0), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(encoded.pixel_at(2, 0), Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn denoising_a_flat_canvas_changes_nothing() {
+        let c = Canvas::parallel_fill(6, 5, |_, _| Color::new(0.2, 0.4, 0.6));
+        let denoised = c.denoise_nlm(0.1, 1, 2);
+
+        for y in 0..5 {
+            for x in 0..6 {
+                assert_eq!(denoised.pixel_at(x, y), Color::new(0.2, 0.4, 0.6));
+            }
+        }
+    }
+
+    #[test]
+    fn denoising_smooths_noise_and_keeps_edges() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut c = Canvas::new(16, 16);
+        for y in 0..16 {
+            for x in 0..16 {
+                let value = if x < 8 { 0.2 } else { 0.8 } + rng.gen_range(-0.1..0.1);
+                c.write_pixel(x, y, Color::new(value, value, value));
+            }
+        }
+        let denoised = c.denoise_nlm(0.15, 1, 3);
+
+        // standard deviation of the red channel of the left half
+        let deviation = |canvas: &Canvas| {
+            let values: Vec<f64> = (0..16)
+                .flat_map(|y| (0..8).map(move |x| (x, y)))
+                .map(|(x, y)| canvas.pixel_at(x, y).red)
+                .collect();
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64).sqrt()
+        };
+        assert!(deviation(&denoised) < deviation(&c) / 2.0);
+
+        for y in 0..16 {
+            assert!(denoised.pixel_at(7, y).red < 0.35);
+            assert!(denoised.pixel_at(8, y).red > 0.65);
+        }
+    }
 }
@@ -96,6 +96,184 @@ impl Color {
         ]
     }
 
+    /// The color `t` of the way from `self` to `other`, mixing each of red,
+    /// green and blue. `t` of 0.0 gives `self` and 1.0 gives `other`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::Color;
+    ///
+    /// let black = Color::new(0.0, 0.0, 0.0);
+    /// let white = Color::new(1.0, 1.0, 1.0);
+    ///
+    /// assert_eq!(black.lerp(white, 0.25), Color::new(0.25, 0.25, 0.25));
+    /// ```
+    pub fn lerp(self, other: Color, t: f64) -> Color {
+        self * (1.0 - t) + other * t
+    }
+
+    /// The color `t` of the way from `self` to `other`, mixing hue,
+    /// saturation and lightness. The hue turns the short way around the
+    /// color wheel, so going between two saturated colors does not fade
+    /// through grey like [`Color::lerp`] does.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::Color;
+    ///
+    /// let red = Color::new(1.0, 0.0, 0.0);
+    /// let yellow = Color::new(1.0, 1.0, 0.0);
+    ///
+    /// assert_eq!(red.lerp_hsl(yellow, 0.5), Color::new(1.0, 0.5, 0.0));
+    /// ```
+    pub fn lerp_hsl(self, other: Color, t: f64) -> Color {
+        let (h1, s1, l1) = self.to_hsl();
+        let (h2, s2, l2) = other.to_hsl();
+
+        // a grey has no hue of its own, so it takes the hue of the other
+        let (h1, h2) = match (s1 > 0.0, s2 > 0.0) {
+            (false, true) => (h2, h2),
+            (true, false) => (h1, h1),
+            _ => (h1, h2),
+        };
+        let mut turn = h2 - h1;
+        if turn > 180.0 {
+            turn -= 360.0;
+        } else if turn < -180.0 {
+            turn += 360.0;
+        }
+
+        Color::from_hsl(h1 + turn * t, s1 + (s2 - s1) * t, l1 + (l2 - l1) * t)
+    }
+
+    /// Convert the color to hue, saturation and lightness. The hue is in
+    /// degrees from 0.0 to 360.0, starting at red. For colors with every
+    /// component from 0.0 to 1.0 the saturation and lightness go from 0.0 to
+    /// 1.0 as well. Greys have no hue, so they give a hue and saturation of
+    /// 0.0.
+    ///
+    /// Brighter colors can have a lightness above 1.0 and a saturation above
+    /// 1.0. From a lightness of 1.0 up, where the usual saturation would be
+    /// divided by zero or less, the saturation is the difference between the
+    /// largest and the smallest component.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::Color;
+    ///
+    /// assert_eq!(Color::new(0.0, 0.5, 0.5).to_hsl(), (180.0, 1.0, 0.25));
+    /// assert_eq!(Color::new(0.3, 0.3, 0.3).to_hsl(), (0.0, 0.0, 0.3));
+    /// assert_eq!(Color::new(1.5, 0.5, 0.5).to_hsl(), (0.0, 1.0, 1.0));
+    /// ```
+    pub fn to_hsl(self) -> (f64, f64, f64) {
+        let max = self.red.max(self.green).max(self.blue);
+        let min = self.red.min(self.green).min(self.blue);
+        let lightness = (max + min) / 2.0;
+        let chroma = max - min;
+        if chroma <= 0.0 {
+            return (0.0, 0.0, lightness);
+        }
+
+        let saturation = chroma / hsl_chroma_scale(lightness);
+        let hue = if max == self.red {
+            ((self.green - self.blue) / chroma).rem_euclid(6.0)
+        } else if max == self.green {
+            (self.blue - self.red) / chroma + 2.0
+        } else {
+            (self.red - self.green) / chroma + 4.0
+        };
+
+        (hue * 60.0, saturation, lightness)
+    }
+
+    /// Create a color from the hue `h` in degrees, the saturation `s` and the
+    /// lightness `l`, see [`Color::to_hsl`]. Hues outside of 0.0 to 360.0
+    /// wrap around the color wheel.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::Color;
+    ///
+    /// assert_eq!(Color::from_hsl(60.0, 1.0, 0.5), Color::new(1.0, 1.0, 0.0));
+    /// assert_eq!(Color::from_hsl(-60.0, 1.0, 0.5), Color::new(1.0, 0.0, 1.0));
+    /// ```
+    pub fn from_hsl(h: f64, s: f64, l: f64) -> Color {
+        let chroma = hsl_chroma_scale(l) * s;
+        let sector = h.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+        let (r, g, b) = match sector as usize {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = l - chroma / 2.0;
+
+        Color::new(r + m, g + m, b + m)
+    }
+
+    /// Convert the color to the CIE XYZ color space. The red, green and blue
+    /// of the color are taken as linear sRGB, the way the renderer adds up
+    /// light, with white at the D65 white point.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::Color;
+    ///
+    /// let (_, y, _) = Color::new(1.0, 1.0, 1.0).to_xyz();
+    ///
+    /// assert!((y - 1.0).abs() < 1e-4);
+    /// ```
+    pub fn to_xyz(self) -> (f64, f64, f64) {
+        let (r, g, b) = (self.red, self.green, self.blue);
+        (
+            0.4124 * r + 0.3576 * g + 0.1805 * b,
+            0.2126 * r + 0.7152 * g + 0.0722 * b,
+            0.0193 * r + 0.1192 * g + 0.9505 * b,
+        )
+    }
+
+    /// Create a linear sRGB color from the CIE XYZ coordinates `x`, `y` and
+    /// `z`, the opposite of [`Color::to_xyz`]. Colors too saturated for sRGB
+    /// get negative channels.
+    pub fn from_xyz(x: f64, y: f64, z: f64) -> Color {
+        Color::new(
+            3.2406 * x - 1.5372 * y - 0.4986 * z,
+            -0.9689 * x + 1.8758 * y + 0.0415 * z,
+            0.0557 * x - 0.2040 * y + 1.0570 * z,
+        )
+    }
+
+    /// The color of light of a single wavelength of `nm` nanometers, from
+    /// the CIE 1931 color matching functions, with the brightest green at
+    /// 555 nm having a luminance of 1.0. Wavelengths outside of the visible
+    /// range from about 380 to 780 nm are close to black.
+    ///
+    /// Most pure wavelengths are more saturated than sRGB can show, so some
+    /// channels of the color are negative. Adding up many wavelengths, like
+    /// a spectral renderer does, gives colors inside of the range again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::Color;
+    ///
+    /// let red = Color::from_wavelength(650.0);
+    ///
+    /// assert!(red.red > red.green && red.red > red.blue);
+    /// ```
+    pub fn from_wavelength(nm: f64) -> Color {
+        let (x, y, z) = color_matching(nm);
+        Color::from_xyz(x, y, z)
+    }
+
     fn clip_color(color: f64) -> f64 {
         if color < 0.0 {
             0.0
@@ -107,6 +285,43 @@ impl Color {
     }
 }
 
+/// The largest chroma of a color with the lightness `l` and a saturation of
+/// 1.0, see [`Color::to_hsl`]. It is 1.0 for lightnesses of HDR colors where
+/// it would otherwise be zero or less.
+fn hsl_chroma_scale(l: f64) -> f64 {
+    let scale = 1.0 - (2.0 * l - 1.0).abs();
+    if scale > 0.0 {
+        scale
+    } else {
+        1.0
+    }
+}
+
+/// The CIE 1931 standard observer color matching functions `x̄`, `ȳ` and `z̄`
+/// at the wavelength of `nm` nanometers, using the multi-lobe Gaussian fit
+/// of Wyman, Sloan and Shirley, "Simple Analytic Approximations to the CIE
+/// XYZ Color Matching Functions" (2013).
+pub(crate) fn color_matching(nm: f64) -> (f64, f64, f64) {
+    // a Gaussian with a different width on each side of its peak
+    let g = |mu: f64, below: f64, above: f64| {
+        let t = (nm - mu) / if nm < mu { below } else { above };
+        (-0.5 * t * t).exp()
+    };
+
+    (
+        1.056 * g(599.8, 37.9, 31.0) + 0.362 * g(442.0, 16.0, 26.7) - 0.065 * g(501.1, 20.4, 26.2),
+        0.821 * g(568.8, 46.9, 40.5) + 0.286 * g(530.9, 16.3, 31.1),
+        1.217 * g(437.0, 11.8, 36.0) + 0.681 * g(459.0, 26.0, 13.8),
+    )
+}
+
+impl From<[f64; 3]> for Color {
+    /// Create a `Color` from an array of `[red, green, blue]`.
+    fn from(rgb: [f64; 3]) -> Self {
+        Color::new(rgb[0], rgb[1], rgb[2])
+    }
+}
+
 impl Sub for Color {
     type Output = Self;
 
@@ -249,4 +464,152 @@ mod tests {
 
         assert_eq!(c1 * c2, Color::new(0.9, 0.2, 0.04));
     }
+
+    #[test]
+    fn halfway_from_black_to_white_is_grey() {
+        let black = Color::new(0.0, 0.0, 0.0);
+        let white = Color::new(1.0, 1.0, 1.0);
+
+        assert_eq!(black.lerp(white, 0.5), Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn interpolating_ends_at_the_endpoints() {
+        let a = Color::new(0.9, 0.2, 0.4);
+        let b = Color::new(0.1, 0.7, 0.3);
+
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp_hsl(b, 0.0), a);
+        assert_eq!(a.lerp_hsl(b, 1.0), b);
+    }
+
+    #[test]
+    fn interpolating_from_red_to_blue_in_hsl_goes_through_purple() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let blue = Color::new(0.0, 0.0, 1.0);
+
+        let rgb = red.lerp(blue, 0.5);
+        let hsl = red.lerp_hsl(blue, 0.5);
+
+        assert_eq!(rgb, Color::new(0.5, 0.0, 0.5));
+        assert_eq!(hsl, Color::new(1.0, 0.0, 1.0));
+        // every step keeps full saturation instead of fading
+        for i in 0..=10 {
+            let c = red.lerp_hsl(blue, i as f64 / 10.0);
+            assert!(float_eq(c.green, 0.0));
+            assert!(float_eq(c.red.max(c.blue), 1.0));
+        }
+    }
+
+    #[test]
+    fn interpolating_from_grey_in_hsl_keeps_the_hue_of_the_color() {
+        let grey = Color::new(0.5, 0.5, 0.5);
+        let green = Color::new(0.0, 1.0, 0.0);
+
+        assert_eq!(grey.lerp_hsl(green, 0.5), Color::new(0.25, 0.75, 0.25));
+    }
+
+    #[test]
+    fn the_primary_colors_from_hsl() {
+        assert_eq!(Color::from_hsl(0.0, 1.0, 0.5), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(Color::from_hsl(120.0, 1.0, 0.5), Color::new(0.0, 1.0, 0.0));
+        assert_eq!(Color::from_hsl(240.0, 1.0, 0.5), Color::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn greys_have_no_saturation() {
+        assert_eq!(Color::new(0.0, 0.0, 0.0).to_hsl(), (0.0, 0.0, 0.0));
+        assert_eq!(Color::new(1.0, 1.0, 1.0).to_hsl(), (0.0, 0.0, 1.0));
+        assert_eq!(Color::from_hsl(200.0, 0.0, 0.4), Color::new(0.4, 0.4, 0.4));
+    }
+
+    #[test]
+    fn hsl_round_trips() {
+        for &(h, s, l) in &[
+            (0.0, 1.0, 0.5),
+            (30.0, 0.25, 0.75),
+            (95.0, 0.6, 0.2),
+            (200.0, 0.9, 0.55),
+            (310.0, 0.4, 0.9),
+        ] {
+            let (h2, s2, l2) = Color::from_hsl(h, s, l).to_hsl();
+
+            assert!(float_eq(h, h2), "{} {}", h, h2);
+            assert!(float_eq(s, s2), "{} {}", s, s2);
+            assert!(float_eq(l, l2), "{} {}", l, l2);
+        }
+    }
+
+    #[test]
+    fn hdr_colors_round_trip_through_hsl() {
+        for &c in &[
+            Color::new(1.5, 0.5, 0.5),
+            Color::new(2.0, 1.0, 3.0),
+            Color::new(0.9, 1.1, 1.0),
+        ] {
+            let (h, s, l) = c.to_hsl();
+
+            assert!(s.is_finite(), "{:?}", c);
+            assert_eq!(Color::from_hsl(h, s, l), c);
+            assert_eq!(c.lerp_hsl(Color::new(0.2, 0.4, 0.6), 0.0), c);
+        }
+    }
+
+    #[test]
+    fn the_srgb_primaries_in_xyz() {
+        let xyz = |c: Color| {
+            let (x, y, z) = c.to_xyz();
+            Color::new(x, y, z)
+        };
+
+        assert_eq!(
+            xyz(Color::new(1.0, 0.0, 0.0)),
+            Color::new(0.4124, 0.2126, 0.0193)
+        );
+        assert_eq!(
+            xyz(Color::new(0.0, 1.0, 0.0)),
+            Color::new(0.3576, 0.7152, 0.1192)
+        );
+        assert_eq!(
+            xyz(Color::new(0.0, 0.0, 1.0)),
+            Color::new(0.1805, 0.0722, 0.9505)
+        );
+        // the D65 white point
+        assert_eq!(
+            xyz(Color::new(1.0, 1.0, 1.0)),
+            Color::new(0.9505, 1.0, 1.089)
+        );
+    }
+
+    #[test]
+    fn xyz_round_trips() {
+        let c = Color::new(0.2, 0.7, 0.4);
+        let (x, y, z) = c.to_xyz();
+
+        let back = Color::from_xyz(x, y, z);
+
+        assert!((back.red - c.red).abs() < 1e-3);
+        assert!((back.green - c.green).abs() < 1e-3);
+        assert!((back.blue - c.blue).abs() < 1e-3);
+    }
+
+    #[test]
+    fn colors_of_wavelengths() {
+        let strongest = |c: Color| {
+            if c.red >= c.green && c.red >= c.blue {
+                "red"
+            } else if c.green >= c.blue {
+                "green"
+            } else {
+                "blue"
+            }
+        };
+
+        assert_eq!(strongest(Color::from_wavelength(450.0)), "blue");
+        assert_eq!(strongest(Color::from_wavelength(530.0)), "green");
+        assert_eq!(strongest(Color::from_wavelength(650.0)), "red");
+        assert!((color_matching(555.0).1 - 1.0).abs() < 0.01);
+        assert!(Color::from_wavelength(900.0).to_xyz().1.abs() < 1e-6);
+    }
 }
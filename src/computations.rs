@@ -1,6 +1,6 @@
 #[allow(unused_imports)]
 use crate::Intersection;
-use crate::{shapes::Shape, Point, Vector};
+use crate::{shapes::Shape, Point, Ray, Vector};
 
 /// Encapsulating precomputed information relating to an [`Intersection`].
 pub struct Computations<'a> {
@@ -33,24 +33,63 @@ pub struct Computations<'a> {
 }
 
 impl Computations<'_> {
+    /// The Schlick approximation of the fraction of light reflected at the
+    /// intersection, see [`Computations::fresnel_reflectance`].
     pub fn schlick(&self) -> f64 {
-        // find the cosine of the angle between the eye and normal vector
-        let mut cos = self.eyev.dot(self.normalv);
-
-        // total internal reflection can only occur if n1 > n2
-        if self.n1 > self.n2 {
-            let n = self.n1 / self.n2;
-            let sin2_t = n.powf(2.0) * (1.0 - cos.powf(2.0));
-            if sin2_t > 1.0 {
-                return 1.0;
-            }
-
-            // computer cosine of theta_t using trig identity
-            // when n1 > n2 use cos(theta_t) instead
-            cos = (1.0 - sin2_t).sqrt();
+        self.fresnel_reflectance()
+    }
+
+    /// Fraction of the light reflected at the intersection, the rest being
+    /// refracted into the next material. All of it is reflected under total
+    /// internal reflection, otherwise the fraction is the Schlick
+    /// approximation of the Fresnel equations.
+    ///
+    /// Schlick, Christophe "An Inexpensive BRDF Model for Physically-based
+    /// Rendering" (1994)
+    pub fn fresnel_reflectance(&self) -> f64 {
+        let cos_t = match self.cos_t() {
+            Some(cos_t) => cos_t,
+            None => return 1.0,
+        };
+
+        // when n1 > n2 the angle of the refracted ray is the larger one
+        let cos = if self.n1 > self.n2 {
+            cos_t
+        } else {
+            self.eyev.dot(self.normalv)
+        };
+        let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powi(2);
+        r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+    }
+
+    /// Cosine of the angle between the refracted ray and the inverted normal
+    /// by Snell's law, or `None` under total internal reflection.
+    fn cos_t(&self) -> Option<f64> {
+        let n_ratio = self.n1 / self.n2;
+        let cos_i = self.eyev.dot(self.normalv);
+        let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
+
+        if sin2_t > 1.0 {
+            None
+        } else {
+            Some((1.0 - sin2_t).sqrt())
         }
+    }
+
+    /// The ray reflected off the surface at the intersection.
+    pub fn reflect_ray(&self) -> Ray {
+        Ray::new(self.over_point, self.reflectv)
+    }
+
+    /// The ray bent into the next material by Snell's law at the
+    /// intersection, or `None` when the light is totally reflected.
+    pub fn refract_ray(&self) -> Option<Ray> {
+        let n_ratio = self.n1 / self.n2;
+        let cos_i = self.eyev.dot(self.normalv);
 
-        let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powf(2.0);
-        r0 + (1.0 - r0) * (1.0 - cos).powf(5.0)
+        self.cos_t().map(|cos_t| {
+            let direction = self.normalv * (n_ratio * cos_i - cos_t) - self.eyev * n_ratio;
+            Ray::new(self.under_point, direction)
+        })
     }
 }
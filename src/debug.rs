@@ -0,0 +1,296 @@
+use std::fmt;
+
+use uuid::Uuid;
+
+use crate::{Camera, Color, Colors, Intersection, Point, Ray, Vector, World};
+
+/// Number of bounces followed, the same as when rendering with a [`Camera`].
+const MAX_DEPTH: usize = 5;
+
+/// What a ray of a [`RayTree`] hit.
+#[derive(Debug, Clone)]
+pub struct HitInfo {
+    /// Id of the object hit.
+    pub object: Uuid,
+    /// Distance along the ray to the hit.
+    pub t: f64,
+    /// Point in world space of the hit.
+    pub point: Point,
+    /// Normal of the surface at the hit, facing the ray.
+    pub normalv: Vector,
+    /// Whether the point `light` of the world is blocked at the hit, `false`
+    /// for a world without one.
+    pub in_shadow: bool,
+    /// Color lit directly by the light sources, without reflections and
+    /// refractions.
+    pub surface: Color,
+}
+
+/// A ray followed through a [`World`] along with the rays it spawned when
+/// bouncing off or passing through the objects it hit.
+#[derive(Debug, Clone)]
+pub struct RayTree {
+    /// The ray cast.
+    pub ray: Ray,
+    /// What the ray hit, `None` if it missed everything.
+    pub hit: Option<HitInfo>,
+    /// Color seen along the ray, including the colors of the subtrees.
+    pub color: Color,
+    /// The ray reflected at the hit, if the object is reflective.
+    pub reflected: Option<Box<RayTree>>,
+    /// The ray refracted at the hit, if the object is transparent.
+    pub refracted: Option<Box<RayTree>>,
+}
+
+impl RayTree {
+    /// Number of levels in the tree, 1 for a ray without bounces.
+    pub fn depth(&self) -> usize {
+        let reflected = self.reflected.as_ref().map_or(0, |t| t.depth());
+        let refracted = self.refracted.as_ref().map_or(0, |t| t.depth());
+        1 + reflected.max(refracted)
+    }
+
+    fn write_indented(&self, f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
+        let indent = "  ".repeat(level);
+        let color = |c: Color| format!("({:.5}, {:.5}, {:.5})", c.red, c.green, c.blue);
+
+        writeln!(f, "{}ray from {}", indent, self.ray.origin)?;
+        writeln!(f, "{}  towards {}", indent, self.ray.direction)?;
+        match &self.hit {
+            Some(hit) => {
+                writeln!(f, "{}  hit {} at t = {:.5}", indent, hit.object, hit.t)?;
+                writeln!(f, "{}  point {}", indent, hit.point)?;
+                writeln!(f, "{}  normal {}", indent, hit.normalv)?;
+                if hit.in_shadow {
+                    writeln!(f, "{}  in shadow", indent)?;
+                }
+                writeln!(f, "{}  surface {}", indent, color(hit.surface))?;
+            }
+            None => writeln!(f, "{}  missed", indent)?,
+        }
+        writeln!(f, "{}  color {}", indent, color(self.color))?;
+
+        if let Some(reflected) = &self.reflected {
+            writeln!(f, "{}  reflected:", indent)?;
+            reflected.write_indented(f, level + 2)?;
+        }
+        if let Some(refracted) = &self.refracted {
+            writeln!(f, "{}  refracted:", indent)?;
+            refracted.write_indented(f, level + 2)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for RayTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_indented(f, 0)
+    }
+}
+
+/// Records every ray followed to find the color of a pixel, to inspect which
+/// objects each bounce hit.
+pub struct RayDebugger {}
+
+impl RayDebugger {
+    /// Follow the ray through the pixel at `px` and `py` of the `camera` into
+    /// the `world`, recording the tree of reflected and refracted rays. The
+    /// `color` of the tree is the color the pixel gets when rendering, so a
+    /// pixel outside of the circle of a fisheye lens is black and hits
+    /// nothing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Camera, RayDebugger, World};
+    /// use std::f64::consts::PI;
+    ///
+    /// let w = World::default();
+    /// let c = Camera::new(11, 11, PI / 2.0);
+    /// let tree = RayDebugger::trace_pixel(&c, &w, 5, 5);
+    ///
+    /// assert!(tree.hit.is_some());
+    /// assert_eq!(tree.color, c.render(&w).pixel_at(5, 5));
+    /// println!("{}", tree);
+    /// ```
+    pub fn trace_pixel(camera: &Camera, world: &World, px: usize, py: usize) -> RayTree {
+        match camera.visible_ray_for_pixel(px, py) {
+            Some(ray) => RayDebugger::trace(world, ray, MAX_DEPTH),
+            None => RayTree {
+                ray: camera.ray_for_pixel(px, py),
+                hit: None,
+                color: Colors::BLACK,
+                reflected: None,
+                refracted: None,
+            },
+        }
+    }
+
+    /// Follow `ray` the way [`World::color_at`] does, keeping every ray cast.
+    fn trace(world: &World, ray: Ray, remaining: usize) -> RayTree {
+        let miss = RayTree {
+            ray,
+            hit: None,
+            color: world.background,
+            reflected: None,
+            refracted: None,
+        };
+
+        let xs = match world.intersect_world(ray) {
+            Some(xs) => xs,
+            None => return miss,
+        };
+        let i = match Intersection::hit(&xs) {
+            Some(i) => i,
+            None => return miss,
+        };
+
+        let comps = i.prepare_computations(ray, &xs, Some(world));
+        let in_shadow = world
+            .light
+            .is_some_and(|light| world.is_shadow_between(comps.over_point, light.position));
+        let material = world.get_object_material(comps.object);
+
+        let reflected = if material.reflective > 0.0 && remaining > 0 {
+            let tree = RayDebugger::trace(world, comps.reflect_ray(), remaining - 1);
+            Some(Box::new(tree))
+        } else {
+            None
+        };
+        let refracted = if material.transparency > 0.0 && remaining > 0 {
+            comps
+                .refract_ray()
+                .map(|r| Box::new(RayDebugger::trace(world, r, remaining - 1)))
+        } else {
+            None
+        };
+
+        // the colors come from the world itself so they match the render
+        let surface = world.surface_color(&comps);
+        let color = world.shade_hit(&comps, remaining);
+
+        RayTree {
+            ray,
+            hit: Some(HitInfo {
+                object: comps.object.id(),
+                t: comps.t,
+                point: comps.point,
+                normalv: comps.normalv,
+                in_shadow,
+                surface,
+            }),
+            color,
+            reflected,
+            refracted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shapes::{Plane, Shape, Sphere},
+        AmbientOcclusion, DirectionalLight, PointLight, Transformation,
+    };
+    use std::f64::consts::PI;
+
+    /// A world with a single `sphere` in front of a camera looking at it.
+    fn scene(sphere: Sphere) -> (World, Camera) {
+        let mut w = World::new();
+        w.light = Some(PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        w.add_object(Box::new(sphere));
+
+        let mut c = Camera::new(11, 11, PI / 2.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        (w, c)
+    }
+
+    #[test]
+    fn a_simple_hit_has_a_single_level() {
+        let s = Sphere::new();
+        let id = s.id();
+        let (w, c) = scene(s);
+        let tree = RayDebugger::trace_pixel(&c, &w, 5, 5);
+
+        assert_eq!(tree.depth(), 1);
+        assert_eq!(tree.hit.as_ref().map(|h| h.object), Some(id));
+        assert_eq!(tree.hit.as_ref().map(|h| h.t), Some(4.0));
+        assert_eq!(tree.color, c.render(&w).pixel_at(5, 5));
+    }
+
+    #[test]
+    fn a_mirror_ball_has_two_levels() {
+        let mut s = Sphere::new();
+        s.material.reflective = 1.0;
+        let (w, c) = scene(s);
+        let tree = RayDebugger::trace_pixel(&c, &w, 5, 5);
+
+        assert_eq!(tree.depth(), 2);
+        assert!(tree.refracted.is_none());
+        let reflected = tree.reflected.as_ref().expect("No reflected ray");
+        assert!(reflected.hit.is_none());
+        assert_eq!(tree.color, c.render(&w).pixel_at(5, 5));
+    }
+
+    #[test]
+    fn a_glass_ball_has_reflected_and_refracted_rays() {
+        let mut s = Sphere::new();
+        s.material.reflective = 0.9;
+        s.material.transparency = 1.0;
+        s.material.refractive_index = 1.5;
+        let (w, c) = scene(s);
+        let tree = RayDebugger::trace_pixel(&c, &w, 5, 5);
+
+        assert!(tree.reflected.is_some());
+        let refracted = tree.refracted.as_ref().expect("No refracted ray");
+        // the refracted ray leaves through the back of the ball
+        let t = refracted.hit.as_ref().map_or(0.0, |h| h.t);
+        assert!((t - 2.0).abs() < 0.01);
+        assert_eq!(tree.color, c.render(&w).pixel_at(5, 5));
+        assert!(tree.to_string().contains("refracted:"));
+    }
+
+    #[test]
+    fn ambient_occlusion_is_the_same_as_when_rendering() {
+        let mut s = Sphere::new();
+        s.material.ambient = 0.5;
+        let (mut w, c) = scene(s);
+        let mut floor = Plane::new();
+        floor.transform = Transformation::new().translate(0.0, -1.0, 0.0).build();
+        w.add_object(Box::new(floor));
+        w.ambient_occlusion = Some(AmbientOcclusion {
+            samples: 16,
+            max_distance: 2.0,
+            seed: 7,
+        });
+        let image = c.render(&w);
+
+        for y in 0..c.vsize {
+            let tree = RayDebugger::trace_pixel(&c, &w, 5, y);
+            assert_eq!(tree.color, image.pixel_at(5, y), "{}", y);
+        }
+    }
+
+    #[test]
+    fn a_world_lit_only_by_a_directional_light_can_be_traced() {
+        let (mut w, c) = scene(Sphere::new());
+        w.light = None;
+        w.directional_lights.push(DirectionalLight::new(
+            Vector::new(0.0, 0.0, 1.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        let tree = RayDebugger::trace_pixel(&c, &w, 5, 5);
+
+        assert!(tree.hit.as_ref().is_some_and(|h| !h.in_shadow));
+        assert_eq!(tree.color, c.render(&w).pixel_at(5, 5));
+        assert_eq!(tree.hit.map(|h| h.surface), Some(tree.color));
+    }
+}
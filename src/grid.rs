@@ -0,0 +1,366 @@
+use std::sync::Arc;
+
+use crate::{shapes::Shape, Bounds, Intersection, Point, Ray, Vector, EPSILON};
+
+/// A spatial index over shapes, splitting the box around them into a
+/// uniform grid of cells so a ray only tests the shapes in the cells it
+/// passes through.
+///
+/// Every shape is kept in each cell its box overlaps. A ray steps from cell
+/// to cell along its path with a 3-D digital differential analyzer, which
+/// finds the next cell by comparing the distances to the next cell wall
+/// along each axis. Shapes without bounds, such as planes, are kept outside
+/// of the grid and tested against every ray.
+///
+/// A grid suits shapes of about the same size spread evenly through the
+/// scene, while a [`crate::KdTree`] adapts better to shapes bunched together.
+#[derive(Debug, Clone)]
+pub struct Grid {
+    shapes: Vec<Arc<dyn Shape>>,
+    unbounded: Vec<usize>,
+    bounds: Bounds,
+    resolution: [usize; 3],
+    cells: Vec<Vec<usize>>,
+}
+
+/// The components of `p` along the x, y and z axes.
+fn components(p: Vector) -> [f64; 3] {
+    [p.x, p.y, p.z]
+}
+
+impl Grid {
+    /// Build a grid of `resolution` cells along the x, y and z axes over the
+    /// `shapes`, using the box around each of them in the space of the world.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resolution along any axis is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::{Shape, Sphere}, Grid, Point, Ray, Vector};
+    /// use std::sync::Arc;
+    ///
+    /// let shapes: Vec<Arc<dyn Shape>> = vec![Arc::new(Sphere::new())];
+    /// let grid = Grid::build(&shapes, [4, 4, 4]);
+    /// let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    ///
+    /// assert_eq!(grid.intersect(r).unwrap().len(), 2);
+    /// ```
+    pub fn build(shapes: &[Arc<dyn Shape>], resolution: [usize; 3]) -> Grid {
+        assert!(
+            resolution.iter().all(|r| *r > 0),
+            "a grid needs at least one cell along each axis"
+        );
+
+        let shape_bounds: Vec<Bounds> = shapes.iter().map(|s| s.parent_space_bounds()).collect();
+        let (bounded, unbounded): (Vec<usize>, Vec<usize>) = (0..shapes.len())
+            .filter(|i| !shape_bounds[*i].is_empty())
+            .partition(|i| shape_bounds[*i].is_finite());
+
+        let padding = Vector::new(EPSILON, EPSILON, EPSILON);
+        let bounds = bounded
+            .iter()
+            .fold(Bounds::empty(), |b, s| b.merge(&shape_bounds[*s]));
+        let bounds = if bounds.is_empty() {
+            bounds
+        } else {
+            Bounds::new(bounds.min - padding, bounds.max + padding)
+        };
+
+        let mut grid = Grid {
+            shapes: shapes.to_vec(),
+            unbounded,
+            bounds,
+            resolution,
+            cells: vec![Vec::new(); resolution.iter().product()],
+        };
+        for s in bounded {
+            let (lo, hi) = (
+                grid.cell_of(shape_bounds[s].min),
+                grid.cell_of(shape_bounds[s].max),
+            );
+            for x in lo[0]..=hi[0] {
+                for y in lo[1]..=hi[1] {
+                    for z in lo[2]..=hi[2] {
+                        let index = grid.index([x, y, z]);
+                        grid.cells[index].push(s);
+                    }
+                }
+            }
+        }
+        grid
+    }
+
+    /// Size of a cell along each axis.
+    fn cell_size(&self) -> [f64; 3] {
+        let size = components(self.bounds.max - self.bounds.min);
+        [
+            size[0] / self.resolution[0] as f64,
+            size[1] / self.resolution[1] as f64,
+            size[2] / self.resolution[2] as f64,
+        ]
+    }
+
+    /// The cell holding `point`, or the nearest one when it is outside.
+    fn cell_of(&self, point: Point) -> [usize; 3] {
+        let offset = components(point - self.bounds.min);
+        let size = self.cell_size();
+        let mut cell = [0; 3];
+        for axis in 0..3 {
+            let i = (offset[axis] / size[axis]).floor().max(0.0) as usize;
+            cell[axis] = i.min(self.resolution[axis] - 1);
+        }
+        cell
+    }
+
+    fn index(&self, cell: [usize; 3]) -> usize {
+        cell[0] + self.resolution[0] * (cell[1] + self.resolution[1] * cell[2])
+    }
+
+    /// Intersect the `ray` with the shapes in the cells it passes through.
+    /// The intersections are sorted like the ones of
+    /// [`crate::World::intersect_world`].
+    pub fn intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
+        let mut visited = vec![false; self.shapes.len()];
+        let mut candidates = self.unbounded.clone();
+        self.collect(ray, &mut visited, &mut candidates);
+
+        let mut xs: Vec<Intersection> = Vec::new();
+        candidates.sort_unstable();
+        for index in candidates {
+            if let Some(shape_xs) = self.shapes[index].intersect(ray) {
+                xs.extend(shape_xs);
+            }
+        }
+
+        if xs.is_empty() {
+            None
+        } else {
+            xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            Some(xs)
+        }
+    }
+
+    /// Add the shapes of the cells along the whole line of the `ray` to the
+    /// `candidates`, once each.
+    fn collect(&self, ray: Ray, visited: &mut [bool], candidates: &mut Vec<usize>) {
+        if self.bounds.is_empty() {
+            return;
+        }
+        let (t_enter, t_exit) = match self.bounds.intersect(ray) {
+            Some(ts) => ts,
+            None => return,
+        };
+
+        let size = self.cell_size();
+        let min = components(self.bounds.min - Point::new(0.0, 0.0, 0.0));
+        let origin = components(ray.origin - Point::new(0.0, 0.0, 0.0));
+        let direction = components(ray.direction);
+        let mut cell = self.cell_of(ray.position(t_enter));
+
+        // distance along the ray to the next wall of the cell, and between
+        // walls, along each axis
+        let mut t_next = [f64::INFINITY; 3];
+        let mut t_delta = [f64::INFINITY; 3];
+        let mut step = [0_isize; 3];
+        for axis in 0..3 {
+            if direction[axis] > 0.0 {
+                let wall = min[axis] + (cell[axis] + 1) as f64 * size[axis];
+                t_next[axis] = (wall - origin[axis]) / direction[axis];
+                t_delta[axis] = size[axis] / direction[axis];
+                step[axis] = 1;
+            } else if direction[axis] < 0.0 {
+                let wall = min[axis] + cell[axis] as f64 * size[axis];
+                t_next[axis] = (wall - origin[axis]) / direction[axis];
+                t_delta[axis] = -size[axis] / direction[axis];
+                step[axis] = -1;
+            }
+        }
+
+        loop {
+            for &shape in &self.cells[self.index(cell)] {
+                if !visited[shape] {
+                    visited[shape] = true;
+                    candidates.push(shape);
+                }
+            }
+
+            let axis = (0..3)
+                .min_by(|a, b| t_next[*a].partial_cmp(&t_next[*b]).unwrap())
+                .unwrap();
+            if t_next[axis] > t_exit {
+                break;
+            }
+            let next = cell[axis] as isize + step[axis];
+            if next < 0 || next >= self.resolution[axis] as isize {
+                break;
+            }
+            cell[axis] = next as usize;
+            t_next[axis] += t_delta[axis];
+        }
+    }
+
+    /// Number of shapes in the grid.
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    /// Whether the grid holds no shapes.
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shapes::{Plane, Sphere},
+        Transformation,
+    };
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// `n` small spheres spread randomly through a cube `size` units wide.
+    fn cloud(n: usize, size: f64) -> Vec<Arc<dyn Shape>> {
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..n)
+            .map(|_| {
+                let mut s = Sphere::new();
+                s.transform = Transformation::new()
+                    .scale(0.3, 0.3, 0.3)
+                    .translate(
+                        rng.gen_range(0.0..size),
+                        rng.gen_range(0.0..size),
+                        rng.gen_range(0.0..size),
+                    )
+                    .build();
+                Arc::new(s) as Arc<dyn Shape>
+            })
+            .collect()
+    }
+
+    fn brute_force(shapes: &[Arc<dyn Shape>], r: Ray) -> Vec<f64> {
+        let mut ts: Vec<f64> = shapes
+            .iter()
+            .filter_map(|s| s.intersect(r))
+            .flatten()
+            .map(|i| i.t)
+            .collect();
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        ts
+    }
+
+    fn ts(grid: &Grid, r: Ray) -> Vec<f64> {
+        grid.intersect(r)
+            .unwrap_or_default()
+            .iter()
+            .map(|i| i.t)
+            .collect()
+    }
+
+    #[test]
+    fn the_grid_finds_the_same_intersections_as_testing_every_shape() {
+        let shapes = cloud(200, 10.0);
+        let grid = Grid::build(&shapes, [8, 8, 8]);
+        let mut rng = StdRng::seed_from_u64(3);
+
+        for _ in 0..200 {
+            let r = Ray::new(
+                Point::new(
+                    rng.gen_range(-5.0..15.0),
+                    rng.gen_range(-5.0..15.0),
+                    rng.gen_range(-5.0..15.0),
+                ),
+                Vector::new(
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                ),
+            );
+            assert_eq!(ts(&grid, r), brute_force(&shapes, r));
+        }
+
+        // along an axis and through the corners of the cells
+        let r = Ray::new(Point::new(5.0, 5.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(ts(&grid, r), brute_force(&shapes, r));
+        let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(1.0, 1.0, 1.0));
+        assert_eq!(ts(&grid, r), brute_force(&shapes, r));
+    }
+
+    #[test]
+    fn a_shape_spanning_many_cells_is_hit_once() {
+        let mut big = Sphere::new();
+        big.transform = Transformation::new().scale(5.0, 5.0, 5.0).build();
+        let shapes: Vec<Arc<dyn Shape>> = vec![Arc::new(big), Arc::new(Sphere::new())];
+        let grid = Grid::build(&shapes, [10, 10, 10]);
+        let r = Ray::new(Point::new(0.0, 0.0, -10.0), Vector::new(0.0, 0.0, 1.0));
+
+        let found = ts(&grid, r);
+        assert_eq!(found.len(), 4);
+        for (t, expected) in found.iter().zip([5.0, 9.0, 11.0, 15.0]) {
+            assert!((t - expected).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn shapes_without_bounds_are_always_tested() {
+        let mut shapes = cloud(10, 4.0);
+        shapes.push(Arc::new(Plane::new()));
+        let grid = Grid::build(&shapes, [4, 4, 4]);
+        let r = Ray::new(Point::new(20.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+
+        assert_eq!(ts(&grid, r), vec![5.0]);
+        assert_eq!(grid.len(), 11);
+    }
+
+    /// Compare the grid with testing every shape of a cloud of 1000 spheres,
+    /// run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn benchmark_grid_against_linear_scan() {
+        use std::time::Instant;
+
+        let shapes = cloud(1000, 20.0);
+        let grid = Grid::build(&shapes, [16, 16, 16]);
+        let rays: Vec<Ray> = (0..10_000)
+            .map(|k| {
+                let (x, y) = ((k % 100) as f64 * 0.2, (k / 100) as f64 * 0.2);
+                Ray::new(
+                    Point::new(x, y, -5.0),
+                    Vector::new(0.01, 0.02, 1.0).normalize(),
+                )
+            })
+            .collect();
+
+        let start = Instant::now();
+        let linear: usize = rays
+            .iter()
+            .map(|r| {
+                shapes
+                    .iter()
+                    .filter_map(|s| s.intersect(*r))
+                    .flatten()
+                    .count()
+            })
+            .sum();
+        let linear_time = start.elapsed();
+
+        let start = Instant::now();
+        let indexed: usize = rays
+            .iter()
+            .map(|r| grid.intersect(*r).map_or(0, |xs| xs.len()))
+            .sum();
+        let grid_time = start.elapsed();
+
+        println!(
+            "linear: {:?}, grid: {:?}, speedup: {:.1}",
+            linear_time,
+            grid_time,
+            linear_time.as_secs_f64() / grid_time.as_secs_f64()
+        );
+        assert_eq!(linear, indexed);
+        assert!(grid_time < linear_time);
+    }
+}
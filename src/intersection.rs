@@ -1,5 +1,6 @@
 use crate::{float_cmp, shapes::Shape, Computations, Ray, World, EPSILON};
-use std::cmp::Ordering;
+use std::{cmp::Ordering, collections::HashMap, error::Error, fmt, ops::Deref};
+use uuid::Uuid;
 
 /// Aggregate of the distance from a [`Ray`]'s origin and the object that was
 /// intersected by a [`Ray`] at that distance.
@@ -9,10 +10,17 @@ pub struct Intersection<'a> {
     pub t: f64,
     /// The object intersected by a ray.
     pub object: &'a dyn Shape,
-    /// `u` property
+    /// `u` coordinate of the hit on a triangle, along its first edge.
+    ///
+    /// Shapes that map points to texture coordinates, like spheres, leave
+    /// `u` and `v` unset and give them through
+    /// [`crate::shapes::Shape::local_uv_at`] only when a pattern needs them.
     pub u: Option<f64>,
-    /// `v` property
+    /// `v` coordinate of the hit on a triangle, along its second edge.
     pub v: Option<f64>,
+    /// Index of the face hit when the object is made of many faces, like a
+    /// [`crate::shapes::Mesh`].
+    pub face: Option<usize>,
 }
 
 impl<'a> Intersection<'a> {
@@ -37,6 +45,7 @@ impl<'a> Intersection<'a> {
             object,
             u: None,
             v: None,
+            face: None,
         }
     }
 
@@ -61,9 +70,67 @@ impl<'a> Intersection<'a> {
             object,
             u: Some(u),
             v: Some(v),
+            face: None,
         }
     }
 
+    /// Constructs a new `Intersection` with the `face` of the object that was
+    /// hit, along with the `u` and `v` properties on that face.
+    pub fn intersection_with_face(
+        t: f64,
+        object: &dyn Shape,
+        face: usize,
+        u: f64,
+        v: f64,
+    ) -> Intersection {
+        Intersection {
+            t,
+            object,
+            u: Some(u),
+            v: Some(v),
+            face: Some(face),
+        }
+    }
+
+    /// Wrap the intersections `xs` as [`SortedIntersections`] after checking
+    /// they are sorted by ascending `t`, see
+    /// [`SortedIntersections::from_sorted`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Intersection, shapes::Sphere};
+    ///
+    /// let s = Sphere::new();
+    /// let xs = vec![Intersection::new(-1.0, &s), Intersection::new(2.0, &s)];
+    /// let sorted = Intersection::from_sorted(xs).unwrap();
+    ///
+    /// assert_eq!(Intersection::hit_sorted(&sorted).unwrap().t, 2.0);
+    /// ```
+    pub fn from_sorted(
+        xs: Vec<Intersection<'a>>,
+    ) -> Result<SortedIntersections<'a>, NotSortedError> {
+        SortedIntersections::from_sorted(xs)
+    }
+
+    /// Wrap the intersections `xs` as [`SortedIntersections`] without
+    /// checking the order, see [`SortedIntersections::from_sorted_unchecked`].
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee `xs` is sorted by ascending `t`. Otherwise
+    /// the hit may not be the closest intersection.
+    pub unsafe fn from_sorted_unchecked(xs: Vec<Intersection<'a>>) -> SortedIntersections<'a> {
+        SortedIntersections::from_sorted_unchecked(xs)
+    }
+
+    /// The hit of intersections known to be sorted, the first one with a
+    /// non-negative `t`, found with a linear scan that stops there. The same
+    /// intersection as [`Intersection::hit`] gives for the list.
+    pub fn hit_sorted<'b>(xs: &'b SortedIntersections<'a>) -> Option<&'b Intersection<'a>> {
+        xs.hit()
+    }
+
     /// Compute information related to an `Intersection` returning the
     /// information as [`Computations].
     pub fn prepare_computations<'h>(
@@ -73,9 +140,11 @@ impl<'a> Intersection<'a> {
         w: Option<&World>,
     ) -> Computations<'h> {
         let point = r.position(self.t);
+        // the ray may be of any length, the shading needs unit vectors
+        let eyev = -r.direction.normalize();
         let mut normalv = self.object.normal_at(point, Some(self), w);
         let mut inside = false;
-        if normalv.dot(-r.direction) < 0.0 {
+        if normalv.dot(eyev) < 0.0 {
             inside = true;
             normalv = -normalv;
         }
@@ -83,15 +152,17 @@ impl<'a> Intersection<'a> {
         let over_point = point + normalv * EPSILON;
         let under_point = point - normalv * EPSILON;
 
-        let reflectv = r.direction.reflect(normalv);
+        let reflectv = (-eyev).reflect(normalv);
 
+        // outside of every object the ray is in the medium around the scene
+        let ambient = w.map_or(1.0, |w| w.ambient_refractive_index);
         let mut n1 = 0.0;
         let mut n2 = 0.0;
         let mut container: Vec<&dyn Shape> = Vec::new();
         for i in xs {
             if i == self {
                 if container.is_empty() {
-                    n1 = 1.0;
+                    n1 = ambient;
                 } else if let Some(object) = container.last() {
                     n1 = match w {
                         Some(w) => w.get_object_material(*object).refractive_index,
@@ -108,7 +179,7 @@ impl<'a> Intersection<'a> {
 
             if i == self {
                 if container.is_empty() {
-                    n2 = 1.0;
+                    n2 = ambient;
                 } else if let Some(object) = container.last() {
                     n2 = match w {
                         Some(w) => w.get_object_material(*object).refractive_index,
@@ -126,7 +197,7 @@ impl<'a> Intersection<'a> {
             point,
             over_point,
             under_point,
-            eyev: -r.direction,
+            eyev,
             normalv,
             inside,
             reflectv,
@@ -162,6 +233,152 @@ impl Intersection<'_> {
     pub fn hit<'a>(xs: &'a [Intersection]) -> Option<&'a Intersection<'a>> {
         xs.iter().filter(|x| x.t >= 0.0).min()
     }
+
+    /// The index in `xs` of the `hit`, the same intersection as returned by
+    /// [`Intersection::hit`], for when holding on to a reference into the
+    /// list gets in the way.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Intersection, shapes::Sphere};
+    ///
+    /// let s = Sphere::new();
+    /// let xs = vec![Intersection::new(2.0, &s), Intersection::new(1.0, &s)];
+    ///
+    /// assert_eq!(Intersection::hit_index(&xs), Some(1));
+    /// ```
+    pub const fn hit_index(xs: &[Intersection]) -> Option<usize> {
+        let mut hit: Option<usize> = None;
+        let mut i = 0;
+        while i < xs.len() {
+            let t = xs[i].t;
+            if t >= 0.0 {
+                hit = match hit {
+                    Some(h) if xs[h].t <= t => Some(h),
+                    _ => Some(i),
+                };
+            }
+            i += 1;
+        }
+        hit
+    }
+
+    /// The smallest non-negative `t` of `xs`, the distance to the `hit`, or
+    /// `None` when every intersection is behind the origin of the ray.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Intersection, shapes::Sphere};
+    ///
+    /// let s = Sphere::new();
+    /// let xs = vec![Intersection::new(-1.0, &s), Intersection::new(4.0, &s)];
+    ///
+    /// assert_eq!(Intersection::t_min(&xs), Some(4.0));
+    /// ```
+    pub const fn t_min(xs: &[Intersection]) -> Option<f64> {
+        match Self::hit_index(xs) {
+            Some(i) => Some(xs[i].t),
+            None => None,
+        }
+    }
+
+    /// The largest non-negative `t` of `xs`, where the ray leaves the last
+    /// of the shapes in front of it, or `None` when every intersection is
+    /// behind the origin of the ray.
+    pub const fn t_max(xs: &[Intersection]) -> Option<f64> {
+        let mut max: Option<f64> = None;
+        let mut i = 0;
+        while i < xs.len() {
+            let t = xs[i].t;
+            if t >= 0.0 {
+                max = match max {
+                    Some(m) if m >= t => Some(m),
+                    _ => Some(t),
+                };
+            }
+            i += 1;
+        }
+        max
+    }
+
+    /// Returns every intersection in front of the ray's origin, the ones with
+    /// a non-negative `t`, sorted from nearest to farthest. The first element
+    /// is the `hit`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Intersection, shapes::Sphere};
+    ///
+    /// let s = Sphere::new();
+    /// let xs = vec![
+    ///     Intersection::new(5.0, &s),
+    ///     Intersection::new(-3.0, &s),
+    ///     Intersection::new(2.0, &s),
+    /// ];
+    /// let hits = Intersection::all_hits(&xs);
+    ///
+    /// assert_eq!(hits.len(), 2);
+    /// assert_eq!(hits[0].t, 2.0);
+    /// assert_eq!(hits[1].t, 5.0);
+    /// ```
+    pub fn all_hits<'a>(xs: &'a [Intersection]) -> Vec<&'a Intersection<'a>> {
+        let mut hits: Vec<&Intersection> = xs.iter().filter(|x| x.t >= 0.0).collect();
+        hits.sort();
+        hits
+    }
+
+    /// The intersections of `xs` sorted into lists by the `id` of the object
+    /// hit, each list in the same order as in `xs`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Intersection, shapes::Shape, shapes::Sphere};
+    ///
+    /// let a = Sphere::new();
+    /// let b = Sphere::new();
+    /// let xs = vec![
+    ///     Intersection::new(1.0, &a),
+    ///     Intersection::new(2.0, &b),
+    ///     Intersection::new(3.0, &a),
+    /// ];
+    /// let groups = Intersection::group_by_object(&xs);
+    ///
+    /// assert_eq!(groups[&a.id()].len(), 2);
+    /// assert_eq!(groups[&b.id()][0].t, 2.0);
+    /// ```
+    pub fn group_by_object<'a>(xs: &'a [Intersection]) -> HashMap<Uuid, Vec<&'a Intersection<'a>>> {
+        let mut groups: HashMap<Uuid, Vec<&Intersection>> = HashMap::new();
+        for i in xs {
+            groups.entry(i.object.id()).or_default().push(i);
+        }
+        groups
+    }
+
+    /// The `id`s of the objects the ray is inside of at the distance `t`,
+    /// in the order the ray entered them. Going along the ray, each
+    /// intersection with an object enters it when the ray was outside and
+    /// leaves it when the ray was inside, the same way the refractive indices
+    /// are found by `prepare_computations`.
+    pub fn objects_at_point(xs: &[Intersection], t: f64) -> Vec<Uuid> {
+        let mut sorted: Vec<&Intersection> = xs.iter().collect();
+        sorted.sort();
+
+        let mut inside: Vec<Uuid> = Vec::new();
+        for i in sorted.iter().take_while(|i| i.t < t) {
+            let id = i.object.id();
+            match inside.iter().position(|o| *o == id) {
+                Some(index) => {
+                    inside.remove(index);
+                }
+                None => inside.push(id),
+            }
+        }
+        inside
+    }
 }
 
 impl PartialEq for Intersection<'_> {
@@ -184,6 +401,79 @@ impl Ord for Intersection<'_> {
     }
 }
 
+/// A list of [`Intersection`]s known to be sorted by ascending `t`. Knowing
+/// the order lets the `hit` be found with a linear scan that stops at the
+/// first non-negative intersection instead of searching the whole list.
+#[derive(Debug)]
+pub struct SortedIntersections<'a>(Vec<Intersection<'a>>);
+
+/// Error returned by [`SortedIntersections::from_sorted`] when the given
+/// intersections are not sorted by ascending `t`.
+#[derive(Debug, PartialEq)]
+pub struct NotSortedError;
+
+impl fmt::Display for NotSortedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "intersections are not sorted by ascending t")
+    }
+}
+
+impl Error for NotSortedError {}
+
+impl<'a> SortedIntersections<'a> {
+    /// Wrap `xs` after checking they are sorted by ascending `t`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Intersection, SortedIntersections, shapes::Sphere};
+    ///
+    /// let s = Sphere::new();
+    /// let sorted = vec![Intersection::new(1.0, &s), Intersection::new(2.0, &s)];
+    /// let unsorted = vec![Intersection::new(2.0, &s), Intersection::new(1.0, &s)];
+    ///
+    /// assert!(SortedIntersections::from_sorted(sorted).is_ok());
+    /// assert!(SortedIntersections::from_sorted(unsorted).is_err());
+    /// ```
+    pub fn from_sorted(xs: Vec<Intersection<'a>>) -> Result<Self, NotSortedError> {
+        if xs.windows(2).all(|w| w[0].t <= w[1].t) {
+            Ok(SortedIntersections(xs))
+        } else {
+            Err(NotSortedError)
+        }
+    }
+
+    /// Wrap `xs` without checking the order, for intersections produced in
+    /// order such as from an ordered traversal.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee `xs` is sorted by ascending `t`. Otherwise
+    /// `hit` may return an intersection that is not the closest.
+    pub unsafe fn from_sorted_unchecked(xs: Vec<Intersection<'a>>) -> Self {
+        SortedIntersections(xs)
+    }
+
+    /// Returns the first intersection with a non-negative `t`, which is the
+    /// `hit` because the intersections are sorted.
+    pub fn hit(&self) -> Option<&Intersection<'a>> {
+        self.0.iter().find(|x| x.t >= 0.0)
+    }
+
+    /// Unwrap the sorted intersections.
+    pub fn into_inner(self) -> Vec<Intersection<'a>> {
+        self.0
+    }
+}
+
+impl<'a> Deref for SortedIntersections<'a> {
+    type Target = [Intersection<'a>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -282,6 +572,172 @@ mod tests {
         assert_eq!(*i, xs[3]);
     }
 
+    #[test]
+    fn t_min_and_t_max_of_negative_intersections_are_none() {
+        let s = Sphere::new();
+        let xs = vec![Intersection::new(-1.0, &s), Intersection::new(-2.0, &s)];
+
+        assert_eq!(Intersection::t_min(&xs), None);
+        assert_eq!(Intersection::t_max(&xs), None);
+        assert_eq!(Intersection::hit_index(&xs), None);
+    }
+
+    #[test]
+    fn t_min_and_t_max_skip_negative_intersections() {
+        let s = Sphere::new();
+        let xs = vec![
+            Intersection::new(5.0, &s),
+            Intersection::new(-3.0, &s),
+            Intersection::new(7.0, &s),
+            Intersection::new(2.0, &s),
+            Intersection::new(-9.0, &s),
+        ];
+
+        assert_eq!(Intersection::t_min(&xs), Some(2.0));
+        assert_eq!(Intersection::t_max(&xs), Some(7.0));
+    }
+
+    #[test]
+    fn the_hit_index_points_at_the_hit() {
+        let s = Sphere::new();
+        let xs = vec![
+            Intersection::new(5.0, &s),
+            Intersection::new(7.0, &s),
+            Intersection::new(-3.0, &s),
+            Intersection::new(2.0, &s),
+        ];
+
+        let index = Intersection::hit_index(&xs).expect("Expected hit intersection");
+
+        assert_eq!(index, 3);
+        assert_eq!(&xs[index], Intersection::hit(&xs).unwrap());
+    }
+
+    #[test]
+    fn the_ray_is_inside_the_spheres_it_entered() {
+        let mut outer = Sphere::new();
+        outer.transform = Transformation::new().scale(2.0, 2.0, 2.0).build();
+        let inner = Sphere::new();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let mut xs = outer.intersect(r).unwrap();
+        xs.extend(inner.intersect(r).unwrap());
+        xs.sort();
+
+        let ts: Vec<f64> = xs.iter().map(|i| i.t).collect();
+        assert_eq!(ts, vec![3.0, 4.0, 6.0, 7.0]);
+        assert!(Intersection::objects_at_point(&xs, 2.0).is_empty());
+        assert_eq!(Intersection::objects_at_point(&xs, 3.5), vec![outer.id()]);
+        assert_eq!(
+            Intersection::objects_at_point(&xs, 5.0),
+            vec![outer.id(), inner.id()]
+        );
+        assert_eq!(Intersection::objects_at_point(&xs, 6.5), vec![outer.id()]);
+        assert!(Intersection::objects_at_point(&xs, 8.0).is_empty());
+
+        let groups = Intersection::group_by_object(&xs);
+        assert_eq!(groups.len(), 2);
+        let inner_ts: Vec<f64> = groups[&inner.id()].iter().map(|i| i.t).collect();
+        assert_eq!(inner_ts, vec![4.0, 6.0]);
+    }
+
+    #[test]
+    fn all_hits_excludes_negative_intersections() {
+        let s = Sphere::new();
+        let xs = vec![
+            Intersection::new(-1.0, &s),
+            Intersection::new(1.0, &s),
+            Intersection::new(-2.0, &s),
+        ];
+        let hits = Intersection::all_hits(&xs);
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(*hits[0], xs[1]);
+    }
+
+    #[test]
+    fn all_hits_are_sorted_ascending() {
+        let s = Sphere::new();
+        let xs = vec![
+            Intersection::new(5.0, &s),
+            Intersection::new(7.0, &s),
+            Intersection::new(-3.0, &s),
+            Intersection::new(2.0, &s),
+            Intersection::new(0.0, &s),
+        ];
+        let hits = Intersection::all_hits(&xs);
+        let ts: Vec<f64> = hits.iter().map(|i| i.t).collect();
+
+        assert_eq!(ts, vec![0.0, 2.0, 5.0, 7.0]);
+    }
+
+    #[test]
+    fn all_hits_when_all_intersections_have_negative_t() {
+        let s = Sphere::new();
+        let xs = vec![Intersection::new(-2.0, &s), Intersection::new(-1.0, &s)];
+
+        assert!(Intersection::all_hits(&xs).is_empty());
+    }
+
+    #[test]
+    fn sorted_intersections_reject_unsorted_input() {
+        let s = Sphere::new();
+        let xs = vec![Intersection::new(2.0, &s), Intersection::new(1.0, &s)];
+
+        assert_eq!(
+            SortedIntersections::from_sorted(xs).unwrap_err(),
+            NotSortedError
+        );
+    }
+
+    #[test]
+    fn the_hit_of_sorted_intersections_matches_the_hit_of_the_list() {
+        let s = Sphere::new();
+        let mut xs = vec![
+            Intersection::new(5.0, &s),
+            Intersection::new(7.0, &s),
+            Intersection::new(-3.0, &s),
+            Intersection::new(2.0, &s),
+        ];
+        xs.sort();
+        let expected = Intersection::hit(&xs).unwrap().t;
+        let sorted = SortedIntersections::from_sorted(xs).unwrap();
+
+        assert_eq!(sorted.hit().unwrap().t, expected);
+    }
+
+    #[test]
+    fn sorted_intersections_are_built_from_intersection() {
+        let s = Sphere::new();
+        let unsorted = vec![Intersection::new(2.0, &s), Intersection::new(1.0, &s)];
+        assert_eq!(
+            Intersection::from_sorted(unsorted).unwrap_err(),
+            NotSortedError
+        );
+
+        let mut xs = vec![
+            Intersection::new(5.0, &s),
+            Intersection::new(-3.0, &s),
+            Intersection::new(2.0, &s),
+        ];
+        xs.sort();
+        let expected = Intersection::hit(&xs).unwrap().t;
+        let sorted = Intersection::from_sorted(xs).unwrap();
+        assert_eq!(Intersection::hit_sorted(&sorted).unwrap().t, expected);
+
+        let unchecked = unsafe { Intersection::from_sorted_unchecked(sorted.into_inner()) };
+        assert_eq!(Intersection::hit_sorted(&unchecked).unwrap().t, expected);
+    }
+
+    #[test]
+    fn unchecked_sorted_intersections_find_the_hit() {
+        let s = Sphere::new();
+        let xs = vec![Intersection::new(-1.0, &s), Intersection::new(1.0, &s)];
+        let sorted = unsafe { SortedIntersections::from_sorted_unchecked(xs) };
+
+        assert_eq!(sorted.hit().unwrap().t, 1.0);
+        assert_eq!(sorted.len(), 2);
+    }
+
     // Chapter 7 Making a Scene
     // Page 93
     #[test]
@@ -469,6 +925,21 @@ mod tests {
         assert!(float_eq(reflectance, 0.48873));
     }
 
+    #[test]
+    fn the_fresnel_reflectance_grows_with_the_angle() {
+        let shape = Sphere::glass_sphere();
+        let half = 2_f64.sqrt() / 2.0;
+        // hits the sphere with 45 degrees between the ray and the normal
+        let r = Ray::new(Point::new(0.0, half, -2.0), Vector::new(0.0, 0.0, 1.0));
+        let i = Intersection::new(2.0 - half, &shape);
+        let xs = vec![Intersection::new(2.0 - half, &shape)];
+        let comps = i.prepare_computations(r, &xs, None);
+        let reflectance = comps.fresnel_reflectance();
+
+        assert!(float_eq(reflectance, 0.04 + 0.96 * (1.0 - half).powi(5)));
+        assert!(reflectance > 0.04 && reflectance < 0.48873);
+    }
+
     // Chapter 15 Triangles
     // Page 221
     #[test]
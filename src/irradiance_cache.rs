@@ -0,0 +1,433 @@
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    sampling::{cosine_weighted_direction, random_unit_vector},
+    shading::sample_occlusion,
+    Color, Colors, HasPosition, Intersection, Octree, Point, Ray, Vector, World,
+};
+
+/// Normals of a cached sample and a new point must be at least this close,
+/// as the cosine of the angle between them, for the sample to be reused.
+const MIN_NORMAL_DOT: f64 = 0.95;
+
+/// An ambient occlusion value computed at a point of a surface.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct CacheRecord {
+    position: Point,
+    normal: Vector,
+    occlusion: f64,
+    /// Distance over which the value is expected to stay about the same.
+    radius: f64,
+}
+
+/// Number of records kept in a box of the octree of irradiance records
+/// before it is split.
+const RECORDS_PER_LEAF: usize = 8;
+/// Number of nearby records the irradiance at a point is interpolated from.
+const NEAREST_RECORDS: usize = 8;
+/// Rays shot from the light to find surface points for every record the
+/// cache may hold.
+const CANDIDATES_PER_RECORD: usize = 4;
+
+/// Diffuse light arriving at a point of a surface, computed ahead of
+/// rendering by [`IrradianceCache::precompute_irradiance`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct IrradianceRecord {
+    /// Point in world space the light was gathered at.
+    pub position: Point,
+    /// Normal of the surface at the point.
+    pub normal: Vector,
+    /// Average color of the light arriving from the hemisphere above the
+    /// point, weighted by the cosine of its angle with the normal.
+    pub irradiance: Color,
+    /// Distance over which the light is expected to stay about the same.
+    pub validity_radius: f64,
+}
+
+impl HasPosition for IrradianceRecord {
+    fn position(&self) -> Point {
+        self.position
+    }
+}
+
+/// Reuses [`crate::ambient_occlusion`] values computed at nearby points.
+///
+/// Occlusion changes slowly across a surface far from other objects and
+/// quickly close to them. Every computed value is kept along with the
+/// harmonic mean of the distances its rays travelled, which is used as the
+/// radius within which it stays valid. A new point close enough to cached
+/// values on a surface facing the same way is given their weighted average
+/// instead of firing rays of its own.
+///
+/// The cache can also hold the diffuse light bounced between surfaces, for
+/// global illumination, see [`IrradianceCache::precompute_irradiance`].
+#[derive(Debug, Clone)]
+pub struct IrradianceCache {
+    /// Number of rays fired when a value has to be computed.
+    pub samples: usize,
+    /// How far from cached values a point may be and still reuse them,
+    /// smaller values give more accurate results and a larger cache.
+    pub tolerance: f64,
+    /// Smallest radius given to a cached value.
+    pub min_radius: f64,
+    /// Largest radius given to a cached value, used when its rays hit nothing.
+    pub max_radius: f64,
+    records: Vec<CacheRecord>,
+    interpolated: usize,
+    irradiance: Octree<IrradianceRecord>,
+}
+
+impl IrradianceCache {
+    /// Create an empty cache computing values with `samples` rays, reusing
+    /// them within `tolerance`.
+    pub fn new(samples: usize, tolerance: f64) -> Self {
+        IrradianceCache {
+            samples,
+            tolerance,
+            min_radius: 0.01,
+            max_radius: 10.0,
+            records: Vec::new(),
+            interpolated: 0,
+            irradiance: Octree::new(RECORDS_PER_LEAF),
+        }
+    }
+
+    /// The ambient occlusion factor at `point` facing `normal`, from the
+    /// cached values around it or computed with new rays and stored when
+    /// there are none close enough.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::Plane, IrradianceCache, Point, Vector, World};
+    ///
+    /// let mut w = World::new();
+    /// w.add_object(Box::new(Plane::new()));
+    /// let mut cache = IrradianceCache::new(16, 0.5);
+    /// let up = Vector::new(0.0, 1.0, 0.0);
+    ///
+    /// assert_eq!(cache.ambient_occlusion(&w, Point::new(0.0, 0.001, 0.0), up), 1.0);
+    /// assert_eq!(cache.ambient_occlusion(&w, Point::new(0.1, 0.001, 0.0), up), 1.0);
+    /// assert_eq!(cache.len(), 1);
+    /// assert_eq!(cache.interpolated_count(), 1);
+    /// ```
+    pub fn ambient_occlusion(&mut self, world: &World, point: Point, normal: Vector) -> f64 {
+        if let Some(occlusion) = self.interpolate(point, normal) {
+            self.interpolated += 1;
+            return occlusion;
+        }
+
+        let (occlusion, mean_distance) = sample_occlusion(world, point, normal, self.samples);
+        self.records.push(CacheRecord {
+            position: point,
+            normal,
+            occlusion,
+            radius: mean_distance.max(self.min_radius).min(self.max_radius),
+        });
+
+        occlusion
+    }
+
+    /// The weighted average of the cached values valid at `point`, using the
+    /// weights of Ward's irradiance caching. A value counts less the further
+    /// the point is compared to its radius and the more its normal differs.
+    fn interpolate(&self, point: Point, normal: Vector) -> Option<f64> {
+        let mut total = 0.0;
+        let mut weights = 0.0;
+
+        for record in &self.records {
+            let normal_dot = normal.dot(record.normal);
+            if normal_dot < MIN_NORMAL_DOT {
+                continue;
+            }
+
+            let error = (point - record.position).magnitude() / record.radius
+                + (1.0 - normal_dot).max(0.0).sqrt();
+            if error >= self.tolerance {
+                continue;
+            }
+
+            let weight = 1.0 / error.max(1e-6);
+            total += weight * record.occlusion;
+            weights += weight;
+        }
+
+        if weights > 0.0 {
+            Some(total / weights)
+        } else {
+            None
+        }
+    }
+
+    /// Gather the diffuse light arriving at up to `resolution` points of the
+    /// surfaces of `world`, firing `samples` rays from each.
+    ///
+    /// The points are where rays shot from the point light of the world, or
+    /// the origin when it has none, in random directions first hit a
+    /// surface. A point close enough to a record already made, judged like
+    /// the ambient occlusion values with the `tolerance`, is skipped. Each
+    /// record is valid within the harmonic mean of the distances its rays
+    /// travelled, like the ambient occlusion values.
+    pub fn precompute_irradiance(&mut self, world: &World, resolution: usize) {
+        let origin = world
+            .light
+            .map_or(Point::new(0.0, 0.0, 0.0), |light| light.position);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..resolution * CANDIDATES_PER_RECORD {
+            if self.irradiance.len() >= resolution {
+                break;
+            }
+
+            let ray = Ray::new_unit(origin, random_unit_vector(&mut rng));
+            let xs = match world.intersect_world(ray) {
+                Some(xs) => xs,
+                None => continue,
+            };
+            let comps = match Intersection::hit(&xs) {
+                Some(hit) => hit.prepare_computations(ray, &xs, Some(world)),
+                None => continue,
+            };
+            if self
+                .interpolate_irradiance(comps.over_point, comps.normalv)
+                .is_some()
+            {
+                continue;
+            }
+
+            let (irradiance, mean_distance) =
+                self.sample_irradiance(world, comps.over_point, comps.normalv, &mut rng);
+            self.irradiance.insert(IrradianceRecord {
+                position: comps.over_point,
+                normal: comps.normalv,
+                irradiance,
+                validity_radius: mean_distance.max(self.min_radius).min(self.max_radius),
+            });
+        }
+    }
+
+    /// The average light arriving at `point` from the hemisphere facing
+    /// `normal`, along with the harmonic mean of the distances to the
+    /// objects hit.
+    fn sample_irradiance(
+        &self,
+        world: &World,
+        point: Point,
+        normal: Vector,
+        rng: &mut StdRng,
+    ) -> (Color, f64) {
+        let mut total = Colors::BLACK;
+        let mut inverse_distances = 0.0;
+
+        for _ in 0..self.samples {
+            let ray = Ray::new_unit(point, cosine_weighted_direction(normal, rng));
+            let xs = world.intersect_world(ray);
+            let hit = xs.as_ref().and_then(|xs| Intersection::hit(xs));
+
+            total = total
+                + match (hit, &xs) {
+                    (Some(hit), Some(xs)) => {
+                        inverse_distances += 1.0 / hit.t;
+                        let comps = hit.prepare_computations(ray, xs, Some(world));
+                        world.shade_hit(&comps, 1)
+                    }
+                    _ => world.background,
+                };
+        }
+
+        let samples = self.samples.max(1) as f64;
+        (total * (1.0 / samples), samples / inverse_distances)
+    }
+
+    /// The diffuse light arriving at `point` facing `normal`, interpolated
+    /// from the nearby records of `precompute_irradiance` the way ambient
+    /// occlusion values are. When no record is close enough, the light of
+    /// the nearest record facing the same way is used, and black when there
+    /// is none.
+    pub fn irradiance_at(&self, point: Point, normal: Vector) -> Color {
+        if let Some(irradiance) = self.interpolate_irradiance(point, normal) {
+            return irradiance;
+        }
+
+        self.irradiance
+            .query_k_nearest(point, NEAREST_RECORDS)
+            .into_iter()
+            .find(|record| normal.dot(record.normal) >= MIN_NORMAL_DOT)
+            .map_or(Colors::BLACK, |record| record.irradiance)
+    }
+
+    /// The weighted average of the irradiance records valid at `point`,
+    /// weighted like in `interpolate`.
+    fn interpolate_irradiance(&self, point: Point, normal: Vector) -> Option<Color> {
+        let mut total = Colors::BLACK;
+        let mut weights = 0.0;
+
+        for record in self.irradiance.query_k_nearest(point, NEAREST_RECORDS) {
+            let normal_dot = normal.dot(record.normal);
+            if normal_dot < MIN_NORMAL_DOT {
+                continue;
+            }
+
+            let error = (point - record.position).magnitude() / record.validity_radius
+                + (1.0 - normal_dot).max(0.0).sqrt();
+            if error >= self.tolerance {
+                continue;
+            }
+
+            let weight = 1.0 / error.max(1e-6);
+            total = total + record.irradiance * weight;
+            weights += weight;
+        }
+
+        if weights > 0.0 {
+            Some(total * (1.0 / weights))
+        } else {
+            None
+        }
+    }
+
+    /// Number of irradiance records made by `precompute_irradiance`.
+    pub fn irradiance_len(&self) -> usize {
+        self.irradiance.len()
+    }
+
+    /// Number of ambient occlusion values computed and stored in the cache.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether no value was computed yet.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Number of queries answered from cached values.
+    pub fn interpolated_count(&self) -> usize {
+        self.interpolated
+    }
+
+    /// Forget every cached value, for when the objects of the world change.
+    pub fn clear(&mut self) {
+        self.records.clear();
+        self.interpolated = 0;
+        self.irradiance = Octree::new(RECORDS_PER_LEAF);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ambient_occlusion, shapes::Plane, AmbientOcclusion, Camera, PointLight, Transformation,
+    };
+    use std::f64::consts::PI;
+
+    /// A red floor and a white ceiling 3 units above it, lit from between
+    /// them.
+    fn room() -> World {
+        let mut w = World::new();
+        let mut floor = Plane::new();
+        floor.material.set_color(Color::new(1.0, 0.0, 0.0));
+        w.add_object(Box::new(floor));
+        let mut ceiling = Plane::new();
+        ceiling.transform = Transformation::new().translate(0.0, 3.0, 0.0).build();
+        w.add_object(Box::new(ceiling));
+        w.light = Some(PointLight::new(
+            Point::new(0.0, 1.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        w
+    }
+
+    #[test]
+    fn a_plane_with_even_occlusion_is_filled_quickly() {
+        let mut w = World::new();
+        w.add_object(Box::new(Plane::new()));
+        let mut cache = IrradianceCache::new(16, 0.5);
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        for i in 0..10 {
+            for j in 0..10 {
+                let point = Point::new(i as f64 * 0.2, 0.0001, j as f64 * 0.2);
+                assert_eq!(cache.ambient_occlusion(&w, point, up), 1.0);
+            }
+        }
+
+        assert!(cache.len() <= 4);
+        assert_eq!(cache.len() + cache.interpolated_count(), 100);
+    }
+
+    #[test]
+    fn cached_values_are_not_shared_between_facing_surfaces() {
+        let w = World::new();
+        let mut cache = IrradianceCache::new(4, 0.5);
+
+        cache.ambient_occlusion(&w, Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+        cache.ambient_occlusion(&w, Point::new(0.0, 0.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.interpolated_count(), 0);
+    }
+
+    #[test]
+    fn cached_occlusion_matches_firing_rays_at_every_point() {
+        let mut w = World::new();
+        w.add_object(Box::new(Plane::new()));
+        let mut wall = Plane::new();
+        wall.transform = Transformation::new()
+            .rotate_z(std::f64::consts::PI / 2.0)
+            .translate(1.0, 0.0, 0.0)
+            .build();
+        w.add_object(Box::new(wall));
+        w.ambient_occlusion = Some(AmbientOcclusion::new(256, 10.0));
+        let mut cache = IrradianceCache::new(1024, 0.3);
+        cache.max_radius = 1.0;
+        let up = Vector::new(0.0, 1.0, 0.0);
+        let tolerance = 0.1;
+
+        for i in 0..40 {
+            let point = Point::new(0.95 - i as f64 * 0.1, 0.0001, 0.3);
+            let cached = cache.ambient_occlusion(&w, point, up);
+            let exact = ambient_occlusion(&w, point, up, 1024);
+
+            assert!((cached - exact).abs() < tolerance, "{} {}", cached, exact);
+        }
+        assert!(cache.interpolated_count() > 0);
+    }
+
+    #[test]
+    fn light_bounced_off_the_floor_reaches_the_ceiling() {
+        let w = room();
+        let cache = w.diffuse_irradiance_cache(64);
+        let mut c = Camera::new(9, 9, PI / 3.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 1.5, 0.0),
+            Point::new(0.0, 3.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+        );
+
+        let direct = c.render(&w);
+        let lit = w.render_with_irradiance_cache(&c, &cache);
+
+        // only the red of the floor bounces up to the white ceiling
+        let bounced = lit.pixel_at(4, 4) - direct.pixel_at(4, 4);
+        assert!(bounced.red > 0.05, "{:?}", bounced);
+        assert!(bounced.green < 0.001 && bounced.blue < 0.001);
+    }
+
+    #[test]
+    fn the_irradiance_cache_is_bounded_by_its_resolution() {
+        let w = room();
+
+        for resolution in [1, 5, 20] {
+            let cache = w.diffuse_irradiance_cache(resolution);
+            assert!(cache.irradiance_len() <= resolution);
+            assert!(cache.irradiance_len() > 0);
+        }
+
+        let cache = w.diffuse_irradiance_cache(16);
+        let up = cache.irradiance_at(Point::new(0.0, 0.0001, 0.0), Vector::new(0.0, 1.0, 0.0));
+        // the floor is lit by the white ceiling above it
+        assert!(up.red > 0.0 && up.green > 0.0 && up.blue > 0.0);
+    }
+}
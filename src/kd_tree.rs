@@ -0,0 +1,322 @@
+use std::sync::Arc;
+
+use crate::{shapes::Shape, Bounds, Intersection, Point, Ray};
+
+/// Largest number of shapes kept in a leaf of the tree.
+const MAX_LEAF_SHAPES: usize = 4;
+/// Deepest a node of the tree may be, stopping shapes spread over the same
+/// area from being split forever.
+const MAX_DEPTH: usize = 24;
+
+/// A node of a [`KdTree`], holding the box around the shapes below it.
+#[derive(Debug, Clone)]
+enum KdNode {
+    Leaf {
+        bounds: Bounds,
+        shapes: Vec<usize>,
+    },
+    Branch {
+        bounds: Bounds,
+        left: Box<KdNode>,
+        right: Box<KdNode>,
+    },
+}
+
+/// A spatial index over shapes, splitting space in two along a plane at each
+/// node so a ray only tests the shapes in the parts of space it passes
+/// through.
+///
+/// Each plane crosses the longest axis of the box around the shapes of a node
+/// at the median of their centers. A shape lying across the plane is kept on
+/// both sides. Shapes without bounds, such as planes, are kept outside of the
+/// tree and tested against every ray.
+#[derive(Debug, Clone)]
+pub struct KdTree {
+    shapes: Vec<Arc<dyn Shape>>,
+    bounds: Vec<Bounds>,
+    unbounded: Vec<usize>,
+    root: KdNode,
+}
+
+/// The coordinate of `p` along the `axis`, 0 for x, 1 for y and 2 for z.
+fn coordinate(p: Point, axis: usize) -> f64 {
+    match axis {
+        0 => p.x,
+        1 => p.y,
+        _ => p.z,
+    }
+}
+
+impl KdTree {
+    /// Build a tree over the `shapes`, using the box around each of them in
+    /// the space of the world.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::{Shape, Sphere}, KdTree, Point, Ray, Vector};
+    /// use std::sync::Arc;
+    ///
+    /// let shapes: Vec<Arc<dyn Shape>> = vec![Arc::new(Sphere::new())];
+    /// let tree = KdTree::build(&shapes);
+    /// let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    ///
+    /// assert_eq!(tree.intersect(r).unwrap().len(), 2);
+    /// ```
+    pub fn build(shapes: &[Arc<dyn Shape>]) -> KdTree {
+        let bounds: Vec<Bounds> = shapes.iter().map(|s| s.parent_space_bounds()).collect();
+        let (bounded, unbounded): (Vec<usize>, Vec<usize>) = (0..shapes.len())
+            .filter(|i| !bounds[*i].is_empty())
+            .partition(|i| bounds[*i].is_finite());
+
+        let mut tree = KdTree {
+            shapes: shapes.to_vec(),
+            bounds,
+            unbounded,
+            root: KdNode::Leaf {
+                bounds: Bounds::empty(),
+                shapes: Vec::new(),
+            },
+        };
+        tree.root = tree.build_node(bounded, 0);
+        tree
+    }
+
+    fn build_node(&self, shapes: Vec<usize>, depth: usize) -> KdNode {
+        let bounds = shapes
+            .iter()
+            .fold(Bounds::empty(), |b, s| b.merge(&self.bounds[*s]));
+
+        if shapes.len() <= MAX_LEAF_SHAPES || depth >= MAX_DEPTH {
+            return KdNode::Leaf { bounds, shapes };
+        }
+
+        let size = bounds.max - bounds.min;
+        let axis = if size.x >= size.y && size.x >= size.z {
+            0
+        } else if size.y >= size.z {
+            1
+        } else {
+            2
+        };
+
+        let mut centers: Vec<f64> = shapes
+            .iter()
+            .map(|s| coordinate(self.bounds[*s].centroid(), axis))
+            .collect();
+        centers.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let split = centers[centers.len() / 2];
+
+        let left: Vec<usize> = shapes
+            .iter()
+            .copied()
+            .filter(|s| coordinate(self.bounds[*s].min, axis) < split)
+            .collect();
+        let right: Vec<usize> = shapes
+            .iter()
+            .copied()
+            .filter(|s| coordinate(self.bounds[*s].max, axis) >= split)
+            .collect();
+
+        // splitting gains nothing when one side keeps every shape
+        if left.len() == shapes.len() || right.len() == shapes.len() {
+            return KdNode::Leaf { bounds, shapes };
+        }
+
+        KdNode::Branch {
+            bounds,
+            left: Box::new(self.build_node(left, depth + 1)),
+            right: Box::new(self.build_node(right, depth + 1)),
+        }
+    }
+
+    /// Intersect the `ray` with the shapes in the parts of the tree it passes
+    /// through. The intersections are sorted like the ones of
+    /// [`crate::World::intersect_world`].
+    pub fn intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
+        let mut visited = vec![false; self.shapes.len()];
+        let mut candidates = self.unbounded.clone();
+        Self::collect(&self.root, ray, &mut visited, &mut candidates);
+
+        let mut xs: Vec<Intersection> = Vec::new();
+        candidates.sort_unstable();
+        for index in candidates {
+            if let Some(shape_xs) = self.shapes[index].intersect(ray) {
+                xs.extend(shape_xs);
+            }
+        }
+
+        if xs.is_empty() {
+            None
+        } else {
+            xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            Some(xs)
+        }
+    }
+
+    /// Add the shapes of the leaves below `node` that the `ray` reaches to the
+    /// `candidates`, once each.
+    fn collect(node: &KdNode, ray: Ray, visited: &mut [bool], candidates: &mut Vec<usize>) {
+        match node {
+            KdNode::Leaf { bounds, shapes } => {
+                if bounds.is_empty() || bounds.intersect(ray).is_none() {
+                    return;
+                }
+                for &shape in shapes {
+                    if !visited[shape] {
+                        visited[shape] = true;
+                        candidates.push(shape);
+                    }
+                }
+            }
+            KdNode::Branch {
+                bounds,
+                left,
+                right,
+            } => {
+                if bounds.intersect(ray).is_some() {
+                    Self::collect(left, ray, visited, candidates);
+                    Self::collect(right, ray, visited, candidates);
+                }
+            }
+        }
+    }
+
+    /// Number of shapes in the tree.
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    /// Whether the tree holds no shapes.
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shapes::{Plane, Sphere},
+        Transformation, Vector,
+    };
+
+    /// A grid of `n` by `n` small spheres in the xy plane, `spacing` apart.
+    fn grid(n: usize, spacing: f64) -> Vec<Arc<dyn Shape>> {
+        let mut shapes: Vec<Arc<dyn Shape>> = Vec::new();
+        for i in 0..n {
+            for j in 0..n {
+                let mut s = Sphere::new();
+                s.transform = Transformation::new()
+                    .scale(0.4, 0.4, 0.4)
+                    .translate(i as f64 * spacing, j as f64 * spacing, 0.0)
+                    .build();
+                shapes.push(Arc::new(s));
+            }
+        }
+        shapes
+    }
+
+    #[test]
+    fn a_ray_hits_only_the_shape_it_passes_through() {
+        let shapes = grid(10, 1.0);
+        let tree = KdTree::build(&shapes);
+        let r = Ray::new(Point::new(3.0, 4.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        let xs = tree.intersect(r).unwrap();
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].object.id(), shapes[34].id());
+        assert!(tree
+            .intersect(Ray::new(
+                Point::new(3.5, 4.5, -5.0),
+                Vector::new(0.0, 0.0, 1.0)
+            ))
+            .is_none());
+    }
+
+    #[test]
+    fn shapes_without_bounds_are_always_tested() {
+        let mut shapes = grid(4, 1.0);
+        shapes.push(Arc::new(Plane::new()));
+        let tree = KdTree::build(&shapes);
+        let r = Ray::new(Point::new(20.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+
+        let xs = tree.intersect(r).unwrap();
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 5.0);
+    }
+
+    #[test]
+    fn the_tree_finds_the_same_intersections_as_testing_every_shape() {
+        let shapes = grid(32, 0.6);
+        let tree = KdTree::build(&shapes);
+
+        for k in 0..50 {
+            let angle = k as f64 * 0.37;
+            let r = Ray::new(
+                Point::new(9.0 + angle.cos() * 30.0, 9.0 + angle.sin() * 30.0, -3.0),
+                Vector::new(-angle.cos(), -angle.sin(), 0.1).normalize(),
+            );
+            let mut expected: Vec<f64> = shapes
+                .iter()
+                .filter_map(|s| s.intersect(r))
+                .flatten()
+                .map(|i| i.t)
+                .collect();
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            let found: Vec<f64> = tree
+                .intersect(r)
+                .unwrap_or_default()
+                .iter()
+                .map(|i| i.t)
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    /// Compare the tree with testing every shape of a large scene, run with
+    /// `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn benchmark_kd_tree_against_linear_scan() {
+        use std::time::Instant;
+
+        let shapes = grid(40, 1.0);
+        let tree = KdTree::build(&shapes);
+        let rays: Vec<Ray> = (0..10_000)
+            .map(|k| {
+                let (x, y) = ((k % 100) as f64 * 0.4, (k / 100) as f64 * 0.4);
+                Ray::new(
+                    Point::new(x, y, -5.0),
+                    Vector::new(0.01, 0.02, 1.0).normalize(),
+                )
+            })
+            .collect();
+
+        let start = Instant::now();
+        let linear: usize = rays
+            .iter()
+            .map(|r| {
+                shapes
+                    .iter()
+                    .filter_map(|s| s.intersect(*r))
+                    .flatten()
+                    .count()
+            })
+            .sum();
+        let linear_time = start.elapsed();
+
+        let start = Instant::now();
+        let indexed: usize = rays
+            .iter()
+            .map(|r| tree.intersect(*r).map_or(0, |xs| xs.len()))
+            .sum();
+        let tree_time = start.elapsed();
+
+        println!("linear: {:?}, kd tree: {:?}", linear_time, tree_time);
+        assert_eq!(linear, indexed);
+    }
+}
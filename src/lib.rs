@@ -1,39 +1,97 @@
 //! Rustic Ray is as ray tracer library based on the book The Ray Tracer
 //! Challenge by Jamis Buck
+mod ao_map;
+mod bounds;
+mod cached_matrix;
 mod camera;
 mod canvas;
 mod color;
 mod colors;
 mod computations;
+mod debug;
+mod grid;
 mod intersection;
+mod irradiance_cache;
+mod kd_tree;
 mod light;
+mod light_probe;
 mod material;
 mod matrix;
 mod obj_file;
+mod octree;
 pub mod patterns;
+mod photon_map;
+mod ply_parser;
 mod point;
+mod post_process;
 mod ray;
+mod ray_batch;
+mod render_job;
+#[cfg(feature = "network")]
+mod render_network;
+mod sampling;
+mod scene_builder;
+mod scene_validator;
+mod shading;
 pub mod shapes;
+mod spectral;
+mod subsurface;
+mod texture_atlas;
 mod transformation;
 mod vector;
+mod video_output;
+mod ward;
 mod world;
+mod world_diff;
+mod world_file;
+mod world_statistics;
 
-pub use crate::camera::Camera;
+pub use crate::ao_map::{AoMap, DEFAULT_AO_MAP_RESOLUTION};
+pub use crate::bounds::Bounds;
+pub use crate::cached_matrix::CachedMatrix;
+pub use crate::camera::{Camera, FisheyeProjection, Projection};
 pub use crate::canvas::Canvas;
 pub use crate::color::Color;
 pub use crate::colors::Colors;
 pub use crate::computations::Computations;
+pub use crate::debug::{HitInfo, RayDebugger, RayTree};
+pub use crate::grid::Grid;
 pub use crate::intersection::Intersection;
-pub use crate::light::PointLight;
+pub use crate::intersection::{NotSortedError, SortedIntersections};
+pub use crate::irradiance_cache::{IrradianceCache, IrradianceRecord};
+pub use crate::kd_tree::KdTree;
+pub use crate::light::{AreaLightShape, DirectionalLight, PointLight, DEFAULT_AREA_LIGHT_SAMPLES};
+pub use crate::light_probe::LightProbe;
 pub use crate::material::Material;
 pub use crate::matrix::Matrix;
-pub use crate::matrix::IDENTITY;
+pub use crate::matrix::{IDENTITY, ZERO_MATRIX};
 pub use crate::obj_file::ObjFile;
+pub use crate::octree::{HasPosition, Octree};
+pub use crate::photon_map::{caustics_pass, Photon, PhotonMap};
+pub use crate::ply_parser::{PlyError, PlyFile};
 pub use crate::point::Point;
+pub use crate::post_process::{
+    ChromaticAberration, EdgeDetect, Fxaa, GaussianBlur, PostPipeline, PostProcessor, Vignette,
+};
 pub use crate::ray::Ray;
+pub use crate::ray_batch::RayBatch;
+pub use crate::render_job::{RenderJob, RenderPass};
+#[cfg(feature = "network")]
+pub use crate::render_network::{start_render_node, RenderNode};
+pub use crate::scene_builder::{SceneBuilder, ShapeBuilder};
+pub use crate::scene_validator::SceneWarning;
+pub use crate::shading::{ambient_occlusion, AmbientOcclusion};
+pub use crate::spectral::SpectralRay;
+pub use crate::subsurface::SssParams;
+pub use crate::texture_atlas::{AtlasRegion, TextureAtlas, UvBounds};
 pub use crate::transformation::Transformation;
 pub use crate::vector::Vector;
+pub use crate::video_output::VideoOutput;
+pub use crate::ward::Ward;
 pub use crate::world::World;
+pub use crate::world_diff::WorldDiff;
+pub use crate::world_file::{DeserializeError, SerializeError};
+pub use crate::world_statistics::WorldStatistics;
 
 use std::cmp::Ordering;
 
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{Color, Point};
+use crate::{shapes::Shape, Color, Point, Vector};
 
 /// A light source with no size, existing at a single point in space.
 ///
@@ -37,6 +37,139 @@ impl PointLight {
             intensity,
         }
     }
+
+    /// Creates a new `PointLight` at the `position` with the color of a
+    /// glowing body at `kelvin` degrees, such as 2700 for a warm tungsten
+    /// bulb or 6500 for daylight, made as bright as `intensity`.
+    ///
+    /// The color is found with the curve fitted to the colors of glowing
+    /// bodies by Tanner Helland, good from 1000 to 40000 kelvin. The
+    /// brightest channel of the color is scaled to `intensity`.
+    ///
+    /// Helland, Tanner "How to Convert Temperature (K) to RGB" (2012)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{PointLight, Point};
+    ///
+    /// let light = PointLight::color_temperature(Point::new(0.0, 10.0, 0.0), 1.0, 2700.0);
+    ///
+    /// assert_eq!(light.intensity.red, 1.0);
+    /// assert!(light.intensity.blue < light.intensity.green);
+    /// ```
+    pub fn color_temperature(position: Point, intensity: f64, kelvin: f64) -> PointLight {
+        PointLight::new(position, temperature_color(kelvin) * intensity)
+    }
+}
+
+/// Distance at which a [`DirectionalLight`] is stood in for by a
+/// [`PointLight`], far enough for its rays to be parallel.
+const DIRECTIONAL_LIGHT_DISTANCE: f64 = 1.0e6;
+
+/// A light so far away that its rays are parallel and equally bright
+/// everywhere, like sunlight.
+///
+/// # Example
+///
+/// ```
+/// use rustic_ray::{Color, DirectionalLight, Vector};
+///
+/// let sun = DirectionalLight::new(Vector::new(0.0, -2.0, 0.0), Color::new(1.0, 1.0, 1.0));
+///
+/// assert_eq!(sun.direction, Vector::new(0.0, -1.0, 0.0));
+/// ```
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct DirectionalLight {
+    /// Direction the light travels in, of unit length
+    pub direction: Vector,
+    /// Brightness and color of the light
+    pub intensity: Color,
+}
+
+impl DirectionalLight {
+    /// Creates a new `DirectionalLight` shining along `direction` with the
+    /// given intensity and color.
+    pub fn new(direction: Vector, intensity: Color) -> DirectionalLight {
+        DirectionalLight {
+            direction: direction.normalize(),
+            intensity,
+        }
+    }
+
+    /// A [`PointLight`] lighting the `point` the way this light does, found
+    /// far back along the direction of the light.
+    pub(crate) fn as_point_light(&self, point: Point) -> PointLight {
+        PointLight::new(
+            point - self.direction * DIRECTIONAL_LIGHT_DISTANCE,
+            self.intensity,
+        )
+    }
+}
+
+/// The color of a glowing body at `kelvin` degrees, with channels from 0.0
+/// to 1.0.
+fn temperature_color(kelvin: f64) -> Color {
+    let t = kelvin.clamp(1000.0, 40000.0) / 100.0;
+
+    let red = if t <= 66.0 {
+        255.0
+    } else {
+        329.698_727_446 * (t - 60.0).powf(-0.133_204_759_2)
+    };
+    let green = if t <= 66.0 {
+        99.470_802_586_1 * t.ln() - 161.119_568_166_1
+    } else {
+        288.122_169_528_3 * (t - 60.0).powf(-0.075_514_849_2)
+    };
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_731_223_1 * (t - 10.0).ln() - 305.044_792_730_7
+    };
+
+    let channel = |c: f64| c.clamp(0.0, 255.0) / 255.0;
+    Color::new(channel(red), channel(green), channel(blue))
+}
+
+/// Number of points picked on the surface of an [`AreaLightShape`] for every
+/// point shaded, unless set otherwise.
+pub const DEFAULT_AREA_LIGHT_SAMPLES: usize = 16;
+
+/// A shape glowing from its whole surface, lighting the objects around it
+/// with soft shadows.
+///
+/// Each point shaded picks `samples` points on the surface of the `shape`
+/// and is lit from those not blocked by another object, so a point the shape
+/// is partly hidden from gets only part of the light. The light reaching a
+/// point falls off with the square of the distance to the shape and is
+/// weighed by how much the surface faces the point. The shape only lights up
+/// the points in front of its outside and is not seen by the rays cast into
+/// the world itself.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AreaLightShape {
+    /// Shape emitting the light, its surface must be able to be sampled.
+    pub shape: Box<dyn Shape>,
+    /// Brightness and color of every point of the surface. A shape large
+    /// enough to fill the view from a point lights it as much as a
+    /// [`PointLight`] of the same intensity straight above it would.
+    pub intensity: Color,
+    /// Number of points picked on the surface for every point shaded.
+    pub samples: usize,
+}
+
+impl AreaLightShape {
+    /// Create a light glowing from the surface of the `shape` with
+    /// `intensity`, using [`DEFAULT_AREA_LIGHT_SAMPLES`] samples.
+    pub fn new(shape: Box<dyn Shape>, intensity: Color) -> AreaLightShape {
+        AreaLightShape {
+            shape,
+            intensity,
+            samples: DEFAULT_AREA_LIGHT_SAMPLES,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -55,4 +188,33 @@ mod tests {
         assert_eq!(light.position, position);
         assert_eq!(light.intensity, intensity);
     }
+
+    #[test]
+    fn daylight_is_close_to_white() {
+        let light = PointLight::color_temperature(Point::new(0.0, 0.0, 0.0), 1.0, 6500.0);
+        let c = light.intensity;
+
+        assert_eq!(c.red, 1.0);
+        assert!(c.green > 0.97 && c.blue > 0.95);
+    }
+
+    #[test]
+    fn tungsten_light_is_warm_orange() {
+        let c = PointLight::color_temperature(Point::new(0.0, 0.0, 0.0), 1.0, 2700.0).intensity;
+
+        assert_eq!(c.red, 1.0);
+        assert!(c.green > 0.6 && c.green < 0.7);
+        assert!(c.blue > 0.3 && c.blue < 0.4);
+    }
+
+    #[test]
+    fn the_color_of_a_temperature_is_scaled_by_the_intensity() {
+        let position = Point::new(1.0, 2.0, 3.0);
+        let dim = PointLight::color_temperature(position, 1.0, 4000.0);
+        let bright = PointLight::color_temperature(position, 2.5, 4000.0);
+
+        assert_eq!(bright.position, position);
+        assert_eq!(bright.intensity, dim.intensity * 2.5);
+        assert_eq!(bright.intensity.red, 2.5);
+    }
 }
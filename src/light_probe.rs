@@ -0,0 +1,159 @@
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{shapes::spherical_map, Canvas, Color, Point, Vector};
+
+/// Light coming from every direction of a distant environment, such as the
+/// sky, read from an image of the environment all around the scene.
+///
+/// The image is an equirectangular map with the top row looking straight
+/// up, the bottom row straight down and the columns going once around the
+/// horizon, the way a [`crate::shapes::Sphere`] maps its texture coordinates.
+/// The colors of a [`Canvas`] are not limited to 1.0, so bright parts of the
+/// environment like the sun can be far brighter than the rest.
+///
+/// When shading, the diffuse light is read from the map in the direction of
+/// the normal and the specular light in the direction of the reflection. A
+/// direction blocked by another object brings no light.
+///
+/// # Example
+///
+/// ```
+/// use rustic_ray::{Canvas, Color, LightProbe, Vector};
+///
+/// let sky = Canvas::parallel_fill(8, 4, |_, y| {
+///     if y < 2 {
+///         Color::new(0.4, 0.6, 1.0)
+///     } else {
+///         Color::new(0.2, 0.1, 0.0)
+///     }
+/// });
+/// let probe = LightProbe::new(&sky);
+///
+/// assert_eq!(probe.sample(Vector::new(0.0, 1.0, 0.0)), Color::new(0.4, 0.6, 1.0));
+/// assert_eq!(probe.sample(Vector::new(0.0, -1.0, 0.0)), Color::new(0.2, 0.1, 0.0));
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "LightProbeData")]
+pub struct LightProbe {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+/// Serialized form of a [`LightProbe`], checked to have as many pixels as
+/// its size.
+#[derive(Deserialize)]
+struct LightProbeData {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl TryFrom<LightProbeData> for LightProbe {
+    type Error = &'static str;
+
+    fn try_from(data: LightProbeData) -> Result<Self, Self::Error> {
+        if data.width == 0 || data.height == 0 {
+            return Err("a light probe needs at least one pixel");
+        }
+        if data.pixels.len() != data.width * data.height {
+            return Err("the pixels of a light probe do not match its size");
+        }
+
+        Ok(LightProbe {
+            width: data.width,
+            height: data.height,
+            pixels: data.pixels,
+        })
+    }
+}
+
+impl LightProbe {
+    /// Create a probe lighting the world with the environment in the pixels
+    /// of `image`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image has no pixels.
+    pub fn new(image: &Canvas) -> LightProbe {
+        assert!(
+            image.width > 0 && image.height > 0,
+            "a light probe needs at least one pixel"
+        );
+        let mut pixels = Vec::with_capacity(image.width * image.height);
+        for y in 0..image.height {
+            for x in 0..image.width {
+                pixels.push(image.pixel_at(x, y));
+            }
+        }
+
+        LightProbe {
+            width: image.width,
+            height: image.height,
+            pixels,
+        }
+    }
+
+    /// The color of the environment seen looking along `direction`.
+    pub fn sample(&self, direction: Vector) -> Color {
+        let (u, v) = spherical_map(Point::new(0.0, 0.0, 0.0) + direction);
+        let x = ((u * self.width as f64) as usize).min(self.width - 1);
+        let y = (((1.0 - v) * self.height as f64) as usize).min(self.height - 1);
+
+        self.pixels[x + y * self.width]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_probe_is_sampled_at_spherical_coordinates() {
+        // every pixel has its own color, red for the column, green for the row
+        let image = Canvas::parallel_fill(8, 4, |x, y| Color::new(x as f64, y as f64, 0.0));
+        let probe = LightProbe::new(&image);
+
+        assert_eq!(probe.sample(Vector::new(0.0, 1.0, 0.0)).green, 0.0);
+        assert_eq!(probe.sample(Vector::new(0.0, -1.0, 0.0)).green, 3.0);
+        // +z is looked at from the middle column and +x a quarter of the way
+        assert_eq!(
+            probe.sample(Vector::new(0.0, 0.1, 1.0)),
+            Color::new(4.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            probe.sample(Vector::new(1.0, -0.1, 0.0)),
+            Color::new(2.0, 2.0, 0.0)
+        );
+        assert_eq!(
+            probe.sample(Vector::new(-1.0, 0.1, 0.0)),
+            Color::new(6.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one pixel")]
+    fn a_probe_of_an_empty_image() {
+        LightProbe::new(&Canvas::new(0, 0));
+    }
+
+    #[test]
+    fn deserializing_a_probe_without_its_pixels_is_an_error() {
+        let probe = LightProbe::new(&Canvas::new(4, 2));
+        let json = serde_json::to_string(&probe).unwrap();
+        assert_eq!(serde_json::from_str::<LightProbe>(&json).unwrap(), probe);
+
+        let mut value = serde_json::to_value(&probe).unwrap();
+        value["pixels"].as_array_mut().unwrap().pop();
+
+        assert!(serde_json::from_value::<LightProbe>(value.clone()).is_err());
+
+        value["width"] = serde_json::json!(0);
+        value["height"] = serde_json::json!(0);
+        value["pixels"] = serde_json::json!([]);
+
+        assert!(serde_json::from_value::<LightProbe>(value).is_err());
+    }
+}
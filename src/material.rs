@@ -1,4 +1,8 @@
-use crate::{patterns::Pattern, shapes::Shape, Color, Point, PointLight, Vector};
+use crate::{
+    patterns::{Pattern, SolidColor},
+    shapes::{displacement_at, Mesh, Shape},
+    Color, Point, PointLight, SssParams, Vector, Ward,
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -19,10 +23,9 @@ use serde::{Deserialize, Serialize};
 /// highlight.
 ///
 /// Buck, Jamis "The Ray Tracer Challenge" (84)
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(from = "MaterialData")]
 pub struct Material {
-    /// Color of the material.
-    pub color: Color,
     /// Background light, or light reflected from other objects in the environment.
     pub ambient: f64,
     /// Light reflected form a matte surface.
@@ -39,8 +42,85 @@ pub struct Material {
     pub transparency: f64,
     /// Attribute to designate the index of refraction for an object.
     pub refractive_index: f64,
-    /// Apply an optional `pattern` for shape instead of a color.
-    pub pattern: Option<Box<dyn Pattern>>,
+    /// The `pattern` coloring the surface, a [`SolidColor`] for a material
+    /// with a single color.
+    pub pattern: Box<dyn Pattern>,
+    /// Light scattering under the surface, for translucent materials like
+    /// skin.
+    #[serde(default)]
+    pub subsurface_scattering: Option<SssParams>,
+    /// Anisotropic specular highlights using the [`Ward`] model, replacing
+    /// the round highlight controlled by `shininess`.
+    #[serde(default)]
+    pub anisotropy: Option<Ward>,
+    /// Light both faces of a thin surface, like a plane or a sheet of paper,
+    /// from a light on either side. A surface that is not two sided is only
+    /// lit on the side facing the light, looking dark from the other side.
+    #[serde(default)]
+    pub two_sided: bool,
+    /// Moves the surface along its normal by the average of the channels of
+    /// the pattern's color. Meshes are displaced by
+    /// [`Material::displace_mesh`], spheres and planes are rendered displaced
+    /// by marching along the rays.
+    #[serde(default)]
+    pub displacement_map: Option<Box<dyn Pattern>>,
+    /// Fraction of the light reflected at each wavelength, as pairs of a
+    /// wavelength in nanometers and a reflectance sorted by wavelength, used
+    /// in place of the color of the `pattern` by
+    /// [`crate::Camera::render_spectral`].
+    #[serde(default)]
+    pub spectral_reflectance: Option<Vec<(f64, f64)>>,
+}
+
+/// Serialized form of a [`Material`], also reading materials saved before
+/// the `pattern` replaced the `color`, with a `color` and a `pattern` that
+/// may be `null`.
+#[derive(Deserialize)]
+struct MaterialData {
+    #[serde(default)]
+    color: Option<Color>,
+    ambient: f64,
+    diffuse: f64,
+    specular: f64,
+    shininess: f64,
+    reflective: f64,
+    transparency: f64,
+    refractive_index: f64,
+    #[serde(default)]
+    pattern: Option<Box<dyn Pattern>>,
+    #[serde(default)]
+    subsurface_scattering: Option<SssParams>,
+    #[serde(default)]
+    anisotropy: Option<Ward>,
+    #[serde(default)]
+    two_sided: bool,
+    #[serde(default)]
+    displacement_map: Option<Box<dyn Pattern>>,
+    #[serde(default)]
+    spectral_reflectance: Option<Vec<(f64, f64)>>,
+}
+
+impl From<MaterialData> for Material {
+    fn from(data: MaterialData) -> Self {
+        let color = data.color.unwrap_or_else(|| Color::new(1.0, 1.0, 1.0));
+        Material {
+            ambient: data.ambient,
+            diffuse: data.diffuse,
+            specular: data.specular,
+            shininess: data.shininess,
+            reflective: data.reflective,
+            transparency: data.transparency,
+            refractive_index: data.refractive_index,
+            pattern: data
+                .pattern
+                .unwrap_or_else(|| Box::new(SolidColor::new(color))),
+            subsurface_scattering: data.subsurface_scattering,
+            anisotropy: data.anisotropy,
+            two_sided: data.two_sided,
+            displacement_map: data.displacement_map,
+            spectral_reflectance: data.spectral_reflectance,
+        }
+    }
 }
 
 impl Material {
@@ -59,7 +139,7 @@ impl Material {
     ///
     /// let m = Material::new();
     ///
-    /// assert_eq!(m.color, Color::new(1.0, 1.0, 1.0));
+    /// assert_eq!(m.solid_color(), Some(Color::new(1.0, 1.0, 1.0)));
     /// assert_eq!(m.ambient, 0.1);
     /// assert_eq!(m.diffuse, 0.9);
     /// assert_eq!(m.specular, 0.9);
@@ -67,7 +147,6 @@ impl Material {
     /// ```
     pub fn new() -> Self {
         Material {
-            color: Color::new(1.0, 1.0, 1.0),
             ambient: 0.1,
             diffuse: 0.9,
             specular: 0.9,
@@ -75,10 +154,191 @@ impl Material {
             reflective: 0.0,
             refractive_index: 1.0,
             transparency: 0.0,
-            pattern: None,
+            pattern: Box::new(SolidColor::new(Color::new(1.0, 1.0, 1.0))),
+            subsurface_scattering: None,
+            anisotropy: None,
+            two_sided: false,
+            displacement_map: None,
+            spectral_reflectance: None,
         }
     }
 
+    /// The color of the material if its pattern is a [`SolidColor`].
+    pub fn solid_color(&self) -> Option<Color> {
+        self.pattern
+            .as_any()
+            .and_then(|a| a.downcast_ref::<SolidColor>())
+            .map(|p| p.color)
+    }
+
+    /// Color the whole material with `color`, replacing its pattern with a
+    /// [`SolidColor`].
+    pub fn set_color(&mut self, color: Color) {
+        self.pattern = Box::new(SolidColor::new(color));
+    }
+
+    /// Set the color of the material, replacing its pattern with a
+    /// [`SolidColor`].
+    ///
+    /// # Example
+    /// ```
+    /// use rustic_ray::{Color, Material};
+    ///
+    /// let m = Material::new()
+    ///     .color(Color::new(1.0, 0.2, 1.0))
+    ///     .diffuse(0.7)
+    ///     .specular(0.3);
+    ///
+    /// assert_eq!(m.solid_color(), Some(Color::new(1.0, 0.2, 1.0)));
+    /// assert_eq!(m.diffuse, 0.7);
+    /// assert_eq!(m.specular, 0.3);
+    /// ```
+    pub fn color(mut self, color: Color) -> Self {
+        self.set_color(color);
+        self
+    }
+
+    /// Set the `ambient` reflection of the material.
+    pub fn ambient(mut self, ambient: f64) -> Self {
+        self.ambient = ambient;
+        self
+    }
+
+    /// Set the `diffuse` reflection of the material.
+    pub fn diffuse(mut self, diffuse: f64) -> Self {
+        self.diffuse = diffuse;
+        self
+    }
+
+    /// Set the `specular` reflection of the material.
+    pub fn specular(mut self, specular: f64) -> Self {
+        self.specular = specular;
+        self
+    }
+
+    /// Set the `shininess` of the material.
+    pub fn shininess(mut self, shininess: f64) -> Self {
+        self.shininess = shininess;
+        self
+    }
+
+    /// Set how `reflective` the material is.
+    pub fn reflective(mut self, reflective: f64) -> Self {
+        self.reflective = reflective;
+        self
+    }
+
+    /// Set how transparent the material is.
+    pub fn transparency(mut self, transparency: f64) -> Self {
+        self.transparency = transparency;
+        self
+    }
+
+    /// Set the index of refraction of the material.
+    pub fn refractive_index(mut self, refractive_index: f64) -> Self {
+        self.refractive_index = refractive_index;
+        self
+    }
+
+    /// Scatter light under the surface of the material with `params`.
+    pub fn subsurface_scattering(mut self, params: SssParams) -> Self {
+        self.subsurface_scattering = Some(params);
+        self
+    }
+
+    /// Shade the specular highlight of the material with the [`Ward`] model.
+    pub fn anisotropy(mut self, ward: Ward) -> Self {
+        self.anisotropy = Some(ward);
+        self
+    }
+
+    /// Light the material from a light on either side of its surface.
+    pub fn two_sided(mut self, two_sided: bool) -> Self {
+        self.two_sided = two_sided;
+        self
+    }
+
+    /// Set the `pattern` coloring the material.
+    pub fn pattern(mut self, pattern: Box<dyn Pattern>) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
+    /// Displace the surface by the `map`, see [`Material::displacement_map`].
+    pub fn displacement_map(mut self, map: Box<dyn Pattern>) -> Self {
+        self.displacement_map = Some(map);
+        self
+    }
+
+    /// Reflect the fraction of light given by the `curve` at each wavelength,
+    /// see [`Material::spectral_reflectance`].
+    pub fn spectral_reflectance(mut self, curve: Vec<(f64, f64)>) -> Self {
+        self.spectral_reflectance = Some(curve);
+        self
+    }
+
+    /// A copy of the `mesh` with every vertex moved along its normal by
+    /// `scale` times the [`Material::displacement_map`] at the vertex, in
+    /// object space. Vertices without a normal in the mesh use the average
+    /// normal of the faces around them, and vertices of no face stay where
+    /// they are. A mesh with normals gets new ones for the displaced surface.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{patterns::SolidColor, shapes::Mesh, Color, Material, Point};
+    ///
+    /// let flat = Mesh::new(
+    ///     vec![
+    ///         Point::new(0.0, 0.0, 0.0),
+    ///         Point::new(1.0, 0.0, 0.0),
+    ///         Point::new(0.0, 0.0, 1.0),
+    ///     ],
+    ///     vec![],
+    ///     vec![(0, 1, 2)],
+    /// );
+    /// let m = Material::new().displacement_map(Box::new(SolidColor::new(Color::new(1.0, 1.0, 1.0))));
+    /// let raised = m.displace_mesh(&flat, 0.5);
+    ///
+    /// assert_eq!(raised.vertices[1], Point::new(1.0, 0.5, 0.0));
+    /// ```
+    pub fn displace_mesh(&self, mesh: &Mesh, scale: f64) -> Mesh {
+        let map = match &self.displacement_map {
+            Some(map) => map,
+            None => return mesh.clone(),
+        };
+
+        let normals = if mesh.normals.is_empty() {
+            vertex_normals(&mesh.vertices, &mesh.faces)
+        } else {
+            mesh.normals.clone()
+        };
+        let vertices: Vec<Point> = mesh
+            .vertices
+            .iter()
+            .zip(normals.iter())
+            .map(|(v, n)| {
+                // a vertex of no face has no normal to move along
+                if n.magnitude() > 0.0 {
+                    *v + n.normalize() * (scale * displacement_at(map.as_ref(), *v))
+                } else {
+                    *v
+                }
+            })
+            .collect();
+        let normals = if mesh.normals.is_empty() {
+            Vec::new()
+        } else {
+            vertex_normals(&vertices, &mesh.faces)
+        };
+
+        let mut displaced = Mesh::new(vertices, normals, mesh.faces.clone());
+        displaced.transform = mesh.transform;
+        displaced.material = mesh.material.clone();
+        displaced.colors = mesh.colors.clone();
+        displaced
+    }
+
     /// Add together the material's ambient, diffuse, and specular components,
     /// weighted by the angels between the different vectors.
     pub fn lighting(
@@ -90,10 +350,7 @@ impl Material {
         normalv: Vector,
         in_shadow: bool,
     ) -> Color {
-        let color = match self.pattern.as_ref() {
-            Some(pattern) => pattern.pattern_at_shape(object, point),
-            None => self.color,
-        };
+        let color = self.pattern.pattern_at_shape(object, point);
         // combine the surface color with the light's color/intensity
         let effective_color = color * light.intensity;
 
@@ -106,7 +363,7 @@ impl Material {
         // light_dot_normal represents the cosine of the the angle between the
         // light vector and the normal vector. A negative number means the
         // light is on the other side of the surface.
-        let diffuse: Color;
+        let mut diffuse: Color;
         let specular: Color;
         let light_dot_normal = lightv.dot(normalv);
         if light_dot_normal < 0.0 || in_shadow {
@@ -121,7 +378,13 @@ impl Material {
             // light reflects away from the eye.
             let reflectv = (-lightv).reflect(normalv);
             let reflect_dot_eye = reflectv.dot(eyev);
-            if reflect_dot_eye <= 0.0 {
+            if let Some(ward) = self.anisotropy {
+                // the Ward model spreads the highlight by the roughness along
+                // and across the tangent of the surface instead.
+                let tangent = object.tangent_at(point);
+                let factor = ward.specular(normalv, tangent, lightv, eyev);
+                specular = light.intensity * self.specular * factor;
+            } else if reflect_dot_eye <= 0.0 {
                 specular = Color::new(0.0, 0.0, 0.0);
             } else {
                 // Compute the specular contribution
@@ -130,11 +393,54 @@ impl Material {
             }
         }
 
+        // light scattered under the surface leaves it around where it entered,
+        // reaching points facing away from the light. A point facing the light
+        // but in shadow is shadowed by another object and stays dark.
+        if let Some(sss) = self.subsurface_scattering.filter(|s| s.scale > 0.0) {
+            if !(in_shadow && light_dot_normal >= 0.0) {
+                let factor = sss.diffuse_factor(object, light, point, normalv);
+                diffuse = effective_color * factor * self.diffuse;
+            }
+        }
+
         // Add teh three contributions together to get the final shading
         ambient + diffuse + specular
     }
 }
 
+// Deriving `PartialEq` tries to move the boxed pattern out of the reference,
+// so the fields are compared by hand.
+impl PartialEq for Material {
+    fn eq(&self, other: &Self) -> bool {
+        self.ambient == other.ambient
+            && self.diffuse == other.diffuse
+            && self.specular == other.specular
+            && self.shininess == other.shininess
+            && self.reflective == other.reflective
+            && self.transparency == other.transparency
+            && self.refractive_index == other.refractive_index
+            && *self.pattern == *other.pattern
+            && self.subsurface_scattering == other.subsurface_scattering
+            && self.anisotropy == other.anisotropy
+            && self.two_sided == other.two_sided
+            && self.displacement_map.as_deref() == other.displacement_map.as_deref()
+            && self.spectral_reflectance == other.spectral_reflectance
+    }
+}
+
+/// The normal of every vertex, the sum of the normals of the faces around
+/// it weighted by their area.
+fn vertex_normals(vertices: &[Point], faces: &[(usize, usize, usize)]) -> Vec<Vector> {
+    let mut normals = vec![Vector::new(0.0, 0.0, 0.0); vertices.len()];
+    for &(a, b, c) in faces {
+        let normal = (vertices[c] - vertices[a]).cross(vertices[b] - vertices[a]);
+        for i in [a, b, c] {
+            normals[i] = normals[i] + normal;
+        }
+    }
+    normals
+}
+
 impl Default for Material {
     fn default() -> Self {
         Self::new()
@@ -143,17 +449,53 @@ impl Default for Material {
 
 #[cfg(test)]
 mod tests {
-    use crate::{patterns::Stripe, shapes::Sphere, Colors, Point, PointLight, Vector};
+    use crate::{
+        float_eq,
+        patterns::{Marble, Stripe},
+        shapes::Sphere,
+        Colors, Point, PointLight, Transformation, Vector,
+    };
 
     use super::*;
 
+    #[test]
+    fn reading_a_material_saved_with_a_color() {
+        let json = r#"{
+            "color": { "red": 1.0, "green": 0.5, "blue": 0.0 },
+            "ambient": 0.1,
+            "diffuse": 0.9,
+            "specular": 0.9,
+            "shininess": 200.0,
+            "reflective": 0.0,
+            "transparency": 0.0,
+            "refractive_index": 1.0,
+            "pattern": null
+        }"#;
+
+        let m: Material = serde_json::from_str(json).unwrap();
+
+        assert_eq!(m.solid_color(), Some(Color::new(1.0, 0.5, 0.0)));
+        assert_eq!(m, Material::new().color(Color::new(1.0, 0.5, 0.0)));
+    }
+
+    #[test]
+    fn a_material_survives_a_round_trip() {
+        let m = Material::new()
+            .pattern(Box::new(Stripe::new(Colors::WHITE, Colors::BLACK)))
+            .reflective(0.5);
+
+        let json = serde_json::to_string(&m).unwrap();
+
+        assert_eq!(serde_json::from_str::<Material>(&json).unwrap(), m);
+    }
+
     /// Chapter 6 Light and Shading
     /// Page 85
     #[test]
     fn the_default_material() {
         let m = Material::new();
 
-        assert_eq!(m.color, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(m.solid_color(), Some(Color::new(1.0, 1.0, 1.0)));
         assert_eq!(m.ambient, 0.1);
         assert_eq!(m.diffuse, 0.9);
         assert_eq!(m.specular, 0.9);
@@ -251,7 +593,7 @@ mod tests {
     fn lighting_with_a_pattern_applied() {
         let mut m = Material::new();
 
-        m.pattern = Some(Box::new(Stripe::new(Colors::WHITE, Colors::BLACK)));
+        m.pattern = Box::new(Stripe::new(Colors::WHITE, Colors::BLACK));
         m.ambient = 1.0;
         m.diffuse = 0.0;
         m.specular = 0.0;
@@ -278,6 +620,179 @@ mod tests {
         assert_eq!(c2, Colors::BLACK);
     }
 
+    #[test]
+    fn lighting_evaluates_the_pattern_in_object_space() {
+        let mut m = Material::new();
+        m.pattern = Box::new(Stripe::new(Colors::WHITE, Colors::BLACK));
+        m.ambient = 1.0;
+        m.diffuse = 0.0;
+        m.specular = 0.0;
+        let mut object = Sphere::new();
+        object.transform = Transformation::new().translate(1.0, 0.0, 0.0).build();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        // x = 1.5 in world space is x = 0.5 on the sphere, a white stripe
+        let c = m.lighting(
+            &object,
+            light,
+            Point::new(1.5, 0.0, 0.0),
+            eyev,
+            normalv,
+            false,
+        );
+
+        assert_eq!(c, Colors::WHITE);
+    }
+
+    #[test]
+    fn the_default_material_has_a_white_solid_color() {
+        let m = Material::new();
+        let solid = m
+            .pattern
+            .as_any()
+            .and_then(|a| a.downcast_ref::<SolidColor>())
+            .expect("Not a solid color");
+
+        assert_eq!(solid.color, Colors::WHITE);
+    }
+
+    #[test]
+    fn changing_the_color_updates_the_solid_pattern() {
+        let mut m = Material::new();
+        m.set_color(Colors::RED);
+
+        assert_eq!(m.solid_color(), Some(Colors::RED));
+        assert_eq!(m.pattern.pattern_at(Point::new(1.0, 2.0, 3.0)), Colors::RED);
+    }
+
+    #[test]
+    fn a_material_with_another_pattern_has_no_color() {
+        let mut m = Material::new();
+        m.pattern = Box::new(Stripe::new(Colors::WHITE, Colors::BLACK));
+
+        assert_eq!(m.solid_color(), None);
+    }
+
+    #[test]
+    fn chaining_builder_methods_matches_field_assignment() {
+        let built = Material::new()
+            .color(Colors::RED)
+            .ambient(0.2)
+            .diffuse(0.7)
+            .specular(0.3)
+            .shininess(50.0)
+            .reflective(0.5)
+            .transparency(0.8)
+            .refractive_index(1.5);
+
+        let mut m = Material::new();
+        m.set_color(Colors::RED);
+        m.ambient = 0.2;
+        m.diffuse = 0.7;
+        m.specular = 0.3;
+        m.shininess = 50.0;
+        m.reflective = 0.5;
+        m.transparency = 0.8;
+        m.refractive_index = 1.5;
+
+        assert_eq!(built, m);
+    }
+
+    #[test]
+    fn setting_a_pattern_with_the_builder() {
+        let built = Material::new().pattern(Box::new(Stripe::new(Colors::WHITE, Colors::BLACK)));
+
+        assert_eq!(built.solid_color(), None);
+        assert_eq!(
+            built.pattern.pattern_at(Point::new(1.5, 0.0, 0.0)),
+            Colors::BLACK
+        );
+    }
+
+    /// A flat square mesh of `n` by `n` cells a unit wide in the `xz` plane,
+    /// facing up.
+    fn flat_grid(n: usize) -> Mesh {
+        let index = |i: usize, j: usize| i * (n + 1) + j;
+        let vertices = (0..=n)
+            .flat_map(|i| (0..=n).map(move |j| Point::new(i as f64, 0.0, j as f64)))
+            .collect();
+        let faces = (0..n)
+            .flat_map(|i| (0..n).map(move |j| (i, j)))
+            .flat_map(|(i, j)| {
+                vec![
+                    (index(i, j), index(i + 1, j), index(i, j + 1)),
+                    (index(i + 1, j), index(i + 1, j + 1), index(i, j + 1)),
+                ]
+            })
+            .collect();
+
+        Mesh::new(vertices, Vec::new(), faces)
+    }
+
+    /// A displacement map going up and down like `sin(x)` between 0 and 1.
+    fn sine_wave() -> Box<dyn Pattern> {
+        let mut wave = Marble::new(Colors::BLACK, Colors::WHITE);
+        wave.amplitude = 0.0;
+        Box::new(wave)
+    }
+
+    #[test]
+    fn a_sine_wave_displacement_makes_a_flat_mesh_wavy() {
+        let flat = flat_grid(6);
+        let m = Material::new().displacement_map(sine_wave());
+        let wavy = m.displace_mesh(&flat, 0.5);
+
+        assert_eq!(wavy.faces, flat.faces);
+        for (before, after) in flat.vertices.iter().zip(wavy.vertices.iter()) {
+            let expected = 0.5 * (before.x.sin() * 0.5 + 0.5);
+            assert!(float_eq(after.y, expected));
+            assert_eq!((after.x, after.z), (before.x, before.z));
+        }
+        let heights: Vec<f64> = wavy.vertices.iter().map(|v| v.y).collect();
+        assert!(heights.iter().cloned().fold(f64::MIN, f64::max) > 0.45);
+        assert!(heights.iter().cloned().fold(f64::MAX, f64::min) < 0.05);
+    }
+
+    #[test]
+    fn displacing_by_zero_keeps_the_mesh() {
+        let flat = flat_grid(4);
+        let m = Material::new().displacement_map(sine_wave());
+
+        assert_eq!(m.displace_mesh(&flat, 0.0).vertices, flat.vertices);
+        assert_eq!(
+            Material::new().displace_mesh(&flat, 2.0).vertices,
+            flat.vertices
+        );
+    }
+
+    #[test]
+    fn a_vertex_of_no_face_is_not_displaced() {
+        let mut flat = flat_grid(2);
+        let unused = Point::new(5.0, 5.0, 5.0);
+        flat.vertices.push(unused);
+        let flat = Mesh::new(flat.vertices, Vec::new(), flat.faces);
+        let m = Material::new().displacement_map(sine_wave());
+        let wavy = m.displace_mesh(&flat, 0.5);
+
+        assert_eq!(wavy.vertices.last(), Some(&unused));
+        assert!(wavy
+            .vertices
+            .iter()
+            .all(|v| v.x.is_finite() && v.y.is_finite() && v.z.is_finite()));
+    }
+
+    #[test]
+    fn serializing_a_solid_color_material() {
+        let mut m = Material::new();
+        m.set_color(Colors::RED);
+        let json = serde_json::to_string(&m).unwrap();
+        let de: Material = serde_json::from_str(&json).unwrap();
+
+        assert!(json.contains("\"type\":\"SolidColor\""));
+        assert_eq!(de, m);
+    }
+
     // Chapter 11 Reflection and Refraction
     // Page 143
     #[test]
@@ -294,4 +809,141 @@ mod tests {
         assert_eq!(m.transparency, 0.0);
         assert_eq!(m.refractive_index, 1.0);
     }
+
+    /// Light the point of the unit sphere at `angle` past the edge of the side
+    /// lit by a light along the x axis.
+    fn light_past_the_edge(m: Material, angle: f64) -> Color {
+        let mut s = Sphere::new();
+        s.material = m;
+        let light = PointLight::new(Point::new(100.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0));
+        let normalv = Vector::new(-angle.sin(), angle.cos(), 0.0);
+        let point = Point::new(normalv.x, normalv.y, normalv.z);
+
+        s.material
+            .lighting(&s, light, point, normalv, normalv, true)
+    }
+
+    fn skin(scale: f64) -> Material {
+        Material::new()
+            .ambient(0.0)
+            .specular(0.0)
+            .subsurface_scattering(SssParams::new(
+                Color::new(1.0, 0.8, 0.6),
+                Color::new(0.05, 0.1, 0.2),
+                scale,
+            ))
+    }
+
+    #[test]
+    fn subsurface_scattering_lights_the_shadowed_side() {
+        let lambertian = Material::new().ambient(0.0).specular(0.0);
+
+        assert_eq!(light_past_the_edge(lambertian, 0.1), Colors::BLACK);
+
+        let c = light_past_the_edge(skin(0.3), 0.1);
+        assert!(c.red > 0.0 && c.green > 0.0 && c.blue > 0.0);
+    }
+
+    #[test]
+    fn a_larger_scale_scatters_further() {
+        let near = light_past_the_edge(skin(0.05), 0.4);
+        let far = light_past_the_edge(skin(0.5), 0.4);
+
+        assert!(far.red > near.red);
+        assert!(far.green > near.green);
+    }
+
+    #[test]
+    fn zero_scale_is_lambertian_shading() {
+        let mut s = Sphere::new();
+        s.material = skin(0.0);
+        let light = PointLight::new(Point::new(10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let point = Point::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let lambertian = Material::new().ambient(0.0).specular(0.0);
+
+        assert_eq!(
+            s.material
+                .lighting(&s, light, point, normalv, normalv, false),
+            lambertian.lighting(&s, light, point, normalv, normalv, false)
+        );
+        assert_eq!(light_past_the_edge(skin(0.0), 0.1), Colors::BLACK);
+    }
+
+    /// The specular light of `m` at the origin of a plane lit from straight
+    /// above, seen from the direction tilted `angle` from the normal toward
+    /// `axis`.
+    fn highlight_toward(m: &Material, axis: Vector, angle: f64) -> f64 {
+        let plane = crate::shapes::Plane::new();
+        let light = PointLight::new(Point::new(0.0, 1000.0, 0.0), Color::new(1.0, 1.0, 1.0));
+        let normalv = Vector::new(0.0, 1.0, 0.0);
+        let eyev = (normalv * angle.cos() + axis * angle.sin()).normalize();
+
+        m.lighting(
+            &plane,
+            light,
+            Point::new(0.0, 0.0, 0.0),
+            eyev,
+            normalv,
+            false,
+        )
+        .red
+    }
+
+    fn brushed(ax: f64, ay: f64) -> Material {
+        Material::new()
+            .ambient(0.0)
+            .diffuse(0.0)
+            .specular(1.0)
+            .anisotropy(Ward::new(ax, ay))
+    }
+
+    #[test]
+    fn equal_roughness_gives_a_round_highlight() {
+        let m = brushed(0.2, 0.2);
+        let along = highlight_toward(&m, Vector::new(1.0, 0.0, 0.0), 0.3);
+        let across = highlight_toward(&m, Vector::new(0.0, 0.0, 1.0), 0.3);
+        let diagonal = highlight_toward(&m, Vector::new(1.0, 0.0, 1.0).normalize(), 0.3);
+
+        assert!(float_eq(along, across));
+        assert!(float_eq(along, diagonal));
+        assert!(highlight_toward(&m, Vector::new(1.0, 0.0, 0.0), 0.0) > along);
+    }
+
+    #[test]
+    fn larger_roughness_along_the_tangent_stretches_the_highlight() {
+        let m = brushed(0.5, 0.05);
+        let along = highlight_toward(&m, Vector::new(1.0, 0.0, 0.0), 0.3);
+        let across = highlight_toward(&m, Vector::new(0.0, 0.0, 1.0), 0.3);
+
+        assert!(along > 100.0 * across);
+    }
+
+    #[test]
+    fn the_highlight_follows_the_eye() {
+        let mut s = Sphere::new();
+        s.material = brushed(0.1, 0.1);
+        let light = PointLight::new(Point::new(0.0, 0.0, -1000.0), Color::new(1.0, 1.0, 1.0));
+
+        for eye_angle in [0.4, 1.0] {
+            let eyev = Vector::new(-f64::sin(eye_angle), 0.0, -f64::cos(eye_angle));
+            let brightest = (0..=300)
+                .map(|i| -1.5 + i as f64 * 0.01)
+                .max_by(|a, b| {
+                    let shade = |angle: f64| {
+                        let normalv = Vector::new(-angle.sin(), 0.0, -angle.cos());
+                        let point = Point::new(normalv.x, normalv.y, normalv.z);
+                        s.material
+                            .lighting(&s, light, point, eyev, normalv, false)
+                            .red
+                    };
+                    shade(*a).partial_cmp(&shade(*b)).unwrap()
+                })
+                .unwrap();
+
+            // the normal at the brightest point is halfway between the
+            // light and the eye.
+            assert!((brightest - eye_angle / 2.0).abs() < 0.05);
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use crate::{float_eq, Point, Vector};
+use crate::{float_eq, multiple_array, Point, Vector};
 use std::{
     fmt,
     ops::{Index, IndexMut, Mul},
@@ -33,6 +33,14 @@ pub const IDENTITY: Matrix = Matrix {
     ],
 };
 
+/// A matrix with all of its elements zero. Multiplying any matrix by it gives
+/// the zero matrix. It has no inverse, so its inverse is `NaN` like the
+/// inverse [`Matrix::new`] finds for any other matrix that cannot be inverted.
+pub const ZERO_MATRIX: Matrix = Matrix {
+    data: [[0.0; 4]; 4],
+    inverse: [[f64::NAN; 4]; 4],
+};
+
 impl Matrix {
     /// Creates a Matrix with the provide 4x4 array of [`f64`] numbers. Even
     /// though the storage of an array is 4x4 the matrix is used for 3x3 and
@@ -80,6 +88,55 @@ impl Matrix {
         Self { data, inverse }
     }
 
+    /// Creates a Matrix from its `rows`, so `rows[i][j]` is the element in row
+    /// `i` and column `j`. The same as [`Matrix::new`].
+    pub fn from_rows(rows: [[f64; 4]; 4]) -> Self {
+        Matrix::new(rows)
+    }
+
+    /// Creates a Matrix from its `cols`, so `cols[j][i]` is the element in row
+    /// `i` and column `j`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::Matrix;
+    ///
+    /// let m = Matrix::from_cols([
+    ///     [1.0, 0.0, 0.0, 0.0],
+    ///     [0.0, 1.0, 0.0, 0.0],
+    ///     [0.0, 0.0, 1.0, 0.0],
+    ///     [5.0, 6.0, 7.0, 1.0],
+    /// ]);
+    ///
+    /// assert_eq!(m.col(3), [5.0, 6.0, 7.0, 1.0]);
+    /// assert_eq!(m.row(0), [1.0, 0.0, 0.0, 5.0]);
+    /// ```
+    pub fn from_cols(cols: [[f64; 4]; 4]) -> Self {
+        let mut rows = [[0.0; 4]; 4];
+        for (j, col) in cols.iter().enumerate() {
+            for (i, value) in col.iter().enumerate() {
+                rows[i][j] = *value;
+            }
+        }
+        Matrix::new(rows)
+    }
+
+    /// The elements of row `i`.
+    pub fn row(&self, i: usize) -> [f64; 4] {
+        self.data[i]
+    }
+
+    /// The elements of column `j`.
+    pub fn col(&self, j: usize) -> [f64; 4] {
+        [
+            self.data[0][j],
+            self.data[1][j],
+            self.data[2][j],
+            self.data[3][j],
+        ]
+    }
+
     /// Returns a new `Matrix` where it's `data` is created from the `inverse`
     /// array of `self`. Calculating an inverse of a matrix is expensive. The
     /// `inverse` function is called multiple times for a single ray. The
@@ -260,25 +317,22 @@ impl Matrix {
 impl Mul for Matrix {
     type Output = Self;
 
+    /// Multiply two matrices. The inverse of the product is the product of
+    /// the inverses in reverse order, `(A * B)-1 = B-1 * A-1`, which is much
+    /// cheaper than inverting the result again.
     fn mul(self, rhs: Matrix) -> Self {
-        let mut results = [[0.0; 4]; 4];
-
-        for row in 0..4 {
-            for col in 0..4 {
-                results[row][col] = self[row][0] * rhs[0][col]
-                    + self[row][1] * rhs[1][col]
-                    + self[row][2] * rhs[2][col]
-                    + self[row][3] * rhs[3][col];
-            }
+        Matrix {
+            data: multiple_array(self.data, rhs.data),
+            inverse: multiple_array(rhs.inverse, self.inverse),
         }
-
-        Matrix::new(results)
     }
 }
 
 impl Mul<Point> for Matrix {
     type Output = Point;
 
+    /// Transform a point, taken as a tuple with `w` of 1 so that it is moved
+    /// by the translation of the matrix.
     fn mul(self, rhs: Point) -> Point {
         let x =
             (self[0][0] * rhs.x) + (self[0][1] * rhs.y) + (self[0][2] * rhs.z) + (self[0][3] * 1.0);
@@ -294,6 +348,8 @@ impl Mul<Point> for Matrix {
 impl Mul<Vector> for Matrix {
     type Output = Vector;
 
+    /// Transform a vector, taken as a tuple with `w` of 0 so that the
+    /// translation of the matrix leaves it unchanged.
     fn mul(self, rhs: Vector) -> Vector {
         let x =
             (self[0][0] * rhs.x) + (self[0][1] * rhs.y) + (self[0][2] * rhs.z) + (self[0][3] * 0.0);
@@ -362,8 +418,8 @@ impl fmt::Display for Matrix {
 
 #[cfg(test)]
 mod tests {
-    use super::{Matrix, IDENTITY};
-    use crate::{float_eq, Point};
+    use super::{Matrix, IDENTITY, ZERO_MATRIX};
+    use crate::{float_eq, Point, Vector};
 
     // Chapter 3 Matrices
     // Page 26
@@ -520,6 +576,49 @@ mod tests {
         assert_eq!(IDENTITY * m1, m1);
     }
 
+    #[test]
+    fn multiplying_a_point_and_a_vector_by_the_identity_matrix() {
+        let p = Point::new(1.0, -2.0, 3.0);
+        let v = Vector::new(-4.0, 5.0, 0.5);
+
+        assert_eq!(IDENTITY * p, p);
+        assert_eq!(IDENTITY * v, v);
+    }
+
+    #[test]
+    fn the_constant_matrices() {
+        const M: Matrix = IDENTITY;
+        static ZERO: Matrix = ZERO_MATRIX;
+        let a = Matrix::new([
+            [-5.0, 2.0, 6.0, -8.0],
+            [1.0, -5.0, 1.0, 8.0],
+            [7.0, 7.0, -6.0, -7.0],
+            [1.0, -3.0, 7.0, 4.0],
+        ]);
+
+        assert_eq!(M * IDENTITY, IDENTITY);
+        assert_eq!(ZERO * a, ZERO_MATRIX);
+        assert_eq!(a * ZERO, ZERO_MATRIX);
+    }
+
+    #[test]
+    fn building_a_matrix_from_rows_and_from_columns() {
+        let values = [
+            [-5.0, 2.0, 6.0, -8.0],
+            [1.0, -5.0, 1.0, 8.0],
+            [7.0, 7.0, -6.0, -7.0],
+            [1.0, -3.0, 7.0, 4.0],
+        ];
+        let by_rows = Matrix::from_rows(values);
+        let by_cols = Matrix::from_cols(values);
+
+        assert_eq!(by_rows.row(0), values[0]);
+        assert_eq!(by_cols.col(0), values[0]);
+        assert_eq!(by_rows.col(2), [6.0, 1.0, -6.0, 7.0]);
+        assert_eq!(by_rows.transpose(), by_cols);
+        assert_eq!(by_cols.transpose(), by_rows);
+    }
+
     // Chapter 3 Matrices
     // Page 33
     #[test]
@@ -550,6 +649,19 @@ mod tests {
         assert_eq!(a, IDENTITY);
     }
 
+    #[test]
+    fn transposing_twice_and_inverting_a_transpose() {
+        let m = Matrix::new([
+            [-5.0, 2.0, 6.0, -8.0],
+            [1.0, -5.0, 1.0, 8.0],
+            [7.0, 7.0, -6.0, -7.0],
+            [1.0, -3.0, 7.0, 4.0],
+        ]);
+
+        assert_eq!(m.transpose().transpose(), m);
+        assert_eq!(m.inverse().transpose(), m.transpose().inverse());
+    }
+
     // Chapter 3 Matrices
     // Page 34
     #[test]
@@ -810,4 +922,26 @@ mod tests {
         
         assert_eq!(c * b.inverse(), a);
     }
+
+    #[test]
+    #[rustfmt::skip]
+    fn the_inverse_of_a_product_matches_inverting_the_product() {
+        let a = Matrix::new([
+            [ 3.0, -9.0,  7.0,  3.0],
+            [ 3.0, -8.0,  2.0, -9.0],
+            [-4.0,  4.0,  4.0,  1.0],
+            [-6.0,  5.0, -1.0,  1.0],
+        ]);
+        let b = Matrix::new([
+            [8.0,  2.0, 2.0, 2.0],
+            [3.0, -1.0, 7.0, 0.0],
+            [7.0,  0.0, 5.0, 4.0],
+            [6.0, -2.0, 0.0, 5.0],
+        ]);
+
+        let c = a * b;
+
+        assert_eq!(c.inverse(), Matrix::new(c.data).inverse());
+        assert_eq!(c * c.inverse(), IDENTITY);
+    }
 }
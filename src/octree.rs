@@ -0,0 +1,371 @@
+use crate::{Bounds, Point};
+
+/// Deepest a node of the tree may be. Leaves this deep keep every item put in
+/// them, so many items at the same point do not split the tree forever.
+const MAX_DEPTH: usize = 16;
+
+/// Something with a position in space that can be stored in an [`Octree`].
+pub trait HasPosition {
+    /// The point in world space where the item is.
+    fn position(&self) -> Point;
+}
+
+impl HasPosition for Point {
+    fn position(&self) -> Point {
+        *self
+    }
+}
+
+/// A node of an [`Octree`], either holding items or split into eight
+/// children around the center of its box.
+#[derive(Debug, Clone)]
+enum OctreeNode<T> {
+    Leaf(Vec<T>),
+    Branch(Vec<OctreeNode<T>>),
+}
+
+/// A spatial index over items at points in space, for finding the items near
+/// a point.
+///
+/// The tree starts as a single box around the first items and grows when an
+/// item is put outside of it. A box holding more than `max_items_per_leaf`
+/// items is split into eight equal boxes around its center.
+///
+/// # Example
+///
+/// ```
+/// use rustic_ray::{Octree, Point};
+///
+/// let mut tree = Octree::new(4);
+/// for i in 0..10 {
+///     tree.insert(Point::new(i as f64, 0.0, 0.0));
+/// }
+///
+/// assert_eq!(tree.query_radius(Point::new(0.0, 0.0, 0.0), 2.5).len(), 3);
+/// assert_eq!(tree.query_k_nearest(Point::new(9.2, 0.0, 0.0), 1), vec![&Point::new(9.0, 0.0, 0.0)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Octree<T> {
+    /// Number of items a box can hold before it is split.
+    pub max_items_per_leaf: usize,
+    bounds: Bounds,
+    root: OctreeNode<T>,
+    len: usize,
+}
+
+/// The child of a box split at `center` holding `point`.
+fn octant(center: Point, point: Point) -> usize {
+    (point.x >= center.x) as usize
+        | ((point.y >= center.y) as usize) << 1
+        | ((point.z >= center.z) as usize) << 2
+}
+
+/// The box of the child `index` of `bounds` split at its center.
+fn child_bounds(bounds: &Bounds, index: usize) -> Bounds {
+    let center = bounds.centroid();
+    let pick = |bit: usize, min: f64, mid: f64, max: f64| {
+        if index & bit == 0 {
+            (min, mid)
+        } else {
+            (mid, max)
+        }
+    };
+    let (x0, x1) = pick(1, bounds.min.x, center.x, bounds.max.x);
+    let (y0, y1) = pick(2, bounds.min.y, center.y, bounds.max.y);
+    let (z0, z1) = pick(4, bounds.min.z, center.z, bounds.max.z);
+    Bounds::new(Point::new(x0, y0, z0), Point::new(x1, y1, z1))
+}
+
+/// Squared distance from `point` to the closest point of `bounds`.
+fn distance_squared_to(bounds: &Bounds, point: Point) -> f64 {
+    let gap = |v: f64, min: f64, max: f64| (min - v).max(0.0).max(v - max);
+    gap(point.x, bounds.min.x, bounds.max.x).powi(2)
+        + gap(point.y, bounds.min.y, bounds.max.y).powi(2)
+        + gap(point.z, bounds.min.z, bounds.max.z).powi(2)
+}
+
+fn contains(bounds: &Bounds, point: Point) -> bool {
+    (bounds.min.x..=bounds.max.x).contains(&point.x)
+        && (bounds.min.y..=bounds.max.y).contains(&point.y)
+        && (bounds.min.z..=bounds.max.z).contains(&point.z)
+}
+
+impl<T: HasPosition> Octree<T> {
+    /// Create an empty tree splitting boxes holding more than
+    /// `max_items_per_leaf` items.
+    pub fn new(max_items_per_leaf: usize) -> Self {
+        Octree {
+            max_items_per_leaf: max_items_per_leaf.max(1),
+            bounds: Bounds::empty(),
+            root: OctreeNode::Leaf(Vec::new()),
+            len: 0,
+        }
+    }
+
+    /// Put the `item` in the tree. When it lies outside of the box of the
+    /// tree the box is grown to twice the size needed and every item is put
+    /// in again.
+    pub fn insert(&mut self, item: T) {
+        let (position, max) = (item.position(), self.max_items_per_leaf);
+        if !contains(&self.bounds, position) {
+            let needed = self.bounds.merge(&Bounds::new(position, position));
+            let size = needed.max - needed.min;
+            let half = size.x.max(size.y).max(size.z).max(1.0);
+            let center = needed.centroid();
+            self.bounds = Bounds::new(
+                Point::new(center.x - half, center.y - half, center.z - half),
+                Point::new(center.x + half, center.y + half, center.z + half),
+            );
+
+            let root = std::mem::replace(&mut self.root, OctreeNode::Leaf(Vec::new()));
+            let mut items = Vec::with_capacity(self.len + 1);
+            Self::take_items(root, &mut items);
+            items.push(item);
+            for item in items {
+                Self::insert_into(&mut self.root, self.bounds, item, 0, max);
+            }
+        } else {
+            Self::insert_into(&mut self.root, self.bounds, item, 0, max);
+        }
+        self.len += 1;
+    }
+
+    fn insert_into(node: &mut OctreeNode<T>, bounds: Bounds, item: T, depth: usize, max: usize) {
+        match node {
+            OctreeNode::Branch(children) => {
+                let index = octant(bounds.centroid(), item.position());
+                Self::insert_into(
+                    &mut children[index],
+                    child_bounds(&bounds, index),
+                    item,
+                    depth + 1,
+                    max,
+                );
+            }
+            OctreeNode::Leaf(items) => {
+                items.push(item);
+                if items.len() > max && depth < MAX_DEPTH {
+                    let items = std::mem::take(items);
+                    *node =
+                        OctreeNode::Branch((0..8).map(|_| OctreeNode::Leaf(Vec::new())).collect());
+                    for item in items {
+                        Self::insert_into(node, bounds, item, depth, max);
+                    }
+                }
+            }
+        }
+    }
+
+    fn take_items(node: OctreeNode<T>, out: &mut Vec<T>) {
+        match node {
+            OctreeNode::Leaf(items) => out.extend(items),
+            OctreeNode::Branch(children) => {
+                for child in children {
+                    Self::take_items(child, out);
+                }
+            }
+        }
+    }
+
+    /// Every item at most `radius` away from `center`, in no particular
+    /// order.
+    pub fn query_radius(&self, center: Point, radius: f64) -> Vec<&T> {
+        let mut found = Vec::new();
+        if !self.bounds.is_empty() {
+            Self::search_radius(
+                &self.root,
+                &self.bounds,
+                center,
+                radius * radius,
+                &mut found,
+            );
+        }
+        found
+    }
+
+    fn search_radius<'a>(
+        node: &'a OctreeNode<T>,
+        bounds: &Bounds,
+        center: Point,
+        radius_squared: f64,
+        found: &mut Vec<&'a T>,
+    ) {
+        if distance_squared_to(bounds, center) > radius_squared {
+            return;
+        }
+
+        match node {
+            OctreeNode::Leaf(items) => found.extend(
+                items
+                    .iter()
+                    .filter(|i| (i.position() - center).magnitude().powi(2) <= radius_squared),
+            ),
+            OctreeNode::Branch(children) => {
+                for (index, child) in children.iter().enumerate() {
+                    let child_box = child_bounds(bounds, index);
+                    Self::search_radius(child, &child_box, center, radius_squared, found);
+                }
+            }
+        }
+    }
+
+    /// The `k` items closest to `center`, nearest first. Fewer are returned
+    /// only when the tree holds fewer than `k` items.
+    pub fn query_k_nearest(&self, center: Point, k: usize) -> Vec<&T> {
+        let mut found: Vec<(f64, &T)> = Vec::with_capacity(k + 1);
+        if k > 0 && !self.bounds.is_empty() {
+            Self::search_nearest(&self.root, &self.bounds, center, k, &mut found);
+        }
+        found.into_iter().map(|(_, item)| item).collect()
+    }
+
+    fn search_nearest<'a>(
+        node: &'a OctreeNode<T>,
+        bounds: &Bounds,
+        center: Point,
+        k: usize,
+        found: &mut Vec<(f64, &'a T)>,
+    ) {
+        match node {
+            OctreeNode::Leaf(items) => {
+                for item in items {
+                    let distance = (item.position() - center).magnitude().powi(2);
+                    if found.len() < k || distance < found[found.len() - 1].0 {
+                        let at = found.partition_point(|(d, _)| *d <= distance);
+                        found.insert(at, (distance, item));
+                        found.truncate(k);
+                    }
+                }
+            }
+            OctreeNode::Branch(children) => {
+                // visit the closest boxes first so the far ones can be skipped
+                let mut order: Vec<(f64, usize, Bounds)> = (0..8)
+                    .map(|index| {
+                        let child_box = child_bounds(bounds, index);
+                        (distance_squared_to(&child_box, center), index, child_box)
+                    })
+                    .collect();
+                order.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+                for (distance, index, child_box) in order {
+                    if found.len() == k && distance > found[k - 1].0 {
+                        break;
+                    }
+                    Self::search_nearest(&children[index], &child_box, center, k, found);
+                }
+            }
+        }
+    }
+
+    /// Iterate over every item of the tree, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let mut stack = vec![&self.root];
+        let mut leaves = Vec::new();
+        while let Some(node) = stack.pop() {
+            match node {
+                OctreeNode::Leaf(items) => leaves.push(items),
+                OctreeNode::Branch(children) => stack.extend(children.iter()),
+            }
+        }
+        leaves.into_iter().flatten()
+    }
+
+    /// Number of items in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the tree holds no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Points spread over a 10 by 10 by 10 box, picked without randomness.
+    fn scattered(count: usize) -> Vec<Point> {
+        (0..count)
+            .map(|i| {
+                let i = i as f64;
+                Point::new(
+                    (i * 0.618_034).fract() * 10.0,
+                    (i * 0.414_214).fract() * 10.0,
+                    (i * 0.732_051).fract() * 10.0,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn inserted_items_are_found_within_a_radius() {
+        let points = scattered(500);
+        let mut tree = Octree::new(4);
+        for p in &points {
+            tree.insert(*p);
+        }
+        let center = Point::new(5.0, 5.0, 5.0);
+
+        let mut found: Vec<Point> = tree
+            .query_radius(center, 2.0)
+            .into_iter()
+            .copied()
+            .collect();
+        let mut expected: Vec<Point> = points
+            .iter()
+            .copied()
+            .filter(|p| (*p - center).magnitude() <= 2.0)
+            .collect();
+        let key = |p: &Point| (p.x, p.y, p.z);
+        found.sort_by(|a, b| key(a).partial_cmp(&key(b)).unwrap());
+        expected.sort_by(|a, b| key(a).partial_cmp(&key(b)).unwrap());
+
+        assert_eq!(tree.len(), 500);
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn the_k_nearest_items_match_a_linear_search() {
+        let points = scattered(500);
+        let mut tree = Octree::new(8);
+        for p in &points {
+            tree.insert(*p);
+        }
+        let center = Point::new(2.0, 7.5, 4.0);
+
+        let found: Vec<f64> = tree
+            .query_k_nearest(center, 12)
+            .iter()
+            .map(|p| (**p - center).magnitude())
+            .collect();
+        let mut expected: Vec<f64> = points.iter().map(|p| (*p - center).magnitude()).collect();
+        expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        assert_eq!(found.len(), 12);
+        assert_eq!(found, expected[..12].to_vec());
+        assert_eq!(tree.query_k_nearest(center, 600).len(), 500);
+    }
+
+    #[test]
+    fn many_items_at_the_same_point_are_kept() {
+        let mut tree = Octree::new(2);
+        for _ in 0..2000 {
+            tree.insert(Point::new(1.0, 1.0, 1.0));
+        }
+        tree.insert(Point::new(-3.0, 0.0, 0.0));
+
+        assert_eq!(tree.len(), 2001);
+        assert_eq!(tree.iter().count(), 2001);
+        assert_eq!(
+            tree.query_radius(Point::new(1.0, 1.0, 1.0), 0.1).len(),
+            2000
+        );
+        assert_eq!(
+            tree.query_k_nearest(Point::new(-2.0, 0.0, 0.0), 1),
+            vec![&Point::new(-3.0, 0.0, 0.0)]
+        );
+    }
+}
@@ -1,17 +1,33 @@
 //! Geometric rules that define how any given point in space out to be colored.
-//! These patterns are stripes, gradients, rings, and checkers. Patterns are a
-//! function that accepts a point in space and returns a color.
+//! These patterns are stripes, gradients, rings, and checkers, along with
+//! procedural textures such as marble and wood. Patterns are a function that
+//! accepts a point in space and returns a color.
 mod checkers;
 mod gradient;
+mod image_cube_texture;
+mod image_texture;
+mod marble;
+mod noise;
 mod pattern;
 mod ring;
+mod solid;
 mod stripe;
 mod test_pattern;
+mod tiled;
+mod voronoi;
+mod wood;
 
 pub use checkers::Checkers;
-pub use gradient::Gradient;
-pub use pattern::Pattern;
+pub use gradient::{BilinearGradient, Gradient};
+pub use image_cube_texture::ImageCubeTexture;
+pub use image_texture::ImageTexture;
+pub use marble::Marble;
+pub use pattern::{ClonePattern, Pattern};
 pub use ring::Ring;
+pub use solid::SolidColor;
 pub use stripe::Stripe;
 #[cfg(test)]
 pub use test_pattern::TestPattern;
+pub use tiled::Tiled;
+pub use voronoi::{Voronoi, VoronoiMode};
+pub use wood::Wood;
@@ -26,6 +26,19 @@ impl Gradient {
             transform: IDENTITY,
         }
     }
+
+    /// Create a [`BilinearGradient`] blending the four corner colors `a` at
+    /// (0, 0), `b` at (1, 0), `c` at (0, 1) and `d` at (1, 1) in `x` and `y`.
+    pub fn bilinear(a: Color, b: Color, c: Color, d: Color) -> BilinearGradient {
+        BilinearGradient {
+            id: Uuid::new_v4(),
+            a,
+            b,
+            c,
+            d,
+            transform: IDENTITY,
+        }
+    }
 }
 
 #[typetag::serde]
@@ -47,10 +60,47 @@ impl Pattern for Gradient {
     }
 }
 
+/// A blend of four colors, bilinearly interpolating between the corners of
+/// every unit square in the `x` and `y` coordinates
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct BilinearGradient {
+    id: Uuid,
+    a: Color,
+    b: Color,
+    c: Color,
+    d: Color,
+    /// The transformation of the pattern.
+    pub transform: Matrix,
+}
+
+#[typetag::serde]
+impl Pattern for BilinearGradient {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn pattern_at(&self, point: Point) -> Color {
+        let u = point.x - point.x.floor();
+        let v = point.y - point.y.floor();
+        let bottom = self.a + (self.b - self.a) * u;
+        let top = self.c + (self.d - self.c) * u;
+
+        bottom + (top - bottom) * v
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Colors;
+    use crate::{shapes::Sphere, Colors, Transformation};
 
     // Chapter 10 Patterns
     // Page 135
@@ -72,4 +122,44 @@ mod tests {
             Color::new(0.25, 0.25, 0.25)
         );
     }
+
+    #[test]
+    fn a_bilinear_gradient_interpolates_between_four_corners() {
+        let a = Color::new(1.0, 0.0, 0.0);
+        let b = Color::new(0.0, 1.0, 0.0);
+        let c = Color::new(0.0, 0.0, 1.0);
+        let d = Color::new(1.0, 1.0, 1.0);
+        let pattern = Gradient::bilinear(a, b, c, d);
+
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 0.0)), a);
+        // `x` = 1 starts the next square, so `b` is reached just before it
+        assert_eq!(pattern.pattern_at(Point::new(1.0 - 1e-9, 0.0, 0.0)), b);
+        assert_eq!(pattern.pattern_at(Point::new(1.0, 0.0, 0.0)), a);
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.5, 0.0)), (a + c) * 0.5);
+        assert_eq!(
+            pattern.pattern_at(Point::new(0.5, 0.5, 0.0)),
+            (a + b + c + d) * 0.25
+        );
+        // repeats every unit square
+        assert_eq!(
+            pattern.pattern_at(Point::new(2.5, -1.5, 0.0)),
+            (a + b + c + d) * 0.25
+        );
+    }
+
+    #[test]
+    fn a_bilinear_gradient_with_a_pattern_transformation() {
+        let a = Colors::BLACK;
+        let b = Color::new(1.0, 0.0, 0.0);
+        let c = Color::new(0.0, 1.0, 0.0);
+        let d = Colors::WHITE;
+        let mut pattern = Gradient::bilinear(a, b, c, d);
+        pattern.set_transform(Transformation::new().scale(2.0, 2.0, 2.0).build());
+        let object = Sphere::new();
+
+        assert_eq!(
+            pattern.pattern_at_shape(&object, Point::new(1.0, 1.0, 0.0)),
+            (a + b + c + d) * 0.25
+        );
+    }
 }
@@ -0,0 +1,204 @@
+use super::{ImageTexture, Pattern};
+use crate::{shapes::cube_map, shapes::CubeFace, Canvas, Color, Matrix, Point, IDENTITY};
+
+use serde::{Deserialize, Serialize};
+use typetag;
+use uuid::Uuid;
+
+/// Wraps six images around a cube, one for each [`CubeFace`], as used for
+/// skyboxes. The face and texture coordinates come from projecting the point
+/// in pattern space on the unit cube, see [`crate::shapes::Cube::uv_at`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ImageCubeTexture {
+    id: Uuid,
+    faces: [ImageTexture; 6],
+    /// The transformation of the pattern.
+    pub transform: Matrix,
+}
+
+impl ImageCubeTexture {
+    /// Create a new cube texture from one image for each face.
+    pub fn new(
+        left: &Canvas,
+        front: &Canvas,
+        right: &Canvas,
+        back: &Canvas,
+        up: &Canvas,
+        down: &Canvas,
+    ) -> ImageCubeTexture {
+        ImageCubeTexture {
+            id: Uuid::new_v4(),
+            faces: [
+                ImageTexture::new(left),
+                ImageTexture::new(front),
+                ImageTexture::new(right),
+                ImageTexture::new(back),
+                ImageTexture::new(up),
+                ImageTexture::new(down),
+            ],
+            transform: IDENTITY,
+        }
+    }
+
+    /// Create a new cube texture from an atlas laid out as a cross, four faces
+    /// wide and three faces high. The middle row holds the `left`, `front`,
+    /// `right` and `back` faces, with the `up` and `down` faces above and
+    /// below the `front` face.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size of the `atlas` is not a multiple of 4 by 3.
+    pub fn from_cross(atlas: &Canvas) -> ImageCubeTexture {
+        assert!(
+            atlas.width % 4 == 0 && atlas.height % 3 == 0 && atlas.width > 0 && atlas.height > 0,
+            "a cross atlas must be a multiple of 4 by 3 pixels"
+        );
+        let (width, height) = (atlas.width / 4, atlas.height / 3);
+
+        let face = |column: usize, row: usize| {
+            let mut canvas = Canvas::new(width, height);
+            for y in 0..height {
+                for x in 0..width {
+                    let color = atlas.pixel_at(column * width + x, row * height + y);
+                    canvas.write_pixel(x, y, color);
+                }
+            }
+            canvas
+        };
+
+        ImageCubeTexture::new(
+            &face(0, 1),
+            &face(1, 1),
+            &face(2, 1),
+            &face(3, 1),
+            &face(1, 0),
+            &face(1, 2),
+        )
+    }
+
+    /// The image of the `face`.
+    pub fn face(&self, face: CubeFace) -> &ImageTexture {
+        &self.faces[face as usize]
+    }
+}
+
+#[typetag::serde]
+impl Pattern for ImageCubeTexture {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn pattern_at(&self, point: Point) -> Color {
+        let (face, u, v) = cube_map(point);
+        self.face(face).uv_pattern_at(u, v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Colors;
+
+    /// A 2×2 image of a single color with a red pixel in the top left corner.
+    fn image(color: Color) -> Canvas {
+        let mut c = Canvas::new(2, 2);
+        c.write_pixel(0, 0, Colors::RED);
+        c.write_pixel(1, 0, color);
+        c.write_pixel(0, 1, color);
+        c.write_pixel(1, 1, color);
+        c
+    }
+
+    fn colors() -> [Color; 6] {
+        [
+            Color::new(0.1, 0.0, 0.0),
+            Color::new(0.2, 0.0, 0.0),
+            Color::new(0.3, 0.0, 0.0),
+            Color::new(0.4, 0.0, 0.0),
+            Color::new(0.5, 0.0, 0.0),
+            Color::new(0.6, 0.0, 0.0),
+        ]
+    }
+
+    fn texture() -> ImageCubeTexture {
+        let c = colors();
+        ImageCubeTexture::new(
+            &image(c[0]),
+            &image(c[1]),
+            &image(c[2]),
+            &image(c[3]),
+            &image(c[4]),
+            &image(c[5]),
+        )
+    }
+
+    #[test]
+    fn each_face_samples_its_own_image() {
+        let t = texture();
+        let c = colors();
+
+        assert_eq!(t.pattern_at(Point::new(-1.0, -0.5, 0.5)), c[0]);
+        assert_eq!(t.pattern_at(Point::new(0.5, -0.5, 1.0)), c[1]);
+        assert_eq!(t.pattern_at(Point::new(1.0, -0.5, -0.5)), c[2]);
+        assert_eq!(t.pattern_at(Point::new(-0.5, -0.5, -1.0)), c[3]);
+        assert_eq!(t.pattern_at(Point::new(0.5, 1.0, 0.5)), c[4]);
+        assert_eq!(t.pattern_at(Point::new(0.5, -1.0, -0.5)), c[5]);
+    }
+
+    #[test]
+    fn the_seams_between_faces_sample_edge_pixels() {
+        let t = texture();
+
+        // the top left corner of the front face touches the left and up faces
+        assert_eq!(t.pattern_at(Point::new(-0.99, 0.99, 1.0)), Colors::RED);
+        // the edge between the front and right faces is the right column of
+        // the front image and the left column of the right image
+        assert_eq!(
+            t.pattern_at(Point::new(0.999, 0.9, 1.0)),
+            colors()[1],
+            "front face"
+        );
+        assert_eq!(
+            t.pattern_at(Point::new(1.0, 0.9, 0.999)),
+            Colors::RED,
+            "right face"
+        );
+    }
+
+    #[test]
+    fn creating_a_texture_from_a_cross_atlas() {
+        let c = colors();
+        let mut atlas = Canvas::new(8, 6);
+        let cells = [(0, 1), (1, 1), (2, 1), (3, 1), (1, 0), (1, 2)];
+        for (i, (column, row)) in cells.iter().enumerate() {
+            for y in 0..2 {
+                for x in 0..2 {
+                    atlas.write_pixel(column * 2 + x, row * 2 + y, c[i]);
+                }
+            }
+        }
+        let t = ImageCubeTexture::from_cross(&atlas);
+
+        for (i, face) in [
+            CubeFace::Left,
+            CubeFace::Front,
+            CubeFace::Right,
+            CubeFace::Back,
+            CubeFace::Up,
+            CubeFace::Down,
+        ]
+        .iter()
+        .enumerate()
+        {
+            assert_eq!(t.face(*face).uv_pattern_at(0.5, 0.5), c[i]);
+        }
+    }
+}
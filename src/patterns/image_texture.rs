@@ -0,0 +1,240 @@
+use std::convert::TryFrom;
+
+use super::Pattern;
+use crate::{
+    shapes::spherical_map, shapes::Shape, Canvas, Color, Intersection, Matrix, Point, Ray, IDENTITY,
+};
+
+use serde::{Deserialize, Serialize};
+use typetag;
+use uuid::Uuid;
+
+/// Wraps an image around a shape using its texture coordinates.
+///
+/// On shapes with a texture mapping, such as a [`crate::shapes::Sphere`], the
+/// `(u, v)` coordinates of the shape pick the pixel, with `v = 1` at the top
+/// row of the image. Other shapes and direct calls to `pattern_at` fall back
+/// to a spherical projection of the point in pattern space.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "ImageTextureData")]
+pub struct ImageTexture {
+    id: Uuid,
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+    /// The transformation of the pattern.
+    pub transform: Matrix,
+}
+
+/// Serialized form of an [`ImageTexture`], checked to have as many pixels
+/// as its size.
+#[derive(Deserialize)]
+struct ImageTextureData {
+    id: Uuid,
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+    transform: Matrix,
+}
+
+impl TryFrom<ImageTextureData> for ImageTexture {
+    type Error = &'static str;
+
+    fn try_from(data: ImageTextureData) -> Result<Self, Self::Error> {
+        if data.width == 0 || data.height == 0 {
+            return Err("an image texture needs at least one pixel");
+        }
+        if data.pixels.len() != data.width * data.height {
+            return Err("the pixels of an image texture do not match its size");
+        }
+
+        Ok(ImageTexture {
+            id: data.id,
+            width: data.width,
+            height: data.height,
+            pixels: data.pixels,
+            transform: data.transform,
+        })
+    }
+}
+
+impl ImageTexture {
+    /// Create a new texture from the pixels of a `canvas`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the canvas has no pixels.
+    pub fn new(canvas: &Canvas) -> ImageTexture {
+        assert!(
+            canvas.width > 0 && canvas.height > 0,
+            "an image texture needs at least one pixel"
+        );
+        let mut pixels = Vec::with_capacity(canvas.width * canvas.height);
+        for y in 0..canvas.height {
+            for x in 0..canvas.width {
+                pixels.push(canvas.pixel_at(x, y));
+            }
+        }
+
+        ImageTexture {
+            id: Uuid::new_v4(),
+            width: canvas.width,
+            height: canvas.height,
+            pixels,
+            transform: IDENTITY,
+        }
+    }
+
+    /// The color of the pixel nearest to the texture coordinates `u` and `v`.
+    pub fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        let x = (u.clamp(0.0, 1.0) * (self.width - 1) as f64).round() as usize;
+        let y = ((1.0 - v.clamp(0.0, 1.0)) * (self.height - 1) as f64).round() as usize;
+
+        self.pixels[x + y * self.width]
+    }
+}
+
+#[typetag::serde]
+impl Pattern for ImageTexture {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn pattern_at(&self, point: Point) -> Color {
+        let (u, v) = spherical_map(point);
+        self.uv_pattern_at(u, v)
+    }
+
+    /// Sample the image with the texture coordinates of `object` at
+    /// `world_point`.
+    fn pattern_at_shape(&self, object: &dyn Shape, world_point: Point) -> Color {
+        let object_point = object.transform().inverse() * world_point;
+
+        match object.local_uv_at(object_point) {
+            Some((u, v)) => self.uv_pattern_at(u, v),
+            None => self.pattern_at(self.transform().inverse() * object_point),
+        }
+    }
+
+    /// Sample the image with the texture coordinates of the object hit, or
+    /// else with the `u` and `v` of the intersection, such as the ones of a
+    /// triangle.
+    fn pattern_at_intersection(&self, i: &Intersection, ray: Ray) -> Color {
+        let object_point = i.object.transform().inverse() * ray.position(i.t);
+
+        match (i.object.local_uv_at(object_point), i.u, i.v) {
+            (Some((u, v)), _, _) | (None, Some(u), Some(v)) => self.uv_pattern_at(u, v),
+            _ => self.pattern_at(self.transform().inverse() * object_point),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shapes::{Sphere, Triangle},
+        Colors, Transformation, Vector,
+    };
+
+    /// A 2×2 image, red and green on the top row, blue and white on the
+    /// bottom row.
+    fn texture() -> ImageTexture {
+        let mut c = Canvas::new(2, 2);
+        c.write_pixel(0, 0, Colors::RED);
+        c.write_pixel(1, 0, Color::new(0.0, 1.0, 0.0));
+        c.write_pixel(0, 1, Color::new(0.0, 0.0, 1.0));
+        c.write_pixel(1, 1, Colors::WHITE);
+        ImageTexture::new(&c)
+    }
+
+    #[test]
+    fn sampling_the_corners_of_the_image() {
+        let t = texture();
+
+        assert_eq!(t.uv_pattern_at(0.0, 1.0), Colors::RED);
+        assert_eq!(t.uv_pattern_at(1.0, 1.0), Color::new(0.0, 1.0, 0.0));
+        assert_eq!(t.uv_pattern_at(0.0, 0.0), Color::new(0.0, 0.0, 1.0));
+        assert_eq!(t.uv_pattern_at(1.0, 0.0), Colors::WHITE);
+    }
+
+    #[test]
+    fn a_texture_on_a_transformed_sphere_uses_its_uv_coordinates() {
+        let t = texture();
+        let mut s = Sphere::new();
+        s.transform = Transformation::new()
+            .scale(2.0, 2.0, 2.0)
+            .translate(5.0, 0.0, 0.0)
+            .build();
+
+        // the top pole is at v = 1 and u = 0.5 rounds to the right column
+        assert_eq!(
+            t.pattern_at_shape(&s, Point::new(5.0, 2.0, 0.0)),
+            Color::new(0.0, 1.0, 0.0)
+        );
+        // just right of the seam at -z, u is close to 0
+        assert_eq!(
+            t.pattern_at_shape(&s, Point::new(5.01, -1.9, -0.5)),
+            Color::new(0.0, 0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn a_texture_at_an_intersection_uses_its_uv_coordinates() {
+        let t = texture();
+        let s = Sphere::new();
+        let r = Ray::new(Point::new(0.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let i = Intersection::new(4.0, &s);
+
+        assert_eq!(t.pattern_at_intersection(&i, r), Color::new(0.0, 1.0, 0.0));
+
+        let tri = Triangle::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+        );
+        let r = Ray::new(Point::new(0.0, 0.5, -2.0), Vector::new(0.0, 0.0, 1.0));
+        let i = Intersection::intersection_with_uv(2.0, &tri, 1.0, 1.0);
+
+        assert_eq!(t.pattern_at_intersection(&i, r), Color::new(0.0, 1.0, 0.0));
+        let i = Intersection::intersection_with_uv(2.0, &tri, 0.0, 0.0);
+        assert_eq!(t.pattern_at_intersection(&i, r), Color::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn serializing_a_texture() {
+        let t = texture();
+        let json = serde_json::to_string(&t).unwrap();
+        let de: ImageTexture = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(de, t);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one pixel")]
+    fn a_texture_of_an_empty_image() {
+        ImageTexture::new(&Canvas::new(0, 0));
+    }
+
+    #[test]
+    fn deserializing_a_texture_without_its_pixels_is_an_error() {
+        let mut value = serde_json::to_value(texture()).unwrap();
+        value["pixels"].as_array_mut().unwrap().pop();
+
+        assert!(serde_json::from_value::<ImageTexture>(value.clone()).is_err());
+
+        value["width"] = serde_json::json!(0);
+        value["height"] = serde_json::json!(0);
+        value["pixels"] = serde_json::json!([]);
+
+        assert!(serde_json::from_value::<ImageTexture>(value).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use super::{noise, Pattern};
+use crate::{Color, Matrix, Point, IDENTITY};
+
+use serde::{Deserialize, Serialize};
+use typetag;
+use uuid::Uuid;
+
+/// Veins of color `b` running through color `a` along the `x` axis, bent by
+/// turbulence.
+///
+/// The color is blended by `sin(frequency * x + amplitude * turbulence)`
+/// remapped to `[0, 1]`, where the turbulence is the sum of `octaves` layers
+/// of Perlin noise, each with twice the frequency and `persistence` times the
+/// strength of the previous one.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct Marble {
+    id: Uuid,
+    pub a: Color,
+    pub b: Color,
+    /// How close together the veins are.
+    pub frequency: f64,
+    /// How much the turbulence bends the veins.
+    pub amplitude: f64,
+    /// Number of layers of noise in the turbulence.
+    pub octaves: usize,
+    /// Strength of each layer of noise relative to the previous one.
+    pub persistence: f64,
+    /// The transformation of the pattern.
+    pub transform: Matrix,
+}
+
+impl Marble {
+    /// Create a new marble pattern with veins of color `b` in color `a`.
+    pub fn new(a: Color, b: Color) -> Marble {
+        Marble {
+            id: Uuid::new_v4(),
+            a,
+            b,
+            frequency: 1.0,
+            amplitude: 5.0,
+            octaves: 4,
+            persistence: 0.5,
+            transform: IDENTITY,
+        }
+    }
+
+    /// The blend between `a` and `b` at `point`, in `[0, 1]`.
+    fn value_at(&self, point: Point) -> f64 {
+        let turbulence = noise::turbulence(point, self.octaves, self.persistence);
+
+        (self.frequency * point.x + self.amplitude * turbulence).sin() * 0.5 + 0.5
+    }
+}
+
+#[typetag::serde]
+impl Pattern for Marble {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn pattern_at(&self, point: Point) -> Color {
+        self.a + (self.b - self.a) * self.value_at(point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Colors;
+
+    fn samples(pattern: &Marble) -> Vec<f64> {
+        (0..2000)
+            .map(|i| pattern.value_at(Point::new(i as f64 * 0.01, 0.3, -0.7)))
+            .collect()
+    }
+
+    #[test]
+    fn the_value_is_between_zero_and_one() {
+        let pattern = Marble::new(Colors::WHITE, Colors::BLACK);
+
+        assert!(samples(&pattern).iter().all(|v| (0.0..=1.0).contains(v)));
+    }
+
+    #[test]
+    fn the_pattern_is_smooth() {
+        let pattern = Marble::new(Colors::WHITE, Colors::BLACK);
+
+        for w in samples(&pattern).windows(2) {
+            assert!((w[0] - w[1]).abs() < 0.2);
+        }
+    }
+
+    #[test]
+    fn a_higher_frequency_gives_more_veins() {
+        let count_veins = |frequency: f64| {
+            let mut pattern = Marble::new(Colors::WHITE, Colors::BLACK);
+            pattern.frequency = frequency;
+            pattern.amplitude = 0.5;
+            samples(&pattern)
+                .windows(2)
+                .filter(|w| (w[0] - 0.5).signum() != (w[1] - 0.5).signum())
+                .count()
+        };
+
+        assert!(count_veins(8.0) > 2 * count_veins(1.0));
+    }
+}
@@ -0,0 +1,126 @@
+//! Noise functions shared by the procedural patterns.
+use crate::Point;
+
+/// Mix the bits of `value` (SplitMix64 finalizer).
+pub(crate) fn hash(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+/// Hash the integer coordinates of a lattice point.
+pub(crate) fn hash_point(seed: u64, x: i64, y: i64, z: i64) -> u64 {
+    let mut h = hash(seed);
+    for c in [x, y, z] {
+        h = hash(h ^ c as u64);
+    }
+    h
+}
+
+/// Map a hash to a uniform value in `[0, 1)` using its top 53 bits.
+pub(crate) fn unit(h: u64) -> f64 {
+    (h >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// The gradients pointing from the center of a cube to its edges.
+const GRADIENTS: [(f64, f64, f64); 12] = [
+    (1.0, 1.0, 0.0),
+    (-1.0, 1.0, 0.0),
+    (1.0, -1.0, 0.0),
+    (-1.0, -1.0, 0.0),
+    (1.0, 0.0, 1.0),
+    (-1.0, 0.0, 1.0),
+    (1.0, 0.0, -1.0),
+    (-1.0, 0.0, -1.0),
+    (0.0, 1.0, 1.0),
+    (0.0, -1.0, 1.0),
+    (0.0, 1.0, -1.0),
+    (0.0, -1.0, -1.0),
+];
+
+/// Smooth the interpolation weight so the noise has continuous first and
+/// second derivatives.
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// Gradient noise (Perlin's improved noise) at `point`, roughly in
+/// `[-1, 1]` and 0 at every integer lattice point.
+pub(crate) fn perlin(point: Point) -> f64 {
+    let (x0, y0, z0) = (point.x.floor(), point.y.floor(), point.z.floor());
+    let (fx, fy, fz) = (point.x - x0, point.y - y0, point.z - z0);
+    let (x0, y0, z0) = (x0 as i64, y0 as i64, z0 as i64);
+
+    let corner = |dx: i64, dy: i64, dz: i64| {
+        let g = GRADIENTS[(hash_point(0, x0 + dx, y0 + dy, z0 + dz) % 12) as usize];
+        g.0 * (fx - dx as f64) + g.1 * (fy - dy as f64) + g.2 * (fz - dz as f64)
+    };
+
+    let (u, v, w) = (fade(fx), fade(fy), fade(fz));
+
+    lerp(
+        w,
+        lerp(
+            v,
+            lerp(u, corner(0, 0, 0), corner(1, 0, 0)),
+            lerp(u, corner(0, 1, 0), corner(1, 1, 0)),
+        ),
+        lerp(
+            v,
+            lerp(u, corner(0, 0, 1), corner(1, 0, 1)),
+            lerp(u, corner(0, 1, 1), corner(1, 1, 1)),
+        ),
+    )
+}
+
+/// Sum of the absolute value of `octaves` layers of [`perlin`] noise. Each
+/// layer doubles the frequency and scales the amplitude by `persistence`.
+pub(crate) fn turbulence(point: Point, octaves: usize, persistence: f64) -> f64 {
+    let mut sum = 0.0;
+    let mut frequency = 1.0;
+    let mut amplitude = 1.0;
+
+    for _ in 0..octaves {
+        let p = Point::new(
+            point.x * frequency,
+            point.y * frequency,
+            point.z * frequency,
+        );
+        sum += perlin(p).abs() * amplitude;
+        frequency *= 2.0;
+        amplitude *= persistence;
+    }
+
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_is_zero_on_the_lattice() {
+        assert_eq!(perlin(Point::new(3.0, -2.0, 7.0)), 0.0);
+    }
+
+    #[test]
+    fn noise_is_bounded_and_varies() {
+        let values: Vec<f64> = (0..1000)
+            .map(|i| {
+                perlin(Point::new(
+                    i as f64 * 0.173,
+                    i as f64 * 0.029,
+                    i as f64 * -0.311,
+                ))
+            })
+            .collect();
+
+        assert!(values.iter().all(|v| v.abs() <= 1.5));
+        assert!(values.iter().any(|v| *v > 0.1));
+        assert!(values.iter().any(|v| *v < -0.1));
+    }
+}
@@ -1,13 +1,16 @@
-#[allow(unused_imports)]
-use crate::Transformation;
-use crate::{shapes::Shape, Color, Matrix, Point};
-use std::fmt;
+use super::Tiled;
+use crate::{shapes::Shape, Color, Intersection, Matrix, Point, Ray, Transformation};
+use std::{any::Any, fmt};
 
 use typetag;
 use uuid::Uuid;
 
 #[typetag::serde(tag = "type")]
-pub trait Pattern: fmt::Debug {
+pub trait Pattern: ClonePattern + fmt::Debug + Send + Sync {
+    fn as_any(&self) -> Option<&dyn Any> {
+        None
+    }
+
     /// Get the unique identifier for a pattern.
     fn id(&self) -> Uuid;
 
@@ -23,21 +26,18 @@ pub trait Pattern: fmt::Debug {
     fn set_transform(&mut self, transform: Matrix);
 
     /// Determine a color from a pattern a particular point on the pattern.
+    /// The `point` is in pattern space, see `pattern_at_shape` to color a
+    /// point in world space.
     ///
     /// # Example
     ///
     /// ```
-    /// use rustic_ray::{
-    ///     shapes::Sphere, patterns::Pattern, patterns::Checkers, Color, Colors,
-    ///     Point, Transformation
-    /// };
+    /// use rustic_ray::{patterns::Pattern, patterns::Checkers, Colors, Point};
     ///
-    /// let mut object = Sphere::new();
-    /// object.transform = Transformation::new().scale(2.0, 2.0, 2.0).build();
     /// let pattern = Checkers::new(Colors::WHITE, Colors::BLACK);
-    /// let c = pattern.pattern_at_shape(&object, Point::new(2.0, 3.0, 4.0));
     ///
-    /// assert_eq!(c, Colors::WHITE);
+    /// assert_eq!(pattern.pattern_at(Point::new(0.5, 0.5, 0.5)), Colors::WHITE);
+    /// assert_eq!(pattern.pattern_at(Point::new(1.5, 0.5, 0.5)), Colors::BLACK);
     ///```
     fn pattern_at(&self, point: Point) -> Color;
 
@@ -45,9 +45,12 @@ pub trait Pattern: fmt::Debug {
     ///
     /// 1. Convert the point from world space to object space
     /// 2. Convert the object space point to *pattern space*
-    /// 3. Get the color of the pattern by calling `stripe_at` with the
+    /// 3. Get the color of the pattern by calling `pattern_at` with the
     /// point on the pattern.
     ///
+    /// Callers such as [`crate::Material::lighting`] only need the world
+    /// space point and the shape it lies on.
+    ///
     /// # Example
     ///
     /// ```
@@ -63,22 +66,119 @@ pub trait Pattern: fmt::Debug {
     ///
     /// assert_eq!(c, Colors::WHITE);
     /// ```
-    fn pattern_at_shape(&self, object: &dyn Shape, word_point: Point) -> Color {
-        let object_point = object.transform().inverse() * word_point;
+    fn pattern_at_shape(&self, object: &dyn Shape, world_point: Point) -> Color {
+        let object_point = object.transform().inverse() * world_point;
         let pattern_point = self.transform().inverse() * object_point;
         self.pattern_at(pattern_point)
     }
+
+    /// Determine the color of the pattern where `ray` hits the object of the
+    /// intersection `i`, with the full hit available rather than only the
+    /// point, so patterns can use the texture coordinates, the direction of
+    /// the ray or the distance `t`.
+    ///
+    /// By default it colors the world space point of the hit like
+    /// `pattern_at_shape`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{
+    ///     shapes::Sphere, patterns::Pattern, patterns::Stripe, Colors,
+    ///     Intersection, Point, Ray, Vector
+    /// };
+    ///
+    /// let object = Sphere::new();
+    /// let pattern = Stripe::new(Colors::WHITE, Colors::BLACK);
+    /// let r = Ray::new(Point::new(-0.5, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    /// let i = Intersection::new(4.5, &object);
+    ///
+    /// assert_eq!(pattern.pattern_at_intersection(&i, r), Colors::BLACK);
+    /// ```
+    fn pattern_at_intersection(&self, i: &Intersection, ray: Ray) -> Color {
+        self.pattern_at_shape(i.object, ray.position(i.t))
+    }
+
+    /// Wrap the pattern in a [`Tiled`] pattern repeating it every `scale`
+    /// units.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{patterns::Pattern, patterns::Gradient, Colors, Point};
+    ///
+    /// let pattern = Gradient::new(Colors::WHITE, Colors::BLACK).tile(1.0);
+    ///
+    /// assert_eq!(
+    ///     pattern.pattern_at(Point::new(1.5, 0.0, 0.0)),
+    ///     pattern.pattern_at(Point::new(0.5, 0.0, 0.0))
+    /// );
+    /// ```
+    fn tile(self, scale: f64) -> Box<dyn Pattern>
+    where
+        Self: Sized + 'static,
+    {
+        let mut tiled = Tiled::new(Box::new(self));
+        tiled.transform = Transformation::new().scale(scale, scale, scale).build();
+        Box::new(tiled)
+    }
+
+    /// Set the transform of the pattern to `m` and box it, ready to be put in
+    /// a [`crate::Material`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{patterns::Checkers, patterns::Pattern, Colors, Material, Transformation};
+    ///
+    /// let m = Material::new().pattern(
+    ///     Checkers::new(Colors::WHITE, Colors::BLACK)
+    ///         .transformed_with(Transformation::new().scale(0.5, 0.5, 0.5).build()),
+    /// );
+    ///
+    /// assert_eq!(m.pattern.transform(), Transformation::new().scale(0.5, 0.5, 0.5).build());
+    /// ```
+    fn transformed_with(mut self, m: Matrix) -> Box<dyn Pattern>
+    where
+        Self: Sized + 'static,
+    {
+        self.set_transform(m);
+        Box::new(self)
+    }
+}
+
+/// Copying a pattern behind a `Box<dyn Pattern>`, implemented for every
+/// pattern that is [`Clone`].
+pub trait ClonePattern {
+    /// A copy of the pattern, keeping its `id`.
+    fn clone_box(&self) -> Box<dyn Pattern>;
 }
 
-impl PartialEq for Box<dyn Pattern> {
-    fn eq(&self, other: &Box<dyn Pattern>) -> bool {
-        self.pattern_eq(other.as_ref())
+impl<T: Pattern + Clone + 'static> ClonePattern for T {
+    fn clone_box(&self) -> Box<dyn Pattern> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Pattern> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl PartialEq for dyn Pattern {
+    fn eq(&self, other: &dyn Pattern) -> bool {
+        self.pattern_eq(other)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{patterns::TestPattern, shapes::Sphere, Transformation, IDENTITY};
+    use crate::{
+        patterns::{Checkers, TestPattern},
+        shapes::Sphere,
+        Colors, Transformation, IDENTITY,
+    };
 
     use super::*;
 
@@ -141,4 +241,51 @@ mod tests {
 
         assert_eq!(c, Color::new(0.75, 0.5, 0.25));
     }
+
+    #[test]
+    fn a_pattern_on_a_translated_sphere_uses_the_sphere_space() {
+        let mut object = Sphere::new();
+        object.transform = Transformation::new().translate(5.0, 0.0, 0.0).build();
+        let pattern = TestPattern::new();
+        let c = pattern.pattern_at_shape(&object, Point::new(5.5, 1.0, 0.0));
+
+        assert_eq!(c, Color::new(0.5, 1.0, 0.0));
+    }
+
+    #[test]
+    fn a_pattern_transformation_applies_on_top_of_the_shape() {
+        let mut object = Sphere::new();
+        object.transform = Transformation::new().translate(5.0, 0.0, 0.0).build();
+        let mut pattern = TestPattern::new();
+        pattern.transform = Transformation::new().scale(0.5, 0.5, 0.5).build();
+        let c = pattern.pattern_at_shape(&object, Point::new(5.5, 1.0, 0.0));
+
+        assert_eq!(c, Color::new(1.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn transformed_with_matches_setting_the_transform() {
+        let m = Transformation::new()
+            .scale(2.0, 2.0, 2.0)
+            .translate(0.5, 0.0, 0.0)
+            .build();
+        let mut manual = Checkers::new(Colors::WHITE, Colors::BLACK);
+        manual.set_transform(m);
+        let adapted = Checkers::new(Colors::WHITE, Colors::BLACK).transformed_with(m);
+        let object = Sphere::new();
+
+        for x in 0..10 {
+            let p = Point::new(x as f64 * 0.7 - 3.0, 0.2, -0.4);
+            assert_eq!(
+                adapted.pattern_at_shape(&object, p),
+                manual.pattern_at_shape(&object, p)
+            );
+        }
+
+        let json = serde_json::to_string(&adapted).unwrap();
+        let de: Box<dyn Pattern> = serde_json::from_str(&json).unwrap();
+        assert!(json.contains("\"type\":\"Checkers\""));
+        assert_eq!(de.transform(), m);
+        assert_eq!(*de, *adapted);
+    }
 }
@@ -0,0 +1,90 @@
+use std::any::Any;
+
+use super::Pattern;
+use crate::{Color, Matrix, Point, IDENTITY};
+
+use serde::{Deserialize, Serialize};
+use typetag;
+use uuid::Uuid;
+
+/// The same color everywhere. This is the pattern of a [`crate::Material`]
+/// that has only a color.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct SolidColor {
+    id: Uuid,
+    pub color: Color,
+    /// The transformation of the pattern.
+    pub transform: Matrix,
+}
+
+impl SolidColor {
+    /// Create a new pattern that is `color` at every point.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Colors, Point, patterns::Pattern, patterns::SolidColor};
+    ///
+    /// let pattern = SolidColor::new(Colors::RED);
+    ///
+    /// assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 0.0)), Colors::RED);
+    /// assert_eq!(pattern.pattern_at(Point::new(1.5, -2.0, 3.0)), Colors::RED);
+    /// ```
+    pub fn new(color: Color) -> SolidColor {
+        SolidColor {
+            id: Uuid::new_v4(),
+            color,
+            transform: IDENTITY,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Pattern for SolidColor {
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Two solid colors are equal when they have the same color, even if they
+    /// were created separately.
+    fn pattern_eq(&self, other: &dyn Pattern) -> bool {
+        match other.as_any().and_then(|a| a.downcast_ref::<SolidColor>()) {
+            Some(other) => self.color == other.color,
+            None => false,
+        }
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn pattern_at(&self, _point: Point) -> Color {
+        self.color
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{patterns::Stripe, Colors};
+
+    #[test]
+    fn solid_colors_compare_by_color() {
+        let a: Box<dyn Pattern> = Box::new(SolidColor::new(Colors::RED));
+        let b: Box<dyn Pattern> = Box::new(SolidColor::new(Colors::RED));
+        let c: Box<dyn Pattern> = Box::new(SolidColor::new(Colors::WHITE));
+        let d: Box<dyn Pattern> = Box::new(Stripe::new(Colors::RED, Colors::RED));
+
+        assert!(a == b);
+        assert!(a != c);
+        assert!(a != d);
+    }
+}
@@ -0,0 +1,123 @@
+use super::Pattern;
+use crate::{Color, Matrix, Point, IDENTITY};
+
+use serde::{Deserialize, Serialize};
+use typetag;
+use uuid::Uuid;
+
+/// Repeats the unit cube of another pattern in every direction.
+///
+/// Each coordinate is wrapped into `[0, 1)` before the point is passed on to
+/// the `inner` pattern and its own transformation. The transformation of the
+/// `Tiled` pattern itself sets the size of the tiles.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Tiled {
+    id: Uuid,
+    /// The pattern repeated in every tile.
+    pub inner: Box<dyn Pattern>,
+    /// The transformation of the pattern.
+    pub transform: Matrix,
+}
+
+impl Tiled {
+    /// Create a new pattern repeating `inner` with a period of 1.
+    pub fn new(inner: Box<dyn Pattern>) -> Tiled {
+        Tiled {
+            id: Uuid::new_v4(),
+            inner,
+            transform: IDENTITY,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Pattern for Tiled {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn pattern_at(&self, point: Point) -> Color {
+        let tile_point = Point::new(
+            point.x - point.x.floor(),
+            point.y - point.y.floor(),
+            point.z - point.z.floor(),
+        );
+
+        self.inner
+            .pattern_at(self.inner.transform().inverse() * tile_point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        patterns::{Gradient, Stripe},
+        shapes::Sphere,
+        Colors, Transformation,
+    };
+
+    #[test]
+    fn a_tiled_gradient_repeats_every_unit() {
+        let mut gradient = Gradient::new(Colors::WHITE, Colors::BLACK);
+        // on its own the gradient would only repeat every 2 units
+        gradient.transform = Transformation::new().scale(2.0, 2.0, 2.0).build();
+        let pattern = Tiled::new(Box::new(gradient));
+
+        assert_eq!(
+            pattern.pattern_at(Point::new(1.5, 0.0, 0.0)),
+            pattern.pattern_at(Point::new(0.5, 0.0, 0.0))
+        );
+        assert_eq!(
+            pattern.pattern_at(Point::new(0.5, 0.0, 0.0)),
+            Color::new(0.75, 0.75, 0.75)
+        );
+    }
+
+    #[test]
+    fn a_tiled_stripe_repeats_every_unit() {
+        let mut stripe = Stripe::new(Colors::WHITE, Colors::BLACK);
+        stripe.transform = Transformation::new().scale(0.5, 0.5, 0.5).build();
+        let pattern = Tiled::new(Box::new(stripe));
+
+        assert_eq!(
+            pattern.pattern_at(Point::new(0.25, 0.0, 0.0)),
+            Colors::WHITE
+        );
+        assert_eq!(
+            pattern.pattern_at(Point::new(0.75, 0.0, 0.0)),
+            Colors::BLACK
+        );
+        assert_eq!(
+            pattern.pattern_at(Point::new(1.25, 0.0, 0.0)),
+            Colors::WHITE
+        );
+        assert_eq!(
+            pattern.pattern_at(Point::new(-0.25, 0.0, 0.0)),
+            Colors::BLACK
+        );
+    }
+
+    #[test]
+    fn tiling_a_pattern_with_a_scale() {
+        let pattern = Gradient::new(Colors::WHITE, Colors::BLACK).tile(2.0);
+        let object = Sphere::new();
+
+        assert_eq!(
+            pattern.pattern_at_shape(&object, Point::new(3.0, 0.0, 0.0)),
+            pattern.pattern_at_shape(&object, Point::new(1.0, 0.0, 0.0))
+        );
+        assert_eq!(
+            pattern.pattern_at_shape(&object, Point::new(1.0, 0.0, 0.0)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use super::{noise, Pattern};
+use crate::{Color, Matrix, Point, IDENTITY};
+
+use serde::{Deserialize, Serialize};
+use typetag;
+use uuid::Uuid;
+
+/// Which distance of the [`Voronoi`] pattern blends between its colors.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+pub enum VoronoiMode {
+    /// The distance to the nearest feature point, giving round cells that are
+    /// darkest at their centers.
+    #[default]
+    F1,
+    /// The difference between the distances to the second nearest and the
+    /// nearest feature point, giving cells with sharp borders.
+    F2MinusF1,
+}
+
+/// Cell pattern (Worley noise) blending between two colors by the distance to
+/// the nearest feature points.
+///
+/// Every unit cube of pattern space holds one feature point placed by a hash
+/// of the cube's coordinates and the `seed`, so the pattern is the same every
+/// time for the same `seed`. The cells are scaled with the `transform`.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct Voronoi {
+    id: Uuid,
+    pub a: Color,
+    pub b: Color,
+    /// Distance blending between `a` and `b`.
+    pub mode: VoronoiMode,
+    /// Seed of the feature point positions.
+    pub seed: u64,
+    /// The transformation of the pattern.
+    pub transform: Matrix,
+}
+
+impl Voronoi {
+    /// Create a new cell pattern going from color `a` at distance 0 to color
+    /// `b` at distance 1.
+    pub fn new(a: Color, b: Color) -> Voronoi {
+        Voronoi {
+            id: Uuid::new_v4(),
+            a,
+            b,
+            mode: VoronoiMode::F1,
+            seed: 0,
+            transform: IDENTITY,
+        }
+    }
+
+    /// The feature point of the unit cube at `x`, `y` and `z`.
+    fn feature_point(&self, x: i64, y: i64, z: i64) -> Point {
+        let h = noise::hash_point(self.seed, x, y, z);
+
+        Point::new(
+            x as f64 + noise::unit(h),
+            y as f64 + noise::unit(noise::hash(h)),
+            z as f64 + noise::unit(noise::hash(h ^ 1)),
+        )
+    }
+
+    /// The distances to the nearest and the second nearest feature points.
+    fn distances(&self, point: Point) -> (f64, f64) {
+        let (cx, cy, cz) = (
+            point.x.floor() as i64,
+            point.y.floor() as i64,
+            point.z.floor() as i64,
+        );
+
+        let mut f1 = f64::INFINITY;
+        let mut f2 = f64::INFINITY;
+
+        // the nearest two feature points are always in a neighboring cube
+        for x in cx - 1..=cx + 1 {
+            for y in cy - 1..=cy + 1 {
+                for z in cz - 1..=cz + 1 {
+                    let d = (self.feature_point(x, y, z) - point).magnitude();
+
+                    if d < f1 {
+                        f2 = f1;
+                        f1 = d;
+                    } else if d < f2 {
+                        f2 = d;
+                    }
+                }
+            }
+        }
+
+        (f1, f2)
+    }
+}
+
+#[typetag::serde]
+impl Pattern for Voronoi {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn pattern_at(&self, point: Point) -> Color {
+        let (f1, f2) = self.distances(point);
+        let value = match self.mode {
+            VoronoiMode::F1 => f1,
+            VoronoiMode::F2MinusF1 => f2 - f1,
+        };
+
+        self.a + (self.b - self.a) * value.clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{shapes::Sphere, Colors, Transformation, EPSILON};
+
+    fn distance(a: Color, b: Color) -> f64 {
+        (a.red - b.red)
+            .abs()
+            .max((a.green - b.green).abs())
+            .max((a.blue - b.blue).abs())
+    }
+
+    #[test]
+    fn the_pattern_is_continuous() {
+        for mode in [VoronoiMode::F1, VoronoiMode::F2MinusF1] {
+            let mut pattern = Voronoi::new(Colors::BLACK, Colors::WHITE);
+            pattern.mode = mode;
+
+            for i in 0..100 {
+                let p = Point::new(i as f64 * 0.137, i as f64 * -0.071, i as f64 * 0.053);
+                let q = Point::new(p.x + 1e-6, p.y - 1e-6, p.z + 1e-6);
+
+                assert!(distance(pattern.pattern_at(p), pattern.pattern_at(q)) < EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn the_pattern_is_deterministic() {
+        let p1 = Voronoi::new(Colors::BLACK, Colors::WHITE);
+        let p2 = Voronoi::new(Colors::BLACK, Colors::WHITE);
+        let mut p3 = Voronoi::new(Colors::BLACK, Colors::WHITE);
+        p3.seed = 42;
+        let point = Point::new(1.3, -4.2, 7.9);
+
+        assert_eq!(p1.pattern_at(point), p1.pattern_at(point));
+        assert_eq!(p1.pattern_at(point), p2.pattern_at(point));
+        assert_ne!(p1.pattern_at(point), p3.pattern_at(point));
+    }
+
+    #[test]
+    fn the_color_is_a_at_a_feature_point() {
+        let pattern = Voronoi::new(Colors::RED, Colors::WHITE);
+        let feature = pattern.feature_point(3, -2, 5);
+
+        assert_eq!(pattern.pattern_at(feature), Colors::RED);
+    }
+
+    #[test]
+    fn the_transformation_scales_the_cells() {
+        let object = Sphere::new();
+        let unscaled = Voronoi::new(Colors::BLACK, Colors::WHITE);
+        let mut scaled = Voronoi::new(Colors::BLACK, Colors::WHITE);
+        scaled.transform = Transformation::new().scale(4.0, 4.0, 4.0).build();
+        let point = Point::new(0.7, 1.9, -2.3);
+        let expected = unscaled.pattern_at(point);
+
+        assert!(
+            distance(
+                scaled.pattern_at_shape(&object, Point::new(2.8, 7.6, -9.2)),
+                expected
+            ) < EPSILON
+        );
+    }
+}
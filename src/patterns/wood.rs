@@ -0,0 +1,136 @@
+use super::{noise, Pattern};
+use crate::{Color, Matrix, Point, IDENTITY};
+
+use serde::{Deserialize, Serialize};
+use typetag;
+use uuid::Uuid;
+
+/// Number of layers of noise in the turbulence bending the rings.
+const OCTAVES: usize = 4;
+
+/// Wood grain made of concentric rings around the `y` axis alternating
+/// between the colors `a` and `b`, bent by turbulence.
+///
+/// The ring of a point is `floor(sqrt(x² + z²) * rings_per_unit +
+/// turbulence_scale * turbulence)`, even rings are `a` and odd rings are `b`.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct Wood {
+    id: Uuid,
+    /// Color of the even rings, including the one at the center.
+    pub a: Color,
+    /// Color of the odd rings.
+    pub b: Color,
+    /// Number of rings per unit of distance from the center.
+    pub rings_per_unit: f64,
+    /// How much the turbulence bends the rings, 0 gives perfect circles.
+    pub turbulence_scale: f64,
+    /// The transformation of the pattern.
+    pub transform: Matrix,
+}
+
+impl Wood {
+    /// Create a new wood pattern alternating between ring colors `a` and
+    /// `b`, with one ring per unit.
+    pub fn new(a: Color, b: Color) -> Wood {
+        Wood {
+            id: Uuid::new_v4(),
+            a,
+            b,
+            rings_per_unit: 1.0,
+            turbulence_scale: 0.5,
+            transform: IDENTITY,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Pattern for Wood {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn pattern_at(&self, point: Point) -> Color {
+        let radius = (point.x.powi(2) + point.z.powi(2)).sqrt();
+        let turbulence = noise::turbulence(point, OCTAVES, 0.5);
+        let ring = (radius * self.rings_per_unit + self.turbulence_scale * turbulence).floor();
+
+        if ring.rem_euclid(2.0) == 0.0 {
+            self.a
+        } else {
+            self.b
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Colors;
+
+    #[test]
+    fn the_center_is_the_lighter_color() {
+        let pattern = Wood::new(Colors::WHITE, Colors::BLACK);
+
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 0.0)), Colors::WHITE);
+    }
+
+    #[test]
+    fn the_color_changes_at_radius_one() {
+        let mut pattern = Wood::new(Colors::WHITE, Colors::BLACK);
+        pattern.turbulence_scale = 0.0;
+
+        assert_eq!(
+            pattern.pattern_at(Point::new(0.99, 0.0, 0.0)),
+            Colors::WHITE
+        );
+        assert_eq!(
+            pattern.pattern_at(Point::new(1.01, 0.0, 0.0)),
+            Colors::BLACK
+        );
+        assert_eq!(
+            pattern.pattern_at(Point::new(0.0, 0.0, 2.01)),
+            Colors::WHITE
+        );
+    }
+
+    #[test]
+    fn without_turbulence_the_rings_are_concentric() {
+        let mut pattern = Wood::new(Colors::WHITE, Colors::BLACK);
+        pattern.turbulence_scale = 0.0;
+        pattern.rings_per_unit = 3.0;
+
+        for i in 0..50 {
+            let radius = 0.07 + i as f64 * 0.13;
+            let expected = pattern.pattern_at(Point::new(radius, 0.0, 0.0));
+
+            for j in 0..16 {
+                let angle = j as f64 * std::f64::consts::PI / 8.0;
+                let p = Point::new(radius * angle.cos(), j as f64 * 0.7, radius * angle.sin());
+
+                assert_eq!(pattern.pattern_at(p), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn turbulence_bends_the_rings() {
+        let pattern = Wood::new(Colors::WHITE, Colors::BLACK);
+        let mut perfect = Wood::new(Colors::WHITE, Colors::BLACK);
+        perfect.turbulence_scale = 0.0;
+
+        let differs = (0..200).any(|i| {
+            let p = Point::new(i as f64 * 0.037, i as f64 * 0.11, i as f64 * -0.023);
+            pattern.pattern_at(p) != perfect.pattern_at(p)
+        });
+
+        assert!(differs);
+    }
+}
@@ -0,0 +1,451 @@
+use std::f64::consts::PI;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    sampling::{cosine_weighted_direction, random_unit_vector},
+    Camera, Canvas, Color, Colors, Computations, HasPosition, Intersection, Octree, Point, Ray,
+    Vector, World,
+};
+
+/// Number of times a photon bounces before it is dropped.
+const MAX_BOUNCES: usize = 5;
+/// Number of photons kept in a box of the octree before it is split.
+const PHOTONS_PER_LEAF: usize = 8;
+/// Number of nearby photons the light of a caustic is estimated from.
+const CAUSTIC_ESTIMATE_PHOTONS: usize = 20;
+
+/// A packet of light energy that landed on a matte surface.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Photon {
+    /// Point in world space where the photon landed.
+    pub position: Point,
+    /// Direction the photon travelled in when it landed.
+    pub direction: Vector,
+    /// Energy carried by the photon.
+    pub power: Color,
+}
+
+/// Indirect light of a [`World`] stored as photons, for global illumination.
+///
+/// The map is made in two passes. Photons are first shot from the light in
+/// random directions and followed as they bounce off matte surfaces, mirrors
+/// and through transparent objects, storing every photon that reaches a matte
+/// surface after at least one bounce. The light reaching a point is then
+/// estimated from the photons landed closest to it. Light coming straight from
+/// the light source is left out, as [`crate::Material::lighting`] already
+/// adds it.
+///
+/// The photons are kept in an [`Octree`] to find the ones near a point
+/// quickly.
+#[derive(Debug, Clone)]
+pub struct PhotonMap {
+    photons: Octree<Photon>,
+}
+
+impl HasPosition for Photon {
+    fn position(&self) -> Point {
+        self.position
+    }
+}
+
+impl PhotonMap {
+    /// Shoot `photon_count` photons from the light of `world` and store where
+    /// they land. The random directions are picked from `seed`, so the same
+    /// seed gives the same map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::Plane, PhotonMap, Transformation, World};
+    ///
+    /// let mut w = World::default();
+    /// let mut floor = Plane::new();
+    /// floor.transform = Transformation::new().translate(0.0, -1.0, 0.0).build();
+    /// let mut ceiling = Plane::new();
+    /// ceiling.transform = Transformation::new().translate(0.0, 20.0, 0.0).build();
+    /// w.add_object(Box::new(floor));
+    /// w.add_object(Box::new(ceiling));
+    ///
+    /// let map = PhotonMap::build(&w, 1000, 1);
+    ///
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn build(world: &World, photon_count: usize, seed: u64) -> PhotonMap {
+        let mut photons = Vec::new();
+
+        if let Some(light) = world.light {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let power = light.intensity * (1.0 / photon_count as f64);
+
+            for _ in 0..photon_count {
+                let ray = Ray::new_unit(light.position, random_unit_vector(&mut rng));
+                Self::trace(world, ray, power, &mut rng, &mut photons);
+            }
+        }
+
+        Self::from_photons(photons)
+    }
+
+    /// Shoot `photon_count` photons from the light of `world` and store only
+    /// the ones landing on a matte surface after going through transparent
+    /// objects or off mirrors, the light focused into caustics. Photons
+    /// hitting a matte surface first are dropped, as that light is already
+    /// added by [`crate::Material::lighting`].
+    ///
+    /// Lights do not fade with distance, so each photon is given the power
+    /// of the light spread over the sphere around the light reaching the
+    /// first surface it hits. Unfocused light through a caustic then has the
+    /// same brightness as straight from the light.
+    ///
+    /// Set the map as the [`World::caustics`] to add the caustics when
+    /// shading.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::{Plane, Sphere}, PhotonMap, Transformation, World};
+    ///
+    /// let mut w = World::default();
+    /// let mut floor = Plane::new();
+    /// floor.transform = Transformation::new().translate(0.0, -2.0, 0.0).build();
+    /// w.add_object(Box::new(floor));
+    /// w.add_object(Box::new(Sphere::glass_sphere()));
+    ///
+    /// w.caustics = Some(PhotonMap::build_caustics(&w, 1000, 1));
+    /// ```
+    pub fn build_caustics(world: &World, photon_count: usize, seed: u64) -> PhotonMap {
+        let mut photons = Vec::new();
+
+        if let Some(light) = world.light {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let power = light.intensity * (1.0 / photon_count as f64);
+
+            for _ in 0..photon_count {
+                let ray = Ray::new_unit(light.position, random_unit_vector(&mut rng));
+                Self::trace_caustic(world, ray, power, &mut rng, &mut photons);
+            }
+        }
+
+        Self::from_photons(photons)
+    }
+
+    fn from_photons(photons: Vec<Photon>) -> PhotonMap {
+        let mut tree = Octree::new(PHOTONS_PER_LEAF);
+        for photon in photons {
+            tree.insert(photon);
+        }
+        PhotonMap { photons: tree }
+    }
+
+    /// Follow a photon through the world. At each hit it is stored when the
+    /// surface is matte, and then bounced, refracted, scattered or absorbed at
+    /// random in proportion to the material.
+    fn trace(
+        world: &World,
+        mut ray: Ray,
+        mut power: Color,
+        rng: &mut StdRng,
+        out: &mut Vec<Photon>,
+    ) {
+        for bounce in 0..MAX_BOUNCES {
+            let xs = match world.intersect_world(ray) {
+                Some(xs) => xs,
+                None => return,
+            };
+            let hit = match Intersection::hit(&xs) {
+                Some(hit) => hit,
+                None => return,
+            };
+            let comps = hit.prepare_computations(ray, &xs, Some(world));
+            let material = world.get_object_material(comps.object);
+            let color = material.pattern.pattern_at_shape(comps.object, comps.point);
+
+            if bounce > 0 && material.diffuse > 0.0 {
+                out.push(Photon {
+                    position: comps.point,
+                    direction: ray.direction.normalize(),
+                    power,
+                });
+            }
+
+            let albedo = (color.red + color.green + color.blue) / 3.0 * material.diffuse;
+            let scatter = albedo.min(1.0 - material.reflective - material.transparency);
+            let choice: f64 = rng.gen();
+
+            ray = if choice < material.reflective {
+                comps.reflect_ray()
+            } else if choice < material.reflective + material.transparency {
+                comps.refract_ray().unwrap_or_else(|| comps.reflect_ray())
+            } else if choice < material.reflective + material.transparency + scatter {
+                power = power * color * (material.diffuse / scatter);
+                let direction = cosine_weighted_direction(comps.normalv, rng);
+                Ray::new_unit(comps.over_point, direction)
+            } else {
+                return;
+            };
+        }
+    }
+
+    /// Follow a photon off mirrors and through transparent objects until it
+    /// lands on a matte surface, storing it there unless it was its first
+    /// hit.
+    fn trace_caustic(
+        world: &World,
+        mut ray: Ray,
+        mut power: Color,
+        rng: &mut StdRng,
+        out: &mut Vec<Photon>,
+    ) {
+        for bounce in 0..MAX_BOUNCES {
+            let xs = match world.intersect_world(ray) {
+                Some(xs) => xs,
+                None => return,
+            };
+            let hit = match Intersection::hit(&xs) {
+                Some(hit) => hit,
+                None => return,
+            };
+            let comps = hit.prepare_computations(ray, &xs, Some(world));
+            let material = world.get_object_material(comps.object);
+
+            if bounce == 0 {
+                power = power * (4.0 * PI * hit.t.powi(2));
+            }
+
+            let choice: f64 = rng.gen();
+            ray = if choice < material.reflective {
+                comps.reflect_ray()
+            } else if choice < material.reflective + material.transparency {
+                comps.refract_ray().unwrap_or_else(|| comps.reflect_ray())
+            } else {
+                if bounce > 0 && material.diffuse > 0.0 {
+                    out.push(Photon {
+                        position: comps.point,
+                        direction: ray.direction.normalize(),
+                        power,
+                    });
+                }
+                return;
+            };
+        }
+    }
+
+    /// Light of the caustics in the map reflected toward the eye at the
+    /// intersection, in the color of the surface.
+    pub(crate) fn caustic_lighting(&self, world: &World, comps: &Computations) -> Color {
+        let material = world.get_object_material(comps.object);
+        let color = material.pattern.pattern_at_shape(comps.object, comps.point);
+        let irradiance = self.irradiance(comps.point, comps.normalv, CAUSTIC_ESTIMATE_PHOTONS);
+
+        color * irradiance * material.diffuse
+    }
+
+    /// Number of photons stored in the map.
+    pub fn len(&self) -> usize {
+        self.photons.len()
+    }
+
+    /// Whether no photon landed on a matte surface.
+    pub fn is_empty(&self) -> bool {
+        self.photons.is_empty()
+    }
+
+    /// The `k` photons closest to `point` paired with their squared distance
+    /// to it, nearest first.
+    pub fn nearest(&self, point: Point, k: usize) -> Vec<(f64, &Photon)> {
+        self.photons
+            .query_k_nearest(point, k)
+            .into_iter()
+            .map(|photon| ((photon.position - point).magnitude().powi(2), photon))
+            .collect()
+    }
+
+    /// Estimate the indirect light arriving at `point` on a surface facing
+    /// `normal`. The power of the `k` nearest photons that arrived from above
+    /// the surface is spread over the disc reaching the farthest of them.
+    pub fn irradiance(&self, point: Point, normal: Vector, k: usize) -> Color {
+        let nearest = self.nearest(point, k);
+        let radius_squared = match nearest.last() {
+            Some((d, _)) if *d > 0.0 => *d,
+            _ => return Colors::BLACK,
+        };
+
+        let power = nearest
+            .iter()
+            .filter(|(_, p)| p.direction.dot(normal) < 0.0)
+            .fold(Colors::BLACK, |sum, (_, p)| sum + p.power);
+
+        power * (1.0 / (PI * radius_squared))
+    }
+}
+
+/// An image of only the light focused by `world` into caustics as seen by
+/// the `camera`, from a map of `num_photons` photons made by
+/// [`PhotonMap::build_caustics`]. The image can be added over a render that
+/// has no caustics.
+pub fn caustics_pass(world: &World, num_photons: usize, camera: &Camera) -> Canvas {
+    let map = PhotonMap::build_caustics(world, num_photons, 0);
+
+    Canvas::parallel_fill(camera.hsize, camera.vsize, |x, y| {
+        let ray = match camera.visible_ray_for_pixel(x, y) {
+            Some(ray) => ray,
+            None => return Colors::BLACK,
+        };
+        let xs = match world.intersect_world(ray) {
+            Some(xs) => xs,
+            None => return Colors::BLACK,
+        };
+        match Intersection::hit(&xs) {
+            Some(hit) => {
+                let comps = hit.prepare_computations(ray, &xs, Some(world));
+                map.caustic_lighting(world, &comps)
+            }
+            None => Colors::BLACK,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shapes::{Cube, Plane, Sphere},
+        Material, PointLight, Transformation,
+    };
+
+    /// A closed room with a light near the ceiling and a table hiding the
+    /// middle of the floor from the light.
+    fn room() -> World {
+        let mut w = World::new();
+        w.light = Some(PointLight::new(
+            Point::new(0.0, 4.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+
+        let mut walls = Cube::new();
+        walls.transform = Transformation::new().scale(5.0, 5.0, 5.0).build();
+        walls.material = Material::new().diffuse(0.9).specular(0.0);
+        w.add_object(Box::new(walls));
+
+        let mut table = Cube::new();
+        table.transform = Transformation::new()
+            .scale(2.0, 0.1, 2.0)
+            .translate(0.0, 1.0, 0.0)
+            .build();
+        table.material = Material::new().diffuse(0.9).specular(0.0);
+        w.add_object(Box::new(table));
+
+        w
+    }
+
+    /// A glass ball at `x` above a matte floor, lit from straight above.
+    fn glass_ball_over_floor(x: f64) -> World {
+        let mut w = World::new();
+        w.light = Some(PointLight::new(
+            Point::new(x, 5.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+
+        let mut floor = Plane::new();
+        floor.transform = Transformation::new().translate(0.0, -1.0, 0.0).build();
+        w.add_object(Box::new(floor));
+
+        let mut ball = Sphere::glass_sphere();
+        ball.transform = Transformation::new().translate(x, 1.0, 0.0).build();
+        w.add_object(Box::new(ball));
+
+        w
+    }
+
+    #[test]
+    fn a_glass_ball_focuses_light_below_itself() {
+        let up = Vector::new(0.0, 1.0, 0.0);
+        let caustic_at = |ball: f64, x: f64| {
+            let w = glass_ball_over_floor(ball);
+            PhotonMap::build_caustics(&w, 20000, 1)
+                .irradiance(Point::new(x, -1.0, 0.0), up, CAUSTIC_ESTIMATE_PHOTONS)
+                .red
+        };
+
+        // the floor below the ball is in its shadow, but brighter than the
+        // unfocused light of the lamp
+        assert!(caustic_at(0.0, 0.0) > 1.0);
+        assert!(caustic_at(0.0, 3.0) < 0.01);
+        assert!(caustic_at(3.0, 3.0) > 1.0);
+        assert!(caustic_at(3.0, 0.0) < 0.01);
+    }
+
+    #[test]
+    fn the_caustics_are_added_when_shading() {
+        let mut w = glass_ball_over_floor(0.0);
+        let mut c = Camera::new(11, 11, PI / 6.0);
+        c.transform = Transformation::view_transform(
+            Point::new(-5.0, -0.5, 0.0),
+            Point::new(0.0, -1.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        let without = c.render(&w).pixel_at(5, 5);
+        let overlay = caustics_pass(&w, 20000, &c);
+        w.caustics = Some(PhotonMap::build_caustics(&w, 20000, 0));
+        let with = c.render(&w).pixel_at(5, 5);
+
+        assert!(overlay.pixel_at(5, 5).red > 0.5);
+        assert_eq!(overlay.pixel_at(0, 0), Colors::BLACK);
+        assert_eq!(with, without + overlay.pixel_at(5, 5));
+    }
+
+    #[test]
+    fn the_nearest_photons_match_a_linear_search() {
+        let w = room();
+        let map = PhotonMap::build(&w, 500, 3);
+        let point = Point::new(1.0, -5.0, 2.0);
+
+        let mut expected: Vec<f64> = map
+            .photons
+            .iter()
+            .map(|p| (p.position - point).magnitude().powi(2))
+            .collect();
+        expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let found: Vec<f64> = map.nearest(point, 10).iter().map(|(d, _)| *d).collect();
+
+        assert_eq!(found, expected[..10].to_vec());
+    }
+
+    #[test]
+    fn a_surface_hidden_from_the_light_receives_indirect_light() {
+        let w = room();
+        let under_table = Point::new(0.0, -5.0, 0.0);
+        assert!(w.is_shadow(under_table + Vector::new(0.0, 0.01, 0.0)));
+
+        let map = PhotonMap::build(&w, 5000, 1);
+        let e = map.irradiance(under_table, Vector::new(0.0, 1.0, 0.0), 50);
+
+        assert!(e.red > 0.0);
+        assert!(e.green > 0.0);
+        assert!(e.blue > 0.0);
+    }
+
+    #[test]
+    fn more_photons_give_less_noise() {
+        let w = room();
+        let point = Point::new(3.0, -5.0, 3.0);
+        let normal = Vector::new(0.0, 1.0, 0.0);
+
+        let spread = |photons: usize, k: usize| {
+            let estimates: Vec<f64> = (0..6)
+                .map(|seed| {
+                    PhotonMap::build(&w, photons, seed)
+                        .irradiance(point, normal, k)
+                        .red
+                })
+                .collect();
+            let mean = estimates.iter().sum::<f64>() / estimates.len() as f64;
+            let variance =
+                estimates.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / estimates.len() as f64;
+            variance.sqrt() / mean
+        };
+
+        assert!(spread(8000, 160) < spread(500, 10));
+    }
+}
@@ -0,0 +1,400 @@
+use std::{error::Error, fmt};
+
+use crate::{shapes::Mesh, Color, Point, Vector};
+
+/// Error returned by [`PlyFile::parse`].
+#[derive(Debug, PartialEq)]
+pub enum PlyError {
+    /// The file is not in the ASCII PLY format, binary files are not
+    /// supported.
+    UnsupportedFormat(String),
+    /// The header is malformed or misses the vertex or face elements.
+    InvalidHeader(String),
+    /// A line of the body could not be read. The `line` is one based.
+    ParseError { line: usize, message: String },
+}
+
+impl fmt::Display for PlyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlyError::UnsupportedFormat(format) => {
+                write!(f, "unsupported PLY format: {}", format)
+            }
+            PlyError::InvalidHeader(message) => write!(f, "invalid PLY header: {}", message),
+            PlyError::ParseError { line, message } => {
+                write!(f, "failed to parse PLY at line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl Error for PlyError {}
+
+/// A property of an element declared in the header.
+enum Property {
+    /// A single value, with its name and whether its type is an integer.
+    Scalar { name: String, integer: bool },
+    /// A list of values preceded by their count.
+    List { name: String },
+}
+
+/// An element declared in the header, with the number of lines it takes in
+/// the body.
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// Build meshes by parsing a PLY (Polygon File Format) file, the format
+/// written by many 3D scanners.
+pub struct PlyFile {}
+
+impl PlyFile {
+    /// Parse an ASCII PLY string returning a [`Mesh`] with the `vertex` and
+    /// `face` elements in the `buffer`. Polygons are split into triangles,
+    /// and the `nx`, `ny` and `nz` normals and `red`, `green` and `blue`
+    /// colors of the vertices are kept when present. Other elements and
+    /// properties are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::PlyFile;
+    ///
+    /// let ply = "ply
+    /// format ascii 1.0
+    /// element vertex 3
+    /// property float x
+    /// property float y
+    /// property float z
+    /// element face 1
+    /// property list uchar int vertex_index
+    /// end_header
+    /// -1 1 0
+    /// -1 0 0
+    /// 1 0 0
+    /// 3 0 1 2
+    /// ";
+    /// let mesh = PlyFile::parse(ply).unwrap();
+    ///
+    /// assert_eq!(mesh.vertices.len(), 3);
+    /// assert_eq!(mesh.faces, vec![(0, 1, 2)]);
+    /// ```
+    pub fn parse(buffer: &str) -> Result<Mesh, PlyError> {
+        let mut lines = buffer.lines().enumerate().map(|(i, l)| (i + 1, l.trim()));
+
+        let elements = PlyFile::parse_header(&mut lines)?;
+
+        let mut vertices = Vec::new();
+        let mut normals = Vec::new();
+        let mut colors = Vec::new();
+        let mut faces = Vec::new();
+
+        for element in elements.iter() {
+            for _ in 0..element.count {
+                let (number, line) =
+                    lines.by_ref().find(|(_, l)| !l.is_empty()).ok_or_else(|| {
+                        PlyError::ParseError {
+                            line: buffer.lines().count(),
+                            message: format!("missing {} element", element.name),
+                        }
+                    })?;
+                let error = |message: String| PlyError::ParseError {
+                    line: number,
+                    message,
+                };
+
+                let tokens: Vec<&str> = line.split_whitespace().collect();
+                let values = || {
+                    tokens
+                        .iter()
+                        .map(|v| v.parse::<f64>())
+                        .collect::<Result<Vec<f64>, _>>()
+                        .map_err(|e| error(e.to_string()))
+                };
+
+                match element.name.as_str() {
+                    "vertex" => {
+                        let vertex = PlyFile::read_vertex(element, &values()?).map_err(error)?;
+                        vertices.push(vertex.0);
+                        normals.extend(vertex.1);
+                        colors.extend(vertex.2);
+                    }
+                    "face" => {
+                        let polygon = PlyFile::read_face(element, &tokens).map_err(error)?;
+                        if let Some(&i) = polygon.iter().find(|&&i| i >= vertices.len()) {
+                            return Err(error(format!("missing vertex {}", i)));
+                        }
+                        for i in 1..polygon.len().saturating_sub(1) {
+                            faces.push((polygon[0], polygon[i], polygon[i + 1]));
+                        }
+                    }
+                    _ => {
+                        values()?;
+                    }
+                }
+            }
+        }
+
+        if normals.len() != vertices.len() {
+            normals.clear();
+        }
+
+        let mut mesh = Mesh::new(vertices, normals, faces);
+        if colors.len() == mesh.vertices.len() {
+            mesh.colors = colors;
+        }
+        Ok(mesh)
+    }
+
+    fn parse_header<'a, I>(lines: &mut I) -> Result<Vec<Element>, PlyError>
+    where
+        I: Iterator<Item = (usize, &'a str)>,
+    {
+        match lines.next() {
+            Some((_, "ply")) => (),
+            _ => return Err(PlyError::InvalidHeader("missing ply magic".to_string())),
+        }
+
+        let mut elements: Vec<Element> = Vec::new();
+        let mut ascii = false;
+
+        for (_, line) in lines {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens.as_slice() {
+                ["format", "ascii", _] => ascii = true,
+                ["format", format, ..] => {
+                    return Err(PlyError::UnsupportedFormat(format.to_string()))
+                }
+                ["element", name, count] => {
+                    let count = count
+                        .parse()
+                        .map_err(|_| PlyError::InvalidHeader(format!("bad count in {}", line)))?;
+                    elements.push(Element {
+                        name: name.to_string(),
+                        count,
+                        properties: Vec::new(),
+                    });
+                }
+                ["property", "list", _, _, name] => elements
+                    .last_mut()
+                    .ok_or_else(|| PlyError::InvalidHeader("property before element".into()))?
+                    .properties
+                    .push(Property::List {
+                        name: name.to_string(),
+                    }),
+                ["property", kind, name] => elements
+                    .last_mut()
+                    .ok_or_else(|| PlyError::InvalidHeader("property before element".into()))?
+                    .properties
+                    .push(Property::Scalar {
+                        name: name.to_string(),
+                        integer: !matches!(*kind, "float" | "float32" | "double" | "float64"),
+                    }),
+                ["end_header"] => {
+                    if !ascii {
+                        return Err(PlyError::InvalidHeader("missing format".to_string()));
+                    }
+                    if !elements.iter().any(|e| e.name == "vertex") {
+                        return Err(PlyError::InvalidHeader("missing vertex element".into()));
+                    }
+                    return Ok(elements);
+                }
+                _ => (),
+            }
+        }
+
+        Err(PlyError::InvalidHeader("missing end_header".to_string()))
+    }
+
+    /// Read the position of a vertex, its normal and color if the element
+    /// has them, from the `values` of its line.
+    fn read_vertex(
+        element: &Element,
+        values: &[f64],
+    ) -> Result<(Point, Option<Vector>, Option<Color>), String> {
+        let mut scalars: Vec<(&str, f64)> = Vec::new();
+        let mut values = values.iter();
+
+        for property in element.properties.iter() {
+            match property {
+                Property::Scalar { name, integer } => {
+                    let value = *values.next().ok_or("missing vertex property")?;
+                    // integer colors range from 0 to 255
+                    let value = match name.as_str() {
+                        "red" | "green" | "blue" if *integer => value / 255.0,
+                        _ => value,
+                    };
+                    scalars.push((name, value));
+                }
+                Property::List { .. } => {
+                    let count = *values.next().ok_or("missing vertex property")? as usize;
+                    values.by_ref().take(count).for_each(drop);
+                }
+            }
+        }
+
+        let get = |name: &str| scalars.iter().find(|(n, _)| *n == name).map(|(_, v)| *v);
+        let triple = |a, b, c| Some((get(a)?, get(b)?, get(c)?));
+
+        let (x, y, z) = triple("x", "y", "z").ok_or("vertex without a position")?;
+        let normal = triple("nx", "ny", "nz").map(|(x, y, z)| Vector::new(x, y, z));
+        let color = triple("red", "green", "blue").map(|(r, g, b)| Color::new(r, g, b));
+
+        Ok((Point::new(x, y, z), normal, color))
+    }
+
+    /// Read the vertex indices of a face from the `tokens` of its line. The
+    /// counts of the lists and the indices must be whole numbers that are
+    /// not negative.
+    fn read_face(element: &Element, tokens: &[&str]) -> Result<Vec<usize>, String> {
+        let mut values = tokens.iter();
+        let integer = |token: &str| {
+            token
+                .parse::<usize>()
+                .map_err(|_| format!("bad face value {}", token))
+        };
+
+        for property in element.properties.iter() {
+            match property {
+                Property::List { name } if name == "vertex_index" || name == "vertex_indices" => {
+                    let count = integer(values.next().ok_or("missing face property")?)?;
+                    let indices = values
+                        .take(count)
+                        .map(|i| integer(i))
+                        .collect::<Result<Vec<usize>, _>>()?;
+                    if indices.len() != count {
+                        return Err("missing vertex index".to_string());
+                    }
+                    return Ok(indices);
+                }
+                Property::List { .. } => {
+                    let count = integer(values.next().ok_or("missing face property")?)?;
+                    values.by_ref().take(count).for_each(drop);
+                }
+                Property::Scalar { .. } => {
+                    values.next();
+                }
+            }
+        }
+
+        Err("face without vertex indices".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The header of the Stanford bunny, with fewer vertices and faces.
+    const BUNNY: &str = "ply
+format ascii 1.0
+comment zipper output
+element vertex 5
+property float x
+property float y
+property float z
+property float confidence
+property float intensity
+element face 3
+property list uchar int vertex_indices
+end_header
+-0.0378297 0.12794 0.00447467 0.850855 0.5
+-0.0447794 0.128887 0.00190497 0.900159 0.5
+-0.0680095 0.151244 0.0371953 0.398443 0.5
+-0.00228741 0.13015 0.0232201 0.85268 0.5
+-0.0226054 0.126675 0.00715587 0.675938 0.5
+3 1 2 0
+3 0 4 3
+4 0 1 3 4
+";
+
+    #[test]
+    fn parsing_the_bunny_header() {
+        let mesh = PlyFile::parse(BUNNY).unwrap();
+
+        assert_eq!(mesh.vertices.len(), 5);
+        assert_eq!(mesh.faces.len(), 4);
+        assert_eq!(
+            mesh.vertices[0],
+            Point::new(-0.0378297, 0.12794, 0.00447467)
+        );
+        assert_eq!(mesh.faces[2], (0, 1, 3));
+        assert_eq!(mesh.faces[3], (0, 3, 4));
+        assert!(mesh.normals.is_empty());
+        assert!(mesh.colors.is_empty());
+    }
+
+    #[test]
+    fn parsing_vertex_colors() {
+        let ply = "ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_index
+end_header
+0 1 0 255 0 0
+-1 0 0 0 255 0
+1 0 0 0 0 255
+3 0 1 2
+";
+        let mesh = PlyFile::parse(ply).unwrap();
+
+        assert_eq!(
+            mesh.colors,
+            vec![
+                Color::new(1.0, 0.0, 0.0),
+                Color::new(0.0, 1.0, 0.0),
+                Color::new(0.0, 0.0, 1.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn binary_files_are_unsupported() {
+        let ply = "ply\nformat binary_little_endian 1.0\nelement vertex 0\nend_header\n";
+
+        assert_eq!(
+            PlyFile::parse(ply).err(),
+            Some(PlyError::UnsupportedFormat(
+                "binary_little_endian".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn a_face_with_a_missing_vertex_reports_its_line() {
+        let ply = BUNNY.replace("3 0 4 3", "3 0 4 9");
+
+        assert!(matches!(
+            PlyFile::parse(&ply),
+            Err(PlyError::ParseError { line: 19, .. })
+        ));
+    }
+
+    #[test]
+    fn negative_and_fractional_vertex_indices_are_errors() {
+        for (face, bad) in [
+            ("3 0 -4 3", "-4"),
+            ("3 0 4.5 3", "4.5"),
+            ("3.0 0 4 3", "3.0"),
+        ] {
+            let ply = BUNNY.replace("3 0 4 3", face);
+
+            assert_eq!(
+                PlyFile::parse(&ply).err(),
+                Some(PlyError::ParseError {
+                    line: 19,
+                    message: format!("bad face value {}", bad),
+                })
+            );
+        }
+    }
+}
@@ -0,0 +1,333 @@
+mod fxaa;
+
+use crate::{Canvas, Color};
+
+pub use fxaa::Fxaa;
+
+/// An effect applied to a rendered image, working only on the pixels of the
+/// [`Canvas`].
+pub trait PostProcessor {
+    /// Apply the effect to the pixels of the `canvas`.
+    fn process(&self, canvas: &mut Canvas);
+}
+
+/// The pixel at `x` and `y` of the `canvas`, with coordinates outside of the
+/// canvas clamped to its edges.
+fn clamped_pixel(canvas: &Canvas, x: isize, y: isize) -> Color {
+    let x = x.clamp(0, canvas.width as isize - 1) as usize;
+    let y = y.clamp(0, canvas.height as isize - 1) as usize;
+    canvas.pixel_at(x, y)
+}
+
+/// Perceived brightness of a color.
+fn luma(c: Color) -> f64 {
+    0.2126 * c.red + 0.7152 * c.green + 0.0722 * c.blue
+}
+
+/// Distance from the center of the `canvas` to the pixel at `x` and `y`, and
+/// to its corners.
+fn distance_to_center(canvas: &Canvas, x: usize, y: usize) -> (f64, f64) {
+    let cx = (canvas.width as f64 - 1.0) / 2.0;
+    let cy = (canvas.height as f64 - 1.0) / 2.0;
+    let d = ((x as f64 - cx).powi(2) + (y as f64 - cy).powi(2)).sqrt();
+    (d, (cx * cx + cy * cy).sqrt())
+}
+
+/// Blur the image with a Gaussian kernel reaching `radius` pixels from each
+/// pixel. A `radius` of 0 leaves the image as it is.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GaussianBlur {
+    pub radius: usize,
+}
+
+impl GaussianBlur {
+    /// Normalized weights of the kernel from `-radius` to `radius`, with the
+    /// radius covering three standard deviations.
+    fn kernel(&self) -> Vec<f64> {
+        let r = self.radius as isize;
+        let sigma = self.radius as f64 / 3.0;
+        let weights: Vec<f64> = (-r..=r)
+            .map(|i| (-(i * i) as f64 / (2.0 * sigma * sigma)).exp())
+            .collect();
+        let total: f64 = weights.iter().sum();
+
+        weights.into_iter().map(|w| w / total).collect()
+    }
+}
+
+impl PostProcessor for GaussianBlur {
+    fn process(&self, canvas: &mut Canvas) {
+        if self.radius == 0 {
+            return;
+        }
+
+        // the kernel is separable, blur the rows then the columns
+        let kernel = self.kernel();
+        let r = self.radius as isize;
+        for (dx, dy) in [(1, 0), (0, 1)] {
+            let source = canvas.clone();
+            for y in 0..canvas.height {
+                for x in 0..canvas.width {
+                    let mut sum = Color::new(0.0, 0.0, 0.0);
+                    for (i, w) in (-r..=r).zip(kernel.iter()) {
+                        let p = clamped_pixel(&source, x as isize + i * dx, y as isize + i * dy);
+                        sum = sum + p * *w;
+                    }
+                    canvas.write_pixel(x, y, sum);
+                }
+            }
+        }
+    }
+}
+
+/// Darken the image towards its corners. The corners are scaled by
+/// `1 - strength` and the center is left as it is.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Vignette {
+    pub strength: f64,
+}
+
+impl PostProcessor for Vignette {
+    fn process(&self, canvas: &mut Canvas) {
+        for y in 0..canvas.height {
+            for x in 0..canvas.width {
+                let (d, corner) = distance_to_center(canvas, x, y);
+                let falloff = if corner > 0.0 { d / corner } else { 0.0 };
+                let factor = 1.0 - self.strength * falloff * falloff;
+                canvas.write_pixel(x, y, canvas.pixel_at(x, y) * factor);
+            }
+        }
+    }
+}
+
+/// Imitate a lens that bends colors differently by pulling the red channel
+/// inwards and pushing the blue channel outwards. The channels are moved by
+/// `offset` pixels in the corners and not at all in the center.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ChromaticAberration {
+    pub offset: f64,
+}
+
+impl PostProcessor for ChromaticAberration {
+    fn process(&self, canvas: &mut Canvas) {
+        let source = canvas.clone();
+        let cx = (canvas.width as f64 - 1.0) / 2.0;
+        let cy = (canvas.height as f64 - 1.0) / 2.0;
+
+        for y in 0..canvas.height {
+            for x in 0..canvas.width {
+                let (_, corner) = distance_to_center(canvas, x, y);
+                let k = if corner > 0.0 {
+                    self.offset / corner
+                } else {
+                    0.0
+                };
+                let (dx, dy) = ((x as f64 - cx) * k, (y as f64 - cy) * k);
+                let sample = |sign: f64| {
+                    let sx = (x as f64 + sign * dx).round() as isize;
+                    let sy = (y as f64 + sign * dy).round() as isize;
+                    clamped_pixel(&source, sx, sy)
+                };
+
+                let color = Color::new(
+                    sample(1.0).red,
+                    source.pixel_at(x, y).green,
+                    sample(-1.0).blue,
+                );
+                canvas.write_pixel(x, y, color);
+            }
+        }
+    }
+}
+
+/// Replace the image with the strength of its edges, found with the Sobel
+/// operator on the brightness of the pixels.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EdgeDetect;
+
+impl PostProcessor for EdgeDetect {
+    fn process(&self, canvas: &mut Canvas) {
+        let source = canvas.clone();
+        let brightness = |x: isize, y: isize| luma(clamped_pixel(&source, x, y));
+
+        for y in 0..canvas.height as isize {
+            for x in 0..canvas.width as isize {
+                let gx = brightness(x + 1, y - 1)
+                    + 2.0 * brightness(x + 1, y)
+                    + brightness(x + 1, y + 1)
+                    - brightness(x - 1, y - 1)
+                    - 2.0 * brightness(x - 1, y)
+                    - brightness(x - 1, y + 1);
+                let gy = brightness(x - 1, y + 1)
+                    + 2.0 * brightness(x, y + 1)
+                    + brightness(x + 1, y + 1)
+                    - brightness(x - 1, y - 1)
+                    - 2.0 * brightness(x, y - 1)
+                    - brightness(x + 1, y - 1);
+                let g = (gx * gx + gy * gy).sqrt();
+
+                canvas.write_pixel(x as usize, y as usize, Color::new(g, g, g));
+            }
+        }
+    }
+}
+
+/// A list of effects applied one after the other.
+///
+/// # Example
+///
+/// ```
+/// use rustic_ray::{Canvas, Color, GaussianBlur, PostPipeline, Vignette};
+///
+/// let mut canvas = Canvas::new(9, 9);
+/// canvas.write_pixel(4, 4, Color::new(1.0, 1.0, 1.0));
+///
+/// PostPipeline::new()
+///     .add(GaussianBlur { radius: 2 })
+///     .add(Vignette { strength: 0.5 })
+///     .apply(&mut canvas);
+///
+/// assert!(canvas.pixel_at(4, 4).red < 1.0);
+/// assert!(canvas.pixel_at(3, 4).red > 0.0);
+/// ```
+#[derive(Default)]
+pub struct PostPipeline {
+    processors: Vec<Box<dyn PostProcessor>>,
+}
+
+impl PostPipeline {
+    /// Create a pipeline without effects.
+    pub fn new() -> Self {
+        PostPipeline {
+            processors: Vec::new(),
+        }
+    }
+
+    /// Add the `processor` to the end of the pipeline.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<P: PostProcessor + 'static>(mut self, processor: P) -> Self {
+        self.processors.push(Box::new(processor));
+        self
+    }
+
+    /// Apply every effect of the pipeline to the `canvas` in order.
+    pub fn apply(&self, canvas: &mut Canvas) {
+        for processor in self.processors.iter() {
+            processor.process(canvas);
+        }
+    }
+}
+
+impl PostProcessor for PostPipeline {
+    fn process(&self, canvas: &mut Canvas) {
+        self.apply(canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A canvas with a diagonal gradient.
+    fn gradient() -> Canvas {
+        Canvas::parallel_fill(8, 6, |x, y| {
+            Color::new(x as f64 / 7.0, y as f64 / 5.0, (x + y) as f64 / 12.0)
+        })
+    }
+
+    fn white(width: usize, height: usize) -> Canvas {
+        Canvas::parallel_fill(width, height, |_, _| Color::new(1.0, 1.0, 1.0))
+    }
+
+    fn assert_same(a: &Canvas, b: &Canvas) {
+        for y in 0..a.height {
+            for x in 0..a.width {
+                assert_eq!(a.pixel_at(x, y), b.pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn a_blur_with_no_radius_does_nothing() {
+        let mut c = gradient();
+        GaussianBlur { radius: 0 }.process(&mut c);
+
+        assert_same(&c, &gradient());
+    }
+
+    #[test]
+    fn a_blur_spreads_a_single_pixel() {
+        let mut c = Canvas::new(9, 9);
+        c.write_pixel(4, 4, Color::new(1.0, 1.0, 1.0));
+        GaussianBlur { radius: 3 }.process(&mut c);
+
+        let total: f64 = (0..9)
+            .flat_map(|y| (0..9).map(move |x| (x, y)))
+            .map(|(x, y)| c.pixel_at(x, y).red)
+            .sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(c.pixel_at(4, 4).red > c.pixel_at(5, 4).red);
+        assert!(c.pixel_at(5, 4).red > c.pixel_at(6, 4).red);
+        assert_eq!(c.pixel_at(5, 4), c.pixel_at(4, 3));
+    }
+
+    #[test]
+    fn a_vignette_darkens_the_corners_more_than_the_center() {
+        let mut c = white(9, 9);
+        Vignette { strength: 0.8 }.process(&mut c);
+
+        assert_eq!(c.pixel_at(4, 4), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(c.pixel_at(0, 0), Color::new(0.2, 0.2, 0.2));
+        assert!(c.pixel_at(2, 2).red > c.pixel_at(0, 0).red);
+        assert!(c.pixel_at(2, 2).red < 1.0);
+    }
+
+    #[test]
+    fn chromatic_aberration_separates_the_channels_away_from_the_center() {
+        let mut c = Canvas::new(9, 9);
+        c.write_pixel(2, 4, Color::new(1.0, 1.0, 1.0));
+        c.write_pixel(4, 4, Color::new(1.0, 1.0, 1.0));
+        ChromaticAberration { offset: 4.0 }.process(&mut c);
+
+        assert_eq!(c.pixel_at(4, 4), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(c.pixel_at(2, 4), Color::new(0.0, 1.0, 0.0));
+        // the red of the pixel left of the center moved inwards
+        assert_eq!(c.pixel_at(3, 4).red, 1.0);
+    }
+
+    #[test]
+    fn edge_detection_finds_a_step() {
+        let mut c = Canvas::parallel_fill(6, 4, |x, _| {
+            if x < 3 {
+                Color::new(0.0, 0.0, 0.0)
+            } else {
+                Color::new(1.0, 1.0, 1.0)
+            }
+        });
+        EdgeDetect.process(&mut c);
+
+        assert_eq!(c.pixel_at(0, 1), Color::new(0.0, 0.0, 0.0));
+        assert_eq!(c.pixel_at(5, 1), Color::new(0.0, 0.0, 0.0));
+        assert!(c.pixel_at(2, 1).red > 1.0);
+        assert!(c.pixel_at(3, 1).red > 1.0);
+    }
+
+    #[test]
+    fn chaining_applies_both_effects() {
+        let mut chained = white(9, 9);
+        chained.write_pixel(4, 4, Color::new(0.0, 0.0, 0.0));
+        let mut separate = white(9, 9);
+        separate.write_pixel(4, 4, Color::new(0.0, 0.0, 0.0));
+
+        PostPipeline::new()
+            .add(GaussianBlur { radius: 2 })
+            .add(Vignette { strength: 0.5 })
+            .apply(&mut chained);
+        GaussianBlur { radius: 2 }.process(&mut separate);
+        Vignette { strength: 0.5 }.process(&mut separate);
+
+        assert_same(&chained, &separate);
+        assert!(chained.pixel_at(4, 4).red > 0.0);
+        assert!(chained.pixel_at(0, 0).red < 1.0);
+    }
+}
@@ -0,0 +1,220 @@
+use super::{clamped_pixel, luma, PostProcessor};
+use crate::{Canvas, Color};
+
+/// Number of pixels searched on each side of a pixel for the ends of its edge.
+const SEARCH_STEPS: usize = 12;
+
+/// Smooth the jagged edges of an image by blending pixels along the edges
+/// found in their brightness, without rendering extra rays.
+///
+/// The brightness of each pixel is compared with its neighbors. Where the
+/// contrast is high enough, the direction of the edge is found and the ends
+/// of the edge are searched for on both sides. The pixel is then blended with
+/// its neighbor across the edge, more so the closer it is to an end.
+///
+/// Lottes, Timothy "FXAA" (2009)
+///
+/// # Example
+///
+/// ```
+/// use rustic_ray::{Canvas, Color, Fxaa, PostProcessor};
+///
+/// let mut canvas = Canvas::parallel_fill(8, 8, |x, y| {
+///     if x > y {
+///         Color::new(1.0, 1.0, 1.0)
+///     } else {
+///         Color::new(0.0, 0.0, 0.0)
+///     }
+/// });
+/// Fxaa::default().process(&mut canvas);
+///
+/// let red = canvas.pixel_at(4, 3).red;
+/// assert!(red > 0.0 && red < 1.0);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Fxaa {
+    /// Contrast needed to smooth a pixel, relative to the brightest of it and
+    /// its neighbors.
+    pub edge_threshold: f64,
+    /// Contrast below which a pixel is never smoothed, so dark areas are left
+    /// alone.
+    pub edge_threshold_min: f64,
+    /// How much pixels thinner than an edge are blended with their neighbors,
+    /// from 0.0 for not at all to 1.0 for fully.
+    pub subpixel: f64,
+}
+
+impl Default for Fxaa {
+    /// The settings recommended for good quality.
+    fn default() -> Self {
+        Fxaa {
+            edge_threshold: 0.125,
+            edge_threshold_min: 0.0312,
+            subpixel: 0.75,
+        }
+    }
+}
+
+/// The color at `x` and `y` of the `canvas`, interpolated from the four
+/// closest pixels.
+fn bilinear(canvas: &Canvas, x: f64, y: f64) -> Color {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as isize, y0 as isize);
+
+    let top = clamped_pixel(canvas, x0, y0) * (1.0 - fx) + clamped_pixel(canvas, x0 + 1, y0) * fx;
+    let bottom =
+        clamped_pixel(canvas, x0, y0 + 1) * (1.0 - fx) + clamped_pixel(canvas, x0 + 1, y0 + 1) * fx;
+    top * (1.0 - fy) + bottom * fy
+}
+
+impl PostProcessor for Fxaa {
+    fn process(&self, canvas: &mut Canvas) {
+        let source = canvas.clone();
+        let brightness = |x: isize, y: isize| luma(clamped_pixel(&source, x, y));
+
+        for y in 0..canvas.height as isize {
+            for x in 0..canvas.width as isize {
+                let m = brightness(x, y);
+                let (n, s, w, e) = (
+                    brightness(x, y - 1),
+                    brightness(x, y + 1),
+                    brightness(x - 1, y),
+                    brightness(x + 1, y),
+                );
+                let max = m.max(n).max(s).max(w).max(e);
+                let min = m.min(n).min(s).min(w).min(e);
+                let range = max - min;
+                if range < self.edge_threshold_min.max(max * self.edge_threshold) {
+                    continue;
+                }
+
+                let (nw, ne, sw, se) = (
+                    brightness(x - 1, y - 1),
+                    brightness(x + 1, y - 1),
+                    brightness(x - 1, y + 1),
+                    brightness(x + 1, y + 1),
+                );
+
+                // blend pixels standing out from all of their neighbors
+                let average = (2.0 * (n + s + w + e) + nw + ne + sw + se) / 12.0;
+                let contrast = ((average - m).abs() / range).clamp(0.0, 1.0);
+                let smooth = contrast * contrast * (3.0 - 2.0 * contrast);
+                let subpixel_blend = smooth * smooth * self.subpixel;
+
+                // a horizontal edge has its brightness changing from top to bottom
+                let horizontal = 2.0 * (n + s - 2.0 * m).abs()
+                    + (ne + se - 2.0 * e).abs()
+                    + (nw + sw - 2.0 * w).abs()
+                    >= 2.0 * (w + e - 2.0 * m).abs()
+                        + (nw + ne - 2.0 * n).abs()
+                        + (sw + se - 2.0 * s).abs();
+                let (before, after) = if horizontal { (n, s) } else { (w, e) };
+
+                // step towards the side of the edge with the stronger change
+                let (step, opposite) = if (before - m).abs() >= (after - m).abs() {
+                    (-1.0, before)
+                } else {
+                    (1.0, after)
+                };
+                let gradient = 0.25 * (before - m).abs().max((after - m).abs());
+                let edge_luma = 0.5 * (m + opposite);
+
+                // search both ways along the edge for where it ends
+                let (ox, oy) = (x as f64, y as f64);
+                let sample = |along: f64| {
+                    let c = if horizontal {
+                        bilinear(&source, ox + along, oy + step * 0.5)
+                    } else {
+                        bilinear(&source, ox + step * 0.5, oy + along)
+                    };
+                    luma(c) - edge_luma
+                };
+                let search = |direction: f64| {
+                    let mut distance = 1.0;
+                    let mut delta = sample(distance * direction);
+                    while delta.abs() < gradient && distance < SEARCH_STEPS as f64 {
+                        distance += 1.0;
+                        delta = sample(distance * direction);
+                    }
+                    (distance, delta)
+                };
+                let (distance_before, delta_before) = search(-1.0);
+                let (distance_after, delta_after) = search(1.0);
+
+                let (closest, delta) = if distance_before < distance_after {
+                    (distance_before, delta_before)
+                } else {
+                    (distance_after, delta_after)
+                };
+                // only blend when the end is on the same side as the pixel
+                let edge_blend = if (delta < 0.0) != (m < edge_luma) {
+                    0.5 - closest / (distance_before + distance_after)
+                } else {
+                    0.0
+                };
+
+                let offset = edge_blend.max(subpixel_blend) * step;
+                let color = if horizontal {
+                    bilinear(&source, ox, oy + offset)
+                } else {
+                    bilinear(&source, ox + offset, oy)
+                };
+                canvas.write_pixel(x as usize, y as usize, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagonal() -> Canvas {
+        Canvas::parallel_fill(16, 12, |x, y| {
+            if x > y {
+                Color::new(1.0, 1.0, 1.0)
+            } else {
+                Color::new(0.0, 0.0, 0.0)
+            }
+        })
+    }
+
+    #[test]
+    fn a_diagonal_edge_is_smoothed() {
+        let mut c = diagonal();
+        Fxaa::default().process(&mut c);
+
+        let blended = (1..11)
+            .filter(|i| {
+                let red = c.pixel_at(*i, *i).red;
+                red > 0.0 && red < 1.0
+            })
+            .count();
+        assert!(blended > 5);
+        // pixels far from the edge are left as they are
+        assert_eq!(c.pixel_at(12, 2), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(c.pixel_at(2, 10), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn a_flat_image_is_unchanged() {
+        let mut c = Canvas::parallel_fill(8, 6, |_, _| Color::new(0.3, 0.6, 0.9));
+        Fxaa::default().process(&mut c);
+
+        for y in 0..6 {
+            for x in 0..8 {
+                assert_eq!(c.pixel_at(x, y), Color::new(0.3, 0.6, 0.9));
+            }
+        }
+    }
+
+    #[test]
+    fn the_image_keeps_its_size() {
+        let mut c = diagonal();
+        Fxaa::default().process(&mut c);
+
+        assert_eq!(c.width, 16);
+        assert_eq!(c.height, 12);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Matrix, Point, Vector};
+use crate::{float_eq, Matrix, Point, Vector};
 
 /// A line which starts at a point and goes off in a particular
 /// direction to infinity.
@@ -28,10 +28,48 @@ impl Ray {
     /// assert_eq!(origin, r.origin);
     /// assert_eq!(direction, r.direction);
     /// ```
+    ///
+    /// The direction does not have to be of unit length. The shapes make no
+    /// such assumption in `local_intersect`, as a ray moved into object
+    /// space by a scaling transformation is no longer of unit length; the
+    /// distances `t` they find are always in units of the direction of the
+    /// ray given to [`crate::shapes::Shape::intersect`].
     pub fn new(origin: Point, direction: Vector) -> Ray {
         Ray { origin, direction }
     }
 
+    /// Create a Ray for the given origin and the `direction` scaled to unit
+    /// length, so the `t` of its intersections are distances in world units.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Point, Ray, Vector};
+    ///
+    /// let r = Ray::new_normalized(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 3.0, 4.0));
+    ///
+    /// assert_eq!(r.direction, Vector::new(0.0, 0.6, 0.8));
+    /// ```
+    pub fn new_normalized(origin: Point, direction: Vector) -> Ray {
+        Ray::new(origin, direction.normalize())
+    }
+
+    /// Create a Ray for a `direction` that is already of unit length, checked
+    /// in debug builds. Used for the rays made while rendering, such as the
+    /// rays of the camera, shadow rays and rays scattered in random directions.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the `direction` is not of unit length.
+    pub fn new_unit(origin: Point, direction: Vector) -> Ray {
+        debug_assert!(
+            float_eq(direction.magnitude(), 1.0),
+            "the direction of the ray is not of unit length: {:?}",
+            direction
+        );
+        Ray::new(origin, direction)
+    }
+
     /// Find the position that lie any distance `t` along te ray.
     ///
     /// # Example
@@ -110,4 +148,19 @@ mod tests {
         assert_eq!(r2.origin, Point::new(2.0, 6.0, 12.0));
         assert_eq!(r2.direction, Vector::new(0.0, 3.0, 0.0));
     }
+
+    #[test]
+    fn a_normalized_ray_has_a_unit_direction() {
+        let r = Ray::new_normalized(Point::new(1.0, 2.0, 3.0), Vector::new(4.0, 5.0, 6.0));
+
+        assert!(float_eq(r.direction.magnitude(), 1.0));
+        assert_eq!(r.direction, Vector::new(4.0, 5.0, 6.0).normalize());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn a_unit_ray_checks_its_direction() {
+        Ray::new_unit(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 2.0, 0.0));
+    }
 }
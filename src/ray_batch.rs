@@ -0,0 +1,82 @@
+use std::iter::FromIterator;
+
+use crate::{Point, Ray, Vector};
+
+/// Many rays kept as a list of origins and a list of directions, to be
+/// intersected with a [`crate::World`] together by
+/// [`crate::World::intersect_batch`].
+///
+/// The batch only holds the rays. They are still intersected one at a time,
+/// each rebuilt as a [`Ray`] from its origin and direction.
+///
+/// # Example
+///
+/// ```
+/// use rustic_ray::{Point, Ray, RayBatch, Vector};
+///
+/// let mut batch = RayBatch::new();
+/// batch.push(Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0)));
+///
+/// assert_eq!(batch.len(), 1);
+/// assert_eq!(batch.ray(0).origin, Point::new(0.0, 0.0, -5.0));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RayBatch {
+    /// Where each ray starts.
+    pub origins: Vec<Point>,
+    /// The direction of each ray, at the same index as its origin.
+    pub directions: Vec<Vector>,
+}
+
+impl RayBatch {
+    /// Create an empty batch.
+    pub fn new() -> Self {
+        RayBatch {
+            origins: Vec::new(),
+            directions: Vec::new(),
+        }
+    }
+
+    /// Add the `ray` to the end of the batch.
+    pub fn push(&mut self, ray: Ray) {
+        self.origins.push(ray.origin);
+        self.directions.push(ray.direction);
+    }
+
+    /// The ray at `index` of the batch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than the number of rays.
+    pub fn ray(&self, index: usize) -> Ray {
+        Ray::new(self.origins[index], self.directions[index])
+    }
+
+    /// Iterate over the rays of the batch.
+    pub fn rays(&self) -> impl Iterator<Item = Ray> + '_ {
+        self.origins
+            .iter()
+            .zip(self.directions.iter())
+            .map(|(origin, direction)| Ray::new(*origin, *direction))
+    }
+
+    /// Number of rays in the batch.
+    pub fn len(&self) -> usize {
+        self.origins.len()
+    }
+
+    /// Whether the batch holds no rays.
+    pub fn is_empty(&self) -> bool {
+        self.origins.is_empty()
+    }
+}
+
+impl FromIterator<Ray> for RayBatch {
+    fn from_iter<I: IntoIterator<Item = Ray>>(rays: I) -> Self {
+        let mut batch = RayBatch::new();
+        for ray in rays {
+            batch.push(ray);
+        }
+        batch
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use crate::{ambient_occlusion, Camera, Canvas, Color, Colors, Intersection, World};
+
+/// Number of rays fired for the [`RenderPass::AmbientOcclusion`] pass when the
+/// world has no [`crate::AmbientOcclusion`] settings.
+const DEFAULT_OCCLUSION_SAMPLES: usize = 16;
+
+/// An image made by a [`RenderJob`]. Pixels where the camera ray hits nothing
+/// are black in the `Albedo` and `AmbientOcclusion` passes and hold a zero
+/// normal in the `Normal` pass.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RenderPass {
+    /// The final shaded image, the same as [`Camera::render`].
+    Beauty,
+    /// Distance from the camera to the hit in every channel, infinite where
+    /// the ray hits nothing.
+    Depth,
+    /// World space normal at the hit, encoded by [`Canvas::encode_normals`].
+    Normal,
+    /// Color of the surface at the hit, before any lighting.
+    Albedo,
+    /// The [`crate::ambient_occlusion`] factor at the hit in every channel.
+    AmbientOcclusion,
+}
+
+/// A camera and a world along with the passes to render, so several images
+/// of the same view can be made while tracing each camera ray only once.
+#[derive(Debug)]
+pub struct RenderJob {
+    /// The world to render.
+    pub world: World,
+    /// The camera looking at the world.
+    pub camera: Camera,
+    /// The images to make.
+    pub passes: Vec<RenderPass>,
+}
+
+impl RenderJob {
+    /// Create a job rendering the `passes` of `world` seen by `camera`.
+    pub fn new(world: World, camera: Camera, passes: Vec<RenderPass>) -> Self {
+        RenderJob {
+            world,
+            camera,
+            passes,
+        }
+    }
+
+    /// Render every pass of the job. Each camera ray is intersected with the
+    /// world once and the hit is shared by all of the passes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Camera, RenderJob, RenderPass, World};
+    /// use std::f64::consts::PI;
+    ///
+    /// let job = RenderJob::new(
+    ///     World::default(),
+    ///     Camera::new(4, 4, PI / 2.0),
+    ///     vec![RenderPass::Beauty, RenderPass::Albedo],
+    /// );
+    /// let canvases = job.run();
+    ///
+    /// assert_eq!(canvases.len(), 2);
+    /// assert_eq!(canvases[&RenderPass::Albedo].width, 4);
+    /// ```
+    pub fn run(&self) -> HashMap<RenderPass, Canvas> {
+        let mut canvases: HashMap<RenderPass, Canvas> = self
+            .passes
+            .iter()
+            .map(|pass| (*pass, Canvas::new(self.camera.hsize, self.camera.vsize)))
+            .collect();
+        let samples = self
+            .world
+            .ambient_occlusion
+            .map_or(DEFAULT_OCCLUSION_SAMPLES, |settings| settings.samples);
+
+        for y in 0..self.camera.vsize {
+            for x in 0..self.camera.hsize {
+                // pixels outside of a fisheye lens stay black in every pass
+                let ray = match self.camera.visible_ray_for_pixel(x, y) {
+                    Some(ray) => ray,
+                    None => continue,
+                };
+                let xs = self.world.intersect_world(ray).unwrap_or_default();
+                let comps = Intersection::hit(&xs)
+                    .map(|hit| hit.prepare_computations(ray, &xs, Some(&self.world)));
+
+                for (pass, canvas) in canvases.iter_mut() {
+                    let color = match (&comps, pass) {
+                        (Some(comps), RenderPass::Beauty) => self.world.shade_hit(comps, 5),
+                        (None, RenderPass::Beauty) => self.world.background,
+                        (Some(comps), RenderPass::Depth) => Color::new(comps.t, comps.t, comps.t),
+                        (None, RenderPass::Depth) => {
+                            Color::new(f64::INFINITY, f64::INFINITY, f64::INFINITY)
+                        }
+                        (Some(comps), RenderPass::Normal) => {
+                            let n = comps.normalv;
+                            Color::new(n.x, n.y, n.z)
+                        }
+                        (Some(comps), RenderPass::Albedo) => self
+                            .world
+                            .get_object_material(comps.object)
+                            .pattern
+                            .pattern_at_shape(comps.object, comps.over_point),
+                        (Some(comps), RenderPass::AmbientOcclusion) => {
+                            let factor = ambient_occlusion(
+                                &self.world,
+                                comps.over_point,
+                                comps.normalv,
+                                samples,
+                            );
+                            Color::new(factor, factor, factor)
+                        }
+                        (None, _) => Colors::BLACK,
+                    };
+                    canvas.write_pixel(x, y, color);
+                }
+            }
+        }
+
+        if let Some(normals) = canvases.get_mut(&RenderPass::Normal) {
+            *normals = normals.encode_normals();
+        }
+
+        canvases
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{float_eq, Point, Transformation, Vector};
+    use std::f64::consts::PI;
+
+    fn job(passes: Vec<RenderPass>) -> RenderJob {
+        let mut c = Camera::new(11, 11, PI / 2.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        RenderJob::new(World::default(), c, passes)
+    }
+
+    #[test]
+    fn the_beauty_pass_matches_rendering_with_the_camera() {
+        let job = job(vec![RenderPass::Beauty, RenderPass::Normal]);
+        let canvases = job.run();
+        let expected = job.camera.render(&job.world);
+
+        for y in 0..11 {
+            for x in 0..11 {
+                assert_eq!(
+                    canvases[&RenderPass::Beauty].pixel_at(x, y),
+                    expected.pixel_at(x, y)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn the_normal_pass_decodes_to_world_normals() {
+        let job = job(vec![RenderPass::Beauty, RenderPass::Normal]);
+        let canvases = job.run();
+        let decoded = canvases[&RenderPass::Normal].decode_normals();
+
+        // the ray through the center hits the front of the outer sphere.
+        assert_eq!(decoded[5][5], Vector::new(0.0, 0.0, -1.0));
+
+        // every hit decodes to a unit vector.
+        for (y, row) in decoded.iter().enumerate() {
+            for (x, n) in row.iter().enumerate() {
+                if canvases[&RenderPass::Beauty].pixel_at(x, y) != Colors::BLACK {
+                    assert!(float_eq(n.magnitude(), 1.0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn the_depth_pass_holds_the_distance_to_the_hit() {
+        let canvases = job(vec![RenderPass::Depth]).run();
+        let depth = &canvases[&RenderPass::Depth];
+
+        assert!(float_eq(depth.pixel_at(5, 5).red, 4.0));
+        assert!(depth.pixel_at(0, 0).red.is_infinite());
+    }
+}
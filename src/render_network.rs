@@ -0,0 +1,319 @@
+use std::convert::TryFrom;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{Camera, Canvas, Color, World};
+
+/// Width and height in pixels of the tiles a render is split into.
+const TILE_SIZE: usize = 32;
+/// Largest message accepted from the network, guarding against a corrupted
+/// length prefix asking for all of the memory.
+const MAX_MESSAGE_LEN: usize = 1 << 30;
+
+/// A rectangle of pixels of the canvas, with its top left corner at `x` and
+/// `y`.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+struct Tile {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+/// Messages between the coordinator, [`Camera::render_network`], and the
+/// render nodes started by [`start_render_node`].
+#[derive(Serialize, Deserialize, Debug)]
+enum Message {
+    /// The scene every following tile is rendered from, sent once per
+    /// connection.
+    Scene {
+        world: serde_json::Value,
+        camera: Box<Camera>,
+    },
+    /// A tile for the node to render.
+    Render(Tile),
+    /// The colors of the pixels of a rendered tile, row by row.
+    Pixels { tile: Tile, pixels: Vec<Color> },
+}
+
+/// Write the `message` as JSON, after its length in bytes as a big endian
+/// `u32`.
+fn send<T: Serialize>(stream: &mut impl Write, message: &T) -> io::Result<()> {
+    let bytes = serde_json::to_vec(message)?;
+    let len = u32::try_from(bytes.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "message too long"))?;
+    stream.write_all(&len.to_be_bytes())?;
+    stream.write_all(&bytes)?;
+    stream.flush()
+}
+
+/// Read a message written by [`send`].
+fn receive<T: DeserializeOwned>(stream: &mut impl Read) -> io::Result<T> {
+    let mut len = [0; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message too long",
+        ));
+    }
+
+    let mut bytes = vec![0; len];
+    stream.read_exact(&mut bytes)?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// A render node started with [`start_render_node`].
+#[derive(Debug)]
+pub struct RenderNode {
+    /// The address the node listens on, with the port picked by the system
+    /// when it was bound to port 0.
+    pub addr: SocketAddr,
+    /// The thread accepting connections, which runs as long as the program.
+    pub handle: JoinHandle<()>,
+}
+
+/// Listen on `bind` for coordinators calling [`Camera::render_network`] and
+/// render the tiles they send.
+///
+/// The address is bound before returning, so the node can be used as soon as
+/// this returns. Connections are served on a background thread each, and
+/// tiles are rendered on all cores.
+///
+/// # Example
+///
+/// ```
+/// use rustic_ray::{start_render_node, Camera, World};
+/// use std::f64::consts::PI;
+///
+/// let node = start_render_node("127.0.0.1:0".parse().unwrap()).unwrap();
+/// let c = Camera::new(8, 8, PI / 2.0);
+/// let w = World::default();
+///
+/// let image = c.render_network(&w, &[node.addr]);
+///
+/// assert_eq!(image.pixel_at(4, 4), c.render(&w).pixel_at(4, 4));
+/// ```
+pub fn start_render_node(bind: SocketAddr) -> io::Result<RenderNode> {
+    let listener = TcpListener::bind(bind)?;
+    let addr = listener.local_addr()?;
+
+    let handle = thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            thread::spawn(move || {
+                // a coordinator going away ends the connection, there is
+                // nobody left to tell about it
+                let _ = serve(stream);
+            });
+        }
+    });
+
+    Ok(RenderNode { addr, handle })
+}
+
+/// Render the tiles asked for on the `stream` until it is closed.
+fn serve(stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+
+    let (world, camera) = match receive(&mut reader)? {
+        Message::Scene { world, camera } => (
+            World::from_value_with_registry(world).map_err(|e| invalid_data(&e.to_string()))?,
+            camera,
+        ),
+        _ => return Err(invalid_data("expected the scene first")),
+    };
+
+    loop {
+        let tile = match receive(&mut reader) {
+            Ok(Message::Render(tile)) => tile,
+            Ok(_) => return Err(invalid_data("expected a tile")),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        let pixels = camera.render_tile(&world, tile.x, tile.y, tile.width, tile.height);
+        send(&mut writer, &Message::Pixels { tile, pixels })?;
+    }
+}
+
+/// Ask the node at `addr` to render tiles taken from `tiles` until there are
+/// none left, returning the rendered tiles. When the node fails, the tile it
+/// was rendering is put back for the others.
+fn render_on_node(
+    addr: SocketAddr,
+    scene: &Message,
+    tiles: &Mutex<Vec<Tile>>,
+    done: &Mutex<Vec<(Tile, Vec<Color>)>>,
+) -> io::Result<()> {
+    let stream = TcpStream::connect(addr)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+    send(&mut writer, scene)?;
+
+    loop {
+        let tile = match tiles.lock().unwrap().pop() {
+            Some(tile) => tile,
+            None => return Ok(()),
+        };
+
+        let result = send(&mut writer, &Message::Render(tile)).and_then(|_| receive(&mut reader));
+        match result {
+            Ok(Message::Pixels { tile: t, pixels })
+                if t == tile && pixels.len() == tile.width * tile.height =>
+            {
+                done.lock().unwrap().push((tile, pixels));
+            }
+            other => {
+                tiles.lock().unwrap().push(tile);
+                return match other {
+                    Err(e) => Err(e),
+                    Ok(_) => Err(invalid_data("unexpected reply")),
+                };
+            }
+        }
+    }
+}
+
+impl Camera {
+    /// Render the world the same way as `render`, split into tiles rendered
+    /// by the render nodes at the `nodes` addresses, see
+    /// [`start_render_node`].
+    ///
+    /// The world and the camera are sent to every node as JSON, so the parts
+    /// of the world that are not saved, such as caustics and baked ambient
+    /// occlusion, are left out. Each node is given a new tile whenever it
+    /// sends back the last one, so faster nodes render more of the image.
+    /// Tiles left when every node failed are rendered by this machine.
+    pub fn render_network(&self, world: &World, nodes: &[SocketAddr]) -> Canvas {
+        let mut tiles = Vec::new();
+        for y in (0..self.vsize).step_by(TILE_SIZE) {
+            for x in (0..self.hsize).step_by(TILE_SIZE) {
+                tiles.push(Tile {
+                    x,
+                    y,
+                    width: TILE_SIZE.min(self.hsize - x),
+                    height: TILE_SIZE.min(self.vsize - y),
+                });
+            }
+        }
+        // the nodes pop tiles from the end, so start from the top left
+        tiles.reverse();
+
+        let scene = Message::Scene {
+            world: serde_json::to_value(world).expect("worlds serialize to JSON"),
+            camera: Box::new(self.clone()),
+        };
+        let tiles = Mutex::new(tiles);
+        let done = Mutex::new(Vec::new());
+
+        thread::scope(|scope| {
+            for addr in nodes {
+                let (scene, tiles, done) = (&scene, &tiles, &done);
+                scope.spawn(move || {
+                    // a failed node leaves its tiles to the other ones
+                    let _ = render_on_node(*addr, scene, tiles, done);
+                });
+            }
+        });
+
+        let mut done = done.into_inner().unwrap();
+        for tile in tiles.into_inner().unwrap() {
+            let pixels = self.render_tile(world, tile.x, tile.y, tile.width, tile.height);
+            done.push((tile, pixels));
+        }
+
+        let mut canvas = Canvas::new(self.hsize, self.vsize);
+        for (tile, pixels) in done {
+            for (i, color) in pixels.into_iter().enumerate() {
+                canvas.write_pixel(tile.x + i % tile.width, tile.y + i / tile.width, color);
+            }
+        }
+        canvas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Point, Transformation, Vector};
+    use std::f64::consts::PI;
+
+    fn camera(size: usize) -> Camera {
+        let mut c = Camera::new(size, size, PI / 2.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        c
+    }
+
+    fn assert_same_image(a: &Canvas, b: &Canvas) {
+        for y in 0..a.height {
+            for x in 0..a.width {
+                assert_eq!(a.pixel_at(x, y), b.pixel_at(x, y), "pixel {} {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn a_render_spread_over_two_nodes_matches_a_local_render() {
+        let nodes: Vec<SocketAddr> = (0..2)
+            .map(|_| {
+                start_render_node("127.0.0.1:0".parse().unwrap())
+                    .unwrap()
+                    .addr
+            })
+            .collect();
+        let c = camera(200);
+        let w = World::default();
+
+        let image = c.render_network(&w, &nodes);
+
+        assert_same_image(&image, &c.render(&w));
+    }
+
+    #[test]
+    fn tiles_of_unreachable_nodes_are_rendered_locally() {
+        // bind a port and free it again, so nothing listens on it
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let c = camera(40);
+        let w = World::default();
+
+        let image = c.render_network(&w, &[addr]);
+
+        assert_same_image(&image, &c.render(&w));
+    }
+
+    #[test]
+    fn messages_are_prefixed_with_their_length() {
+        let mut bytes = Vec::new();
+        let tile = Tile {
+            x: 1,
+            y: 2,
+            width: 3,
+            height: 4,
+        };
+        send(&mut bytes, &Message::Render(tile)).unwrap();
+
+        let len = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+        assert_eq!(len, bytes.len() - 4);
+        match receive(&mut bytes.as_slice()).unwrap() {
+            Message::Render(t) => assert_eq!(t, tile),
+            other => panic!("expected a tile, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::Vector;
+
+/// A direction picked uniformly from all directions on the unit sphere.
+pub(crate) fn random_unit_vector<R: Rng + ?Sized>(rng: &mut R) -> Vector {
+    let z: f64 = rng.gen_range(-1.0..=1.0);
+    let phi = rng.gen_range(0.0..2.0 * PI);
+    let r = (1.0 - z * z).max(0.0).sqrt();
+
+    Vector::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+/// A direction in the hemisphere around `normal`, picked more often close to
+/// the normal in proportion to the cosine of the angle with it, the way a
+/// matte surface scatters light.
+pub(crate) fn cosine_weighted_direction<R: Rng + ?Sized>(normal: Vector, rng: &mut R) -> Vector {
+    let (tangent, bitangent) = orthonormal_basis(normal);
+    let r: f64 = rng.gen::<f64>().sqrt();
+    let phi = rng.gen_range(0.0..2.0 * PI);
+    let up = (1.0 - r * r).max(0.0).sqrt();
+
+    (tangent * (r * phi.cos()) + bitangent * (r * phi.sin()) + normal * up).normalize()
+}
+
+/// Two unit vectors perpendicular to each other and to the unit vector
+/// `normal`.
+pub(crate) fn orthonormal_basis(normal: Vector) -> (Vector, Vector) {
+    let helper = if normal.x.abs() > 0.9 {
+        Vector::new(0.0, 1.0, 0.0)
+    } else {
+        Vector::new(1.0, 0.0, 0.0)
+    };
+    let tangent = helper.cross(normal).normalize();
+    let bitangent = normal.cross(tangent);
+
+    (tangent, bitangent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::float_eq;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn sampled_directions_are_unit_vectors_on_the_right_side() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let normal = Vector::new(0.0, 0.0, 1.0);
+
+        for _ in 0..100 {
+            assert!(float_eq(random_unit_vector(&mut rng).magnitude(), 1.0));
+
+            let d = cosine_weighted_direction(normal, &mut rng);
+            assert!(float_eq(d.magnitude(), 1.0));
+            assert!(d.dot(normal) >= 0.0);
+        }
+    }
+}
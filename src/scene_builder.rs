@@ -0,0 +1,202 @@
+use crate::{
+    patterns::Pattern,
+    shapes::{Plane, Shape, Sphere},
+    Color, Material, Matrix, Point, PointLight, World,
+};
+
+/// Fluent interface for building a [`World`] as an alternative to creating an
+/// empty world and mutating it.
+///
+/// Every method returns `&mut Self` so calls can be chained directly on
+/// `SceneBuilder::new()` without a `mut` binding. Shapes are configured with a
+/// closure receiving a [`ShapeBuilder`].
+///
+/// # Example
+///
+/// ```
+/// use rustic_ray::{Color, Point, SceneBuilder, Transformation};
+///
+/// let w = SceneBuilder::new()
+///     .light(Point::new(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0))
+///     .add_sphere(|s| s.color([0.8, 1.0, 0.6]).diffuse(0.7).specular(0.2))
+///     .add_sphere(|s| s.transform(Transformation::new().scale(0.5, 0.5, 0.5).build()))
+///     .build();
+///
+/// assert_eq!(w.get_object(0).unwrap().material().diffuse, 0.7);
+/// assert!(w.get_object(2).is_none());
+/// ```
+pub struct SceneBuilder {
+    world: World,
+}
+
+impl SceneBuilder {
+    /// Start building a world with no objects and no lights.
+    pub fn new() -> Self {
+        SceneBuilder {
+            world: World::new(),
+        }
+    }
+
+    /// Add a `shape` to the world after applying the `configure` closure to it.
+    pub fn add_shape<S, F>(&mut self, shape: S, configure: F) -> &mut Self
+    where
+        S: Shape,
+        F: FnOnce(ShapeBuilder<S>) -> ShapeBuilder<S>,
+    {
+        let shape = configure(ShapeBuilder { shape }).shape;
+        self.world.add_object(Box::new(shape));
+        self
+    }
+
+    /// Add a [`Sphere`] configured by the `configure` closure.
+    pub fn add_sphere<F>(&mut self, configure: F) -> &mut Self
+    where
+        F: FnOnce(ShapeBuilder<Sphere>) -> ShapeBuilder<Sphere>,
+    {
+        self.add_shape(Sphere::new(), configure)
+    }
+
+    /// Add a [`Plane`] configured by the `configure` closure.
+    pub fn add_plane<F>(&mut self, configure: F) -> &mut Self
+    where
+        F: FnOnce(ShapeBuilder<Plane>) -> ShapeBuilder<Plane>,
+    {
+        self.add_shape(Plane::new(), configure)
+    }
+
+    /// Set the light source of the world.
+    pub fn light(&mut self, position: Point, intensity: Color) -> &mut Self {
+        self.world.light = Some(PointLight::new(position, intensity));
+        self
+    }
+
+    /// Set the color returned for rays that miss every object.
+    pub fn background(&mut self, color: Color) -> &mut Self {
+        self.world.background = color;
+        self
+    }
+
+    /// Finish building returning the [`World`]. The builder is left empty and
+    /// can be used to build another world.
+    pub fn build(&mut self) -> World {
+        std::mem::replace(&mut self.world, World::new())
+    }
+}
+
+impl Default for SceneBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Configures a single shape added with a [`SceneBuilder`]. Each method
+/// consumes and returns the builder so calls can be chained in a closure.
+pub struct ShapeBuilder<S: Shape> {
+    shape: S,
+}
+
+impl<S: Shape> ShapeBuilder<S> {
+    /// Set the transformation [`Matrix`] of the shape.
+    pub fn transform(mut self, transform: Matrix) -> Self {
+        self.shape.set_transform(transform);
+        self
+    }
+
+    /// Replace the [`Material`] of the shape.
+    pub fn material(mut self, material: Material) -> Self {
+        self.shape.set_material(material);
+        self
+    }
+
+    /// Set the color of the shape's material.
+    pub fn color<C: Into<Color>>(mut self, color: C) -> Self {
+        self.shape.material_mut().set_color(color.into());
+        self
+    }
+
+    /// Set the ambient reflection of the shape's material.
+    pub fn ambient(mut self, ambient: f64) -> Self {
+        self.shape.material_mut().ambient = ambient;
+        self
+    }
+
+    /// Set the diffuse reflection of the shape's material.
+    pub fn diffuse(mut self, diffuse: f64) -> Self {
+        self.shape.material_mut().diffuse = diffuse;
+        self
+    }
+
+    /// Set the specular reflection of the shape's material.
+    pub fn specular(mut self, specular: f64) -> Self {
+        self.shape.material_mut().specular = specular;
+        self
+    }
+
+    /// Set the shininess of the shape's material.
+    pub fn shininess(mut self, shininess: f64) -> Self {
+        self.shape.material_mut().shininess = shininess;
+        self
+    }
+
+    /// Set how reflective the shape's material is.
+    pub fn reflective(mut self, reflective: f64) -> Self {
+        self.shape.material_mut().reflective = reflective;
+        self
+    }
+
+    /// Set how transparent the shape's material is.
+    pub fn transparency(mut self, transparency: f64) -> Self {
+        self.shape.material_mut().transparency = transparency;
+        self
+    }
+
+    /// Set the index of refraction of the shape's material.
+    pub fn refractive_index(mut self, refractive_index: f64) -> Self {
+        self.shape.material_mut().refractive_index = refractive_index;
+        self
+    }
+
+    /// Apply a `pattern` to the shape's material.
+    pub fn pattern(mut self, pattern: Box<dyn Pattern>) -> Self {
+        self.shape.material_mut().pattern = pattern;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Colors, Transformation};
+
+    #[test]
+    fn building_the_default_world() {
+        let w = SceneBuilder::new()
+            .light(Point::new(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0))
+            .add_sphere(|s| s.color([0.8, 1.0, 0.6]).diffuse(0.7).specular(0.2))
+            .add_sphere(|s| s.transform(Transformation::new().scale(0.5, 0.5, 0.5).build()))
+            .build();
+        let expected = World::default();
+
+        assert_eq!(w.light, expected.light);
+        for i in 0..2 {
+            let a = w.get_object(i).unwrap();
+            let b = expected.get_object(i).unwrap();
+            assert_eq!(a.transform(), b.transform());
+            assert_eq!(*a.material(), *b.material());
+        }
+        assert!(w.get_object(2).is_none());
+    }
+
+    #[test]
+    fn building_a_plane_with_a_material() {
+        let mut m = Material::new();
+        m.reflective = 0.5;
+        let w = SceneBuilder::new()
+            .add_plane(|p| p.material(m))
+            .background(Colors::RED)
+            .build();
+
+        assert_eq!(w.get_object(0).unwrap().material().reflective, 0.5);
+        assert_eq!(w.background, Colors::RED);
+    }
+}
@@ -0,0 +1,234 @@
+use std::fmt;
+
+use uuid::Uuid;
+
+use crate::{shapes::Shape, World, EPSILON};
+
+/// A likely mistake in the setup of a [`World`], found by
+/// [`World::validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceneWarning {
+    /// The world has no light source, so everything renders black.
+    NoLight,
+    /// The transform of the object can not be inverted, for example because
+    /// it scales by zero, so rays can not be converted to object space.
+    DegenerateTransform(Uuid),
+    /// The object has a refractive index below 1, less than the vacuum.
+    RefractiveIndexBelowOne { object: Uuid, refractive_index: f64 },
+    /// The object is transparent but has the refractive index of the vacuum,
+    /// so light passes through it without bending.
+    TransparentWithoutRefraction(Uuid),
+    /// The transform of the pattern of the object can not be inverted.
+    SingularPatternTransform(Uuid),
+    /// The diffuse and specular reflections of the object add up to more than
+    /// 1, so it reflects more light than it receives.
+    NotEnergyConserving {
+        object: Uuid,
+        diffuse: f64,
+        specular: f64,
+    },
+}
+
+impl fmt::Display for SceneWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneWarning::NoLight => write!(f, "the world has no light source"),
+            SceneWarning::DegenerateTransform(id) => {
+                write!(f, "object {} has a transform that can not be inverted", id)
+            }
+            SceneWarning::RefractiveIndexBelowOne {
+                object,
+                refractive_index,
+            } => write!(
+                f,
+                "object {} has a refractive index of {} which is below 1",
+                object, refractive_index
+            ),
+            SceneWarning::TransparentWithoutRefraction(id) => write!(
+                f,
+                "object {} is transparent with a refractive index of 1",
+                id
+            ),
+            SceneWarning::SingularPatternTransform(id) => write!(
+                f,
+                "object {} has a pattern transform that can not be inverted",
+                id
+            ),
+            SceneWarning::NotEnergyConserving {
+                object,
+                diffuse,
+                specular,
+            } => write!(
+                f,
+                "object {} reflects more light than it receives, diffuse {} + specular {} > 1",
+                object, diffuse, specular
+            ),
+        }
+    }
+}
+
+/// Add the warnings for `object` and every shape it contains to `warnings`.
+fn validate_object(object: &dyn Shape, warnings: &mut Vec<SceneWarning>) {
+    let id = object.id();
+    let material = object.material();
+
+    if !object.transform().is_invertible() {
+        warnings.push(SceneWarning::DegenerateTransform(id));
+    }
+    if material.refractive_index < 1.0 {
+        warnings.push(SceneWarning::RefractiveIndexBelowOne {
+            object: id,
+            refractive_index: material.refractive_index,
+        });
+    }
+    if material.transparency > 0.0 && (material.refractive_index - 1.0).abs() < EPSILON {
+        warnings.push(SceneWarning::TransparentWithoutRefraction(id));
+    }
+    if !material.pattern.transform().is_invertible() {
+        warnings.push(SceneWarning::SingularPatternTransform(id));
+    }
+    if material.diffuse + material.specular > 1.0 + EPSILON {
+        warnings.push(SceneWarning::NotEnergyConserving {
+            object: id,
+            diffuse: material.diffuse,
+            specular: material.specular,
+        });
+    }
+
+    for child in object.children() {
+        validate_object(child, warnings);
+    }
+}
+
+impl World {
+    /// Check the world for common setup mistakes, returning a warning for
+    /// each one found. An empty list means nothing looks wrong.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{SceneWarning, World};
+    ///
+    /// assert_eq!(World::new().validate(), vec![SceneWarning::NoLight]);
+    /// ```
+    pub fn validate(&self) -> Vec<SceneWarning> {
+        let mut warnings = Vec::new();
+
+        if self.light.is_none()
+            && self.area_lights.is_empty()
+            && self.directional_lights.is_empty()
+            && self.light_probe.is_none()
+        {
+            warnings.push(SceneWarning::NoLight);
+        }
+        for object in self.objects() {
+            validate_object(object, &mut warnings);
+        }
+
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shapes::{Group, Sphere},
+        Canvas, Color, LightProbe, Material, Point, PointLight, Transformation,
+    };
+
+    fn lit_world() -> World {
+        let mut w = World::new();
+        w.light = Some(PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        w
+    }
+
+    /// A sphere with a material that reflects less light than it receives.
+    fn sphere() -> Sphere {
+        let mut s = Sphere::new();
+        s.material = Material::new().diffuse(0.7).specular(0.2);
+        s
+    }
+
+    #[test]
+    fn an_empty_world_has_no_light() {
+        assert_eq!(World::new().validate(), vec![SceneWarning::NoLight]);
+    }
+
+    #[test]
+    fn a_world_lit_by_a_light_probe_has_light() {
+        let mut w = World::new();
+        w.light_probe = Some(LightProbe::new(&Canvas::new(2, 1)));
+
+        assert!(w.validate().is_empty());
+    }
+
+    #[test]
+    fn a_sphere_scaled_to_zero_is_degenerate() {
+        let mut w = lit_world();
+        let mut s = sphere();
+        s.transform = Transformation::new().scale(1.0, 0.0, 1.0).build();
+        let id = s.id();
+        w.add_object(Box::new(s));
+
+        assert_eq!(w.validate(), vec![SceneWarning::DegenerateTransform(id)]);
+    }
+
+    #[test]
+    fn a_valid_world_has_no_warnings() {
+        let mut w = lit_world();
+        w.add_object(Box::new(sphere()));
+
+        assert!(w.validate().is_empty());
+    }
+
+    #[test]
+    fn problems_with_materials_are_reported() {
+        let mut w = lit_world();
+        let mut s = Sphere::new();
+        s.material.transparency = 0.5;
+        let id = s.id();
+        let mut g = Group::new();
+        g.material = Material::new().diffuse(0.7).specular(0.2);
+        g.add_object(Box::new(s));
+        w.add_object(Box::new(g));
+
+        assert_eq!(
+            w.validate(),
+            vec![
+                SceneWarning::TransparentWithoutRefraction(id),
+                SceneWarning::NotEnergyConserving {
+                    object: id,
+                    diffuse: 0.9,
+                    specular: 0.9
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn a_low_refractive_index_and_a_singular_pattern_are_reported() {
+        let mut w = lit_world();
+        let mut s = sphere();
+        s.material.refractive_index = 0.5;
+        s.material
+            .pattern
+            .set_transform(Transformation::new().scale(0.0, 1.0, 1.0).build());
+        let id = s.id();
+        w.add_object(Box::new(s));
+
+        assert_eq!(
+            w.validate(),
+            vec![
+                SceneWarning::RefractiveIndexBelowOne {
+                    object: id,
+                    refractive_index: 0.5
+                },
+                SceneWarning::SingularPatternTransform(id)
+            ]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{sampling::cosine_weighted_direction, Intersection, Point, Ray, Vector, World};
+
+/// Settings for darkening the ambient light in creases and corners, where
+/// nearby objects block part of the sky above a point. Enabled by setting
+/// [`World::ambient_occlusion`].
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct AmbientOcclusion {
+    /// Number of rays fired above each point.
+    pub samples: usize,
+    /// Objects further away than this do not block the ambient light.
+    pub max_distance: f64,
+    /// Seed for picking the directions of the rays.
+    pub seed: u64,
+}
+
+impl AmbientOcclusion {
+    /// Create settings firing `samples` rays that are blocked by objects
+    /// closer than `max_distance`.
+    pub fn new(samples: usize, max_distance: f64) -> Self {
+        AmbientOcclusion {
+            samples,
+            max_distance,
+            seed: 0,
+        }
+    }
+}
+
+/// The fraction of the hemisphere above `point` facing `normal` that is not
+/// blocked by other objects, from 0.0 when the point is closed in to 1.0 in
+/// open space.
+///
+/// `samples` rays are fired from the point in directions picked in proportion
+/// to the cosine of their angle with the normal, counting the ones hitting an
+/// object. The maximum distance and the seed are taken from
+/// [`World::ambient_occlusion`], or are unlimited and 0 when it is not set.
+/// The directions depend on the seed and the point, so the result is the same
+/// every time for the same point.
+///
+/// # Example
+///
+/// ```
+/// use rustic_ray::{ambient_occlusion, Point, Vector, World};
+///
+/// let w = World::new();
+/// let factor = ambient_occlusion(&w, Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0), 16);
+///
+/// assert_eq!(factor, 1.0);
+/// ```
+pub fn ambient_occlusion(world: &World, point: Point, normal: Vector, samples: usize) -> f64 {
+    sample_occlusion(world, point, normal, samples).0
+}
+
+/// Fire the rays of [`ambient_occlusion`], returning the unblocked fraction
+/// along with the harmonic mean of the distances to the objects hit. The
+/// mean is infinite when no ray hits anything.
+pub(crate) fn sample_occlusion(
+    world: &World,
+    point: Point,
+    normal: Vector,
+    samples: usize,
+) -> (f64, f64) {
+    if samples == 0 {
+        return (1.0, f64::INFINITY);
+    }
+
+    let (max_distance, seed) = match world.ambient_occlusion {
+        Some(settings) => (settings.max_distance, settings.seed),
+        None => (f64::INFINITY, 0),
+    };
+    let mut rng = StdRng::seed_from_u64(
+        seed ^ point.x.to_bits().rotate_left(7)
+            ^ point.y.to_bits().rotate_left(23)
+            ^ point.z.to_bits().rotate_left(41),
+    );
+
+    let mut blocked = 0;
+    let mut inverse_distances = 0.0;
+    for _ in 0..samples {
+        let ray = Ray::new_unit(point, cosine_weighted_direction(normal, &mut rng));
+        let hit = world
+            .intersect_world(ray)
+            .and_then(|xs| Intersection::hit(&xs).map(|hit| hit.t));
+
+        if let Some(t) = hit.filter(|t| *t < max_distance) {
+            blocked += 1;
+            inverse_distances += 1.0 / t;
+        }
+    }
+
+    (
+        1.0 - blocked as f64 / samples as f64,
+        samples as f64 / inverse_distances,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shapes::{Cube, Plane},
+        Transformation,
+    };
+
+    #[test]
+    fn a_point_inside_a_closed_box_is_occluded() {
+        let mut w = World::new();
+        let mut walls = Cube::new();
+        walls.transform = Transformation::new().scale(5.0, 5.0, 5.0).build();
+        w.add_object(Box::new(walls));
+        w.ambient_occlusion = Some(AmbientOcclusion::new(32, 20.0));
+
+        let factor = ambient_occlusion(
+            &w,
+            Point::new(0.0, -4.99, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            32,
+        );
+
+        assert_eq!(factor, 0.0);
+    }
+
+    #[test]
+    fn a_point_in_open_space_is_not_occluded() {
+        let mut w = World::new();
+        w.add_object(Box::new(Plane::new()));
+
+        let factor = ambient_occlusion(
+            &w,
+            Point::new(0.0, 0.01, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            32,
+        );
+
+        assert_eq!(factor, 1.0);
+    }
+
+    #[test]
+    fn occlusion_is_the_same_for_the_same_seed() {
+        let mut w = World::new();
+        w.add_object(Box::new(Plane::new()));
+        let mut wall = Plane::new();
+        wall.transform = Transformation::new()
+            .rotate_z(std::f64::consts::PI / 2.0)
+            .translate(1.0, 0.0, 0.0)
+            .build();
+        w.add_object(Box::new(wall));
+        w.ambient_occlusion = Some(AmbientOcclusion::new(64, 10.0));
+        let point = Point::new(0.5, 0.01, 0.0);
+        let normal = Vector::new(0.0, 1.0, 0.0);
+
+        let first = ambient_occlusion(&w, point, normal, 64);
+
+        assert!(first > 0.0 && first < 1.0);
+        assert_eq!(ambient_occlusion(&w, point, normal, 64), first);
+    }
+}
@@ -1,26 +1,51 @@
 //! Contains various shapes used in a scene. The shapes are [`Sphere`] and
 //! [`Plane`].
+mod bounded_plane;
+mod capsule;
 mod cone;
 mod csg;
 mod cube;
 mod cylinder;
+mod disc;
+mod displacement;
 mod group;
+mod heightmap;
+mod instance;
+mod mesh;
 mod plane;
+mod sdf;
 mod shape;
+mod shape_registry;
+mod slab;
 mod smooth_triangles;
 mod sphere;
 mod test_shape;
+mod torus;
 mod triangle;
 
+pub use bounded_plane::BoundedPlane;
+pub use capsule::Capsule;
 pub use cone::Cone;
 pub use csg::CsgOperation;
 pub use csg::CSG;
-pub use cube::Cube;
+pub(crate) use cube::cube_map;
+pub use cube::{Cube, CubeFace};
 pub use cylinder::Cylinder;
+pub use disc::Disc;
+pub(crate) use displacement::displacement_at;
 pub use group::Group;
+pub use heightmap::Heightmap;
+pub use instance::Instance;
+pub use mesh::Mesh;
 pub use plane::Plane;
-pub use shape::Shape;
+pub use plane::PlaneOrientation;
+pub use sdf::{smooth_union, DistanceField, Metaball, Sdf};
+pub use shape::{CloneShape, Shape};
+pub use shape_registry::{deserialize_shape, serialize_shape, ShapeRegistry};
+pub use slab::Slab;
+pub(crate) use sphere::spherical_map;
 pub use sphere::Sphere;
+pub use torus::Torus;
 pub use triangle::Triangle;
 
 #[cfg(test)]
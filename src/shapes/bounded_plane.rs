@@ -0,0 +1,201 @@
+use super::Shape;
+#[allow(unused_imports)]
+use crate::Transformation;
+use crate::{Bounds, Intersection, Material, Matrix, Point, Ray, Vector, EPSILON, IDENTITY};
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use typetag;
+use uuid::Uuid;
+
+/// A flat rectangle in the `x` and `z` dimensions, a [`super::Plane`] clipped
+/// to `half_width` on each side of the origin along `x` and `half_depth`
+/// along `z`.
+///
+/// Unlike a plane it has finite bounds, so it can be sorted into spatial
+/// indexes and does not catch rays far away from the scene.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BoundedPlane {
+    id: Uuid,
+    parent_id: Option<Uuid>,
+    /// [`Transformation`] matrix used to manipulate the `BoundedPlane`
+    pub transform: Matrix,
+    /// [`Material`] describing the look of the `BoundedPlane`
+    pub material: Material,
+    /// Distance from the origin to the edges along `x` in object space
+    pub half_width: f64,
+    /// Distance from the origin to the edges along `z` in object space
+    pub half_depth: f64,
+}
+
+impl BoundedPlane {
+    /// Create a new `BoundedPlane` reaching `half_width` along `x` and
+    /// `half_depth` along `z` on each side of the origin.
+    pub fn new(half_width: f64, half_depth: f64) -> Self {
+        BoundedPlane {
+            id: Uuid::new_v4(),
+            parent_id: None,
+            transform: IDENTITY,
+            material: Material::new(),
+            half_width,
+            half_depth,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Shape for BoundedPlane {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn parent_id(&self) -> Option<Uuid> {
+        self.parent_id
+    }
+
+    fn set_parent_id(&mut self, id: Uuid) {
+        self.parent_id = Some(id);
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(
+            Point::new(-self.half_width, 0.0, -self.half_depth),
+            Point::new(self.half_width, 0.0, self.half_depth),
+        )
+    }
+
+    fn local_surface_area(&self) -> f64 {
+        4.0 * self.half_width * self.half_depth
+    }
+
+    /// The local area grown by how much the transformation stretches the
+    /// `xz` plane, exact for any transformation of a flat shape.
+    fn surface_area(&self) -> f64 {
+        let (x, z) = (Vector::new(1.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        self.local_surface_area() * (self.transform * x).cross(self.transform * z).magnitude()
+    }
+
+    fn local_sample_surface(&self, rng: &mut dyn RngCore) -> (Point, Vector) {
+        (
+            Point::new(
+                rng.gen_range(-self.half_width..=self.half_width),
+                0.0,
+                rng.gen_range(-self.half_depth..=self.half_depth),
+            ),
+            Vector::new(0.0, 1.0, 0.0),
+        )
+    }
+
+    /// Texture coordinates from `(0, 0)` at the corner at `-x` and `-z` to
+    /// `(1, 1)` at the corner at `+x` and `+z`.
+    fn local_uv_at(&self, point: Point) -> Option<(f64, f64)> {
+        Some((
+            ((point.x + self.half_width) / (2.0 * self.half_width)).clamp(0.0, 1.0),
+            ((point.z + self.half_depth) / (2.0 * self.half_depth)).clamp(0.0, 1.0),
+        ))
+    }
+
+    fn local_tangent_at(&self, _point: Point) -> Option<Vector> {
+        Some(Vector::new(1.0, 0.0, 0.0))
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
+        if ray.direction.y.abs() < EPSILON {
+            return None;
+        }
+
+        let t = -ray.origin.y / ray.direction.y;
+        let point = ray.position(t);
+
+        if point.x.abs() <= self.half_width + EPSILON && point.z.abs() <= self.half_depth + EPSILON
+        {
+            Some(vec![Intersection::new(t, self)])
+        } else {
+            None
+        }
+    }
+
+    fn local_normal_at(&self, _point: Point, _hit: Option<&Intersection>) -> Vector {
+        Vector::new(0.0, 1.0, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_ray_hits_inside_the_rectangle() {
+        let p = BoundedPlane::new(2.0, 1.0);
+        let r = Ray::new(Point::new(1.5, 1.0, -0.5), Vector::new(0.0, -1.0, 0.0));
+        let xs = p.local_intersect(r).expect("No intersections");
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 1.0);
+        assert!(p.shape_eq(xs[0].object));
+    }
+
+    #[test]
+    fn a_ray_misses_outside_the_rectangle() {
+        let p = BoundedPlane::new(2.0, 1.0);
+
+        for origin in [Point::new(2.5, 1.0, 0.0), Point::new(0.0, 1.0, -1.5)] {
+            let r = Ray::new(origin, Vector::new(0.0, -1.0, 0.0));
+            assert!(p.local_intersect(r).is_none());
+        }
+    }
+
+    #[test]
+    fn a_ray_parallel_to_the_rectangle() {
+        let p = BoundedPlane::new(2.0, 1.0);
+        let r = Ray::new(Point::new(0.0, 0.0, -2.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert!(p.local_intersect(r).is_none());
+    }
+
+    #[test]
+    fn the_texture_coordinates_at_the_corners() {
+        let p = BoundedPlane::new(2.0, 1.0);
+
+        assert_eq!(p.local_uv_at(Point::new(-2.0, 0.0, -1.0)), Some((0.0, 0.0)));
+        assert_eq!(p.local_uv_at(Point::new(2.0, 0.0, -1.0)), Some((1.0, 0.0)));
+        assert_eq!(p.local_uv_at(Point::new(-2.0, 0.0, 1.0)), Some((0.0, 1.0)));
+        assert_eq!(p.local_uv_at(Point::new(2.0, 0.0, 1.0)), Some((1.0, 1.0)));
+    }
+
+    #[test]
+    fn a_bounded_plane_has_finite_bounds() {
+        let p = BoundedPlane::new(2.0, 1.0);
+
+        assert!(p.bounds().is_finite());
+        assert_eq!(p.bounds().max, Point::new(2.0, 0.0, 1.0));
+        assert_eq!(p.local_surface_area(), 8.0);
+    }
+
+    #[test]
+    fn the_surface_area_of_a_stretched_bounded_plane() {
+        let mut p = BoundedPlane::new(2.0, 1.0);
+        p.transform = Transformation::new().scale(0.5, 4.0, 3.0).build();
+
+        assert_eq!(p.surface_area(), 12.0);
+    }
+}
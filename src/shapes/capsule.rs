@@ -0,0 +1,239 @@
+use super::Shape;
+#[allow(unused_imports)]
+use crate::Transformation;
+use crate::{Bounds, Intersection, Material, Matrix, Point, Ray, Vector, IDENTITY};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use typetag;
+use uuid::Uuid;
+
+/// A cylinder around the `y` axis closed with a hemisphere at each end.
+///
+/// The barrel of the `Capsule` spans from `-half_height` to `half_height` and
+/// the hemispheres are centered on the ends of the barrel, so the total height
+/// is `2 * (half_height + radius)`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Capsule {
+    id: Uuid,
+    parent_id: Option<Uuid>,
+    /// [`Transformation`] matrix used to manipulate the `Capsule`
+    pub transform: Matrix,
+    /// [`Material`] describing the look of the `Capsule`
+    pub material: Material,
+    /// Radius of the barrel and of both hemispheres
+    pub radius: f64,
+    /// Half of the length of the barrel along the y-axis
+    pub half_height: f64,
+}
+
+impl Capsule {
+    /// Create a new `Capsule` with a radius of 1 and a half height of 1.
+    pub fn new() -> Self {
+        Capsule {
+            id: Uuid::new_v4(),
+            parent_id: None,
+            transform: IDENTITY,
+            material: Material::new(),
+            radius: 1.0,
+            half_height: 1.0,
+        }
+    }
+
+    /// Solve `a * t² + b * t + c = 0` returning the roots in increasing order.
+    fn solve_quadratic(a: f64, b: f64, c: f64) -> Option<(f64, f64)> {
+        let disc = b.powi(2) - 4.0 * a * c;
+
+        if disc < 0.0 {
+            return None;
+        }
+
+        let t0 = (-b - disc.sqrt()) / (2.0 * a);
+        let t1 = (-b + disc.sqrt()) / (2.0 * a);
+
+        Some((t0.min(t1), t0.max(t1)))
+    }
+
+    fn intersect_barrel(&self, ray: Ray, ts: &mut Vec<f64>) {
+        let a = ray.direction.x.powi(2) + ray.direction.z.powi(2);
+
+        if a.abs() < f64::EPSILON {
+            return;
+        }
+
+        let b = 2.0 * (ray.origin.x * ray.direction.x + ray.origin.z * ray.direction.z);
+        let c = ray.origin.x.powi(2) + ray.origin.z.powi(2) - self.radius.powi(2);
+
+        if let Some((t0, t1)) = Self::solve_quadratic(a, b, c) {
+            for t in [t0, t1] {
+                let y = ray.origin.y + t * ray.direction.y;
+                if -self.half_height <= y && y <= self.half_height {
+                    ts.push(t);
+                }
+            }
+        }
+    }
+
+    fn intersect_cap(&self, ray: Ray, center_y: f64, ts: &mut Vec<f64>) {
+        let origin = ray.origin - Point::new(0.0, center_y, 0.0);
+
+        let a = ray.direction.dot(ray.direction);
+        let b = 2.0 * ray.direction.dot(origin);
+        let c = origin.dot(origin) - self.radius.powi(2);
+
+        if let Some((t0, t1)) = Self::solve_quadratic(a, b, c) {
+            for t in [t0, t1] {
+                let y = ray.origin.y + t * ray.direction.y;
+                // only the half of the sphere outside of the barrel counts
+                if (y - center_y) * center_y.signum() > 0.0 {
+                    ts.push(t);
+                }
+            }
+        }
+    }
+}
+
+impl Default for Capsule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[typetag::serde]
+impl Shape for Capsule {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn parent_id(&self) -> Option<Uuid> {
+        self.parent_id
+    }
+
+    fn set_parent_id(&mut self, id: Uuid) {
+        self.parent_id = Some(id);
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+
+    fn bounds(&self) -> Bounds {
+        let top = self.half_height + self.radius;
+        Bounds::new(
+            Point::new(-self.radius, -top, -self.radius),
+            Point::new(self.radius, top, self.radius),
+        )
+    }
+
+    fn local_surface_area(&self) -> f64 {
+        // the barrel and the two halves of a sphere at its ends
+        4.0 * PI * self.radius * self.half_height + 4.0 * PI * self.radius.powi(2)
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
+        let mut ts: Vec<f64> = Vec::new();
+
+        self.intersect_barrel(ray, &mut ts);
+        self.intersect_cap(ray, self.half_height, &mut ts);
+        self.intersect_cap(ray, -self.half_height, &mut ts);
+
+        if ts.is_empty() {
+            return None;
+        }
+
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        Some(ts.into_iter().map(|t| Intersection::new(t, self)).collect())
+    }
+
+    fn local_normal_at(&self, point: Point, _hit: Option<&Intersection>) -> Vector {
+        if point.y > self.half_height {
+            point - Point::new(0.0, self.half_height, 0.0)
+        } else if point.y < -self.half_height {
+            point - Point::new(0.0, -self.half_height, 0.0)
+        } else {
+            Vector::new(point.x, 0.0, point.z)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EPSILON;
+
+    #[test]
+    fn a_ray_through_the_middle_hits_the_barrel() {
+        let c = Capsule::new();
+        let r = Ray::new(Point::new(0.0, 0.5, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = c.local_intersect(r).expect("No intersections");
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.0);
+        assert_eq!(xs[1].t, 6.0);
+        assert_eq!(
+            c.local_normal_at(r.position(xs[0].t), None),
+            Vector::new(0.0, 0.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn a_ray_through_an_end_hits_the_hemisphere() {
+        let c = Capsule::new();
+        let r = Ray::new(Point::new(0.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let xs = c.local_intersect(r).expect("No intersections");
+
+        // the ray enters through the top cap and leaves through the bottom one
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 3.0);
+        assert_eq!(xs[1].t, 7.0);
+        assert_eq!(
+            c.local_normal_at(r.position(xs[0].t), None),
+            Vector::new(0.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn a_ray_hitting_the_side_of_a_cap() {
+        let c = Capsule::new();
+        let r = Ray::new(Point::new(0.0, 1.5, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = c.local_intersect(r).expect("No intersections");
+        let z = (1.0_f64 - 0.25).sqrt();
+
+        assert_eq!(xs.len(), 2);
+        assert!((xs[0].t - (5.0 - z)).abs() < EPSILON);
+        assert!((xs[1].t - (5.0 + z)).abs() < EPSILON);
+    }
+
+    #[test]
+    fn a_ray_misses_a_capsule() {
+        let c = Capsule::new();
+        let r = Ray::new(Point::new(0.0, 2.5, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert!(c.local_intersect(r).is_none());
+    }
+
+    #[test]
+    fn the_normal_is_continuous_at_the_junction() {
+        let c = Capsule::new();
+        let on_barrel = c.local_normal_at(Point::new(1.0, 1.0, 0.0), None);
+        let on_cap = c.local_normal_at(Point::new(1.0, 1.0 + 1e-9, 0.0), None);
+
+        assert_eq!(on_barrel, Vector::new(1.0, 0.0, 0.0));
+        assert_eq!(on_barrel, on_cap);
+    }
+}
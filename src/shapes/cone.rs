@@ -1,8 +1,13 @@
 use super::Shape;
 #[allow(unused_imports)]
 use crate::Transformation;
-use crate::{float_eq, Intersection, Material, Matrix, Point, Ray, Vector, EPSILON, IDENTITY};
-use std::f64::{INFINITY, NEG_INFINITY};
+use crate::{
+    float_eq, Bounds, Intersection, Material, Matrix, Point, Ray, Vector, EPSILON, IDENTITY,
+};
+use std::f64::{
+    consts::{PI, SQRT_2},
+    INFINITY, NEG_INFINITY,
+};
 
 use serde::{Deserialize, Serialize};
 use typetag;
@@ -14,7 +19,7 @@ use uuid::Uuid;
 /// A `Cone` has a default radius of 1 unit and are infinity in both `+y` and
 /// `-y`. `Cone` can be truncated in either `y` direction or both. They can
 /// also be opened at each end or closed. By default they are open.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Cone {
     id: Uuid,
     parent_id: Option<Uuid>,
@@ -110,6 +115,29 @@ impl Shape for Cone {
         self.material = material;
     }
 
+    fn bounds(&self) -> Bounds {
+        let radius = self.minimum.abs().max(self.maximum.abs());
+        Bounds::new(
+            Point::new(-radius, self.minimum, -radius),
+            Point::new(radius, self.maximum, radius),
+        )
+    }
+
+    fn local_surface_area(&self) -> f64 {
+        // the side from the tip to a height y has an area of π√2·y²
+        let (a, b) = (self.minimum.powi(2), self.maximum.powi(2));
+        let side = if self.minimum.signum() == self.maximum.signum() {
+            PI * SQRT_2 * (b - a).abs()
+        } else {
+            PI * SQRT_2 * (a + b)
+        };
+        if self.closed {
+            side + PI * (a + b)
+        } else {
+            side
+        }
+    }
+
     fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
         let mut xs: Vec<Intersection> = Vec::new();
 
@@ -1,11 +1,13 @@
+use std::any::Any;
+
 use super::Shape;
-use crate::{Intersection, Material, Matrix, Point, Ray, Vector, IDENTITY};
+use crate::{Bounds, Intersection, Material, Matrix, Point, Ray, Vector, IDENTITY};
 
 use serde::{Deserialize, Serialize};
 use typetag;
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CSG {
     id: Uuid,
     parent_id: Option<Uuid>,
@@ -43,6 +45,56 @@ impl CSG {
         }
     }
 
+    /// Combine all of the `shapes` with the same `operation` as a balanced
+    /// tree of `CSG` nodes, so `[a, b, c, d]` joined with
+    /// [`CsgOperation::Union`] gives `Union(Union(a, b), Union(c, d))` instead
+    /// of a chain as deep as the list is long.
+    ///
+    /// A [`CsgOperation::Difference`] subtracts every following shape from the
+    /// first one, which is built as the first shape minus the balanced union
+    /// of the rest. A single shape is returned as it is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shapes` is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::shapes::{CsgOperation, Shape, Sphere, CSG};
+    ///
+    /// let shapes: Vec<Box<dyn Shape>> = (0..4).map(|_| Box::new(Sphere::new()) as _).collect();
+    /// let ids: Vec<_> = shapes.iter().map(|s| s.id()).collect();
+    /// let c = CSG::from_objects_with_operation(CsgOperation::Union, shapes);
+    ///
+    /// assert!(ids.iter().all(|id| c.contains_object_by_id(*id)));
+    /// ```
+    pub fn from_objects_with_operation(
+        operation: CsgOperation,
+        mut shapes: Vec<Box<dyn Shape>>,
+    ) -> Box<dyn Shape> {
+        assert!(!shapes.is_empty(), "CSG needs at least one shape");
+
+        if let CsgOperation::Difference = operation {
+            if shapes.len() == 1 {
+                return shapes.remove(0);
+            }
+            let first = shapes.remove(0);
+            let rest = CSG::from_objects_with_operation(CsgOperation::Union, shapes);
+            return Box::new(CSG::new(operation, first, rest));
+        }
+
+        if shapes.len() == 1 {
+            return shapes.remove(0);
+        }
+
+        let right = shapes.split_off(shapes.len().div_ceil(2));
+        let left = CSG::from_objects_with_operation(operation, shapes);
+        let right = CSG::from_objects_with_operation(operation, right);
+
+        Box::new(CSG::new(operation, left, right))
+    }
+
     pub fn filter_intersections<'a>(&'a self, xs: &'a [Intersection]) -> Vec<Intersection> {
         let mut inl = false;
         let mut inr = false;
@@ -111,8 +163,41 @@ impl Shape for CSG {
         self.material = material;
     }
 
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+
+    fn get_object_by_id(&self, id: Uuid) -> Option<&dyn Shape> {
+        for s in [&self.left, &self.right] {
+            if s.id() == id {
+                return Some(s.as_ref());
+            }
+            if let Some(c) = s.get_object_by_id(id) {
+                return Some(c);
+            }
+        }
+
+        None
+    }
+
+    fn children(&self) -> Vec<&dyn Shape> {
+        vec![self.left.as_ref(), self.right.as_ref()]
+    }
+
     fn contains_object_by_id(&self, id: Uuid) -> bool {
-        self.left.id() == id || self.right.id() == id
+        self.get_object_by_id(id).is_some()
+    }
+
+    fn bounds(&self) -> Bounds {
+        self.left
+            .parent_space_bounds()
+            .merge(&self.right.parent_space_bounds())
+    }
+
+    /// The area of both children, counting the parts hidden inside the other
+    /// child too, so the area of the combined shape is at most this.
+    fn local_surface_area(&self) -> f64 {
+        self.left.surface_area() + self.right.surface_area()
     }
 
     fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
@@ -290,4 +375,81 @@ mod tests {
         assert_eq!(xs[1].t, 6.5);
         assert_eq!(xs[1].object.id(), s2_id);
     }
+
+    fn depth(shape: &dyn Shape) -> usize {
+        match shape.as_any().and_then(|a| a.downcast_ref::<CSG>()) {
+            Some(c) => 1 + depth(c.left.as_ref()).max(depth(c.right.as_ref())),
+            None => 0,
+        }
+    }
+
+    fn spheres_along_x(n: usize) -> Vec<Box<dyn Shape>> {
+        (0..n)
+            .map(|i| {
+                let mut s = Sphere::new();
+                s.transform = Transformation::new()
+                    .translate(3.0 * i as f64, 0.0, 0.0)
+                    .build();
+                Box::new(s) as Box<dyn Shape>
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_nested_csg_contains_its_grandchildren() {
+        let s = Sphere::new();
+        let s_id = s.id();
+        let inner = CSG::new(CsgOperation::Union, Box::new(s), Box::new(Cube::new()));
+        let outer = CSG::new(CsgOperation::Union, Box::new(inner), Box::new(Cube::new()));
+
+        assert!(outer.contains_object_by_id(s_id));
+        assert_eq!(outer.get_object_by_id(s_id).unwrap().id(), s_id);
+    }
+
+    #[test]
+    fn a_union_of_many_shapes_hits_any_of_them() {
+        let c = CSG::from_objects_with_operation(CsgOperation::Union, spheres_along_x(4));
+
+        for i in 0..4 {
+            let r = Ray::new(
+                Point::new(3.0 * i as f64, 0.0, -5.0),
+                Vector::new(0.0, 0.0, 1.0),
+            );
+            let xs = c.local_intersect(r).unwrap();
+
+            assert_eq!(xs.len(), 2);
+            assert_eq!(xs[0].t, 4.0);
+        }
+    }
+
+    #[test]
+    fn a_difference_of_many_shapes_subtracts_the_rest_from_the_first() {
+        let mut shapes: Vec<Box<dyn Shape>> = vec![Box::new(Cube::new())];
+        // three thin cubes cutting slots through the first one along x
+        for x in [-0.5, 0.0, 0.5] {
+            let mut c = Cube::new();
+            c.transform = Transformation::new()
+                .scale(0.1, 2.0, 2.0)
+                .translate(x, 0.0, 0.0)
+                .build();
+            shapes.push(Box::new(c));
+        }
+        let c = CSG::from_objects_with_operation(CsgOperation::Difference, shapes);
+        let r = Ray::new(Point::new(-5.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        let xs = c.local_intersect(r).unwrap();
+        let ts: Vec<f64> = xs.iter().map(|i| i.t).collect();
+
+        assert_eq!(ts, vec![4.0, 4.4, 4.6, 4.9, 5.1, 5.4, 5.6, 6.0]);
+    }
+
+    #[test]
+    fn the_tree_has_minimum_depth() {
+        let examples = vec![(1, 0), (2, 1), (3, 2), (4, 2), (5, 3), (8, 3), (9, 4)];
+
+        for (n, expected) in examples {
+            let c = CSG::from_objects_with_operation(CsgOperation::Union, spheres_along_x(n));
+
+            assert_eq!(depth(c.as_ref()), expected);
+        }
+    }
 }
@@ -1,15 +1,16 @@
 use super::Shape;
 #[allow(unused_imports)]
 use crate::Transformation;
-use crate::{float_cmp, Intersection, Material, Matrix, Point, Ray, Vector, IDENTITY};
+use crate::{float_cmp, Bounds, Intersection, Material, Matrix, Point, Ray, Vector, IDENTITY};
 
+use rand::{Rng, RngCore};
 use serde::{Deserialize, Serialize};
 use typetag;
 use uuid::Uuid;
 
 /// A three-dimensional solid object bounded by six square sides, with three
 /// meeting at each vertex. A default cube is 1 unit size in all directions.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Cube {
     id: Uuid,
     parent_id: Option<Uuid>,
@@ -30,6 +31,24 @@ impl Cube {
         }
     }
 
+    /// The face of the cube `point` lies on and the texture coordinates on
+    /// that face, both in the range `[0, 1]`. Each face is seen from outside
+    /// the cube with `v` growing upwards, and the `up` and `down` faces are
+    /// seen with the `front` face below and above them respectively.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::{Cube, CubeFace}, Point};
+    ///
+    /// let c = Cube::new();
+    ///
+    /// assert_eq!(c.uv_at(Point::new(1.0, 0.0, 0.0)), (CubeFace::Right, 0.5, 0.5));
+    /// ```
+    pub fn uv_at(&self, point: Point) -> (CubeFace, f64, f64) {
+        cube_map(point)
+    }
+
     fn check_axis(&self, origin: f64, direction: f64) -> (f64, f64) {
         let tmin_numerator = -1.0 - origin;
         let tmax_numerator = 1.0 - origin;
@@ -45,6 +64,53 @@ impl Cube {
     }
 }
 
+/// One of the six faces of a [`Cube`], named as seen by a viewer in front of
+/// the cube, on the positive `z` axis.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CubeFace {
+    /// The face at `x = -1`
+    Left,
+    /// The face at `z = 1`
+    Front,
+    /// The face at `x = 1`
+    Right,
+    /// The face at `z = -1`
+    Back,
+    /// The face at `y = 1`
+    Up,
+    /// The face at `y = -1`
+    Down,
+}
+
+/// Map a `point` on the unit cube to its face and texture coordinates, see
+/// [`Cube::uv_at`]. Points off the cube are projected to the face of their
+/// largest coordinate.
+pub(crate) fn cube_map(point: Point) -> (CubeFace, f64, f64) {
+    let (x, y, z) = (point.x, point.y, point.z);
+    let coord = (x.abs().max(y.abs()).max(z.abs())).max(f64::EPSILON);
+    let (x, y, z) = (x / coord, y / coord, z / coord);
+
+    let (face, u, v) = if x.abs() >= y.abs() && x.abs() >= z.abs() {
+        if x > 0.0 {
+            (CubeFace::Right, 1.0 - z, y + 1.0)
+        } else {
+            (CubeFace::Left, z + 1.0, y + 1.0)
+        }
+    } else if y.abs() >= z.abs() {
+        if y > 0.0 {
+            (CubeFace::Up, x + 1.0, 1.0 - z)
+        } else {
+            (CubeFace::Down, x + 1.0, z + 1.0)
+        }
+    } else if z > 0.0 {
+        (CubeFace::Front, x + 1.0, y + 1.0)
+    } else {
+        (CubeFace::Back, 1.0 - x, y + 1.0)
+    };
+
+    (face, (u / 2.0).clamp(0.0, 1.0), (v / 2.0).clamp(0.0, 1.0))
+}
+
 #[typetag::serde]
 impl Shape for Cube {
     fn id(&self) -> Uuid {
@@ -79,6 +145,27 @@ impl Shape for Cube {
         self.material = material;
     }
 
+    fn bounds(&self) -> Bounds {
+        Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+
+    fn local_surface_area(&self) -> f64 {
+        // six faces of two by two
+        24.0
+    }
+
+    fn local_sample_surface(&self, rng: &mut dyn RngCore) -> (Point, Vector) {
+        // every face has the same area, so pick one and then a point on it
+        let face = rng.gen_range(0..6);
+        let (a, b): (f64, f64) = (rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0));
+        let side = if face % 2 == 0 { 1.0 } else { -1.0 };
+        match face / 2 {
+            0 => (Point::new(side, a, b), Vector::new(side, 0.0, 0.0)),
+            1 => (Point::new(a, side, b), Vector::new(0.0, side, 0.0)),
+            _ => (Point::new(a, b, side), Vector::new(0.0, 0.0, side)),
+        }
+    }
+
     fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
         let (xtmin, xtmax) = self.check_axis(ray.origin.x, ray.direction.x);
         let (ytmin, ytmax) = self.check_axis(ray.origin.y, ray.direction.y);
@@ -116,6 +203,11 @@ impl Shape for Cube {
             Vector::new(0.0, 0.0, point.z)
         }
     }
+
+    fn local_uv_at(&self, point: Point) -> Option<(f64, f64)> {
+        let (_, u, v) = self.uv_at(point);
+        Some((u, v))
+    }
 }
 
 impl PartialEq for Cube {
@@ -236,4 +328,86 @@ mod tests {
             assert_eq!(rec.1, normal);
         }
     }
+
+    #[test]
+    fn finding_the_face_of_a_point() {
+        let c = Cube::new();
+        let data = [
+            (Point::new(-1.0, 0.5, -0.25), CubeFace::Left),
+            (Point::new(1.1, -0.75, 0.8), CubeFace::Right),
+            (Point::new(0.1, 0.6, 0.9), CubeFace::Front),
+            (Point::new(-0.7, 0.0, -2.0), CubeFace::Back),
+            (Point::new(0.5, 1.0, 0.9), CubeFace::Up),
+            (Point::new(-0.2, -1.3, 1.1), CubeFace::Down),
+        ];
+
+        for (point, face) in data.iter() {
+            assert_eq!(c.uv_at(*point).0, *face);
+        }
+    }
+
+    #[test]
+    fn the_uv_coordinates_stay_within_each_face() {
+        let c = Cube::new();
+        let steps = [-1.0, -0.5, 0.0, 0.5, 1.0];
+
+        for a in steps.iter() {
+            for b in steps.iter() {
+                for point in [
+                    Point::new(1.0, *a, *b),
+                    Point::new(-1.0, *a, *b),
+                    Point::new(*a, 1.0, *b),
+                    Point::new(*a, -1.0, *b),
+                    Point::new(*a, *b, 1.0),
+                    Point::new(*a, *b, -1.0),
+                ] {
+                    let (_, u, v) = c.uv_at(point);
+                    assert!((0.0..=1.0).contains(&u));
+                    assert!((0.0..=1.0).contains(&v));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn the_uv_coordinates_on_the_front_and_up_faces() {
+        let c = Cube::new();
+
+        assert_eq!(
+            c.uv_at(Point::new(-0.5, 0.5, 1.0)),
+            (CubeFace::Front, 0.25, 0.75)
+        );
+        assert_eq!(
+            c.uv_at(Point::new(-0.5, 1.0, -0.9)),
+            (CubeFace::Up, 0.25, 0.95)
+        );
+    }
+
+    #[test]
+    fn the_surface_area_of_a_unit_cube() {
+        let mut c = Cube::new();
+        c.transform = Transformation::new()
+            .scale(0.5, 0.5, 0.5)
+            .rotate_y(std::f64::consts::PI / 4.0)
+            .build();
+
+        assert_eq!(c.local_surface_area(), 24.0);
+        assert!(crate::float_eq(c.surface_area(), 6.0));
+    }
+
+    #[test]
+    fn points_sampled_on_a_cube_lie_on_its_faces() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let c = Cube::new();
+        let mut rng = StdRng::seed_from_u64(5);
+
+        for _ in 0..100 {
+            let (p, n) = c.sample_surface(&mut rng);
+            let largest = p.x.abs().max(p.y.abs()).max(p.z.abs());
+            assert_eq!(largest, 1.0);
+            assert_eq!(n, c.local_normal_at(p, None));
+            assert_eq!(n.magnitude(), 1.0);
+        }
+    }
 }
@@ -1,9 +1,11 @@
 use super::Shape;
 #[allow(unused_imports)]
 use crate::Transformation;
-use crate::{float_eq, Intersection, Material, Matrix, Point, Ray, Vector, EPSILON, IDENTITY};
+use crate::{
+    float_eq, Bounds, Intersection, Material, Matrix, Point, Ray, Vector, EPSILON, IDENTITY,
+};
 use serde::{Deserialize, Serialize};
-use std::f64::{INFINITY, NEG_INFINITY};
+use std::f64::{consts::PI, INFINITY, NEG_INFINITY};
 use typetag;
 use uuid::Uuid;
 
@@ -13,7 +15,7 @@ use uuid::Uuid;
 /// A cylinder has a default radius of 1 unit and are infinity in both `+y` and
 /// `-y`. Cylinders can be truncated in either `y` direction or both. They can
 /// also be opened at each end or closed. By default they are open.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Cylinder {
     pub id: Uuid,
     pub parent_id: Option<Uuid>,
@@ -113,6 +115,22 @@ impl Shape for Cylinder {
         self.material = material;
     }
 
+    fn bounds(&self) -> Bounds {
+        Bounds::new(
+            Point::new(-1.0, self.minimum, -1.0),
+            Point::new(1.0, self.maximum, 1.0),
+        )
+    }
+
+    fn local_surface_area(&self) -> f64 {
+        let side = 2.0 * PI * (self.maximum - self.minimum);
+        if self.closed {
+            side + 2.0 * PI
+        } else {
+            side
+        }
+    }
+
     fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
         let a = ray.direction.x.powi(2) + ray.direction.z.powi(2);
 
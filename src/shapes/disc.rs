@@ -0,0 +1,192 @@
+use super::Shape;
+#[allow(unused_imports)]
+use crate::Transformation;
+use crate::{Bounds, Intersection, Material, Matrix, Point, Ray, Vector, EPSILON, IDENTITY};
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use typetag;
+use uuid::Uuid;
+
+/// A flat circle in the `x` and `z` dimensions, a [`super::Plane`] clipped to
+/// the given `radius` around the origin.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Disc {
+    id: Uuid,
+    parent_id: Option<Uuid>,
+    /// [`Transformation`] matrix used to manipulate the `Disc`
+    pub transform: Matrix,
+    /// [`Material`] describing the look of the `Disc`
+    pub material: Material,
+    /// Radius of the `Disc` in object space
+    pub radius: f64,
+}
+
+impl Disc {
+    /// Create a new `Disc` with a radius of 1.
+    pub fn new() -> Self {
+        Disc {
+            id: Uuid::new_v4(),
+            parent_id: None,
+            transform: IDENTITY,
+            material: Material::new(),
+            radius: 1.0,
+        }
+    }
+}
+
+impl Default for Disc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[typetag::serde]
+impl Shape for Disc {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn parent_id(&self) -> Option<Uuid> {
+        self.parent_id
+    }
+
+    fn set_parent_id(&mut self, id: Uuid) {
+        self.parent_id = Some(id);
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(
+            Point::new(-self.radius, 0.0, -self.radius),
+            Point::new(self.radius, 0.0, self.radius),
+        )
+    }
+
+    fn local_surface_area(&self) -> f64 {
+        PI * self.radius.powi(2)
+    }
+
+    /// The local area grown by how much the transformation stretches the
+    /// `xz` plane, exact for any transformation of a flat shape.
+    fn surface_area(&self) -> f64 {
+        let (x, z) = (Vector::new(1.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        self.local_surface_area() * (self.transform * x).cross(self.transform * z).magnitude()
+    }
+
+    fn local_sample_surface(&self, rng: &mut dyn RngCore) -> (Point, Vector) {
+        let r = self.radius * rng.gen::<f64>().sqrt();
+        let phi = rng.gen_range(0.0..2.0 * PI);
+        (
+            Point::new(r * phi.cos(), 0.0, r * phi.sin()),
+            Vector::new(0.0, 1.0, 0.0),
+        )
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
+        if ray.direction.y.abs() < EPSILON {
+            return None;
+        }
+
+        let t = -ray.origin.y / ray.direction.y;
+        let point = ray.position(t);
+
+        if point.x.powi(2) + point.z.powi(2) <= self.radius.powi(2) + EPSILON {
+            Some(vec![Intersection::new(t, self)])
+        } else {
+            None
+        }
+    }
+
+    fn local_normal_at(&self, _point: Point, _hit: Option<&Intersection>) -> Vector {
+        Vector::new(0.0, 1.0, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_normal_of_a_disc_is_constant_everywhere() {
+        let d = Disc::new();
+
+        assert_eq!(
+            d.local_normal_at(Point::new(0.0, 0.0, 0.0), None),
+            Vector::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            d.local_normal_at(Point::new(0.5, 0.0, -0.5), None),
+            Vector::new(0.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn a_ray_hits_inside_the_radius() {
+        let d = Disc::new();
+        let r = Ray::new(Point::new(0.5, 1.0, 0.5), Vector::new(0.0, -1.0, 0.0));
+        let xs = d.local_intersect(r).expect("No intersections");
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 1.0);
+        assert!(d.shape_eq(xs[0].object));
+    }
+
+    #[test]
+    fn a_ray_misses_outside_the_radius() {
+        let d = Disc::new();
+        let r = Ray::new(Point::new(1.0, 1.0, 1.0), Vector::new(0.0, -1.0, 0.0));
+
+        assert!(d.local_intersect(r).is_none());
+    }
+
+    #[test]
+    fn a_ray_hitting_the_edge_of_the_disc() {
+        let mut d = Disc::new();
+        d.radius = 2.0;
+        let r = Ray::new(Point::new(2.0, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let xs = d.local_intersect(r).expect("No intersections");
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 1.0);
+    }
+
+    #[test]
+    fn a_ray_parallel_to_the_disc() {
+        let d = Disc::new();
+        let r = Ray::new(Point::new(0.0, 0.0, -2.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert!(d.local_intersect(r).is_none());
+    }
+
+    #[test]
+    fn the_surface_area_of_a_stretched_disc() {
+        let mut d = Disc::new();
+        d.transform = Transformation::new()
+            .scale(2.0, 7.0, 3.0)
+            .rotate_x(0.5)
+            .translate(1.0, 2.0, 3.0)
+            .build();
+
+        assert!(crate::float_eq(d.surface_area(), 6.0 * PI));
+    }
+}
@@ -0,0 +1,100 @@
+use super::Shape;
+use crate::{patterns::Pattern, Intersection, Point, Ray, Vector};
+
+/// Most steps taken along a ray before giving up on finding more of the
+/// displaced surface.
+const MAX_STEPS: usize = 1000;
+/// Farthest distance along a ray the displaced surface is looked for.
+const MAX_DISTANCE: f64 = 1000.0;
+/// Shortest step along a ray, so rays grazing the surface still move on.
+const MIN_STEP: f64 = 1e-3;
+/// Fraction of the distance to the surface taken in a step. The map may
+/// raise the surface faster than the distance shrinks, so less than the
+/// whole distance is taken to avoid stepping over it.
+const STEP_FRACTION: f64 = 0.5;
+/// Number of halvings of a step that crossed the surface.
+const BISECTIONS: usize = 40;
+/// Distance between the points used to estimate the normal.
+const NORMAL_DELTA: f64 = 1e-4;
+
+/// Offset of the surface at `point` in object space read from the `map`, the
+/// average of the channels of its color.
+pub(crate) fn displacement_at(map: &dyn Pattern, point: Point) -> f64 {
+    let c = map.pattern_at(map.transform().inverse() * point);
+    (c.red + c.green + c.blue) / 3.0
+}
+
+/// Intersections of `ray` with the surface of `shape` displaced by `map`, see
+/// [`march`].
+pub(crate) fn intersect<'a>(
+    shape: &'a dyn Shape,
+    map: &dyn Pattern,
+    ray: Ray,
+    height: impl Fn(Point) -> f64,
+) -> Option<Vec<Intersection<'a>>> {
+    let ts = march(map, ray, height);
+    if ts.is_empty() {
+        None
+    } else {
+        Some(
+            ts.into_iter()
+                .map(|t| Intersection::new(t, shape))
+                .collect(),
+        )
+    }
+}
+
+/// Distances along `ray` where it crosses a surface displaced by `map`,
+/// nearest first. The undisplaced surface is where `height` is zero, with
+/// `height` growing like the distance to the surface on the side the normal
+/// points to.
+///
+/// The ray is marched from its origin in steps a fraction of the distance
+/// to the displaced surface, and a step crossing it is halved until the
+/// crossing is found.
+fn march(map: &dyn Pattern, ray: Ray, height: impl Fn(Point) -> f64) -> Vec<f64> {
+    let f = |t: f64| {
+        let p = ray.position(t);
+        height(p) - displacement_at(map, p)
+    };
+    let speed = ray.direction.magnitude();
+
+    let mut ts = Vec::new();
+    let (mut t, mut value) = (0.0, f(0.0));
+    for _ in 0..MAX_STEPS {
+        if t * speed > MAX_DISTANCE {
+            break;
+        }
+
+        let next = t + (value.abs() * STEP_FRACTION).max(MIN_STEP) / speed;
+        let next_value = f(next);
+        if (value < 0.0) != (next_value < 0.0) {
+            let (mut low, mut high) = (t, next);
+            for _ in 0..BISECTIONS {
+                let middle = (low + high) / 2.0;
+                if (f(middle) < 0.0) == (value < 0.0) {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+            ts.push((low + high) / 2.0);
+        }
+        t = next;
+        value = next_value;
+    }
+    ts
+}
+
+/// Normal at `point` of the surface displaced by `map`, see [`march`].
+pub(crate) fn normal(map: &dyn Pattern, point: Point, height: impl Fn(Point) -> f64) -> Vector {
+    let f = |p: Point| height(p) - displacement_at(map, p);
+    let delta = |v: Vector| f(point + v * NORMAL_DELTA) - f(point - v * NORMAL_DELTA);
+
+    Vector::new(
+        delta(Vector::new(1.0, 0.0, 0.0)),
+        delta(Vector::new(0.0, 1.0, 0.0)),
+        delta(Vector::new(0.0, 0.0, 1.0)),
+    )
+    .normalize()
+}
@@ -1,12 +1,12 @@
 use std::any::Any;
 
 use super::Shape;
-use crate::{Intersection, Material, Matrix, Point, Ray, Vector, IDENTITY};
+use crate::{Bounds, Intersection, Material, Matrix, Point, Ray, Vector, IDENTITY};
 use serde::{Deserialize, Serialize};
 use typetag;
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Group {
     id: Uuid,
     parent_id: Option<Uuid>,
@@ -98,6 +98,15 @@ impl Shape for Group {
         shape
     }
 
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + self.objects.capacity() * std::mem::size_of::<Box<dyn Shape>>()
+    }
+
+    fn children(&self) -> Vec<&dyn Shape> {
+        self.objects.iter().map(|o| o.as_ref()).collect()
+    }
+
     fn contains_object_by_id(&self, id: Uuid) -> bool {
         let mut contains = false;
         for s in &self.objects {
@@ -114,6 +123,16 @@ impl Shape for Group {
         contains
     }
 
+    fn bounds(&self) -> Bounds {
+        self.objects
+            .iter()
+            .fold(Bounds::empty(), |b, o| b.merge(&o.parent_space_bounds()))
+    }
+
+    fn local_surface_area(&self) -> f64 {
+        self.objects.iter().map(|o| o.surface_area()).sum()
+    }
+
     fn local_intersect<'a>(&'a self, ray: Ray) -> Option<Vec<Intersection<'a>>> {
         let mut xs: Vec<Intersection> = Vec::new();
 
@@ -236,4 +255,21 @@ mod tests {
         let xs = g.intersect(r).unwrap();
         assert_eq!(xs.len(), 2);
     }
+
+    #[test]
+    fn the_surface_area_of_a_group_adds_up_its_children() {
+        let mut g = Group::new();
+        g.transform = Transformation::new().scale(2.0, 2.0, 2.0).build();
+        let mut s = Sphere::new();
+        s.transform = Transformation::new().translate(5.0, 0.0, 0.0).build();
+        g.add_object(Box::new(s));
+        g.add_object(Box::new(Sphere::new()));
+
+        assert!(crate::float_eq(
+            g.surface_area(),
+            32.0 * std::f64::consts::PI
+        ));
+        g.add_object(Box::new(crate::shapes::Plane::new()));
+        assert_eq!(g.surface_area(), f64::INFINITY);
+    }
 }
@@ -0,0 +1,476 @@
+use std::convert::TryFrom;
+
+use super::Shape;
+#[allow(unused_imports)]
+use crate::Transformation;
+use crate::{Bounds, Intersection, Material, Matrix, Point, Ray, Vector, EPSILON, IDENTITY};
+use serde::{Deserialize, Serialize};
+use typetag;
+use uuid::Uuid;
+
+/// Number of samples taken along the ray inside a single cell of the grid
+/// while searching for a crossing of the terrain.
+const MARCH_STEPS: usize = 8;
+
+/// Number of bisection steps used to refine a crossing found while marching.
+const BISECTION_STEPS: usize = 32;
+
+/// A terrain surface defined by a grid of elevation values.
+///
+/// The `heights` span the unit square `[0, 1] × [0, 1]` in the `x` and `z`
+/// dimensions. Each row of `heights` is a line of constant `z` and each
+/// column a line of constant `x`, with `heights[0][0]` at the origin. Values
+/// between the samples are bilinearly interpolated.
+///
+/// A hierarchy of minimum and maximum elevations is built over the grid so
+/// rays only march through the parts of the terrain they can actually hit.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "HeightmapData")]
+pub struct Heightmap {
+    id: Uuid,
+    parent_id: Option<Uuid>,
+    /// [`Transformation`] matrix used to manipulate the `Heightmap`
+    pub transform: Matrix,
+    /// [`Material`] describing the look of the `Heightmap`
+    pub material: Material,
+    heights: Vec<Vec<f64>>,
+    #[serde(skip_serializing)]
+    mips: Vec<Vec<Vec<(f64, f64)>>>,
+}
+
+/// Serialized form of a [`Heightmap`], the elevation hierarchy is rebuilt when
+/// deserializing.
+#[derive(Deserialize)]
+struct HeightmapData {
+    id: Uuid,
+    parent_id: Option<Uuid>,
+    transform: Matrix,
+    material: Material,
+    heights: Vec<Vec<f64>>,
+}
+
+impl TryFrom<HeightmapData> for Heightmap {
+    type Error = &'static str;
+
+    fn try_from(data: HeightmapData) -> Result<Self, Self::Error> {
+        Heightmap::check(&data.heights)?;
+        Ok(Heightmap {
+            id: data.id,
+            parent_id: data.parent_id,
+            transform: data.transform,
+            material: data.material,
+            mips: Heightmap::build_mips(&data.heights),
+            heights: data.heights,
+        })
+    }
+}
+
+impl Heightmap {
+    /// Create a new `Heightmap` from rows of elevation values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than two rows or two columns, or if the rows
+    /// differ in length.
+    pub fn new(heights: Vec<Vec<f64>>) -> Self {
+        if let Err(e) = Self::check(&heights) {
+            panic!("{}", e);
+        }
+
+        Heightmap {
+            id: Uuid::new_v4(),
+            parent_id: None,
+            transform: IDENTITY,
+            material: Material::new(),
+            mips: Self::build_mips(&heights),
+            heights,
+        }
+    }
+
+    /// Whether `heights` has at least two rows and two columns and all rows
+    /// have the same length.
+    fn check(heights: &[Vec<f64>]) -> Result<(), &'static str> {
+        if heights.len() < 2 {
+            return Err("a heightmap needs at least two rows");
+        }
+        if heights[0].len() < 2 {
+            return Err("a heightmap needs at least two columns");
+        }
+        if !heights.iter().all(|row| row.len() == heights[0].len()) {
+            return Err("all rows of a heightmap must have the same length");
+        }
+        Ok(())
+    }
+
+    /// The elevation values of the `Heightmap`.
+    pub fn heights(&self) -> &[Vec<f64>] {
+        &self.heights
+    }
+
+    /// Build the minimum and maximum elevation of every cell, then of every
+    /// block of 2×2 cells and so on until a single block covers the grid.
+    fn build_mips(heights: &[Vec<f64>]) -> Vec<Vec<Vec<(f64, f64)>>> {
+        let rows = heights.len() - 1;
+        let cols = heights[0].len() - 1;
+
+        let mut level: Vec<Vec<(f64, f64)>> = (0..rows)
+            .map(|i| {
+                (0..cols)
+                    .map(|j| {
+                        let corners = [
+                            heights[i][j],
+                            heights[i][j + 1],
+                            heights[i + 1][j],
+                            heights[i + 1][j + 1],
+                        ];
+                        let min = corners.iter().cloned().fold(f64::INFINITY, f64::min);
+                        let max = corners.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                        (min, max)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut mips = Vec::new();
+        while level.len() > 1 || level[0].len() > 1 {
+            let rows = level.len().div_ceil(2);
+            let cols = level[0].len().div_ceil(2);
+
+            let next = (0..rows)
+                .map(|i| {
+                    (0..cols)
+                        .map(|j| {
+                            let mut bounds = (f64::INFINITY, f64::NEG_INFINITY);
+                            for row in level.iter().skip(2 * i).take(2) {
+                                for &(min, max) in row.iter().skip(2 * j).take(2) {
+                                    bounds = (bounds.0.min(min), bounds.1.max(max));
+                                }
+                            }
+                            bounds
+                        })
+                        .collect()
+                })
+                .collect();
+
+            mips.push(level);
+            level = next;
+        }
+        mips.push(level);
+
+        mips
+    }
+
+    fn cell_width(&self) -> f64 {
+        1.0 / (self.heights[0].len() - 1) as f64
+    }
+
+    fn cell_depth(&self) -> f64 {
+        1.0 / (self.heights.len() - 1) as f64
+    }
+
+    /// Bilinearly interpolate the elevation at `x` and `z`.
+    fn height_at(&self, x: f64, z: f64) -> f64 {
+        self.interpolate(x, z, |i, j| self.heights[i][j])
+    }
+
+    /// Bilinearly interpolate the values given by `sample` for the grid
+    /// points around `x` and `z`.
+    fn interpolate<T, F>(&self, x: f64, z: f64, sample: F) -> T
+    where
+        T: std::ops::Mul<f64, Output = T> + std::ops::Add<Output = T>,
+        F: Fn(usize, usize) -> T,
+    {
+        let cols = self.heights[0].len() - 1;
+        let rows = self.heights.len() - 1;
+
+        let u = (x.clamp(0.0, 1.0) / self.cell_width()).min(cols as f64);
+        let v = (z.clamp(0.0, 1.0) / self.cell_depth()).min(rows as f64);
+        let j = (u.floor() as usize).min(cols - 1);
+        let i = (v.floor() as usize).min(rows - 1);
+        let (fu, fv) = (u - j as f64, v - i as f64);
+
+        let top = sample(i, j) * (1.0 - fu) + sample(i, j + 1) * fu;
+        let bottom = sample(i + 1, j) * (1.0 - fu) + sample(i + 1, j + 1) * fu;
+
+        top * (1.0 - fv) + bottom * fv
+    }
+
+    /// Normal at a grid point computed from the elevation of its neighbors.
+    fn sample_normal(&self, i: usize, j: usize) -> Vector {
+        let last_row = self.heights.len() - 1;
+        let last_col = self.heights[0].len() - 1;
+
+        let (j0, j1) = (j.saturating_sub(1), (j + 1).min(last_col));
+        let (i0, i1) = (i.saturating_sub(1), (i + 1).min(last_row));
+
+        let dx =
+            (self.heights[i][j1] - self.heights[i][j0]) / ((j1 - j0) as f64 * self.cell_width());
+        let dz =
+            (self.heights[i1][j] - self.heights[i0][j]) / ((i1 - i0) as f64 * self.cell_depth());
+
+        Vector::new(-dx, 1.0, -dz).normalize()
+    }
+
+    /// Walk down the elevation hierarchy from the block at `level`, `i` and
+    /// `j`, skipping every block the ray misses, and march through the cells
+    /// it reaches.
+    fn traverse(&self, ray: Ray, level: usize, i: usize, j: usize, ts: &mut Vec<f64>) {
+        let (min_y, max_y) = self.mips[level][i][j];
+        let span = 1 << level;
+        let cols = self.heights[0].len() - 1;
+        let rows = self.heights.len() - 1;
+
+        let min = Point::new(
+            (j * span) as f64 * self.cell_width(),
+            min_y,
+            (i * span) as f64 * self.cell_depth(),
+        );
+        let max = Point::new(
+            (((j + 1) * span).min(cols)) as f64 * self.cell_width(),
+            max_y,
+            (((i + 1) * span).min(rows)) as f64 * self.cell_depth(),
+        );
+
+        let (t0, t1) = match Bounds::new(min, max).intersect(ray) {
+            Some(t) => t,
+            None => return,
+        };
+
+        if level == 0 {
+            self.march(ray, t0, t1, ts);
+            return;
+        }
+
+        let below = &self.mips[level - 1];
+        for ci in (2 * i)..(2 * i + 2).min(below.len()) {
+            for cj in (2 * j)..(2 * j + 2).min(below[0].len()) {
+                self.traverse(ray, level - 1, ci, cj, ts);
+            }
+        }
+    }
+
+    /// Step along the ray from `t0` to `t1` looking for the ray crossing the
+    /// terrain, and refine every crossing with bisection.
+    fn march(&self, ray: Ray, t0: f64, t1: f64, ts: &mut Vec<f64>) {
+        let above = |t: f64| {
+            let p = ray.position(t);
+            p.y - self.height_at(p.x, p.z)
+        };
+
+        let step = (t1 - t0) / MARCH_STEPS as f64;
+        let mut a = t0;
+        let mut fa = above(a);
+
+        for n in 1..=MARCH_STEPS {
+            let b = t0 + step * n as f64;
+            let fb = above(b);
+
+            if fa == 0.0 {
+                ts.push(a);
+            } else if fa.signum() != fb.signum() && fb != 0.0 {
+                let (mut lo, mut hi) = (a, b);
+                for _ in 0..BISECTION_STEPS {
+                    let mid = (lo + hi) / 2.0;
+                    if above(mid).signum() == fa.signum() {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                ts.push((lo + hi) / 2.0);
+            }
+
+            a = b;
+            fa = fb;
+        }
+
+        if fa == 0.0 {
+            ts.push(a);
+        }
+    }
+}
+
+#[typetag::serde]
+impl Shape for Heightmap {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn parent_id(&self) -> Option<Uuid> {
+        self.parent_id
+    }
+
+    fn set_parent_id(&mut self, id: Uuid) {
+        self.parent_id = Some(id);
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+
+    fn bounds(&self) -> Bounds {
+        let (min_y, max_y) = self
+            .heights
+            .iter()
+            .flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), h| {
+                (lo.min(*h), hi.max(*h))
+            });
+        Bounds::new(Point::new(0.0, min_y, 0.0), Point::new(1.0, max_y, 1.0))
+    }
+
+    /// The area of the terrain estimated by splitting every cell of the grid
+    /// into two flat triangles.
+    fn local_surface_area(&self) -> f64 {
+        let (w, d) = (self.cell_width(), self.cell_depth());
+        let mut area = 0.0;
+        for i in 0..self.heights.len() - 1 {
+            for j in 0..self.heights[0].len() - 1 {
+                let corner = |di: usize, dj: usize| {
+                    Point::new(
+                        (j + dj) as f64 * w,
+                        self.heights[i + di][j + dj],
+                        (i + di) as f64 * d,
+                    )
+                };
+                let (a, b, c, e) = (corner(0, 0), corner(0, 1), corner(1, 0), corner(1, 1));
+                area += (b - a).cross(c - a).magnitude() / 2.0;
+                area += (b - e).cross(c - e).magnitude() / 2.0;
+            }
+        }
+        area
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
+        let mut ts: Vec<f64> = Vec::new();
+        self.traverse(ray, self.mips.len() - 1, 0, 0, &mut ts);
+
+        if ts.is_empty() {
+            return None;
+        }
+
+        // neighboring cells share their borders so a crossing on a border is
+        // found twice
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        ts.dedup_by(|a, b| (*a - *b).abs() < EPSILON);
+
+        Some(ts.into_iter().map(|t| Intersection::new(t, self)).collect())
+    }
+
+    fn local_normal_at(&self, point: Point, _hit: Option<&Intersection>) -> Vector {
+        self.interpolate(point.x, point.z, |i, j| self.sample_normal(i, j))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slope() -> Heightmap {
+        // the elevation rises with x from 0 to 1
+        Heightmap::new(
+            (0..5)
+                .map(|_| (0..5).map(|j| j as f64 / 4.0).collect())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn building_the_elevation_hierarchy() {
+        let h = slope();
+
+        assert_eq!(h.mips.len(), 3);
+        assert_eq!(h.mips[0].len(), 4);
+        assert_eq!(h.mips[0][0][1], (0.25, 0.5));
+        assert_eq!(h.mips[2], vec![vec![(0.0, 1.0)]]);
+    }
+
+    #[test]
+    fn a_ray_straight_down_hits_flat_terrain() {
+        let h = Heightmap::new(vec![vec![0.5; 3]; 3]);
+        let r = Ray::new(Point::new(0.3, 2.0, 0.7), Vector::new(0.0, -1.0, 0.0));
+        let xs = h.local_intersect(r).expect("No intersections");
+
+        assert_eq!(xs.len(), 1);
+        assert!((xs[0].t - 1.5).abs() < EPSILON);
+        assert_eq!(
+            h.local_normal_at(r.position(xs[0].t), None),
+            Vector::new(0.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn a_ray_straight_down_hits_sloped_terrain() {
+        let h = slope();
+        let r = Ray::new(Point::new(0.3, 2.0, 0.6), Vector::new(0.0, -1.0, 0.0));
+        let xs = h.local_intersect(r).expect("No intersections");
+
+        assert_eq!(xs.len(), 1);
+        assert!((r.position(xs[0].t).y - 0.3).abs() < EPSILON);
+        assert_eq!(
+            h.local_normal_at(r.position(xs[0].t), None),
+            Vector::new(-1.0, 1.0, 0.0).normalize()
+        );
+    }
+
+    #[test]
+    fn a_ray_across_the_terrain_hits_the_slope() {
+        let h = slope();
+        let r = Ray::new(Point::new(1.5, 0.5, 0.5), Vector::new(-1.0, 0.0, 0.0));
+        let xs = h.local_intersect(r).expect("No intersections");
+
+        assert!((xs[0].t - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn a_ray_outside_the_domain_misses() {
+        let h = slope();
+        let r = Ray::new(Point::new(1.5, 2.0, 0.5), Vector::new(0.0, -1.0, 0.0));
+
+        assert!(h.local_intersect(r).is_none());
+    }
+
+    #[test]
+    fn deserializing_rebuilds_the_elevation_hierarchy() {
+        let h = slope();
+        let json = serde_json::to_string(&h).unwrap();
+        let de: Heightmap = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(de.heights(), h.heights());
+        assert_eq!(de.mips, h.mips);
+    }
+
+    #[test]
+    fn deserializing_a_malformed_heightmap_is_an_error() {
+        let valid = serde_json::to_value(slope()).unwrap();
+
+        for heights in [
+            serde_json::json!([[0.0, 1.0]]),
+            serde_json::json!([[0.0], [1.0]]),
+            serde_json::json!([[0.0, 1.0], [1.0]]),
+        ]
+        .iter()
+        {
+            let mut value = valid.clone();
+            value["heights"] = heights.clone();
+
+            assert!(serde_json::from_value::<Heightmap>(value).is_err());
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use super::Shape;
+#[allow(unused_imports)]
+use crate::Transformation;
+use crate::{Bounds, Intersection, Material, Matrix, Point, Ray, UvBounds, Vector, IDENTITY};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use typetag;
+use uuid::Uuid;
+
+/// A placement of a `shape` that is shared with other instances, so scenes
+/// with many identical objects hold their geometry only once.
+///
+/// The `transform` of the `Instance` is applied on top of the transform of
+/// the shared `shape`, and the `material_override` replaces its material when
+/// set. Intersections with the shared shape are reported as intersections
+/// with the `Instance`. The shared shape is expected to be a single shape or
+/// a [`super::Mesh`], not a container like a [`super::Group`].
+///
+/// The shape is written out with every instance when serializing, so the
+/// instances of a loaded world no longer share it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Instance {
+    id: Uuid,
+    parent_id: Option<Uuid>,
+    /// The shape shared between instances
+    pub shape: Arc<dyn Shape>,
+    /// [`Transformation`] matrix used to place the `Instance`
+    pub transform: Matrix,
+    /// [`Material`] used instead of the material of the shared shape
+    pub material_override: Option<Material>,
+}
+
+impl Instance {
+    /// Create a new `Instance` of `shape` with the identity transform and the
+    /// material of the shape.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::{Instance, Shape, Sphere}, Transformation};
+    /// use std::sync::Arc;
+    ///
+    /// let sphere = Arc::new(Sphere::new());
+    /// let mut a = Instance::new(sphere.clone());
+    /// let mut b = Instance::new(sphere.clone());
+    /// b.transform = Transformation::new().translate(3.0, 0.0, 0.0).build();
+    ///
+    /// assert_eq!(Arc::strong_count(&sphere), 3);
+    /// ```
+    pub fn new(shape: Arc<dyn Shape>) -> Self {
+        Instance {
+            id: Uuid::new_v4(),
+            parent_id: None,
+            shape,
+            transform: IDENTITY,
+            material_override: None,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Shape for Instance {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn parent_id(&self) -> Option<Uuid> {
+        self.parent_id
+    }
+
+    fn set_parent_id(&mut self, id: Uuid) {
+        self.parent_id = Some(id);
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn material(&self) -> &Material {
+        match &self.material_override {
+            Some(material) => material,
+            None => self.shape.material(),
+        }
+    }
+
+    /// The shared material can not be changed through an instance, so it is
+    /// copied into the `material_override` first.
+    fn material_mut(&mut self) -> &mut Material {
+        let shape = &self.shape;
+        self.material_override
+            .get_or_insert_with(|| shape.material().clone())
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.material_override = Some(material);
+    }
+
+    fn local_uv_at(&self, point: Point) -> Option<(f64, f64)> {
+        self.shape
+            .local_uv_at(self.shape.transform().inverse() * point)
+    }
+
+    fn uv_bounds(&self) -> UvBounds {
+        self.shape.uv_bounds()
+    }
+
+    fn bounds(&self) -> Bounds {
+        self.shape.parent_space_bounds()
+    }
+
+    fn local_surface_area(&self) -> f64 {
+        self.shape.surface_area()
+    }
+
+    fn local_sample_surface(&self, rng: &mut dyn RngCore) -> (Point, Vector) {
+        self.shape.sample_surface(rng)
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
+        let xs = self.shape.intersect(ray)?;
+
+        Some(
+            xs.into_iter()
+                .map(|i| Intersection { object: self, ..i })
+                .collect(),
+        )
+    }
+
+    fn local_normal_at(&self, point: Point, hit: Option<&Intersection>) -> Vector {
+        self.shape.normal_at(point, hit, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{shapes::Sphere, Colors, World};
+
+    #[test]
+    fn instances_of_one_sphere_intersect_independently() {
+        let sphere: Arc<dyn Shape> = Arc::new(Sphere::new());
+        let a = Instance::new(sphere.clone());
+        let mut b = Instance::new(sphere.clone());
+        b.transform = Transformation::new().translate(0.0, 0.0, 10.0).build();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        let xs = a.intersect(r).expect("No intersections");
+        assert_eq!((xs[0].t, xs[1].t), (4.0, 6.0));
+        assert!(a.shape_eq(xs[0].object));
+
+        let xs = b.intersect(r).expect("No intersections");
+        assert_eq!((xs[0].t, xs[1].t), (14.0, 16.0));
+        assert!(b.shape_eq(xs[0].object));
+    }
+
+    #[test]
+    fn instances_share_the_geometry() {
+        let mut sphere = Sphere::new();
+        sphere.transform = Transformation::new().scale(2.0, 2.0, 2.0).build();
+        let sphere = Arc::new(sphere);
+        let instances: Vec<Instance> = (0..10).map(|_| Instance::new(sphere.clone())).collect();
+
+        assert_eq!(Arc::strong_count(&sphere), 11);
+        for instance in instances.iter() {
+            assert!(std::ptr::eq(
+                Arc::as_ptr(&instance.shape) as *const u8,
+                Arc::as_ptr(&sphere) as *const u8
+            ));
+        }
+    }
+
+    #[test]
+    fn the_normal_includes_both_transforms() {
+        let mut sphere = Sphere::new();
+        sphere.transform = Transformation::new().scale(2.0, 2.0, 2.0).build();
+        let mut i = Instance::new(Arc::new(sphere));
+        i.transform = Transformation::new().translate(5.0, 0.0, 0.0).build();
+
+        assert_eq!(
+            i.normal_at(Point::new(7.0, 0.0, 0.0), None, None),
+            Vector::new(1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn a_material_override_replaces_the_shared_material() {
+        let mut sphere = Sphere::new();
+        sphere.material.set_color(Colors::RED);
+        let sphere: Arc<dyn Shape> = Arc::new(sphere);
+        let a = Instance::new(sphere.clone());
+        let mut b = Instance::new(sphere.clone());
+        b.material_override = Some(Material::new().color(Colors::BLACK));
+
+        assert_eq!(a.material().solid_color(), Some(Colors::RED));
+        assert_eq!(b.material().solid_color(), Some(Colors::BLACK));
+        assert_eq!(sphere.material().solid_color(), Some(Colors::RED));
+    }
+
+    #[test]
+    fn changing_the_material_of_an_instance_copies_it() {
+        let mut sphere = Sphere::new();
+        sphere.material.set_color(Colors::RED);
+        let sphere: Arc<dyn Shape> = Arc::new(sphere);
+        let mut i = Instance::new(sphere.clone());
+        i.material_mut().ambient = 1.0;
+
+        assert_eq!(i.material().solid_color(), Some(Colors::RED));
+        assert_eq!(i.material().ambient, 1.0);
+        assert_eq!(sphere.material().ambient, 0.1);
+    }
+
+    #[test]
+    fn an_instance_renders_like_the_shape_it_places() {
+        let r = Ray::new(Point::new(0.0, 0.5, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let transform = Transformation::new().translate(0.0, 0.0, 1.0).build();
+
+        let mut w = World::new();
+        w.light = World::default().light;
+        let mut s = Sphere::new();
+        s.transform = transform;
+        s.material.set_color(Colors::RED);
+        w.add_object(Box::new(s));
+        let expected = w.color_at(r, 5);
+
+        let mut w = World::new();
+        w.light = World::default().light;
+        let mut i = Instance::new(Arc::new(Sphere::new()));
+        i.transform = transform;
+        i.material_override = Some(Material::new().color(Colors::RED));
+        w.add_object(Box::new(i));
+
+        assert_eq!(w.color_at(r, 5), expected);
+    }
+}
@@ -0,0 +1,631 @@
+use std::any::Any;
+use std::convert::TryFrom;
+
+use super::Shape;
+#[allow(unused_imports)]
+use crate::Transformation;
+use crate::{Bounds, Color, Intersection, Material, Matrix, Point, Ray, Vector, EPSILON, IDENTITY};
+use serde::{Deserialize, Serialize};
+use typetag;
+use uuid::Uuid;
+
+/// Largest number of faces kept in a leaf of the bounding volume hierarchy.
+const MAX_LEAF_FACES: usize = 4;
+
+/// A node of the bounding volume hierarchy of a [`Mesh`], a box around the
+/// faces below it.
+#[derive(Debug, Clone)]
+enum BvhNode {
+    Leaf {
+        bounds: Bounds,
+        faces: Vec<usize>,
+    },
+    Branch {
+        bounds: Bounds,
+        left: Box<BvhNode>,
+        right: Box<BvhNode>,
+    },
+}
+
+impl BvhNode {
+    /// Number of bytes used by the node and the nodes below it.
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<BvhNode>()
+            + match self {
+                BvhNode::Leaf { faces, .. } => faces.capacity() * std::mem::size_of::<usize>(),
+                BvhNode::Branch { left, right, .. } => left.memory_size() + right.memory_size(),
+            }
+    }
+
+    /// Number of levels of nodes from this one down to the deepest leaf.
+    fn depth(&self) -> usize {
+        match self {
+            BvhNode::Leaf { .. } => 1,
+            BvhNode::Branch { left, right, .. } => 1 + left.depth().max(right.depth()),
+        }
+    }
+}
+
+impl Default for BvhNode {
+    fn default() -> Self {
+        BvhNode::Leaf {
+            bounds: Bounds::empty(),
+            faces: Vec::new(),
+        }
+    }
+}
+
+/// A surface made of triangles sharing a list of vertices, the efficient
+/// representation for large models such as the ones loaded from OBJ files.
+///
+/// Each face is a triple of indices into `vertices`. When there is a normal
+/// for every vertex the normals are interpolated across each face, otherwise
+/// the faces are flat. A bounding volume hierarchy over the faces lets rays
+/// skip every face inside a box they miss.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "MeshData")]
+pub struct Mesh {
+    id: Uuid,
+    parent_id: Option<Uuid>,
+    /// [`Transformation`] matrix used to manipulate the `Mesh`
+    pub transform: Matrix,
+    /// [`Material`] describing the look of the `Mesh`
+    pub material: Material,
+    /// Corners of the faces
+    pub vertices: Vec<Point>,
+    /// Normal at each vertex, or empty for flat faces
+    pub normals: Vec<Vector>,
+    /// Indices of the three vertices of each face
+    pub faces: Vec<(usize, usize, usize)>,
+    /// Color at each vertex, or empty, as loaded from files that have them
+    pub colors: Vec<Color>,
+    #[serde(skip_serializing)]
+    bvh_root: BvhNode,
+}
+
+/// Serialized form of a [`Mesh`], the bounding volume hierarchy is rebuilt
+/// when deserializing.
+#[derive(Deserialize)]
+struct MeshData {
+    id: Uuid,
+    parent_id: Option<Uuid>,
+    transform: Matrix,
+    material: Material,
+    vertices: Vec<Point>,
+    normals: Vec<Vector>,
+    faces: Vec<(usize, usize, usize)>,
+    #[serde(default)]
+    colors: Vec<Color>,
+}
+
+impl TryFrom<MeshData> for Mesh {
+    type Error = &'static str;
+
+    fn try_from(data: MeshData) -> Result<Self, Self::Error> {
+        Mesh::check(&data.vertices, &data.normals, &data.faces)?;
+        let mut mesh = Mesh {
+            id: data.id,
+            parent_id: data.parent_id,
+            transform: data.transform,
+            material: data.material,
+            vertices: data.vertices,
+            normals: data.normals,
+            faces: data.faces,
+            colors: data.colors,
+            bvh_root: BvhNode::default(),
+        };
+        mesh.build_bvh();
+        Ok(mesh)
+    }
+}
+
+impl Mesh {
+    /// Create a new `Mesh` from its `vertices`, the `normals` at each vertex
+    /// (empty for flat faces) and its `faces`, and build its bounding volume
+    /// hierarchy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a face refers to a vertex that does not exist, or if there
+    /// are normals but not one for every vertex.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::{Mesh, Shape}, Point, Ray, Vector};
+    ///
+    /// let m = Mesh::new(
+    ///     vec![
+    ///         Point::new(0.0, 1.0, 0.0),
+    ///         Point::new(-1.0, 0.0, 0.0),
+    ///         Point::new(1.0, 0.0, 0.0),
+    ///     ],
+    ///     vec![],
+    ///     vec![(0, 1, 2)],
+    /// );
+    /// let r = Ray::new(Point::new(0.0, 0.5, -2.0), Vector::new(0.0, 0.0, 1.0));
+    ///
+    /// assert_eq!(m.intersect(r).unwrap()[0].t, 2.0);
+    /// ```
+    pub fn new(
+        vertices: Vec<Point>,
+        normals: Vec<Vector>,
+        faces: Vec<(usize, usize, usize)>,
+    ) -> Self {
+        if let Err(e) = Mesh::check(&vertices, &normals, &faces) {
+            panic!("{}", e);
+        }
+
+        let mut mesh = Mesh {
+            id: Uuid::new_v4(),
+            parent_id: None,
+            transform: IDENTITY,
+            material: Material::new(),
+            vertices,
+            normals,
+            faces,
+            colors: Vec::new(),
+            bvh_root: BvhNode::default(),
+        };
+        mesh.build_bvh();
+        mesh
+    }
+
+    /// Whether the `faces` only refer to existing `vertices` and there is a
+    /// normal for every vertex or none at all.
+    fn check(
+        vertices: &[Point],
+        normals: &[Vector],
+        faces: &[(usize, usize, usize)],
+    ) -> Result<(), &'static str> {
+        if !faces
+            .iter()
+            .all(|&(a, b, c)| a.max(b).max(c) < vertices.len())
+        {
+            return Err("a face of the mesh refers to a missing vertex");
+        }
+        if !normals.is_empty() && normals.len() != vertices.len() {
+            return Err("a mesh needs a normal for every vertex or none at all");
+        }
+        Ok(())
+    }
+
+    /// Number of levels of the bounding volume hierarchy over the faces.
+    pub fn bvh_depth(&self) -> usize {
+        self.bvh_root.depth()
+    }
+
+    /// Rebuild the bounding volume hierarchy, needed after changing the
+    /// `vertices` or the `faces`.
+    ///
+    /// Faces are split in two halves around the median of their centers along
+    /// the longest axis of the box around the centers, until few enough are
+    /// left to be tested one by one.
+    pub fn build_bvh(&mut self) {
+        let faces = (0..self.faces.len()).collect();
+        self.bvh_root = self.build_node(faces);
+    }
+
+    fn build_node(&self, mut faces: Vec<usize>) -> BvhNode {
+        let bounds = faces
+            .iter()
+            .fold(Bounds::empty(), |b, f| b.merge(&self.face_bounds(*f)));
+
+        if faces.len() <= MAX_LEAF_FACES {
+            return BvhNode::Leaf { bounds, faces };
+        }
+
+        let centers: Vec<Point> = faces
+            .iter()
+            .map(|f| self.face_bounds(*f).centroid())
+            .collect();
+        let extent = Bounds::from_points(&centers);
+        let size = extent.max - extent.min;
+        let axis = |p: Point| {
+            if size.x >= size.y && size.x >= size.z {
+                p.x
+            } else if size.y >= size.z {
+                p.y
+            } else {
+                p.z
+            }
+        };
+
+        faces.sort_by(|a, b| {
+            let a = axis(self.face_bounds(*a).centroid());
+            let b = axis(self.face_bounds(*b).centroid());
+            a.partial_cmp(&b).unwrap()
+        });
+        let right = faces.split_off(faces.len() / 2);
+
+        BvhNode::Branch {
+            bounds,
+            left: Box::new(self.build_node(faces)),
+            right: Box::new(self.build_node(right)),
+        }
+    }
+
+    fn face_points(&self, face: usize) -> (Point, Point, Point) {
+        let (a, b, c) = self.faces[face];
+        (self.vertices[a], self.vertices[b], self.vertices[c])
+    }
+
+    /// The face whose center is nearest to `point`, `None` for a mesh
+    /// without faces.
+    fn nearest_face(&self, point: Point) -> Option<usize> {
+        let distance = |face: usize| (self.face_bounds(face).centroid() - point).magnitude();
+        (0..self.faces.len()).min_by(|&a, &b| distance(a).partial_cmp(&distance(b)).unwrap())
+    }
+
+    fn face_bounds(&self, face: usize) -> Bounds {
+        let (p1, p2, p3) = self.face_points(face);
+        Bounds::from_points(&[p1, p2, p3])
+    }
+
+    /// Intersect `ray` with a single `face`, giving the `t`, `u` and `v` of the
+    /// hit, see [`super::Triangle`].
+    fn intersect_face(&self, face: usize, ray: Ray) -> Option<(f64, f64, f64)> {
+        let (p1, p2, p3) = self.face_points(face);
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+
+        let dir_cross_e2 = ray.direction.cross(e2);
+        let det = e1.dot(dir_cross_e2);
+        if det.abs() < EPSILON {
+            return None;
+        }
+
+        let f = 1.0 / det;
+        let p1_to_origin = ray.origin - p1;
+        let u = f * p1_to_origin.dot(dir_cross_e2);
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let origin_cross_e1 = p1_to_origin.cross(e1);
+        let v = f * ray.direction.dot(origin_cross_e1);
+        if v < 0.0 || (u + v) > 1.0 {
+            return None;
+        }
+
+        Some((f * e2.dot(origin_cross_e1), u, v))
+    }
+
+    fn traverse<'a>(&'a self, node: &BvhNode, ray: Ray, xs: &mut Vec<Intersection<'a>>) {
+        match node {
+            BvhNode::Leaf { bounds, faces } => {
+                if bounds.intersect(ray).is_none() {
+                    return;
+                }
+                for &face in faces {
+                    if let Some((t, u, v)) = self.intersect_face(face, ray) {
+                        xs.push(Intersection::intersection_with_face(t, self, face, u, v));
+                    }
+                }
+            }
+            BvhNode::Branch {
+                bounds,
+                left,
+                right,
+            } => {
+                if bounds.intersect(ray).is_some() {
+                    self.traverse(left, ray, xs);
+                    self.traverse(right, ray, xs);
+                }
+            }
+        }
+    }
+}
+
+#[typetag::serde]
+impl Shape for Mesh {
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn parent_id(&self) -> Option<Uuid> {
+        self.parent_id
+    }
+
+    fn set_parent_id(&mut self, id: Uuid) {
+        self.parent_id = Some(id);
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + self.vertices.capacity() * std::mem::size_of::<Point>()
+            + self.normals.capacity() * std::mem::size_of::<Vector>()
+            + self.faces.capacity() * std::mem::size_of::<(usize, usize, usize)>()
+            + self.colors.capacity() * std::mem::size_of::<Color>()
+            + self.bvh_root.memory_size()
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::from_points(&self.vertices)
+    }
+
+    fn local_surface_area(&self) -> f64 {
+        self.faces
+            .iter()
+            .map(|&(a, b, c)| {
+                let (p1, p2, p3) = (self.vertices[a], self.vertices[b], self.vertices[c]);
+                (p2 - p1).cross(p3 - p1).magnitude() / 2.0
+            })
+            .sum()
+    }
+
+    /// The areas of the faces moved by the transformation of the mesh,
+    /// exact for any transformation.
+    fn surface_area(&self) -> f64 {
+        self.faces
+            .iter()
+            .map(|&(a, b, c)| {
+                let (p1, p2, p3) = (self.vertices[a], self.vertices[b], self.vertices[c]);
+                (self.transform * (p2 - p1))
+                    .cross(self.transform * (p3 - p1))
+                    .magnitude()
+                    / 2.0
+            })
+            .sum()
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
+        let mut xs = Vec::new();
+        self.traverse(&self.bvh_root, ray, &mut xs);
+
+        if xs.is_empty() {
+            None
+        } else {
+            xs.sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap());
+            Some(xs)
+        }
+    }
+
+    /// The normal interpolated across the face of the `hit`, or the normal of
+    /// the face for a flat mesh.
+    ///
+    /// Without the face of the intersection the flat normal of the face
+    /// whose center is nearest to `point` is used.
+    fn local_normal_at(&self, point: Point, hit: Option<&Intersection>) -> Vector {
+        let hit = hit.filter(|h| h.face.is_some());
+        let face = match hit
+            .and_then(|h| h.face)
+            .or_else(|| self.nearest_face(point))
+        {
+            Some(face) => face,
+            None => return Vector::new(0.0, 1.0, 0.0),
+        };
+
+        match (hit.and_then(|h| h.u.zip(h.v)), self.normals.is_empty()) {
+            (Some((u, v)), false) => {
+                let (a, b, c) = self.faces[face];
+                self.normals[b] * u + self.normals[c] * v + self.normals[a] * (1.0 - u - v)
+            }
+            _ => {
+                let (p1, p2, p3) = self.face_points(face);
+                (p3 - p1).cross(p2 - p1).normalize()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::Triangle;
+    use std::f64::consts::PI;
+
+    /// A unit sphere made of `2 * rings * segments` faces, with the normals
+    /// pointing away from the center.
+    fn sphere_mesh(rings: usize, segments: usize) -> Mesh {
+        let mut vertices = Vec::new();
+        let mut normals = Vec::new();
+        for r in 0..=rings {
+            let theta = PI * r as f64 / rings as f64;
+            for s in 0..=segments {
+                let phi = 2.0 * PI * s as f64 / segments as f64;
+                let p = Point::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+                vertices.push(p);
+                normals.push(p - Point::new(0.0, 0.0, 0.0));
+            }
+        }
+
+        let index = |r: usize, s: usize| r * (segments + 1) + s;
+        let mut faces = Vec::new();
+        for r in 0..rings {
+            for s in 0..segments {
+                faces.push((index(r, s), index(r + 1, s), index(r + 1, s + 1)));
+                faces.push((index(r, s), index(r + 1, s + 1), index(r, s + 1)));
+            }
+        }
+
+        Mesh::new(vertices, normals, faces)
+    }
+
+    fn rays() -> Vec<Ray> {
+        let mut rays = Vec::new();
+        for i in 0..20 {
+            for j in 0..20 {
+                let target = Point::new(i as f64 / 8.0 - 1.2, j as f64 / 8.0 - 1.2, 0.1);
+                let origin = Point::new(0.3, -0.2, -5.0);
+                rays.push(Ray::new(origin, (target - origin).normalize()));
+            }
+        }
+        rays
+    }
+
+    fn brute_force(triangles: &[Triangle], ray: Ray) -> Vec<f64> {
+        let mut ts: Vec<f64> = triangles
+            .iter()
+            .filter_map(|t| t.local_intersect(ray))
+            .flatten()
+            .map(|i| i.t)
+            .collect();
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        ts
+    }
+
+    fn triangles(mesh: &Mesh) -> Vec<Triangle> {
+        (0..mesh.faces.len())
+            .map(|f| {
+                let (p1, p2, p3) = mesh.face_points(f);
+                Triangle::new(p1, p2, p3)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_hierarchy_matches_a_brute_force_search() {
+        let m = sphere_mesh(8, 12);
+        let triangles = triangles(&m);
+
+        for ray in rays() {
+            let expected = brute_force(&triangles, ray);
+            let actual: Vec<f64> = m
+                .local_intersect(ray)
+                .map(|xs| xs.iter().map(|i| i.t).collect())
+                .unwrap_or_default();
+
+            assert_eq!(actual.len(), expected.len());
+            for (a, e) in actual.iter().zip(expected.iter()) {
+                assert!((a - e).abs() < EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn the_hierarchy_is_faster_than_a_brute_force_search() {
+        use std::time::Instant;
+
+        let m = sphere_mesh(20, 25);
+        assert_eq!(m.faces.len(), 1000);
+        let triangles = triangles(&m);
+        let rays = rays();
+
+        let start = Instant::now();
+        let mut brute_hits = 0;
+        for ray in rays.iter() {
+            brute_hits += brute_force(&triangles, *ray).len();
+        }
+        let brute_time = start.elapsed();
+
+        let start = Instant::now();
+        let mut bvh_hits = 0;
+        for ray in rays.iter() {
+            bvh_hits += m.local_intersect(*ray).map_or(0, |xs| xs.len());
+        }
+        let bvh_time = start.elapsed();
+
+        assert_eq!(bvh_hits, brute_hits);
+        assert!(bvh_time < brute_time);
+    }
+
+    #[test]
+    fn the_normals_of_a_smooth_mesh_are_interpolated() {
+        let m = sphere_mesh(16, 24);
+        let r = Ray::new(Point::new(0.3, 0.2, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = m.local_intersect(r).expect("No intersections");
+        let point = r.position(xs[0].t);
+        let n = m.normal_at(point, Some(&xs[0]), None);
+        let expected = (point - Point::new(0.0, 0.0, 0.0)).normalize();
+
+        assert!((n - expected).magnitude() < 0.01);
+    }
+
+    #[test]
+    fn the_normal_of_a_flat_mesh_is_the_face_normal() {
+        let m = Mesh::new(
+            vec![
+                Point::new(0.0, 1.0, 0.0),
+                Point::new(-1.0, 0.0, 0.0),
+                Point::new(1.0, 0.0, 0.0),
+            ],
+            vec![],
+            vec![(0, 1, 2)],
+        );
+        let r = Ray::new(Point::new(0.0, 0.5, -2.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = m.local_intersect(r).expect("No intersections");
+
+        assert_eq!(xs[0].face, Some(0));
+        assert_eq!(
+            m.local_normal_at(Point::new(0.0, 0.5, 0.0), Some(&xs[0])),
+            Vector::new(0.0, 0.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn the_normal_without_an_intersection_is_the_nearest_face_normal() {
+        let m = Mesh::new(
+            vec![
+                Point::new(0.0, 1.0, 0.0),
+                Point::new(-1.0, 0.0, 0.0),
+                Point::new(1.0, 0.0, 0.0),
+                Point::new(5.0, 1.0, 0.0),
+                Point::new(5.0, 0.0, 0.0),
+                Point::new(5.0, 0.0, 1.0),
+            ],
+            vec![],
+            vec![(0, 1, 2), (3, 4, 5)],
+        );
+
+        assert_eq!(
+            m.local_normal_at(Point::new(0.0, 0.5, 0.0), None),
+            Vector::new(0.0, 0.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn deserializing_a_malformed_mesh_is_an_error() {
+        let m = sphere_mesh(2, 3);
+        let valid = serde_json::to_value(&m).unwrap();
+
+        let mut missing_vertex = valid.clone();
+        missing_vertex["faces"][0][1] = serde_json::json!(1000);
+        let error = serde_json::from_value::<Mesh>(missing_vertex).unwrap_err();
+        assert!(error.to_string().contains("missing vertex"));
+
+        let mut missing_normal = valid;
+        missing_normal["normals"].as_array_mut().unwrap().pop();
+        let error = serde_json::from_value::<Mesh>(missing_normal).unwrap_err();
+        assert!(error.to_string().contains("normal for every vertex"));
+    }
+
+    #[test]
+    fn deserializing_rebuilds_the_hierarchy() {
+        let m = sphere_mesh(4, 6);
+        let json = serde_json::to_string(&m).unwrap();
+        let de: Mesh = serde_json::from_str(&json).unwrap();
+        let r = Ray::new(Point::new(0.13, 0.07, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert_eq!(de.local_intersect(r).map(|xs| xs.len()), Some(2));
+    }
+}
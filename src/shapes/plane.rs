@@ -1,4 +1,4 @@
-use super::Shape;
+use super::{displacement, BoundedPlane, Shape};
 #[allow(unused_imports)]
 use crate::Transformation;
 use crate::{Intersection, Material, Matrix, Point, Ray, Vector, EPSILON, IDENTITY};
@@ -8,9 +8,10 @@ use uuid::Uuid;
 
 /// A perfectly flat surface that extends infinitely in two dimensions.
 ///
-/// The place extends infinitely far in both teh `x` and `z` dimensions passing
-/// through the origin.
-#[derive(Serialize, Deserialize, Debug)]
+/// By default the place extends infinitely far in both teh `x` and `z`
+/// dimensions passing through the origin. The [`PlaneOrientation`] selects
+/// one of the other axis-aligned planes without needing a rotation.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Plane {
     id: Uuid,
     parent_id: Option<Uuid>,
@@ -18,6 +19,22 @@ pub struct Plane {
     pub transform: Matrix,
     /// [`Material`] describing the look of the `Plane`
     pub material: Material,
+    /// The two axes the `Plane` extends along in object space.
+    #[serde(default)]
+    pub orientation: PlaneOrientation,
+}
+
+/// The axis-aligned orientations of a [`Plane`], named after the two axes the
+/// plane extends along.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+pub enum PlaneOrientation {
+    /// The plane `y = 0` with the normal pointing along `+y`.
+    #[default]
+    Xz,
+    /// The plane `z = 0` with the normal pointing along `+z`.
+    Xy,
+    /// The plane `x = 0` with the normal pointing along `+x`.
+    Yz,
 }
 
 impl Plane {
@@ -28,6 +45,55 @@ impl Plane {
             parent_id: None,
             transform: IDENTITY,
             material: Material::new(),
+            orientation: PlaneOrientation::Xz,
+        }
+    }
+
+    /// Create a new plane in the `x` and `z` dimensions, the same as `new`.
+    pub fn xz() -> Self {
+        Self::new()
+    }
+
+    /// Create a new plane in the `x` and `y` dimensions.
+    pub fn xy() -> Self {
+        let mut p = Self::new();
+        p.orientation = PlaneOrientation::Xy;
+        p
+    }
+
+    /// Create a new plane in the `y` and `z` dimensions.
+    pub fn yz() -> Self {
+        let mut p = Self::new();
+        p.orientation = PlaneOrientation::Yz;
+        p
+    }
+
+    /// Create a rectangle in the `x` and `z` dimensions, a plane clipped to
+    /// `half_width` along `x` and `half_depth` along `z` on each side of the
+    /// origin.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::{Plane, Shape}, Point, Ray, Vector};
+    ///
+    /// let floor = Plane::with_extent(10.0, 10.0);
+    /// let r = Ray::new(Point::new(20.0, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+    ///
+    /// assert!(floor.bounds().is_finite());
+    /// assert!(floor.intersect(r).is_none());
+    /// ```
+    pub fn with_extent(half_width: f64, half_depth: f64) -> BoundedPlane {
+        BoundedPlane::new(half_width, half_depth)
+    }
+
+    /// Distance of the object space `point` above the plane, along the
+    /// normal.
+    fn height(&self, point: Point) -> f64 {
+        match self.orientation {
+            PlaneOrientation::Xz => point.y,
+            PlaneOrientation::Xy => point.z,
+            PlaneOrientation::Yz => point.x,
         }
     }
 }
@@ -66,17 +132,42 @@ impl Shape for Plane {
         self.material = material;
     }
 
+    fn local_tangent_at(&self, _point: Point) -> Option<Vector> {
+        Some(match self.orientation {
+            PlaneOrientation::Xz | PlaneOrientation::Xy => Vector::new(1.0, 0.0, 0.0),
+            PlaneOrientation::Yz => Vector::new(0.0, 1.0, 0.0),
+        })
+    }
+
     fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
-        if ray.direction.y.abs() < EPSILON {
+        if let Some(map) = &self.material.displacement_map {
+            return displacement::intersect(self, map.as_ref(), ray, |p| self.height(p));
+        }
+
+        let (origin, direction) = match self.orientation {
+            PlaneOrientation::Xz => (ray.origin.y, ray.direction.y),
+            PlaneOrientation::Xy => (ray.origin.z, ray.direction.z),
+            PlaneOrientation::Yz => (ray.origin.x, ray.direction.x),
+        };
+
+        if direction.abs() < EPSILON {
             return None;
         }
 
-        let t = -ray.origin.y / ray.direction.y;
+        let t = -origin / direction;
         Some(vec![Intersection::new(t, self)])
     }
 
-    fn local_normal_at(&self, _point: Point, _hit: Option<&Intersection>) -> Vector {
-        Vector::new(0.0, 1.0, 0.0)
+    fn local_normal_at(&self, point: Point, _hit: Option<&Intersection>) -> Vector {
+        if let Some(map) = &self.material.displacement_map {
+            return displacement::normal(map.as_ref(), point, |p| self.height(p));
+        }
+
+        match self.orientation {
+            PlaneOrientation::Xz => Vector::new(0.0, 1.0, 0.0),
+            PlaneOrientation::Xy => Vector::new(0.0, 0.0, 1.0),
+            PlaneOrientation::Yz => Vector::new(1.0, 0.0, 0.0),
+        }
     }
 }
 
@@ -145,4 +236,58 @@ mod tests {
         assert_eq!(xs[0].t, 1.0);
         assert!(p.shape_eq(xs[0].object));
     }
+
+    #[test]
+    fn the_normal_of_each_orientation() {
+        let p = Point::new(0.0, 0.0, 0.0);
+
+        assert_eq!(
+            Plane::xz().local_normal_at(p, None),
+            Vector::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            Plane::xy().local_normal_at(p, None),
+            Vector::new(0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            Plane::yz().local_normal_at(p, None),
+            Vector::new(1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn a_ray_intersecting_an_xy_plane() {
+        let p = Plane::xy();
+        let r = Ray::new(Point::new(1.0, 2.0, -3.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = p.local_intersect(r).expect("No intersections");
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 3.0);
+    }
+
+    #[test]
+    fn a_ray_intersecting_a_yz_plane() {
+        let p = Plane::yz();
+        let r = Ray::new(Point::new(2.0, 1.0, 1.0), Vector::new(-1.0, 0.0, 0.0));
+        let xs = p.local_intersect(r).expect("No intersections");
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 2.0);
+    }
+
+    #[test]
+    fn a_ray_parallel_to_an_xy_plane() {
+        let p = Plane::xy();
+        let r = Ray::new(Point::new(0.0, 0.0, 1.0), Vector::new(1.0, 1.0, 0.0));
+
+        assert!(p.local_intersect(r).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn the_surface_of_a_plane_can_not_be_sampled() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        Plane::new().sample_surface(&mut StdRng::seed_from_u64(5));
+    }
 }
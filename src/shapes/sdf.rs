@@ -0,0 +1,412 @@
+use super::Shape;
+#[allow(unused_imports)]
+use crate::Transformation;
+use crate::{Bounds, Intersection, Material, Matrix, Point, Ray, Vector, IDENTITY};
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::Arc};
+use typetag;
+use uuid::Uuid;
+
+/// Distance between the points used to estimate the normal.
+const NORMAL_DELTA: f64 = 1e-5;
+
+/// A signed distance function, giving the distance from a point in object
+/// space to the nearest point of a surface, negative inside it.
+pub type Sdf = Arc<dyn Fn(Point) -> f64 + Send + Sync>;
+
+/// The distance function of a field read from a file, which has no surface.
+fn no_surface() -> Sdf {
+    Arc::new(|_| f64::INFINITY)
+}
+
+/// An implicit surface given by a signed distance function, such as blobs,
+/// metaballs or fractals like the mandelbox.
+///
+/// Rays are sphere marched: the distance to the surface at a point is a step
+/// that can be taken along the ray in any direction without crossing the
+/// surface, so the ray is moved by it until it is within `epsilon` of the
+/// surface. Only hits in front of the origin of the ray are found, and the
+/// function must never give more than the real distance to the surface.
+///
+/// The distance function can not be saved, a field read from a file has no
+/// surface until [`DistanceField::sdf`] is set again.
+///
+/// # Example
+///
+/// ```
+/// use rustic_ray::{shapes::DistanceField, shapes::Shape, Point, Ray, Vector};
+///
+/// let ball = DistanceField::new(|p| (p - Point::new(0.0, 0.0, 0.0)).magnitude() - 1.0);
+/// let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+/// let xs = ball.intersect(r).unwrap();
+///
+/// assert!((xs[0].t - 4.0).abs() < 1e-4);
+/// assert!((xs[1].t - 6.0).abs() < 1e-4);
+/// ```
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DistanceField {
+    id: Uuid,
+    parent_id: Option<Uuid>,
+    /// [`Transformation`] matrix used to manipulate the `DistanceField`
+    pub transform: Matrix,
+    /// [`Material`] describing the look of the `DistanceField`
+    pub material: Material,
+    /// The signed distance function of the surface.
+    #[serde(skip, default = "no_surface")]
+    pub sdf: Sdf,
+    /// How close to the surface a point must be to count as a hit.
+    pub epsilon: f64,
+    /// Most steps taken along a ray before giving up on finding more hits.
+    pub max_steps: usize,
+    /// Farthest distance along a ray the surface is looked for.
+    pub max_distance: f64,
+    /// Box around the surface in object space, infinite when `None`.
+    pub bounds: Option<Bounds>,
+}
+
+impl DistanceField {
+    /// Create a new `DistanceField` of the surface where `sdf` is zero.
+    pub fn new(sdf: impl Fn(Point) -> f64 + Send + Sync + 'static) -> Self {
+        DistanceField {
+            id: Uuid::new_v4(),
+            parent_id: None,
+            transform: IDENTITY,
+            material: Material::new(),
+            sdf: Arc::new(sdf),
+            epsilon: 1e-6,
+            max_steps: 1000,
+            max_distance: 1000.0,
+            bounds: None,
+        }
+    }
+}
+
+/// The smooth minimum of the distances `d1` and `d2` to two surfaces, which
+/// joins them with a rounded fillet `k` wide where they meet instead of the
+/// crease of a plain minimum. A `k` of zero is the plain minimum.
+///
+/// This is the polynomial smooth minimum of Inigo Quilez.
+///
+/// # Example
+///
+/// ```
+/// use rustic_ray::shapes::smooth_union;
+///
+/// assert_eq!(smooth_union(1.0, 2.0, 0.0), 1.0);
+/// assert_eq!(smooth_union(1.0, 1.0, 0.4), 0.9);
+/// assert_eq!(smooth_union(1.0, 2.0, 0.4), 1.0);
+/// ```
+pub fn smooth_union(d1: f64, d2: f64, k: f64) -> f64 {
+    if k <= 0.0 {
+        return d1.min(d2);
+    }
+
+    let h = (0.5 + 0.5 * (d2 - d1) / k).clamp(0.0, 1.0);
+    d2 + (d1 - d2) * h - k * h * (1.0 - h)
+}
+
+/// Blobs of balls melting into each other where they are close.
+pub struct Metaball;
+
+impl Metaball {
+    /// Create a [`DistanceField`] of balls at the `centers` with the `radii`,
+    /// joined with [`smooth_union`] over the `smoothness`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not one radius for every center.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(centers: Vec<Point>, radii: Vec<f64>, smoothness: f64) -> DistanceField {
+        assert_eq!(
+            centers.len(),
+            radii.len(),
+            "every ball of a metaball needs a radius"
+        );
+
+        // each smooth union grows the balls by at most a quarter of the
+        // smoothness, and the unions add up over the balls
+        let unions = centers.len().saturating_sub(1) as f64;
+        let grow = unions * smoothness.max(0.0) / 4.0;
+        let bounds = centers
+            .iter()
+            .zip(&radii)
+            .map(|(c, r)| {
+                let r = Vector::new(r + grow, r + grow, r + grow);
+                Bounds::new(*c - r, *c + r)
+            })
+            .reduce(|a, b| a.merge(&b));
+
+        let mut field = DistanceField::new(move |p| {
+            centers
+                .iter()
+                .zip(&radii)
+                .map(|(c, r)| (p - *c).magnitude() - r)
+                .reduce(|a, b| smooth_union(a, b, smoothness))
+                .unwrap_or(f64::INFINITY)
+        });
+        field.bounds = bounds;
+        field
+    }
+}
+
+impl fmt::Debug for DistanceField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DistanceField")
+            .field("id", &self.id)
+            .field("parent_id", &self.parent_id)
+            .field("transform", &self.transform)
+            .field("material", &self.material)
+            .field("epsilon", &self.epsilon)
+            .field("max_steps", &self.max_steps)
+            .field("max_distance", &self.max_distance)
+            .field("bounds", &self.bounds)
+            .finish()
+    }
+}
+
+#[typetag::serde]
+impl Shape for DistanceField {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn parent_id(&self) -> Option<Uuid> {
+        self.parent_id
+    }
+
+    fn set_parent_id(&mut self, id: Uuid) {
+        self.parent_id = Some(id);
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+
+    fn bounds(&self) -> Bounds {
+        self.bounds.unwrap_or_else(Bounds::infinite)
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
+        let speed = ray.direction.magnitude();
+        let mut xs = Vec::new();
+        // whether the last step ended within `epsilon` of the surface, so a
+        // surface is only hit once however many steps it takes to cross it
+        let mut on_surface = false;
+        let mut t = 0.0;
+
+        for _ in 0..self.max_steps {
+            if t * speed > self.max_distance {
+                break;
+            }
+
+            let distance = (self.sdf)(ray.position(t)).abs();
+            if distance < self.epsilon {
+                if !on_surface {
+                    xs.push(Intersection::new(t, self));
+                }
+                on_surface = true;
+            } else {
+                on_surface = false;
+            }
+            t += distance.max(self.epsilon) / speed;
+        }
+
+        if xs.is_empty() {
+            None
+        } else {
+            Some(xs)
+        }
+    }
+
+    fn local_normal_at(&self, point: Point, _hit: Option<&Intersection>) -> Vector {
+        let gradient = |offset: Vector| (self.sdf)(point + offset) - (self.sdf)(point - offset);
+
+        Vector::new(
+            gradient(Vector::new(NORMAL_DELTA, 0.0, 0.0)),
+            gradient(Vector::new(0.0, NORMAL_DELTA, 0.0)),
+            gradient(Vector::new(0.0, 0.0, NORMAL_DELTA)),
+        )
+        .normalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shapes::{Sphere, Torus},
+        EPSILON,
+    };
+
+    fn ball(radius: f64) -> DistanceField {
+        DistanceField::new(move |p| (p - Point::new(0.0, 0.0, 0.0)).magnitude() - radius)
+    }
+
+    /// The distance function of a [`Torus`] with the same radii.
+    fn ring(major: f64, minor: f64) -> DistanceField {
+        DistanceField::new(move |p| {
+            let q = (p.x * p.x + p.z * p.z).sqrt() - major;
+            (q * q + p.y * p.y).sqrt() - minor
+        })
+    }
+
+    fn ts(xs: Option<Vec<Intersection>>) -> Vec<f64> {
+        xs.map_or(Vec::new(), |xs| xs.iter().map(|i| i.t).collect())
+    }
+
+    fn assert_same_hits(a: &dyn Shape, b: &dyn Shape, r: Ray) {
+        let (a, b) = (ts(a.intersect(r)), ts(b.intersect(r)));
+
+        assert_eq!(a.len(), b.len(), "{:?} {:?}", a, b);
+        for (a, b) in a.iter().zip(&b) {
+            assert!((a - b).abs() < EPSILON, "{} {}", a, b);
+        }
+    }
+
+    #[test]
+    fn a_sphere_field_matches_a_sphere() {
+        let field = ball(1.0);
+        let sphere = Sphere::new();
+
+        for r in [
+            Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+            Ray::new(Point::new(0.5, 0.3, -5.0), Vector::new(0.0, 0.0, 1.0)),
+            Ray::new(Point::new(-3.0, 2.0, -4.0), Vector::new(3.0, -2.0, 4.5)),
+            Ray::new(Point::new(0.0, 2.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+        ] {
+            assert_same_hits(&field, &sphere, r);
+        }
+    }
+
+    #[test]
+    fn a_torus_field_matches_a_torus() {
+        let field = ring(1.0, 0.25);
+        let torus = Torus::new();
+
+        for r in [
+            Ray::new(Point::new(0.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0)),
+            Ray::new(Point::new(1.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0)),
+            Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+        ] {
+            assert_same_hits(&field, &torus, r);
+        }
+    }
+
+    #[test]
+    fn the_normal_of_a_sphere_field() {
+        let field = ball(1.0);
+        let sphere = Sphere::new();
+        let k = 3.0_f64.sqrt() / 3.0;
+
+        for p in [Point::new(1.0, 0.0, 0.0), Point::new(k, k, k)] {
+            assert_eq!(
+                field.normal_at(p, None, None),
+                sphere.normal_at(p, None, None)
+            );
+        }
+    }
+
+    #[test]
+    fn a_field_read_from_a_file_has_no_surface() {
+        let field = ball(1.0);
+        let json = serde_json::to_string(&field).unwrap();
+        let de: DistanceField = serde_json::from_str(&json).unwrap();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert_eq!(de.id(), field.id());
+        assert!(de.local_intersect(r).is_none());
+    }
+
+    fn two_balls(smoothness: f64) -> DistanceField {
+        Metaball::new(
+            vec![Point::new(-0.6, 0.0, 0.0), Point::new(0.6, 0.0, 0.0)],
+            vec![0.5, 0.5],
+            smoothness,
+        )
+    }
+
+    #[test]
+    fn smooth_metaballs_merge_with_a_smooth_neck() {
+        let blob = two_balls(1.0);
+        let down = Vector::new(0.0, -1.0, 0.0);
+
+        // the balls do not touch, but the blob fills the gap between them
+        let neck = blob
+            .local_intersect(Ray::new(Point::new(0.0, 5.0, 0.0), down))
+            .expect("No intersections");
+        assert_eq!(neck.len(), 2);
+
+        let top = Ray::new(Point::new(0.0, 5.0, 0.0), down).position(neck[0].t);
+        assert_eq!(blob.normal_at(top, None, None), Vector::new(0.0, 1.0, 0.0));
+        // the normal turns gradually on either side of the middle of the neck
+        for x in [-0.05, 0.05] {
+            let r = Ray::new(Point::new(x, 5.0, 0.0), down);
+            let xs = blob.local_intersect(r).expect("No intersections");
+            let normal = blob.normal_at(r.position(xs[0].t), None, None);
+
+            assert!(normal.dot(Vector::new(0.0, 1.0, 0.0)) > 0.99);
+        }
+    }
+
+    #[test]
+    fn metaballs_without_smoothness_are_a_plain_union() {
+        let blob = two_balls(0.0);
+        let mut right = Sphere::new();
+        right.transform = crate::Transformation::new()
+            .scale(0.5, 0.5, 0.5)
+            .translate(0.6, 0.0, 0.0)
+            .build();
+        let down = Vector::new(0.0, -1.0, 0.0);
+
+        // nothing fills the gap between the balls
+        assert!(blob
+            .local_intersect(Ray::new(Point::new(0.0, 5.0, 0.0), down))
+            .is_none());
+
+        let r = Ray::new(Point::new(0.7, 5.0, 0.1), down);
+        assert_same_hits(&blob, &right, r);
+
+        let r = Ray::new(Point::new(-5.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        let xs = ts(blob.local_intersect(r));
+        assert_eq!(xs.len(), 4);
+        for (t, expected) in xs.iter().zip([3.9, 4.9, 5.1, 6.1]) {
+            assert!((t - expected).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn the_bounds_of_metaballs_hold_the_growth_of_every_union() {
+        let blob = Metaball::new(
+            vec![
+                Point::new(0.3, 0.0, 0.0),
+                Point::new(-0.15, 0.26, 0.0),
+                Point::new(-0.15, -0.26, 0.0),
+            ],
+            vec![1.0, 1.0, 1.0],
+            1.0,
+        );
+        let r = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, -1.0));
+
+        // the blob reaches past a single ball grown by a quarter of the
+        // smoothness
+        let xs = ts(blob.local_intersect(r));
+        assert!(5.0 - xs[0] > 1.28, "{:?}", xs);
+        assert!(blob.bounds().max.z >= 5.0 - xs[0]);
+    }
+}
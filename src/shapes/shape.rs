@@ -1,6 +1,7 @@
-use crate::{Intersection, Material, Matrix, Point, Ray, Vector, World};
+use crate::{Bounds, Intersection, Material, Matrix, Point, Ray, UvBounds, Vector, World};
 use std::{any::Any, fmt};
 
+use rand::RngCore;
 use typetag;
 use uuid::Uuid;
 
@@ -8,7 +9,7 @@ use uuid::Uuid;
 /// a graphical primitive. Abstraction of the implementation for a particular
 /// shape.
 #[typetag::serde(tag = "type")]
-pub trait Shape: Any + fmt::Debug {
+pub trait Shape: Any + CloneShape + fmt::Debug + Send + Sync {
     fn as_any(&self) -> Option<&dyn Any> {
         None
     }
@@ -36,6 +37,124 @@ pub trait Shape: Any + fmt::Debug {
         false
     }
 
+    /// The shapes directly contained by a container like a group.
+    fn children(&self) -> Vec<&dyn Shape> {
+        Vec::new()
+    }
+
+    /// Approximate number of bytes used by the shape, without the shapes it
+    /// contains.
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+
+    /// Texture coordinates `(u, v)` of a `point` on the surface in object
+    /// space, for shapes that have a texture mapping.
+    fn local_uv_at(&self, _point: Point) -> Option<(f64, f64)> {
+        None
+    }
+
+    /// The range of texture coordinates used by the surface, for planning
+    /// the regions of a [`crate::TextureAtlas`]. Shapes covering the whole
+    /// unit square, like spheres and cubes, keep the default.
+    fn uv_bounds(&self) -> UvBounds {
+        UvBounds::UNIT
+    }
+
+    /// A direction along the surface at `point` in object space, for shapes
+    /// with a natural direction to orient anisotropic materials along.
+    fn local_tangent_at(&self, _point: Point) -> Option<Vector> {
+        None
+    }
+
+    /// The `local_tangent_at` the `point` in world space, moved by the
+    /// transformation of the shape.
+    fn tangent_at(&self, point: Point) -> Option<Vector> {
+        let local = self.local_tangent_at(self.transform().inverse() * point)?;
+        Some((self.transform() * local).normalize())
+    }
+
+    /// The axis aligned box around the shape in object space. Shapes without
+    /// an end, like planes, keep the default infinite box.
+    fn bounds(&self) -> Bounds {
+        Bounds::infinite()
+    }
+
+    /// The box around the shape in the space of its parent, found by moving
+    /// its `bounds` with its transformation.
+    fn parent_space_bounds(&self) -> Bounds {
+        self.bounds().transform(self.transform())
+    }
+
+    /// Area of the surface of the shape in object space. Shapes without an
+    /// end, like planes, keep the default infinite area.
+    fn local_surface_area(&self) -> f64 {
+        f64::INFINITY
+    }
+
+    /// Area of the surface of the shape in the space of its parent.
+    ///
+    /// The `local_surface_area` is scaled by how much the transformation of
+    /// the shape grows areas, which is exact for moves, rotations and scaling
+    /// by the same amount along every axis. Scaling by different amounts
+    /// along the axes grows some parts of a surface more than others, so the
+    /// area is then only an estimate. Flat shapes and triangles replace it
+    /// with their exact area.
+    fn surface_area(&self) -> f64 {
+        let area = self.local_surface_area();
+        let t = self.transform();
+        let (x, y, z) = (
+            t * Vector::new(1.0, 0.0, 0.0),
+            t * Vector::new(0.0, 1.0, 0.0),
+            t * Vector::new(0.0, 0.0, 1.0),
+        );
+        let volume = x.dot(y.cross(z)).abs();
+        if area.is_infinite() {
+            area
+        } else {
+            area * volume.powf(2.0 / 3.0)
+        }
+    }
+
+    /// A random point on the surface of the shape in object space and the
+    /// unit normal pointing out of the surface there, for shapes that can be
+    /// used as the source of area lights.
+    ///
+    /// # Panics
+    ///
+    /// Shapes without an end, like planes, or without a way to pick points
+    /// on them keep the default, which panics.
+    fn local_sample_surface(&self, _rng: &mut dyn RngCore) -> (Point, Vector) {
+        panic!("the surface of {:?} can not be sampled", self)
+    }
+
+    /// A random point on the surface of the shape in the space of its parent
+    /// and the unit normal pointing out of the surface there, found by moving
+    /// the `local_sample_surface` with the transformation of the shape.
+    ///
+    /// The points are spread evenly over the surface as long as the
+    /// transformation scales by the same amount along every axis.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rand::{rngs::StdRng, SeedableRng};
+    /// use rustic_ray::{float_eq, shapes::{Shape, Sphere}, Point};
+    ///
+    /// let mut rng = StdRng::seed_from_u64(1);
+    /// let (point, normal) = Sphere::new().sample_surface(&mut rng);
+    ///
+    /// assert!(float_eq((point - Point::new(0.0, 0.0, 0.0)).magnitude(), 1.0));
+    /// assert!(float_eq(normal.magnitude(), 1.0));
+    /// ```
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> (Point, Vector) {
+        let (point, normal) = self.local_sample_surface(rng);
+        (
+            self.transform() * point,
+            (self.transform().inverse().transpose() * normal).normalize(),
+        )
+    }
+
     /// Gets the transformation [`Matrix`] for an object
     fn transform(&self) -> Matrix;
 
@@ -60,6 +179,10 @@ pub trait Shape: Any + fmt::Debug {
     /// intersection has the distances from the origin of the [`Ray`] and the
     /// object intersected. If there are no intersections
     /// then [`None`] is returned.
+    ///
+    /// The ray is in object space and its direction is generally not of unit
+    /// length, as made by [`Ray::new`], so implementations must not assume
+    /// it is.
     fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>>;
 
     /// Calculate a vector that points perpendicular to a surface at a give point
@@ -147,7 +270,26 @@ pub trait Shape: Any + fmt::Debug {
 
 impl PartialEq for dyn Shape {
     fn eq(&self, other: &Self) -> bool {
-        self.id() == other.id()
+        self.shape_eq(other)
+    }
+}
+
+/// Copying a shape behind a `Box<dyn Shape>`, implemented for every shape
+/// that is [`Clone`]. The copy keeps the `id` of the shape.
+pub trait CloneShape {
+    /// A copy of the shape, with copies of every shape it contains.
+    fn clone_box(&self) -> Box<dyn Shape>;
+}
+
+impl<T: Shape + Clone> CloneShape for T {
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Shape> {
+    fn clone(&self) -> Self {
+        self.clone_box()
     }
 }
 
@@ -158,10 +300,38 @@ mod tests {
     use super::*;
     use crate::{
         shapes::Group,
-        shapes::{Sphere, TestShape},
+        shapes::{Plane, Sphere, TestShape},
         Transformation, IDENTITY,
     };
 
+    #[test]
+    fn two_different_shapes_are_not_equal() {
+        let a = Sphere::new();
+        let b = Sphere::new();
+
+        assert!(!a.shape_eq(&b));
+        assert!(&a as &dyn Shape != &b as &dyn Shape);
+    }
+
+    #[test]
+    fn a_shape_is_equal_to_itself() {
+        let a = Sphere::new();
+
+        assert!(a.shape_eq(&a));
+        assert!(&a as &dyn Shape == &a as &dyn Shape);
+    }
+
+    #[test]
+    fn shapes_with_the_same_id_are_equal() {
+        let s = Sphere::new();
+        let mut value = serde_json::to_value(Plane::new()).unwrap();
+        value["id"] = serde_json::to_value(s.id()).unwrap();
+        let p: Plane = serde_json::from_value(value).unwrap();
+
+        assert!(s.shape_eq(&p));
+        assert!(&s as &dyn Shape == &p as &dyn Shape);
+    }
+
     // Chapter 9 Planes
     // page 119
     #[test]
@@ -0,0 +1,202 @@
+use std::sync::RwLock;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::Shape;
+use crate::DeserializeError;
+
+/// Reads a registered shape type from JSON.
+type ShapeDeserializer = fn(Value) -> Result<Box<dyn Shape>, serde_json::Error>;
+
+static REGISTRY: RwLock<Vec<(String, ShapeDeserializer)>> = RwLock::new(Vec::new());
+
+fn deserialize_as<S: Shape + DeserializeOwned>(
+    value: Value,
+) -> Result<Box<dyn Shape>, serde_json::Error> {
+    Ok(Box::new(serde_json::from_value::<S>(value)?))
+}
+
+fn parse_error(e: serde_json::Error) -> DeserializeError {
+    DeserializeError::ParseError {
+        line: None,
+        message: e.to_string(),
+    }
+}
+
+/// Shape types registered at run time, for shapes `typetag` cannot read back
+/// such as ones from a plugin implementing [`Shape`] with
+/// `#[typetag::serialize]`, which does not add the type to the ones
+/// `typetag` deserializes.
+///
+/// Loading a world, with [`crate::World::from_str`] or
+/// [`crate::World::deserialize_from_file`], falls back to the registry for
+/// the objects of the world whose `type` tag `typetag` does not know.
+/// Shapes held inside a group or another container are not looked up.
+pub struct ShapeRegistry;
+
+impl ShapeRegistry {
+    /// Read shapes tagged with `name` as `S`. Registering a name again
+    /// replaces the type it was registered with before.
+    pub fn register<S: Shape + DeserializeOwned>(name: &str) {
+        let mut registry = REGISTRY.write().unwrap();
+        registry.retain(|(n, _)| n != name);
+        registry.push((name.to_string(), deserialize_as::<S>));
+    }
+
+    /// Whether a shape type was registered with the `tag`.
+    pub fn is_registered(tag: &str) -> bool {
+        REGISTRY.read().unwrap().iter().any(|(n, _)| n == tag)
+    }
+
+    /// Read the shape `v` as the type registered with the `tag`.
+    pub fn deserialize(tag: &str, v: Value) -> Result<Box<dyn Shape>, DeserializeError> {
+        let deserializer = REGISTRY
+            .read()
+            .unwrap()
+            .iter()
+            .find(|(n, _)| n == tag)
+            .map(|(_, d)| *d)
+            .ok_or_else(|| DeserializeError::ParseError {
+                line: None,
+                message: format!("unknown shape type `{}`", tag),
+            })?;
+
+        deserializer(v).map_err(parse_error)
+    }
+}
+
+/// The shape as JSON, tagged with its type in the `type` field.
+///
+/// # Example
+///
+/// ```
+/// use rustic_ray::shapes::{deserialize_shape, serialize_shape, Shape, Sphere};
+///
+/// let sphere = Sphere::new();
+/// let value = serialize_shape(&sphere);
+///
+/// assert_eq!(value["type"], "Sphere");
+/// assert_eq!(deserialize_shape(value).unwrap().id(), sphere.id());
+/// ```
+pub fn serialize_shape(shape: &dyn Shape) -> Value {
+    serde_json::to_value(shape).expect("shapes serialize to JSON")
+}
+
+/// Read a shape written by [`serialize_shape`], with `typetag` or else with
+/// the type registered in the [`ShapeRegistry`] for its `type` tag.
+pub fn deserialize_shape(v: Value) -> Result<Box<dyn Shape>, DeserializeError> {
+    let tag = v.get("type").and_then(Value::as_str).map(str::to_string);
+
+    match tag {
+        Some(tag) if ShapeRegistry::is_registered(&tag) => ShapeRegistry::deserialize(&tag, v),
+        _ => serde_json::from_value(v).map_err(parse_error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bounds, Intersection, Material, Matrix, Point, Ray, Vector, World, IDENTITY};
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    /// A shape `typetag` can write but not read back, like one from a plugin.
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    struct PluginShape {
+        id: Uuid,
+        parent_id: Option<Uuid>,
+        transform: Matrix,
+        material: Material,
+        size: f64,
+    }
+
+    #[typetag::serialize]
+    impl Shape for PluginShape {
+        // only `#[typetag::serde]` adds it, it is never called
+        fn typetag_deserialize(&self) {}
+
+        fn id(&self) -> Uuid {
+            self.id
+        }
+
+        fn parent_id(&self) -> Option<Uuid> {
+            self.parent_id
+        }
+
+        fn set_parent_id(&mut self, id: Uuid) {
+            self.parent_id = Some(id);
+        }
+
+        fn transform(&self) -> Matrix {
+            self.transform
+        }
+
+        fn set_transform(&mut self, transform: Matrix) {
+            self.transform = transform;
+        }
+
+        fn material(&self) -> &Material {
+            &self.material
+        }
+
+        fn material_mut(&mut self) -> &mut Material {
+            &mut self.material
+        }
+
+        fn set_material(&mut self, material: Material) {
+            self.material = material;
+        }
+
+        fn bounds(&self) -> Bounds {
+            let s = self.size;
+            Bounds::new(Point::new(-s, -s, -s), Point::new(s, s, s))
+        }
+
+        fn local_intersect(&self, _ray: Ray) -> Option<Vec<Intersection>> {
+            None
+        }
+
+        fn local_normal_at(&self, point: Point, _hit: Option<&Intersection>) -> Vector {
+            Vector::new(point.x, point.y, point.z)
+        }
+    }
+
+    #[test]
+    fn a_registered_shape_is_read_back_in_a_world() {
+        let shape = PluginShape {
+            id: Uuid::new_v4(),
+            parent_id: None,
+            transform: IDENTITY,
+            material: Material::new(),
+            size: 2.5,
+        };
+        let id = shape.id;
+        let mut w = World::default();
+        w.add_object(Box::new(shape));
+        let json = serde_json::to_string(&w).unwrap();
+
+        assert!(serde_json::from_str::<World>(&json).is_err());
+
+        ShapeRegistry::register::<PluginShape>("PluginShape");
+        let de = World::from_str(&json);
+
+        assert_eq!(de.count_objects(), 3);
+        assert_eq!(de.get_object(2).unwrap().id(), id);
+        assert_eq!(
+            de.get_object(2).unwrap().bounds().max,
+            Point::new(2.5, 2.5, 2.5)
+        );
+        assert_eq!(
+            de.get_object(0).unwrap().id(),
+            w.get_object(0).unwrap().id()
+        );
+    }
+
+    #[test]
+    fn an_unregistered_tag_is_an_error() {
+        let result = ShapeRegistry::deserialize("NoSuchShape", Value::Null);
+
+        assert!(matches!(result, Err(DeserializeError::ParseError { .. })));
+    }
+}
@@ -0,0 +1,193 @@
+use super::{PlaneOrientation, Shape};
+#[allow(unused_imports)]
+use crate::Transformation;
+use crate::{Intersection, Material, Matrix, Point, Ray, Vector, EPSILON, IDENTITY};
+use serde::{Deserialize, Serialize};
+use typetag;
+use uuid::Uuid;
+
+/// The infinite volume between two parallel axis-aligned planes.
+///
+/// The bounding planes share the [`PlaneOrientation`] of the `Slab` and sit at
+/// `minimum` and `maximum` along the axis perpendicular to them. Intersecting
+/// three slabs with a [`super::CSG`] gives a box.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Slab {
+    id: Uuid,
+    parent_id: Option<Uuid>,
+    /// [`Transformation`] matrix used to manipulate the `Slab`
+    pub transform: Matrix,
+    /// [`Material`] describing the look of the `Slab`
+    pub material: Material,
+    /// The two axes the bounding planes extend along in object space
+    pub orientation: PlaneOrientation,
+    /// Position of the lower bounding plane along the perpendicular axis
+    pub minimum: f64,
+    /// Position of the upper bounding plane along the perpendicular axis
+    pub maximum: f64,
+}
+
+impl Slab {
+    /// Create a new `Slab` between the planes `y = -1` and `y = 1`.
+    pub fn new() -> Self {
+        Slab {
+            id: Uuid::new_v4(),
+            parent_id: None,
+            transform: IDENTITY,
+            material: Material::new(),
+            orientation: PlaneOrientation::Xz,
+            minimum: -1.0,
+            maximum: 1.0,
+        }
+    }
+
+    /// Create a new `Slab` with the given `orientation` between `minimum` and
+    /// `maximum`.
+    pub fn with_bounds(orientation: PlaneOrientation, minimum: f64, maximum: f64) -> Self {
+        let mut s = Self::new();
+        s.orientation = orientation;
+        s.minimum = minimum;
+        s.maximum = maximum;
+        s
+    }
+
+    /// The unit vector perpendicular to the bounding planes.
+    fn axis(&self) -> Vector {
+        match self.orientation {
+            PlaneOrientation::Xz => Vector::new(0.0, 1.0, 0.0),
+            PlaneOrientation::Xy => Vector::new(0.0, 0.0, 1.0),
+            PlaneOrientation::Yz => Vector::new(1.0, 0.0, 0.0),
+        }
+    }
+}
+
+impl Default for Slab {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[typetag::serde]
+impl Shape for Slab {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn parent_id(&self) -> Option<Uuid> {
+        self.parent_id
+    }
+
+    fn set_parent_id(&mut self, id: Uuid) {
+        self.parent_id = Some(id);
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
+        let axis = self.axis();
+        let direction = ray.direction.dot(axis);
+
+        if direction.abs() < EPSILON {
+            return None;
+        }
+
+        let origin = (ray.origin - Point::new(0.0, 0.0, 0.0)).dot(axis);
+        let t0 = (self.minimum - origin) / direction;
+        let t1 = (self.maximum - origin) / direction;
+
+        Some(vec![
+            Intersection::new(t0.min(t1), self),
+            Intersection::new(t0.max(t1), self),
+        ])
+    }
+
+    fn local_normal_at(&self, point: Point, _hit: Option<&Intersection>) -> Vector {
+        let axis = self.axis();
+        let position = (point - Point::new(0.0, 0.0, 0.0)).dot(axis);
+
+        if (position - self.minimum).abs() < (position - self.maximum).abs() {
+            -axis
+        } else {
+            axis
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::{CsgOperation, CSG};
+
+    #[test]
+    fn a_ray_perpendicular_to_the_bounding_planes() {
+        let s = Slab::new();
+        let r = Ray::new(Point::new(0.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let xs = s.local_intersect(r).expect("No intersections");
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.0);
+        assert_eq!(xs[1].t, 6.0);
+    }
+
+    #[test]
+    fn a_ray_parallel_to_the_slab() {
+        let s = Slab::with_bounds(PlaneOrientation::Yz, -2.0, 3.0);
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert!(s.local_intersect(r).is_none());
+    }
+
+    #[test]
+    fn the_normal_points_away_from_the_slab() {
+        let s = Slab::with_bounds(PlaneOrientation::Xy, -2.0, 3.0);
+
+        assert_eq!(
+            s.local_normal_at(Point::new(1.0, 1.0, 3.0), None),
+            Vector::new(0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            s.local_normal_at(Point::new(1.0, 1.0, -2.0), None),
+            Vector::new(0.0, 0.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn intersecting_three_slabs_gives_a_box() {
+        let xy = CSG::new(
+            CsgOperation::Intersection,
+            Box::new(Slab::with_bounds(PlaneOrientation::Yz, -1.0, 1.0)),
+            Box::new(Slab::with_bounds(PlaneOrientation::Xz, -1.0, 1.0)),
+        );
+        let c = CSG::new(
+            CsgOperation::Intersection,
+            Box::new(xy),
+            Box::new(Slab::with_bounds(PlaneOrientation::Xy, -1.0, 1.0)),
+        );
+        // the ray is inside the x slab for t in [3, 5], the y slab for
+        // t in [2.5, 4.5] and the z slab for t in [2, 4]
+        let r = Ray::new(Point::new(-4.0, -3.5, -3.0), Vector::new(1.0, 1.0, 1.0));
+        let xs = c.local_intersect(r).expect("No intersections");
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 3.0);
+        assert_eq!(xs[1].t, 4.0);
+    }
+}
@@ -1,15 +1,17 @@
-use super::Shape;
-#[allow(unused_imports)]
+use super::{displacement, Shape};
+use crate::sampling::random_unit_vector;
 use crate::Transformation;
-use crate::{Intersection, Material, Matrix, Point, Ray, Vector, IDENTITY};
+use crate::{Bounds, Intersection, Material, Matrix, Point, Ray, Vector, EPSILON, IDENTITY};
+use rand::{rngs::StdRng, seq::SliceRandom, RngCore, SeedableRng};
 use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
 use typetag;
 use uuid::Uuid;
 
 /// A sphere is a three-dimensional solid figure which is perfectly round in
 /// shape and every point on its surface is equidistant from the point
 /// of the origin.
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Sphere {
     id: Uuid,
     parent_id: Option<Uuid>,
@@ -20,6 +22,12 @@ pub struct Sphere {
     pub inherit_material: bool,
 }
 
+/// Distance of the object space `point` from the surface of the unit sphere,
+/// positive outside.
+fn sphere_height(point: Point) -> f64 {
+    (point - Point::new(0.0, 0.0, 0.0)).magnitude() - 1.0
+}
+
 impl Sphere {
     /// Create a new `Sphere`.
     pub fn new() -> Self {
@@ -45,6 +53,167 @@ impl Sphere {
             inherit_material: false,
         }
     }
+
+    /// Map a `point` on the surface of the sphere in object space to texture
+    /// coordinates using a spherical projection. `v` goes from 0 at the
+    /// bottom pole to 1 at the top pole and `u` wraps around the `y` axis
+    /// with the seam, where `u` jumps from 1 back to 0, facing `-z`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::Sphere, Point};
+    ///
+    /// let s = Sphere::new();
+    ///
+    /// assert_eq!(s.uv_at(Point::new(0.0, 0.0, 1.0)), (0.5, 0.5));
+    /// assert_eq!(s.uv_at(Point::new(0.0, 1.0, 0.0)).1, 1.0);
+    /// ```
+    pub fn uv_at(&self, point: Point) -> (f64, f64) {
+        spherical_map(point)
+    }
+
+    /// Create the smallest sphere containing all of the `points`, found with
+    /// Welzl's algorithm. The unit sphere is scaled to the radius and moved
+    /// to the center, so a single point gives a sphere scaled to nothing.
+    ///
+    /// Useful as a bounding volume around a point cloud or the vertices of a
+    /// mesh.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `points` is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::{Shape, Sphere}, Point};
+    ///
+    /// let s = Sphere::unit_sphere_approximation(&[
+    ///     Point::new(-1.0, 2.0, 0.0),
+    ///     Point::new(3.0, 2.0, 0.0),
+    /// ]);
+    ///
+    /// assert_eq!(s.parent_space_bounds().min, Point::new(-1.0, 0.0, -2.0));
+    /// assert_eq!(s.parent_space_bounds().max, Point::new(3.0, 4.0, 2.0));
+    /// ```
+    pub fn unit_sphere_approximation(points: &[Point]) -> Sphere {
+        assert!(!points.is_empty(), "no points to fit a sphere to");
+
+        // in a random order the algorithm runs in expected linear time
+        let mut points = points.to_vec();
+        points.shuffle(&mut StdRng::seed_from_u64(0));
+
+        let mut ball = (points[0], 0.0);
+        for i in 1..points.len() {
+            if contains(ball, points[i]) {
+                continue;
+            }
+            ball = (points[i], 0.0);
+            for j in 0..i {
+                if contains(ball, points[j]) {
+                    continue;
+                }
+                ball = ball_through_two(points[i], points[j]);
+                for k in 0..j {
+                    if contains(ball, points[k]) {
+                        continue;
+                    }
+                    ball = ball_through_three(points[i], points[j], points[k]);
+                    for l in 0..k {
+                        if !contains(ball, points[l]) {
+                            ball = ball_through_four(points[i], points[j], points[k], points[l]);
+                        }
+                    }
+                }
+            }
+        }
+
+        let (center, radius) = ball;
+        let mut sphere = Sphere::new();
+        sphere.transform = Transformation::new()
+            .scale(radius, radius, radius)
+            .translate(center.x, center.y, center.z)
+            .build();
+        sphere
+    }
+}
+
+/// A ball as its center and radius.
+type Ball = (Point, f64);
+
+fn contains((center, radius): Ball, point: Point) -> bool {
+    (point - center).magnitude() <= radius + EPSILON
+}
+
+/// The smallest ball with `a` and `b` on its surface.
+fn ball_through_two(a: Point, b: Point) -> Ball {
+    let center = a + (b - a) * 0.5;
+    (center, (b - a).magnitude() / 2.0)
+}
+
+/// The smallest ball with `a`, `b` and `c` on its surface, centered on the
+/// circle through them.
+fn ball_through_three(a: Point, b: Point, c: Point) -> Ball {
+    let (ab, ac) = (b - a, c - a);
+    let normal = ab.cross(ac);
+    let denominator = 2.0 * normal.dot(normal);
+    if denominator < EPSILON * EPSILON {
+        // in a line, the two points furthest apart span the ball
+        let balls = [
+            ball_through_two(a, b),
+            ball_through_two(a, c),
+            ball_through_two(b, c),
+        ];
+        return balls.iter().copied().fold(
+            (a, 0.0),
+            |best, ball| if ball.1 > best.1 { ball } else { best },
+        );
+    }
+
+    let offset = (normal.cross(ab) * ac.dot(ac) + ac.cross(normal) * ab.dot(ab)) / denominator;
+    (a + offset, offset.magnitude())
+}
+
+/// The smallest ball with `a`, `b`, `c` and `d` on its surface.
+fn ball_through_four(a: Point, b: Point, c: Point, d: Point) -> Ball {
+    let (u, v, w) = (b - a, c - a, d - a);
+    let denominator = 2.0 * u.dot(v.cross(w));
+    if denominator.abs() < EPSILON * EPSILON {
+        // in a plane, the smallest circle through three of them containing
+        // the fourth
+        return [(a, b, c, d), (a, b, d, c), (a, c, d, b), (b, c, d, a)]
+            .iter()
+            .copied()
+            .map(|(p, q, r, rest)| (ball_through_three(p, q, r), rest))
+            .filter(|&(ball, rest)| contains(ball, rest))
+            .map(|(ball, _)| ball)
+            .fold((a, f64::INFINITY), |best, ball| {
+                if ball.1 < best.1 {
+                    ball
+                } else {
+                    best
+                }
+            });
+    }
+
+    let offset =
+        (v.cross(w) * u.dot(u) + w.cross(u) * v.dot(v) + u.cross(v) * w.dot(w)) / denominator;
+    (a + offset, offset.magnitude())
+}
+
+/// Spherical projection of `point` to texture coordinates, see
+/// [`Sphere::uv_at`]. Points off the unit sphere are projected from the
+/// origin.
+pub(crate) fn spherical_map(point: Point) -> (f64, f64) {
+    let radius = (point - Point::new(0.0, 0.0, 0.0)).magnitude();
+    let theta = point.x.atan2(point.z);
+    let phi = (point.y / radius).clamp(-1.0, 1.0).acos();
+
+    let u = 1.0 - (theta / (2.0 * PI) + 0.5);
+    let v = 1.0 - phi / PI;
+
+    (u, v)
 }
 
 impl Default for Sphere {
@@ -87,7 +256,38 @@ impl Shape for Sphere {
         self.material = material;
     }
 
+    fn bounds(&self) -> Bounds {
+        // how far a displacement map moves the surface is not known
+        if self.material.displacement_map.is_some() {
+            return Bounds::infinite();
+        }
+        Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+
+    fn local_surface_area(&self) -> f64 {
+        4.0 * PI
+    }
+
+    fn local_sample_surface(&self, rng: &mut dyn RngCore) -> (Point, Vector) {
+        let normal = random_unit_vector(rng);
+        (Point::new(0.0, 0.0, 0.0) + normal, normal)
+    }
+
+    // the tangent runs around the y axis, along the lines of latitude.
+    fn local_tangent_at(&self, point: Point) -> Option<Vector> {
+        let around = Vector::new(-point.z, 0.0, point.x);
+        if around.magnitude() < EPSILON {
+            Some(Vector::new(1.0, 0.0, 0.0))
+        } else {
+            Some(around.normalize())
+        }
+    }
+
     fn local_intersect(&self, r: Ray) -> Option<Vec<Intersection>> {
+        if let Some(map) = &self.material.displacement_map {
+            return displacement::intersect(self, map.as_ref(), r, sphere_height);
+        }
+
         let mut xs: Vec<Intersection> = Vec::new();
 
         let sphere_to_ray = r.origin - Point::new(0.0, 0.0, 0.0);
@@ -99,10 +299,12 @@ impl Shape for Sphere {
         let discriminant = b.powi(2) - 4.0 * a * c;
 
         if discriminant >= 0.0 {
-            let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
-            xs.push(Intersection::new(t1, self));
-            let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
-            xs.push(Intersection::new(t2, self));
+            for t in [
+                (-b - discriminant.sqrt()) / (2.0 * a),
+                (-b + discriminant.sqrt()) / (2.0 * a),
+            ] {
+                xs.push(Intersection::new(t, self));
+            }
         }
 
         if !xs.is_empty() {
@@ -113,7 +315,14 @@ impl Shape for Sphere {
     }
 
     fn local_normal_at(&self, object_point: Point, _hit: Option<&Intersection>) -> Vector {
-        object_point - Point::new(0.0, 0.0, 0.0)
+        match &self.material.displacement_map {
+            Some(map) => displacement::normal(map.as_ref(), object_point, sphere_height),
+            None => object_point - Point::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    fn local_uv_at(&self, point: Point) -> Option<(f64, f64)> {
+        Some(self.uv_at(point))
     }
 
     fn inherit_material(&self) -> bool {
@@ -124,8 +333,7 @@ impl Shape for Sphere {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Transformation, Vector};
-    use std::f64::consts::PI;
+    use crate::{float_eq, patterns::SolidColor, Color, Transformation, Vector, EPSILON};
 
     // Chapter 5 Ray-Sphere Intersections
     // Page 59
@@ -319,4 +527,172 @@ mod tests {
         assert_eq!(s.material.transparency, 1.0);
         assert_eq!(s.material.refractive_index, 1.5);
     }
+
+    #[test]
+    fn the_poles_map_to_the_top_and_bottom_of_the_texture() {
+        let s = Sphere::new();
+
+        assert_eq!(s.uv_at(Point::new(0.0, 1.0, 0.0)).1, 1.0);
+        assert_eq!(s.uv_at(Point::new(0.0, -1.0, 0.0)).1, 0.0);
+    }
+
+    #[test]
+    fn the_seam_of_the_texture_faces_away() {
+        let s = Sphere::new();
+        let (u, _) = s.uv_at(Point::new(0.0, 0.0, -1.0));
+        let (before, _) = s.uv_at(Point::new(-EPSILON, 0.0, -1.0));
+        let (after, _) = s.uv_at(Point::new(EPSILON, 0.0, -1.0));
+
+        assert_eq!(u, 0.0);
+        assert!(before > 1.0 - EPSILON);
+        assert!(after < EPSILON);
+    }
+
+    #[test]
+    fn the_texture_coordinates_of_intersections_with_a_sphere() {
+        let s = Sphere::new();
+        let r = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, -1.0));
+        let xs = s.local_intersect(r).unwrap();
+
+        // only found when a pattern needs them
+        assert_eq!((xs[0].u, xs[0].v), (None, None));
+        assert_eq!(s.local_uv_at(r.position(xs[0].t)), Some((0.5, 0.5)));
+        assert_eq!(s.local_uv_at(r.position(xs[1].t)), Some((0.0, 0.5)));
+    }
+
+    #[test]
+    fn the_tangent_of_a_sphere_runs_along_the_surface() {
+        let mut s = Sphere::new();
+        s.transform = Transformation::new()
+            .rotate_z(std::f64::consts::PI / 2.0)
+            .build();
+        let p = Point::new(0.0, 0.6, 0.8);
+        let t = s.tangent_at(p).expect("Spheres have a tangent");
+
+        assert!(float_eq(t.dot(s.normal_at(p, None, None)), 0.0));
+        assert!(float_eq(t.magnitude(), 1.0));
+        assert_eq!(
+            Sphere::new().local_tangent_at(Point::new(0.0, 1.0, 0.0)),
+            Some(Vector::new(1.0, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn the_surface_area_of_a_sphere() {
+        let mut s = Sphere::new();
+        assert!(float_eq(s.surface_area(), 4.0 * PI));
+
+        s.transform = Transformation::new()
+            .scale(2.0, 2.0, 2.0)
+            .translate(3.0, 0.0, 0.0)
+            .build();
+        assert!(float_eq(s.surface_area(), 16.0 * PI));
+    }
+
+    #[test]
+    fn points_sampled_on_a_sphere_lie_on_its_surface() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut s = Sphere::new();
+        s.transform = Transformation::new()
+            .scale(2.0, 2.0, 2.0)
+            .translate(1.0, -3.0, 0.5)
+            .build();
+        let center = Point::new(1.0, -3.0, 0.5);
+        let mut rng = StdRng::seed_from_u64(5);
+
+        for _ in 0..100 {
+            let (p, n) = s.sample_surface(&mut rng);
+            // x² + y² + z² - r² = 0 around the center
+            assert!(float_eq((p - center).magnitude().powi(2) - 4.0, 0.0));
+            assert!(float_eq(n.magnitude(), 1.0));
+            assert!(n.dot(p - center) > 0.0);
+        }
+    }
+
+    #[test]
+    fn a_displaced_sphere_is_found_by_marching() {
+        let mut s = Sphere::new();
+        let lift = Color::new(0.5, 0.5, 0.5);
+        s.material = Material::new().displacement_map(Box::new(SolidColor::new(lift)));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = s.intersect(r).unwrap();
+
+        assert_eq!(xs.len(), 2);
+        assert!(float_eq(xs[0].t, 3.5));
+        assert!(float_eq(xs[1].t, 6.5));
+        let n = s.normal_at(Point::new(0.0, 1.5, 0.0), None, None);
+        assert!(float_eq(n.y, 1.0));
+    }
+
+    /// The center and radius of a sphere made by `unit_sphere_approximation`.
+    fn center_and_radius(s: &Sphere) -> (Point, f64) {
+        let m = s.transform;
+        (Point::new(m[0][3], m[1][3], m[2][3]), m[0][0])
+    }
+
+    #[test]
+    fn a_fitted_sphere_contains_all_of_the_points() {
+        use rand::Rng;
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let points: Vec<Point> = (0..500)
+            .map(|_| {
+                Point::new(
+                    rng.gen_range(-2.0..3.0),
+                    rng.gen_range(0.0..1.0),
+                    rng.gen_range(-5.0..5.0),
+                )
+            })
+            .collect();
+
+        let s = Sphere::unit_sphere_approximation(&points);
+        let (center, radius) = center_and_radius(&s);
+
+        for p in &points {
+            assert!((*p - center).magnitude() <= radius + EPSILON);
+        }
+        // at least two of the points are on the surface
+        let on_surface = points
+            .iter()
+            .filter(|p| ((**p - center).magnitude() - radius).abs() < EPSILON)
+            .count();
+        assert!(on_surface >= 2);
+    }
+
+    #[test]
+    fn a_sphere_fitted_to_a_single_point_has_no_radius() {
+        let s = Sphere::unit_sphere_approximation(&[Point::new(1.0, 2.0, 3.0)]);
+
+        assert_eq!(center_and_radius(&s), (Point::new(1.0, 2.0, 3.0), 0.0));
+    }
+
+    #[test]
+    fn the_sphere_around_a_regular_tetrahedron() {
+        let points = [
+            Point::new(1.0, 1.0, 1.0),
+            Point::new(1.0, -1.0, -1.0),
+            Point::new(-1.0, 1.0, -1.0),
+            Point::new(-1.0, -1.0, 1.0),
+        ];
+        let edge = (points[1] - points[0]).magnitude();
+
+        let (center, radius) = center_and_radius(&Sphere::unit_sphere_approximation(&points));
+
+        assert_eq!(center, Point::new(0.0, 0.0, 0.0));
+        assert!(float_eq(radius, edge * (3.0_f64 / 8.0).sqrt()));
+    }
+
+    #[test]
+    fn the_fitted_sphere_is_not_pulled_toward_clustered_points() {
+        // the mean of the points is close to the origin, but the smallest
+        // sphere is centered between the two ends
+        let mut points = vec![Point::new(0.0, 0.0, 0.0); 20];
+        points.push(Point::new(10.0, 0.0, 0.0));
+
+        let (center, radius) = center_and_radius(&Sphere::unit_sphere_approximation(&points));
+
+        assert_eq!(center, Point::new(5.0, 0.0, 0.0));
+        assert!(float_eq(radius, 5.0));
+    }
 }
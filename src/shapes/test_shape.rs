@@ -1,7 +1,7 @@
 #[cfg(test)]
 use super::Shape;
 #[cfg(test)]
-use crate::{Intersection, Material, Matrix, Point, Ray, Vector, IDENTITY};
+use crate::{Bounds, Intersection, Material, Matrix, Point, Ray, Vector, IDENTITY};
 #[cfg(test)]
 use serde::{Deserialize, Serialize};
 #[cfg(test)]
@@ -9,7 +9,7 @@ use typetag;
 #[cfg(test)]
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[cfg(test)]
 pub struct TestShape {
     id: Uuid,
@@ -65,6 +65,10 @@ impl Shape for TestShape {
         self.material = material;
     }
 
+    fn bounds(&self) -> Bounds {
+        Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+
     fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
         let t = ray.origin.x
             + ray.origin.y
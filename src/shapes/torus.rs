@@ -0,0 +1,293 @@
+use super::Shape;
+#[allow(unused_imports)]
+use crate::Transformation;
+use crate::{Bounds, Intersection, Material, Matrix, Point, Ray, Vector, EPSILON, IDENTITY};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use typetag;
+use uuid::Uuid;
+
+/// A ring shaped surface made by sweeping a circle of `minor_radius` around
+/// the `y` axis at a distance of `major_radius` from the origin.
+///
+/// The surface is the set of points satisfying
+/// `(sqrt(x² + z²) - R)² + y² - r² = 0` where `R` is the `major_radius` and
+/// `r` is the `minor_radius`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Torus {
+    id: Uuid,
+    parent_id: Option<Uuid>,
+    /// [`Transformation`] matrix used to manipulate the `Torus`
+    pub transform: Matrix,
+    /// [`Material`] describing the look of the `Torus`
+    pub material: Material,
+    /// Distance from the origin to the center of the tube
+    pub major_radius: f64,
+    /// Radius of the tube
+    pub minor_radius: f64,
+}
+
+impl Torus {
+    /// Create a new `Torus` with a major radius of 1 and a minor radius of
+    /// 0.25.
+    pub fn new() -> Self {
+        Torus {
+            id: Uuid::new_v4(),
+            parent_id: None,
+            transform: IDENTITY,
+            material: Material::new(),
+            major_radius: 1.0,
+            minor_radius: 0.25,
+        }
+    }
+}
+
+impl Default for Torus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Evaluate the polynomial with the `coefficients` ordered from the highest
+/// degree to the constant term at `x`.
+fn evaluate(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().fold(0.0, |acc, c| acc * x + c)
+}
+
+/// Coefficients of the derivative of the polynomial with the `coefficients`
+/// ordered from the highest degree to the constant term.
+fn derivative(coefficients: &[f64]) -> Vec<f64> {
+    let degree = coefficients.len() - 1;
+    coefficients[..degree]
+        .iter()
+        .enumerate()
+        .map(|(i, c)| c * (degree - i) as f64)
+        .collect()
+}
+
+/// Find the real roots of the polynomial with the `coefficients` ordered from
+/// the highest degree to the constant term. The roots of the derivative split
+/// the real line into intervals where the polynomial is monotonic, so each
+/// interval holds at most one root which is found by bisection. Roots where
+/// the polynomial only touches zero, tangent hits, are found at the roots of
+/// the derivative.
+fn solve_polynomial(coefficients: &[f64]) -> Vec<f64> {
+    let degree = coefficients.len() - 1;
+    let lead = coefficients[0];
+
+    if degree == 0 {
+        return Vec::new();
+    }
+    if lead.abs() < f64::EPSILON {
+        return solve_polynomial(&coefficients[1..]);
+    }
+    if degree == 1 {
+        return vec![-coefficients[1] / lead];
+    }
+
+    // every root lies within the Cauchy bound
+    let bound = 1.0
+        + coefficients[1..]
+            .iter()
+            .map(|c| (c / lead).abs())
+            .fold(0.0, f64::max);
+
+    let mut points = vec![-bound];
+    for c in solve_polynomial(&derivative(coefficients)) {
+        if c > -bound && c < bound {
+            points.push(c);
+        }
+    }
+    points.push(bound);
+
+    let mut roots: Vec<f64> = Vec::new();
+    for w in points.windows(2) {
+        let (mut lo, mut hi) = (w[0], w[1]);
+        let (f_lo, f_hi) = (evaluate(coefficients, lo), evaluate(coefficients, hi));
+
+        if f_lo.signum() == f_hi.signum() {
+            continue;
+        }
+
+        for _ in 0..200 {
+            let mid = (lo + hi) / 2.0;
+            if evaluate(coefficients, mid).signum() == f_lo.signum() {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        roots.push((lo + hi) / 2.0);
+    }
+
+    for c in &points[1..points.len() - 1] {
+        let touches = evaluate(coefficients, *c).abs() < EPSILON * lead.abs();
+        if touches && roots.iter().all(|r| (r - c).abs() > EPSILON) {
+            roots.push(*c);
+        }
+    }
+
+    roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    roots
+}
+
+#[typetag::serde]
+impl Shape for Torus {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn parent_id(&self) -> Option<Uuid> {
+        self.parent_id
+    }
+
+    fn set_parent_id(&mut self, id: Uuid) {
+        self.parent_id = Some(id);
+    }
+
+    fn transform(&self) -> Matrix {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix) {
+        self.transform = transform;
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+
+    fn bounds(&self) -> Bounds {
+        let outer = self.major_radius + self.minor_radius;
+        Bounds::new(
+            Point::new(-outer, -self.minor_radius, -outer),
+            Point::new(outer, self.minor_radius, outer),
+        )
+    }
+
+    fn local_surface_area(&self) -> f64 {
+        4.0 * PI * PI * self.major_radius * self.minor_radius
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
+        // Substituting the ray into
+        // (x² + y² + z² + R² - r²)² - 4R²(x² + z²) = 0
+        // gives a quartic polynomial in t.
+        let o = ray.origin - Point::new(0.0, 0.0, 0.0);
+        let d = ray.direction;
+        let r2 = self.major_radius.powi(2);
+
+        let a = d.dot(d);
+        let b = 2.0 * o.dot(d);
+        let c = o.dot(o) + r2 - self.minor_radius.powi(2);
+
+        let coefficients = [
+            a * a,
+            2.0 * a * b,
+            b * b + 2.0 * a * c - 4.0 * r2 * (d.x * d.x + d.z * d.z),
+            2.0 * b * c - 8.0 * r2 * (o.x * d.x + o.z * d.z),
+            c * c - 4.0 * r2 * (o.x * o.x + o.z * o.z),
+        ];
+
+        let xs: Vec<Intersection> = solve_polynomial(&coefficients)
+            .into_iter()
+            .map(|t| Intersection::new(t, self))
+            .collect();
+
+        if xs.is_empty() {
+            None
+        } else {
+            Some(xs)
+        }
+    }
+
+    fn local_normal_at(&self, point: Point, _hit: Option<&Intersection>) -> Vector {
+        let rho = (point.x.powi(2) + point.z.powi(2)).sqrt();
+        let k = (rho - self.major_radius) / rho;
+
+        Vector::new(point.x * k, point.y, point.z * k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solving_a_quadratic() {
+        // (x - 1)(x - 3)
+        let roots = solve_polynomial(&[1.0, -4.0, 3.0]);
+
+        assert_eq!(roots.len(), 2);
+        assert!((roots[0] - 1.0).abs() < EPSILON);
+        assert!((roots[1] - 3.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn a_ray_through_the_hole_of_a_torus() {
+        let t = Torus::new();
+        let r = Ray::new(Point::new(0.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+
+        assert!(t.local_intersect(r).is_none());
+    }
+
+    #[test]
+    fn a_ray_through_the_body_of_a_torus() {
+        let t = Torus::new();
+        let r = Ray::new(Point::new(1.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let xs = t.local_intersect(r).expect("No intersections");
+
+        assert_eq!(xs.len(), 2);
+        assert!((xs[0].t - 4.75).abs() < EPSILON);
+        assert!((xs[1].t - 5.25).abs() < EPSILON);
+    }
+
+    #[test]
+    fn a_ray_through_both_sides_of_a_torus() {
+        let t = Torus::new();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = t.local_intersect(r).expect("No intersections");
+
+        assert_eq!(xs.len(), 4);
+        for (i, expected) in [3.75, 4.25, 5.75, 6.25].iter().enumerate() {
+            assert!((xs[i].t - expected).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn a_ray_grazing_the_inner_equator() {
+        let t = Torus::new();
+        let r = Ray::new(Point::new(-5.0, 0.0, 0.75), Vector::new(1.0, 0.0, 0.0));
+        let xs = t.local_intersect(r).expect("No intersections");
+
+        assert!(xs.len() == 2 || xs.len() == 4);
+        assert!((xs[0].t - 4.0).abs() < EPSILON);
+        assert!((xs[xs.len() - 1].t - 6.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn the_normal_on_a_torus() {
+        let t = Torus::new();
+
+        assert_eq!(
+            t.normal_at(Point::new(1.25, 0.0, 0.0), None, None),
+            Vector::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            t.normal_at(Point::new(0.0, 0.0, -0.75), None, None),
+            Vector::new(0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            t.normal_at(Point::new(1.0, 0.25, 0.0), None, None),
+            Vector::new(0.0, 1.0, 0.0)
+        );
+    }
+}
@@ -1,13 +1,16 @@
 use std::any::Any;
 
-use crate::{Intersection, Material, Matrix, Point, Ray, Vector, EPSILON, IDENTITY};
+use crate::{
+    Bounds, Intersection, Material, Matrix, Point, Ray, UvBounds, Vector, EPSILON, IDENTITY,
+};
+use rand::{Rng, RngCore};
 use serde::{Deserialize, Serialize};
 use typetag;
 use uuid::Uuid;
 
 use super::Shape;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Triangle {
     id: Uuid,
     parent_id: Option<Uuid>,
@@ -19,6 +22,9 @@ pub struct Triangle {
     pub n1: Option<Vector>,
     pub n2: Option<Vector>,
     pub n3: Option<Vector>,
+    /// Texture coordinates at `p1`, `p2` and `p3`, see [`Triangle::with_uvs`].
+    #[serde(default)]
+    pub uvs: Option<[(f64, f64); 3]>,
     e1: Vector,
     e2: Vector,
     normal: Vector,
@@ -38,6 +44,7 @@ impl Triangle {
             n1: None,
             n2: None,
             n3: None,
+            uvs: None,
             e1: p2 - p1,
             e2: p3 - p1,
             normal: (p3 - p1).cross(p2 - p1).normalize(),
@@ -64,12 +71,35 @@ impl Triangle {
             n1: Some(n1),
             n2: Some(n2),
             n3: Some(n3),
+            uvs: None,
             e1: p2 - p1,
             e2: p3 - p1,
             normal: (p3 - p1).cross(p2 - p1).normalize(),
             smooth_triangle: true,
         }
     }
+
+    /// Set the texture coordinates at the corners `p1`, `p2` and `p3`,
+    /// spread over the surface in between.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::{Shape, Triangle}, Point};
+    ///
+    /// let t = Triangle::new(
+    ///     Point::new(0.0, 1.0, 0.0),
+    ///     Point::new(-1.0, 0.0, 0.0),
+    ///     Point::new(1.0, 0.0, 0.0),
+    /// )
+    /// .with_uvs((0.5, 1.0), (0.0, 0.0), (1.0, 0.0));
+    ///
+    /// assert_eq!(t.local_uv_at(Point::new(-1.0, 0.0, 0.0)), Some((0.0, 0.0)));
+    /// ```
+    pub fn with_uvs(mut self, uv1: (f64, f64), uv2: (f64, f64), uv3: (f64, f64)) -> Self {
+        self.uvs = Some([uv1, uv2, uv3]);
+        self
+    }
 }
 
 #[typetag::serde]
@@ -106,6 +136,66 @@ impl Shape for Triangle {
         self.material = material;
     }
 
+    fn bounds(&self) -> Bounds {
+        Bounds::from_points(&[self.p1, self.p2, self.p3])
+    }
+
+    fn local_surface_area(&self) -> f64 {
+        self.e1.cross(self.e2).magnitude() / 2.0
+    }
+
+    /// Half the area spanned by the edges of the triangle moved by its
+    /// transformation, exact for any transformation.
+    fn surface_area(&self) -> f64 {
+        (self.transform * self.e1)
+            .cross(self.transform * self.e2)
+            .magnitude()
+            / 2.0
+    }
+
+    fn local_uv_at(&self, point: Point) -> Option<(f64, f64)> {
+        let [uv1, uv2, uv3] = self.uvs?;
+
+        // the barycentric coordinates of the point along e1 and e2, the same
+        // as the u and v of an intersection
+        let w = point - self.p1;
+        let (d00, d01, d11) = (
+            self.e1.dot(self.e1),
+            self.e1.dot(self.e2),
+            self.e2.dot(self.e2),
+        );
+        let (d20, d21) = (w.dot(self.e1), w.dot(self.e2));
+        let denom = d00 * d11 - d01 * d01;
+        let a = (d11 * d20 - d01 * d21) / denom;
+        let b = (d00 * d21 - d01 * d20) / denom;
+
+        Some((
+            uv1.0 * (1.0 - a - b) + uv2.0 * a + uv3.0 * b,
+            uv1.1 * (1.0 - a - b) + uv2.1 * a + uv3.1 * b,
+        ))
+    }
+
+    fn uv_bounds(&self) -> UvBounds {
+        match self.uvs {
+            Some(uvs) => UvBounds::from_uvs(&uvs),
+            None => UvBounds::UNIT,
+        }
+    }
+
+    fn local_sample_surface(&self, rng: &mut dyn RngCore) -> (Point, Vector) {
+        // folding the unit square onto the triangle keeps the points even
+        let (r, s) = (rng.gen::<f64>().sqrt(), rng.gen::<f64>());
+        let (u, v) = (r * (1.0 - s), r * s);
+        let point = self.p1 + self.e1 * u + self.e2 * v;
+        let normal = match (self.n1, self.n2, self.n3) {
+            (Some(n1), Some(n2), Some(n3)) if self.smooth_triangle => {
+                (n2 * u + n3 * v + n1 * (1.0 - u - v)).normalize()
+            }
+            _ => self.normal,
+        };
+        (point, normal)
+    }
+
     fn local_intersect(&self, ray: Ray) -> Option<Vec<Intersection>> {
         let dir_cross_e2 = ray.direction.cross(self.e2);
         let det = self.e1.dot(dir_cross_e2);
@@ -265,4 +355,75 @@ mod tests {
         assert_eq!(xs.len(), 1);
         assert_eq!(xs[0].t, 2.0);
     }
+
+    #[test]
+    fn the_surface_area_of_a_triangle() {
+        let t = Triangle::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(3.0, 0.0, 0.0),
+            Point::new(0.0, 4.0, 0.0),
+        );
+
+        assert_eq!(t.surface_area(), 6.0);
+    }
+
+    #[test]
+    fn the_surface_area_of_a_stretched_triangle() {
+        let mut t = Triangle::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(3.0, 0.0, 0.0),
+            Point::new(0.0, 4.0, 0.0),
+        );
+        t.transform = crate::Transformation::new()
+            .scale(2.0, 1.0, 5.0)
+            .rotate_y(1.0)
+            .build();
+
+        assert!(crate::float_eq(t.surface_area(), 12.0));
+    }
+
+    #[test]
+    fn points_sampled_on_a_triangle_lie_inside_it() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let t = Triangle::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+        );
+        let mut rng = StdRng::seed_from_u64(5);
+
+        for _ in 0..100 {
+            let (p, n) = t.sample_surface(&mut rng);
+            // on the plane z = 0, above the base and under both sides
+            assert!(crate::float_eq(p.z, 0.0));
+            assert!(p.y >= 0.0 && p.y <= 1.0 - p.x.abs() + EPSILON);
+            assert_eq!(n, Vector::new(0.0, 0.0, -1.0));
+        }
+    }
+
+    #[test]
+    fn the_uv_bounds_of_a_triangle_contain_its_texture_coordinates() {
+        let t = Triangle::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+        );
+        assert_eq!(t.uv_bounds(), UvBounds::UNIT);
+        assert_eq!(t.local_uv_at(Point::new(0.0, 0.5, 0.0)), None);
+
+        let t = t.with_uvs((0.25, 0.75), (0.1, 0.5), (0.4, 0.5));
+
+        assert_eq!(
+            t.uv_bounds(),
+            UvBounds {
+                u_min: 0.1,
+                u_max: 0.4,
+                v_min: 0.5,
+                v_max: 0.75,
+            }
+        );
+        let (u, v) = t.local_uv_at(Point::new(0.0, 0.5, 0.0)).unwrap();
+        assert!(crate::float_eq(u, 0.25) && crate::float_eq(v, 0.625));
+    }
 }
@@ -0,0 +1,298 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{color::color_matching, Camera, Canvas, Color, Computations, Intersection, Ray, World};
+
+/// Shortest wavelength of visible light sampled, in nanometers.
+const MIN_WAVELENGTH: f64 = 380.0;
+/// Longest wavelength of visible light sampled, in nanometers.
+const MAX_WAVELENGTH: f64 = 780.0;
+/// Wavelengths where the red, green and blue of a color without a spectrum
+/// meet, see [`color_reflectance`].
+const GREEN_START: f64 = 490.0;
+const RED_START: f64 = 590.0;
+/// Bounces followed for every ray, the same as [`Camera::render`].
+const MAX_DEPTH: usize = 5;
+
+/// The light carried along a ray at a single wavelength.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpectralRay {
+    /// Wavelength of the light in nanometers.
+    pub wavelength_nm: f64,
+    /// Amount of light at the wavelength, with 1.0 for a white light seen
+    /// straight on a white surface.
+    pub radiance: f64,
+}
+
+impl SpectralRay {
+    /// The CIE XYZ color the light of the ray adds to its pixel.
+    pub fn to_xyz(self) -> (f64, f64, f64) {
+        let (x, y, z) = color_matching(self.wavelength_nm);
+        (x * self.radiance, y * self.radiance, z * self.radiance)
+    }
+}
+
+/// A spectrum for a `color` without one, at the `wavelength`: the blue of the
+/// color for short wavelengths, the green in the middle and the red for long
+/// ones. A grey gives the same value at every wavelength.
+fn color_reflectance(color: Color, wavelength: f64) -> f64 {
+    if wavelength < GREEN_START {
+        color.blue
+    } else if wavelength < RED_START {
+        color.green
+    } else {
+        color.red
+    }
+}
+
+/// The reflectance of the `curve` at the `wavelength`, interpolated between
+/// the nearest points and kept flat past its ends.
+fn curve_reflectance(curve: &[(f64, f64)], wavelength: f64) -> f64 {
+    match curve.iter().position(|&(nm, _)| nm >= wavelength) {
+        None => curve.last().map_or(0.0, |&(_, r)| r),
+        Some(0) => curve[0].1,
+        Some(i) => {
+            let ((nm0, r0), (nm1, r1)) = (curve[i - 1], curve[i]);
+            r0 + (r1 - r0) * (wavelength - nm0) / (nm1 - nm0)
+        }
+    }
+}
+
+impl World {
+    /// Light reaching the origin of the ray `r` at the wavelength of `nm`
+    /// nanometers, found like [`World::color_at`] with the reflectance of
+    /// the materials at that wavelength in place of their colors.
+    ///
+    /// Only the point `light` of the world, shadows, reflection and
+    /// refraction are followed. Materials without a
+    /// [`crate::Material::spectral_reflectance`] reflect the blue of their
+    /// color below 490 nm, the green up to 590 nm and the red above, and so
+    /// do the light and the background.
+    pub fn spectral_radiance(&self, r: Ray, nm: f64, remaining: usize) -> f64 {
+        let xs = match self.intersect_world(r) {
+            Some(xs) => xs,
+            None => return color_reflectance(self.background, nm),
+        };
+        match Intersection::hit(&xs) {
+            Some(hit) => {
+                let comps = hit.prepare_computations(r, &xs, Some(self));
+                self.spectral_shade_hit(&comps, nm, remaining)
+            }
+            None => color_reflectance(self.background, nm),
+        }
+    }
+
+    /// The spectral version of [`World::shade_hit`].
+    fn spectral_shade_hit(&self, comps: &Computations, nm: f64, remaining: usize) -> f64 {
+        let material = self.get_object_material(comps.object);
+        let point = comps.over_point;
+        let reflectance = match &material.spectral_reflectance {
+            Some(curve) => curve_reflectance(curve, nm),
+            None => color_reflectance(material.pattern.pattern_at_shape(comps.object, point), nm),
+        };
+
+        let mut surface = 0.0;
+        if let Some(light) = self.light {
+            let intensity = color_reflectance(light.intensity, nm);
+            surface += intensity * reflectance * material.ambient;
+
+            let lightv = (light.position - point).normalize();
+            let light_dot_normal = lightv.dot(comps.normalv);
+            if light_dot_normal >= 0.0 && !self.is_shadow_between(point, light.position) {
+                surface += intensity * reflectance * material.diffuse * light_dot_normal;
+
+                let reflect_dot_eye = (-lightv).reflect(comps.normalv).dot(comps.eyev);
+                let factor = if let Some(ward) = material.anisotropy {
+                    let tangent = comps.object.tangent_at(point);
+                    ward.specular(comps.normalv, tangent, lightv, comps.eyev)
+                } else if reflect_dot_eye > 0.0 {
+                    reflect_dot_eye.powf(material.shininess)
+                } else {
+                    0.0
+                };
+                surface += intensity * material.specular * factor;
+            }
+        }
+
+        let mut reflected = 0.0;
+        if material.reflective > 0.0 && remaining > 0 {
+            reflected = material.reflective
+                * self.spectral_radiance(comps.reflect_ray(), nm, remaining - 1);
+        }
+        let mut refracted = 0.0;
+        if material.transparency > 0.0 && remaining > 0 {
+            if let Some(ray) = comps.refract_ray() {
+                refracted = material.transparency * self.spectral_radiance(ray, nm, remaining - 1);
+            }
+        }
+
+        if material.reflective > 0.0 && material.transparency > 0.0 {
+            let fresnel = comps.fresnel_reflectance();
+            surface + reflected * fresnel + refracted * (1.0 - fresnel)
+        } else {
+            surface + reflected + refracted
+        }
+    }
+}
+
+impl Camera {
+    /// Render the world one wavelength at a time, tracing `samples` rays
+    /// through the center of every pixel, each at a random wavelength of
+    /// visible light, see [`World::spectral_radiance`].
+    ///
+    /// The light of the rays is added up as CIE XYZ and turned into sRGB.
+    /// Each of X, Y and Z is divided by what a white surface would give at
+    /// the same wavelengths, so a flat spectrum is exactly grey and worlds
+    /// of grey materials look the same as with [`Camera::render`].
+    pub fn render_spectral(&self, world: &World, samples: usize) -> Canvas {
+        // the XYZ of sRGB white, which a flat spectrum of 1.0 maps to
+        let white = Color::new(1.0, 1.0, 1.0).to_xyz();
+        let samples = samples.max(1);
+
+        Canvas::parallel_fill(self.hsize, self.vsize, |x, y| {
+            let mut rng = StdRng::seed_from_u64((y * self.hsize + x) as u64);
+            let ray = match self.visible_ray_for_pixel(x, y) {
+                Some(ray) => ray,
+                None => return Color::new(0.0, 0.0, 0.0),
+            };
+            let (mut light, mut flat) = ((0.0, 0.0, 0.0), (0.0, 0.0, 0.0));
+
+            for i in 0..samples {
+                // one random wavelength in each of `samples` equal bands
+                let t = (i as f64 + rng.gen::<f64>()) / samples as f64;
+                let nm = MIN_WAVELENGTH + t * (MAX_WAVELENGTH - MIN_WAVELENGTH);
+                let sample = SpectralRay {
+                    wavelength_nm: nm,
+                    radiance: world.spectral_radiance(ray, nm, MAX_DEPTH),
+                };
+
+                let (x, y, z) = sample.to_xyz();
+                let (fx, fy, fz) = color_matching(nm);
+                light = (light.0 + x, light.1 + y, light.2 + z);
+                flat = (flat.0 + fx, flat.1 + fy, flat.2 + fz);
+            }
+
+            let scaled = |total: f64, flat: f64, white: f64| {
+                if flat > 0.0 {
+                    white * total / flat
+                } else {
+                    0.0
+                }
+            };
+            Color::from_xyz(
+                scaled(light.0, flat.0, white.0),
+                scaled(light.1, flat.1, white.1),
+                scaled(light.2, flat.2, white.2),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shapes::{Plane, Sphere},
+        Material, Point, PointLight, Transformation, Vector,
+    };
+    use std::f64::consts::PI;
+
+    fn camera(size: usize) -> Camera {
+        let mut c = Camera::new(size, size, PI / 3.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 1.5, -5.0),
+            Point::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        c
+    }
+
+    /// A sphere with only `material` lit head on, with nothing else to
+    /// reflect or shadow.
+    fn lit_sphere(material: Material) -> World {
+        let mut w = World::new();
+        w.light = Some(PointLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        let mut s = Sphere::new();
+        s.material = material.ambient(1.0).diffuse(0.0).specular(0.0);
+        w.add_object(Box::new(s));
+        w
+    }
+
+    fn center_of_sphere(w: &World, samples: usize) -> Color {
+        let mut c = Camera::new(5, 5, PI / 3.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        c.render_spectral(w, samples).pixel_at(2, 2)
+    }
+
+    #[test]
+    fn a_flat_half_reflectance_is_half_grey() {
+        let w = lit_sphere(Material::new().spectral_reflectance(vec![(380.0, 0.5), (780.0, 0.5)]));
+
+        assert_eq!(center_of_sphere(&w, 16), Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn reflecting_only_long_wavelengths_is_red() {
+        let w = lit_sphere(Material::new().spectral_reflectance(vec![
+            (599.0, 0.0),
+            (600.0, 1.0),
+            (780.0, 1.0),
+        ]));
+
+        let c = center_of_sphere(&w, 64);
+
+        assert!(c.red > 0.5, "{:?}", c);
+        assert!(c.green < 0.2 && c.blue < 0.2, "{:?}", c);
+    }
+
+    #[test]
+    fn grey_worlds_look_the_same_as_with_the_rgb_renderer() {
+        let mut w = World::new();
+        w.light = Some(PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        let mut floor = Plane::new();
+        floor.material = Material::new()
+            .color(Color::new(0.6, 0.6, 0.6))
+            .reflective(0.3);
+        w.add_object(Box::new(floor));
+        let mut ball = Sphere::glass_sphere();
+        ball.transform = Transformation::new().translate(0.0, 1.0, 0.0).build();
+        ball.material.reflective = 0.8;
+        w.add_object(Box::new(ball));
+        let mut matte = Sphere::new();
+        matte.transform = Transformation::new()
+            .scale(0.5, 0.5, 0.5)
+            .translate(1.5, 0.5, -0.5)
+            .build();
+        matte.material.set_color(Color::new(0.3, 0.3, 0.3));
+        w.add_object(Box::new(matte));
+        let c = camera(20);
+
+        let spectral = c.render_spectral(&w, 4);
+        let rgb = c.render(&w);
+
+        for y in 0..c.vsize {
+            for x in 0..c.hsize {
+                assert_eq!(spectral.pixel_at(x, y), rgb.pixel_at(x, y), "{} {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn reflectance_curves_are_interpolated() {
+        let curve = [(400.0, 0.2), (500.0, 0.6), (600.0, 0.4)];
+
+        assert_eq!(curve_reflectance(&curve, 350.0), 0.2);
+        assert!((curve_reflectance(&curve, 450.0) - 0.4).abs() < 1e-12);
+        assert!((curve_reflectance(&curve, 575.0) - 0.45).abs() < 1e-12);
+        assert_eq!(curve_reflectance(&curve, 700.0), 0.4);
+    }
+}
@@ -0,0 +1,174 @@
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{sampling::orthonormal_basis, shapes::Shape, Color, Point, PointLight, Ray, Vector};
+
+/// Number of rings of samples around the shaded point.
+const RINGS: usize = 4;
+/// Number of samples on each ring.
+const SAMPLES_PER_RING: usize = 8;
+/// Relative index of refraction of skin like materials.
+const ETA: f64 = 1.3;
+
+/// Parameters of light scattering under the surface of translucent materials
+/// such as skin, wax or marble. Light entering the surface spreads out and
+/// leaves it again around the point it entered, softening the shading and
+/// bleeding light past the edge of the lit side.
+///
+/// The coefficients of the material are the colors divided by `scale`, so a
+/// larger `scale` lets the light travel further under the surface. A `scale`
+/// of zero turns the scattering off.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct SssParams {
+    /// How strongly each color of light is scattered under the surface.
+    pub scattering_color: Color,
+    /// How strongly each color of light is absorbed under the surface.
+    pub absorption_color: Color,
+    /// Distance scale of the scattering, in world units.
+    pub scale: f64,
+}
+
+impl SssParams {
+    /// Create scattering parameters from the `scattering_color`, the
+    /// `absorption_color` and the `scale` of the scattering distance.
+    pub fn new(scattering_color: Color, absorption_color: Color, scale: f64) -> Self {
+        SssParams {
+            scattering_color,
+            absorption_color,
+            scale,
+        }
+    }
+
+    /// Diffuse reflectance of the dipole model at distance `r` from where the
+    /// light entered, for scattering and absorption coefficients
+    /// `sigma_s` and `sigma_a` of one color channel.
+    ///
+    /// Jensen et al. "A Practical Model for Subsurface Light Transport" (2001)
+    fn dipole(r: f64, sigma_s: f64, sigma_a: f64) -> f64 {
+        let sigma_t = sigma_s + sigma_a;
+        let alpha = sigma_s / sigma_t;
+        let sigma_tr = (3.0 * sigma_a * sigma_t).sqrt();
+
+        let fdr = -1.440 / (ETA * ETA) + 0.710 / ETA + 0.668 + 0.0636 * ETA;
+        let a = (1.0 + fdr) / (1.0 - fdr);
+
+        // a real light source below the surface and a virtual one above it,
+        // placed so the fluence vanishes at the boundary.
+        let z_r = 1.0 / sigma_t;
+        let z_v = z_r + 4.0 * a / (3.0 * sigma_t);
+        let d_r = (r * r + z_r * z_r).sqrt();
+        let d_v = (r * r + z_v * z_v).sqrt();
+
+        let term = |z: f64, d: f64| z * (1.0 + sigma_tr * d) * (-sigma_tr * d).exp() / d.powi(3);
+
+        alpha / (4.0 * PI) * (term(z_r, d_r) + term(z_v, d_v))
+    }
+
+    /// The coefficients of one channel, kept above zero so the profile stays
+    /// finite.
+    fn coefficients(&self, channel: usize) -> (f64, f64) {
+        let pick = |c: Color| match channel {
+            0 => c.red,
+            1 => c.green,
+            _ => c.blue,
+        };
+        (
+            pick(self.scattering_color).max(1e-4) / self.scale,
+            pick(self.absorption_color).max(1e-4) / self.scale,
+        )
+    }
+
+    /// How much of the light arriving around `point` on the surface of
+    /// `object` leaves the surface at `point`, per color channel. The surface
+    /// around the point is sampled on rings in its tangent plane, and the
+    /// cosine of the light falling on each sample is averaged with the
+    /// weights of the dipole profile. Shadows cast on the samples by other
+    /// objects are not taken into account.
+    pub(crate) fn diffuse_factor(
+        &self,
+        object: &dyn Shape,
+        light: PointLight,
+        point: Point,
+        normalv: Vector,
+    ) -> Color {
+        let (sigma_s, sigma_a) = (0..3)
+            .map(|c| self.coefficients(c))
+            .fold((f64::INFINITY, f64::INFINITY), |(s, a), (cs, ca)| {
+                (s.min(cs), a.min(ca))
+            });
+        // sample out to where the widest channel has mostly died out, but no
+        // further than the size of the object.
+        let bounds = object.parent_space_bounds();
+        let size = if bounds.is_finite() {
+            (bounds.max - bounds.min).magnitude() / 2.0
+        } else {
+            f64::INFINITY
+        };
+        let max_radius = (3.0 / (3.0 * sigma_a * (sigma_s + sigma_a)).sqrt()).min(size);
+        let step = max_radius / RINGS as f64;
+        let (tangent, bitangent) = orthonormal_basis(normalv);
+
+        let mut samples = vec![(point, normalv, PI * (step / 2.0).powi(2))];
+        for ring in 1..=RINGS {
+            let radius = ring as f64 * step;
+            let area = 2.0 * PI * radius * step / SAMPLES_PER_RING as f64;
+            for i in 0..SAMPLES_PER_RING {
+                let angle =
+                    2.0 * PI * (i as f64 + 0.5 * (ring % 2) as f64) / SAMPLES_PER_RING as f64;
+                let offset = tangent * (radius * angle.cos()) + bitangent * (radius * angle.sin());
+                if let Some((p, n)) = Self::project(object, point + offset, normalv, max_radius) {
+                    samples.push((p, n, area));
+                }
+            }
+        }
+
+        let mut factor = [0.0; 3];
+        for (channel, value) in factor.iter_mut().enumerate() {
+            let (sigma_s, sigma_a) = self.coefficients(channel);
+            let mut light_sum = 0.0;
+            let mut weight_sum = 0.0;
+
+            for (p, n, area) in &samples {
+                let weight = Self::dipole((*p - point).magnitude(), sigma_s, sigma_a) * area;
+                let lightv = (light.position - *p).normalize();
+                light_sum += weight * lightv.dot(*n).max(0.0);
+                weight_sum += weight;
+            }
+
+            *value = if weight_sum > 0.0 {
+                light_sum / weight_sum
+            } else {
+                0.0
+            };
+        }
+
+        Color::new(factor[0], factor[1], factor[2])
+    }
+
+    /// Find the point of the surface of `object` closest to `point` along
+    /// `normalv`, and the normal there.
+    fn project(
+        object: &dyn Shape,
+        point: Point,
+        normalv: Vector,
+        reach: f64,
+    ) -> Option<(Point, Vector)> {
+        let ray = Ray::new(point + normalv * reach, -normalv);
+        let xs = object.intersect(ray)?;
+        let hit = xs.iter().min_by(|a, b| {
+            (a.t - reach)
+                .abs()
+                .partial_cmp(&(b.t - reach).abs())
+                .unwrap()
+        })?;
+        let p = ray.position(hit.t);
+        let n = object.normal_at(p, Some(hit), None);
+
+        if n.dot(normalv) > 0.0 {
+            Some((p, n))
+        } else {
+            None
+        }
+    }
+}
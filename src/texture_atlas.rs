@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::shapes::Shape;
+
+/// The range of texture coordinates used by the surface of a shape, see
+/// [`Shape::uv_bounds`].
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct UvBounds {
+    pub u_min: f64,
+    pub u_max: f64,
+    pub v_min: f64,
+    pub v_max: f64,
+}
+
+impl UvBounds {
+    /// The whole unit square, used by shapes like spheres and cubes.
+    pub const UNIT: UvBounds = UvBounds {
+        u_min: 0.0,
+        u_max: 1.0,
+        v_min: 0.0,
+        v_max: 1.0,
+    };
+
+    /// The smallest range containing all of the `(u, v)` coordinates.
+    pub fn from_uvs(uvs: &[(f64, f64)]) -> Self {
+        let mut bounds = UvBounds {
+            u_min: f64::INFINITY,
+            u_max: f64::NEG_INFINITY,
+            v_min: f64::INFINITY,
+            v_max: f64::NEG_INFINITY,
+        };
+        for &(u, v) in uvs {
+            bounds.u_min = bounds.u_min.min(u);
+            bounds.u_max = bounds.u_max.max(u);
+            bounds.v_min = bounds.v_min.min(v);
+            bounds.v_max = bounds.v_max.max(v);
+        }
+        bounds
+    }
+
+    /// Length of the range along `u`.
+    pub fn width(&self) -> f64 {
+        self.u_max - self.u_min
+    }
+
+    /// Length of the range along `v`.
+    pub fn height(&self) -> f64 {
+        self.v_max - self.v_min
+    }
+}
+
+/// The part of a [`TextureAtlas`] holding the texture of one shape, in
+/// pixels of the atlas.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct AtlasRegion {
+    /// Id of the shape the region belongs to.
+    pub shape_id: Uuid,
+    /// Column of the left edge of the region.
+    pub x: usize,
+    /// Row of the top edge of the region.
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    /// Texture coordinates of the shape the region covers.
+    pub uv_bounds: UvBounds,
+}
+
+impl AtlasRegion {
+    /// Move the texture coordinates `u` and `v` of the shape to the
+    /// coordinates in the whole atlas, from 0.0 to 1.0.
+    pub fn to_atlas(&self, u: f64, v: f64, atlas_resolution: usize) -> (f64, f64) {
+        let along = |t: f64, min: f64, length: f64| {
+            if length > 0.0 {
+                (t - min) / length
+            } else {
+                0.5
+            }
+        };
+        let size = atlas_resolution as f64;
+        let du = along(u, self.uv_bounds.u_min, self.uv_bounds.width());
+        let dv = along(v, self.uv_bounds.v_min, self.uv_bounds.height());
+
+        (
+            (self.x as f64 + du * self.width as f64) / size,
+            (self.y as f64 + dv * self.height as f64) / size,
+        )
+    }
+
+    /// Whether the region shares any pixel with `other`.
+    pub fn overlaps(&self, other: &AtlasRegion) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+}
+
+/// Regions of a single square texture given to many shapes, so they can be
+/// drawn with one image.
+///
+/// The regions keep the proportions of the texture coordinates used by
+/// each shape, at the largest size that fits all of them into the atlas.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TextureAtlas {
+    /// Width and height of the atlas in pixels.
+    pub resolution: usize,
+    /// A region for every packed shape, in the order of the shapes.
+    pub regions: Vec<AtlasRegion>,
+}
+
+impl TextureAtlas {
+    /// Give each of the `shapes` a region of an atlas of `atlas_resolution`
+    /// by `atlas_resolution` pixels, sized by its [`Shape::uv_bounds`].
+    ///
+    /// The regions are laid out in rows, tallest first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more shapes than pixels in the atlas.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::{Cube, Sphere}, TextureAtlas};
+    ///
+    /// let (s, c) = (Sphere::new(), Cube::new());
+    /// let atlas = TextureAtlas::pack(&[&s, &c], 256);
+    ///
+    /// assert_eq!(atlas.regions.len(), 2);
+    /// assert!(!atlas.regions[0].overlaps(&atlas.regions[1]));
+    /// ```
+    pub fn pack(shapes: &[&dyn Shape], atlas_resolution: usize) -> TextureAtlas {
+        assert!(
+            shapes.len() <= atlas_resolution * atlas_resolution,
+            "{} shapes do not fit into an atlas of {} pixels",
+            shapes.len(),
+            atlas_resolution * atlas_resolution
+        );
+        let uv_bounds: Vec<UvBounds> = shapes.iter().map(|s| s.uv_bounds()).collect();
+
+        // the largest number of pixels per unit of texture coordinates that
+        // fits, every region being at least a pixel
+        let (mut low, mut high) = (0, atlas_resolution);
+        while low < high {
+            let scale = (low + high).div_ceil(2);
+            if Self::layout(&uv_bounds, scale, atlas_resolution).is_some() {
+                low = scale;
+            } else {
+                high = scale - 1;
+            }
+        }
+        let positions = Self::layout(&uv_bounds, low, atlas_resolution)
+            .expect("regions of a pixel fit when there are enough pixels");
+
+        let regions = shapes
+            .iter()
+            .zip(uv_bounds)
+            .zip(positions)
+            .map(|((shape, uv_bounds), (x, y, width, height))| AtlasRegion {
+                shape_id: shape.id(),
+                x,
+                y,
+                width,
+                height,
+                uv_bounds,
+            })
+            .collect();
+
+        TextureAtlas {
+            resolution: atlas_resolution,
+            regions,
+        }
+    }
+
+    /// The region of the shape with `shape_id`.
+    pub fn region(&self, shape_id: Uuid) -> Option<&AtlasRegion> {
+        self.regions.iter().find(|r| r.shape_id == shape_id)
+    }
+
+    /// The position and size of the region of every range of texture
+    /// coordinates, at `scale` pixels for each unit, or `None` when they do
+    /// not fit into the atlas.
+    fn layout(
+        uv_bounds: &[UvBounds],
+        scale: usize,
+        atlas_resolution: usize,
+    ) -> Option<Vec<(usize, usize, usize, usize)>> {
+        let pixels = |length: f64| ((length * scale as f64) as usize).clamp(1, atlas_resolution);
+        let sizes: Vec<(usize, usize)> = uv_bounds
+            .iter()
+            .map(|b| (pixels(b.width()), pixels(b.height())))
+            .collect();
+        let mut order: Vec<usize> = (0..sizes.len()).collect();
+        order.sort_by(|&a, &b| sizes[b].1.cmp(&sizes[a].1));
+
+        let mut positions = vec![(0, 0, 0, 0); sizes.len()];
+        let (mut x, mut y, mut row_height) = (0, 0, 0);
+        for i in order {
+            let (width, height) = sizes[i];
+            if x + width > atlas_resolution {
+                x = 0;
+                y += row_height;
+                row_height = 0;
+            }
+            if y + height > atlas_resolution {
+                return None;
+            }
+
+            positions[i] = (x, y, width, height);
+            x += width;
+            row_height = row_height.max(height);
+        }
+        Some(positions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shapes::{Cube, Sphere, Triangle},
+        Point,
+    };
+
+    fn triangle(uvs: [(f64, f64); 3]) -> Triangle {
+        Triangle::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+        )
+        .with_uvs(uvs[0], uvs[1], uvs[2])
+    }
+
+    fn assert_packed(atlas: &TextureAtlas) {
+        for (i, a) in atlas.regions.iter().enumerate() {
+            assert!(a.width > 0 && a.height > 0);
+            assert!(a.x + a.width <= atlas.resolution, "{:?}", a);
+            assert!(a.y + a.height <= atlas.resolution, "{:?}", a);
+            for b in &atlas.regions[i + 1..] {
+                assert!(!a.overlaps(b), "{:?} {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn packed_regions_fit_without_overlapping() {
+        let s = Sphere::new();
+        let c = Cube::new();
+        let t1 = triangle([(0.0, 0.0), (0.5, 0.0), (0.0, 0.25)]);
+        let t2 = triangle([(0.2, 0.2), (0.3, 0.9), (0.1, 0.4)]);
+        let shapes: Vec<&dyn Shape> = vec![&s, &c, &t1, &t2];
+
+        let atlas = TextureAtlas::pack(&shapes, 512);
+
+        assert_eq!(atlas.regions.len(), 4);
+        assert_packed(&atlas);
+        let r = atlas.region(t1.id()).unwrap();
+        assert_eq!(r.uv_bounds, t1.uv_bounds());
+        // the region keeps the proportions of the texture coordinates
+        assert!((r.width as f64 / r.height as f64 - 2.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn many_shapes_shrink_to_fit() {
+        let spheres: Vec<Sphere> = (0..50).map(|_| Sphere::new()).collect();
+        let shapes: Vec<&dyn Shape> = spheres.iter().map(|s| s as &dyn Shape).collect();
+
+        let atlas = TextureAtlas::pack(&shapes, 64);
+
+        assert_packed(&atlas);
+        assert!(atlas
+            .regions
+            .iter()
+            .all(|r| r.width == atlas.regions[0].width));
+    }
+
+    #[test]
+    fn coordinates_of_a_shape_move_into_its_region() {
+        let region = AtlasRegion {
+            shape_id: Uuid::new_v4(),
+            x: 64,
+            y: 32,
+            width: 32,
+            height: 16,
+            uv_bounds: UvBounds {
+                u_min: 0.5,
+                u_max: 1.0,
+                v_min: 0.0,
+                v_max: 0.5,
+            },
+        };
+
+        assert_eq!(region.to_atlas(0.5, 0.0, 128), (0.5, 0.25));
+        assert_eq!(region.to_atlas(1.0, 0.5, 128), (0.75, 0.375));
+    }
+}
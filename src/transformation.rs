@@ -258,6 +258,47 @@ impl Transformation {
         }
     }
 
+    /// Create an orthographic projection matrix mapping the box between
+    /// `left` and `right`, `bottom` and `top`, and `near` and `far` in front
+    /// of a camera looking toward -z into the cube from -1 to 1, without
+    /// making far objects smaller.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Point, Transformation};
+    ///
+    /// let m = Transformation::orthographic(-2.0, 2.0, -1.0, 1.0, 1.0, 11.0);
+    ///
+    /// assert_eq!(m * Point::new(2.0, -1.0, -1.0), Point::new(1.0, -1.0, -1.0));
+    /// assert_eq!(m * Point::new(0.0, 0.0, -11.0), Point::new(0.0, 0.0, 1.0));
+    /// ```
+    pub fn orthographic(
+        left: f64,
+        right: f64,
+        bottom: f64,
+        top: f64,
+        near: f64,
+        far: f64,
+    ) -> Matrix {
+        Matrix::new([
+            [
+                2.0 / (right - left),
+                0.0,
+                0.0,
+                -(right + left) / (right - left),
+            ],
+            [
+                0.0,
+                2.0 / (top - bottom),
+                0.0,
+                -(top + bottom) / (top - bottom),
+            ],
+            [0.0, 0.0, -2.0 / (far - near), -(far + near) / (far - near)],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
     /// Create a transformation matrix that orients the world relative to
     /// the camera. Specify where you want the camera to be in the scene with
     /// the `from` parameter. A point in the scene the camera is pointing
@@ -267,13 +308,43 @@ impl Transformation {
         let upn = up.normalize();
         let left = forward.cross(upn);
         let true_up = left.cross(forward);
+        Transformation::from_basis(left, true_up, forward, from)
+    }
+
+    /// Create a transformation matrix that orients the world relative to a
+    /// camera at `eye` looking at `target`, the name used by most graphics
+    /// libraries for [`Transformation::view_transform`].
+    pub fn look_at(eye: Point, target: Point, up: Vector) -> Matrix {
+        Transformation::view_transform(eye, target, up)
+    }
+
+    /// Create the view transformation of a camera at `origin` from the
+    /// directions to its `right`, its `up` and the `forward` direction it
+    /// looks at. The three vectors are expected to be of unit length and
+    /// perpendicular to each other.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Point, Transformation, Vector, IDENTITY};
+    ///
+    /// let t = Transformation::from_basis(
+    ///     Vector::new(1.0, 0.0, 0.0),
+    ///     Vector::new(0.0, 1.0, 0.0),
+    ///     Vector::new(0.0, 0.0, -1.0),
+    ///     Point::new(0.0, 0.0, 0.0),
+    /// );
+    ///
+    /// assert_eq!(t, IDENTITY);
+    /// ```
+    pub fn from_basis(right: Vector, up: Vector, forward: Vector, origin: Point) -> Matrix {
         let orientation = [
-            [left.x, left.y, left.z, 0.0],
-            [true_up.x, true_up.y, true_up.z, 0.0],
+            [right.x, right.y, right.z, 0.0],
+            [up.x, up.y, up.z, 0.0],
             [-forward.x, -forward.y, -forward.z, 0.0],
             [0.0, 0.0, 0.0, 1.0],
         ];
-        let translation = Transformation::new().translate(-from.x, -from.y, -from.z);
+        let translation = Transformation::new().translate(-origin.x, -origin.y, -origin.z);
         Matrix::new(multiple_array(orientation, translation.data))
     }
 }
@@ -548,4 +619,39 @@ mod tests {
 
         assert_eq!(t, e);
     }
+
+    #[test]
+    fn look_at_is_the_view_transformation() {
+        let from = Point::new(1.0, 3.0, 2.0);
+        let to = Point::new(4.0, -2.0, 8.0);
+        let up = Vector::new(1.0, 1.0, 0.0);
+
+        assert_eq!(
+            Transformation::look_at(from, to, up),
+            Transformation::view_transform(from, to, up)
+        );
+    }
+
+    #[test]
+    fn a_view_from_the_standard_axes_is_the_identity() {
+        let t = Transformation::from_basis(
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 0.0, -1.0),
+            Point::new(0.0, 0.0, 0.0),
+        );
+
+        assert_eq!(t, IDENTITY);
+    }
+
+    #[test]
+    fn an_orthographic_projection_keeps_parallel_lines_parallel() {
+        let m = Transformation::orthographic(-4.0, 4.0, -2.0, 2.0, 0.5, 10.5);
+        let near = m * Point::new(1.0, 1.0, -0.5);
+        let far = m * Point::new(1.0, 1.0, -10.5);
+
+        assert_eq!(near, Point::new(0.25, 0.5, -1.0));
+        assert_eq!(far, Point::new(0.25, 0.5, 1.0));
+        assert_eq!(m * Vector::new(0.0, 0.0, -1.0), Vector::new(0.0, 0.0, 0.2));
+    }
 }
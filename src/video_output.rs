@@ -0,0 +1,115 @@
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, ExitStatus, Stdio};
+
+use crate::Canvas;
+
+/// A video encoded while it is rendered, by handing every frame to a child
+/// process as it is finished rather than saving each one as an image.
+///
+/// The frames are written as raw RGB bytes with [`Canvas::write_to_fd`], so
+/// the encoder must be told the size of the frames up front.
+#[derive(Debug)]
+pub struct VideoOutput {
+    /// Width of every frame in pixels.
+    pub width: usize,
+    /// Height of every frame in pixels.
+    pub height: usize,
+    child: Child,
+    stdin: Option<ChildStdin>,
+}
+
+impl VideoOutput {
+    /// Start `ffmpeg` encoding frames of `width` by `height` pixels, shown
+    /// `fps` times a second, into the video at `output`. The container and
+    /// codec are picked by ffmpeg from the extension of `output`, and a file
+    /// already there is overwritten.
+    ///
+    /// Fails when ffmpeg cannot be started, for example when it is not
+    /// installed.
+    pub fn ffmpeg_pipe(
+        width: usize,
+        height: usize,
+        fps: u32,
+        output: &Path,
+    ) -> io::Result<VideoOutput> {
+        let mut child = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo"])
+            .args(["-pixel_format", "rgb24"])
+            .args(["-video_size", &format!("{}x{}", width, height)])
+            .args(["-framerate", &fps.to_string()])
+            .args(["-i", "-"])
+            .arg(output)
+            .stdin(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take();
+
+        Ok(VideoOutput {
+            width,
+            height,
+            child,
+            stdin,
+        })
+    }
+
+    /// Send the next frame to the encoder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` is not the size given when the output was created.
+    pub fn write_frame(&mut self, frame: &Canvas) -> io::Result<()> {
+        assert_eq!(
+            (frame.width, frame.height),
+            (self.width, self.height),
+            "every frame of a video must have the same size"
+        );
+
+        match self.stdin.as_mut() {
+            Some(stdin) => frame.write_to_fd(stdin),
+            None => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "the encoder takes no input",
+            )),
+        }
+    }
+
+    /// Tell the encoder there are no more frames and wait for it to finish
+    /// writing the video.
+    pub fn finish(mut self) -> io::Result<ExitStatus> {
+        if let Some(mut stdin) = self.stdin.take() {
+            stdin.flush()?;
+        }
+        self.child.wait()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    // needs ffmpeg and ffprobe on the path, run with `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn piping_frames_makes_a_video() {
+        let path = std::env::temp_dir().join("rustic_ray_video_output_test.mp4");
+        let mut video = VideoOutput::ffmpeg_pipe(64, 48, 24, &path).unwrap();
+        for i in 0..2 {
+            let frame =
+                Canvas::parallel_fill(64, 48, |x, _| Color::new(x as f64 / 64.0, i as f64, 0.0));
+            video.write_frame(&frame).unwrap();
+        }
+        assert!(video.finish().unwrap().success());
+
+        let probe = Command::new("ffprobe")
+            .args(["-v", "error", "-select_streams", "v:0", "-count_frames"])
+            .args(["-show_entries", "stream=width,height,nb_read_frames"])
+            .args(["-of", "csv=p=0"])
+            .arg(&path)
+            .output()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&probe.stdout).trim(), "64,48,2");
+    }
+}
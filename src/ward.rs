@@ -0,0 +1,63 @@
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{sampling::orthonormal_basis, Vector};
+
+/// Roughness of the Ward model of specular highlights, which can be
+/// stretched along the surface like on brushed metal.
+///
+/// `ax` is the roughness along the tangent of the surface and `ay` along the
+/// direction across it. Equal values give a round highlight, and a larger
+/// `ax` stretches the highlight along the tangent.
+///
+/// Ward, Gregory "Measuring and Modeling Anisotropic Reflection" (1992)
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct Ward {
+    /// Roughness along the tangent of the surface.
+    pub ax: f64,
+    /// Roughness across the tangent of the surface.
+    pub ay: f64,
+}
+
+impl Ward {
+    /// Create a Ward model with roughness `ax` along the tangent and `ay`
+    /// across it.
+    pub fn new(ax: f64, ay: f64) -> Self {
+        Ward { ax, ay }
+    }
+
+    /// The specular light reflected toward `eyev` from light arriving along
+    /// `lightv`, on a surface with normal `normalv`, as a fraction of the
+    /// intensity of the light. The `tangent`, when given, is the direction of
+    /// `ax` and is made perpendicular to the normal; otherwise an arbitrary
+    /// direction along the surface is used.
+    pub(crate) fn specular(
+        &self,
+        normalv: Vector,
+        tangent: Option<Vector>,
+        lightv: Vector,
+        eyev: Vector,
+    ) -> f64 {
+        let cos_i = lightv.dot(normalv);
+        let cos_o = eyev.dot(normalv);
+        if cos_i <= 0.0 || cos_o <= 0.0 {
+            return 0.0;
+        }
+
+        let (x, y) = match tangent {
+            Some(t) => {
+                let x = (t - normalv * t.dot(normalv)).normalize();
+                (x, normalv.cross(x))
+            }
+            None => orthonormal_basis(normalv),
+        };
+
+        let halfway = (lightv + eyev).normalize();
+        let exponent = -((halfway.dot(x) / self.ax).powi(2) + (halfway.dot(y) / self.ay).powi(2))
+            / halfway.dot(normalv).powi(2);
+
+        let brdf = exponent.exp() / (4.0 * PI * self.ax * self.ay * (cos_i * cos_o).sqrt());
+        brdf * cos_i
+    }
+}
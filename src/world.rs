@@ -1,9 +1,13 @@
+use rand::{rngs::StdRng, SeedableRng};
 use serde::{Deserialize, Serialize};
+use std::{f64::consts::PI, sync::Arc};
 use uuid::Uuid;
 
 use crate::{
-    shapes::Shape, shapes::Sphere, Color, Colors, Computations, Intersection, Material, Point,
-    PointLight, Ray, Transformation,
+    ambient_occlusion, shapes::Shape, shapes::Sphere, AmbientOcclusion, AoMap, AreaLightShape,
+    Bounds, Camera, Canvas, Color, Colors, Computations, DirectionalLight, Intersection,
+    IrradianceCache, KdTree, LightProbe, Material, Matrix, PhotonMap, Point, PointLight, Ray,
+    RayBatch, SceneWarning, Transformation, Vector, DEFAULT_AO_MAP_RESOLUTION,
 };
 
 /// A collection of all objects in a scene.
@@ -14,7 +18,56 @@ use crate::{
 pub struct World {
     // Light source of the world.
     pub light: Option<PointLight>,
-    objects: Vec<Box<dyn Shape>>,
+    /// Shapes lighting the world from their whole surface.
+    #[serde(default)]
+    pub area_lights: Vec<AreaLightShape>,
+    /// Lights with parallel rays coming from far away, like the sun.
+    #[serde(default)]
+    pub directional_lights: Vec<DirectionalLight>,
+    /// Light from the distant environment all around the world.
+    #[serde(default)]
+    pub light_probe: Option<LightProbe>,
+    /// Color returned for rays that do not hit any object.
+    #[serde(default = "default_background")]
+    pub background: Color,
+    /// Darken the ambient light where nearby objects block it, see
+    /// [`crate::ambient_occlusion`].
+    #[serde(default)]
+    pub ambient_occlusion: Option<AmbientOcclusion>,
+    /// Refractive index of the medium the whole scene is in, like 1.33 for a
+    /// scene under water. Rays travel through it outside of every object.
+    #[serde(default = "default_refractive_index")]
+    pub ambient_refractive_index: f64,
+    /// Light focused by mirrors and transparent objects onto matte surfaces,
+    /// see [`PhotonMap::build_caustics`]. It is not saved with the world.
+    #[serde(skip)]
+    pub caustics: Option<PhotonMap>,
+    /// Ambient occlusion baked ahead of rendering, used in place of firing
+    /// rays at every hit, see [`World::ambient_occlusion_precompute`]. It is
+    /// not saved with the world.
+    #[serde(skip)]
+    pub ao_map: Option<AoMap>,
+    objects: Vec<Arc<dyn Shape>>,
+    /// Boxes around the objects in world space, filled in by `precompute`.
+    #[serde(skip)]
+    object_bounds: Vec<Bounds>,
+    #[serde(skip)]
+    precomputed: bool,
+    /// Spatial index over the objects, built by `build_kd_tree`.
+    #[serde(skip)]
+    kd_tree: Option<KdTree>,
+}
+
+/// Rays fired from every point of the cache made by
+/// [`World::diffuse_irradiance_cache`].
+const DIFFUSE_IRRADIANCE_SAMPLES: usize = 64;
+
+fn default_background() -> Color {
+    Colors::BLACK
+}
+
+fn default_refractive_index() -> f64 {
+    1.0
 }
 
 impl World {
@@ -22,25 +75,308 @@ impl World {
     pub fn new() -> Self {
         World {
             light: None,
+            area_lights: Vec::new(),
+            directional_lights: Vec::new(),
+            light_probe: None,
+            background: default_background(),
+            ambient_occlusion: None,
+            ambient_refractive_index: default_refractive_index(),
+            caustics: None,
+            ao_map: None,
             objects: Vec::new(),
+            object_bounds: Vec::new(),
+            precomputed: false,
+            kd_tree: None,
         }
     }
 
+    /// Read a world from JSON. Objects of a type `typetag` does not know are
+    /// read with the type registered for their tag in the
+    /// [`crate::shapes::ShapeRegistry`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `serialized_world` is not a valid world.
     pub fn from_str(serialized_world: &str) -> Self {
-        serde_json::from_str(serialized_world).unwrap()
+        serde_json::from_str(serialized_world)
+            .or_else(|error| {
+                serde_json::from_str(serialized_world)
+                    .ok()
+                    .and_then(|v| World::from_value_with_registry(v).ok())
+                    .ok_or(error)
+            })
+            .unwrap()
     }
 
     /// Add an `object` to the world `self`.
     pub fn add_object(&mut self, object: Box<dyn Shape>) {
+        self.precomputed = false;
+        self.kd_tree = None;
+        self.objects.push(Arc::from(object));
+    }
+
+    /// Add an `object` that may also be held by other worlds or threads.
+    pub fn add_shared_object(&mut self, object: Arc<dyn Shape>) {
+        self.precomputed = false;
+        self.kd_tree = None;
         self.objects.push(object);
     }
 
+    /// A copy of the world with a copy of every object, so objects of the
+    /// copy can be changed without changing the ones of `self`. Objects added
+    /// with `add_shared_object` are copied too.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Color, World};
+    ///
+    /// let w = World::default();
+    /// let mut copy = w.clone_deep();
+    /// copy.get_object_mut(0).unwrap().material_mut().set_color(Color::new(1.0, 0.0, 0.0));
+    ///
+    /// assert_ne!(copy.get_object(0).unwrap().material(), w.get_object(0).unwrap().material());
+    /// ```
+    pub fn clone_deep(&self) -> World {
+        let objects: Vec<Arc<dyn Shape>> =
+            self.objects().map(|o| Arc::from(o.clone_box())).collect();
+        // the tree of `self` holds its objects, the copy needs its own
+        let kd_tree = self.kd_tree.as_ref().map(|_| KdTree::build(&objects));
+
+        World {
+            light: self.light,
+            area_lights: self.area_lights.clone(),
+            directional_lights: self.directional_lights.clone(),
+            light_probe: self.light_probe.clone(),
+            background: self.background,
+            ambient_occlusion: self.ambient_occlusion,
+            ambient_refractive_index: self.ambient_refractive_index,
+            caustics: self.caustics.clone(),
+            ao_map: self.ao_map.clone(),
+            objects,
+            object_bounds: self.object_bounds.clone(),
+            precomputed: self.precomputed,
+            kd_tree,
+        }
+    }
+
+    /// Set the scene inside a medium with the refractive index `n`, see
+    /// [`World::ambient_refractive_index`].
+    pub fn set_global_refractive_index(&mut self, n: f64) {
+        self.ambient_refractive_index = n;
+    }
+
+    /// Bake the ambient occlusion of the objects of a static scene into an
+    /// [`AoMap`], firing `samples` rays from every point sampled on their
+    /// surfaces. Set [`World::ao_map`] to the result to use it when shading.
+    pub fn ambient_occlusion_precompute(&self, samples: usize) -> AoMap {
+        AoMap::bake(self, samples, DEFAULT_AO_MAP_RESOLUTION)
+    }
+
+    /// The baked ambient occlusion of the shape with `shape_id` at the
+    /// texture coordinates `u` and `v`, or 1.0, for no occlusion, when it was
+    /// not baked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::World;
+    ///
+    /// let w = World::default();
+    /// let id = w.get_object(0).unwrap().id();
+    ///
+    /// assert_eq!(w.sample_ao(id, 0.5, 0.5), 1.0);
+    /// ```
+    pub fn sample_ao(&self, shape_id: Uuid, u: f64, v: f64) -> f64 {
+        self.ao_map
+            .as_ref()
+            .and_then(|map| map.get(shape_id, u, v))
+            .unwrap_or(1.0)
+    }
+
+    /// The baked ambient occlusion at the hit of `comps`, if any.
+    fn baked_ao(&self, comps: &Computations) -> Option<f64> {
+        let map = self.ao_map.as_ref()?;
+        let object_point = comps.object.world_to_object(comps.point, self);
+        let (u, v) = comps.object.local_uv_at(object_point)?;
+        map.get(comps.object.id(), u, v)
+    }
+
+    /// Gather the diffuse light bounced between the surfaces of the world at
+    /// up to `resolution` points ahead of rendering, see
+    /// [`IrradianceCache::precompute_irradiance`]. The result is used by
+    /// [`World::render_with_irradiance_cache`].
+    pub fn diffuse_irradiance_cache(&self, resolution: usize) -> IrradianceCache {
+        let mut cache = IrradianceCache::new(DIFFUSE_IRRADIANCE_SAMPLES, 0.5);
+        cache.precompute_irradiance(self, resolution);
+        cache
+    }
+
+    /// Render the world like [`Camera::render`] on all cores, adding the
+    /// diffuse light bounced off other surfaces looked up in the `cache` to
+    /// the surfaces seen by the camera.
+    pub fn render_with_irradiance_cache(&self, camera: &Camera, cache: &IrradianceCache) -> Canvas {
+        Canvas::parallel_fill(camera.hsize, camera.vsize, |x, y| {
+            let r = match camera.visible_ray_for_pixel(x, y) {
+                Some(r) => r,
+                None => return Colors::BLACK,
+            };
+            let xs = match self.intersect_world(r) {
+                Some(xs) => xs,
+                None => return self.background,
+            };
+            let comps = match Intersection::hit(&xs) {
+                Some(hit) => hit.prepare_computations(r, &xs, Some(self)),
+                None => return self.background,
+            };
+
+            let material = self.get_object_material(comps.object);
+            let color = material
+                .pattern
+                .pattern_at_shape(comps.object, comps.over_point);
+            let indirect = cache.irradiance_at(comps.over_point, comps.normalv);
+
+            self.shade_hit(&comps, 5) + color * indirect * material.diffuse
+        })
+    }
+
+    /// Move the whole scene by `m`, applied after the transform of every
+    /// object, like scaling a scene modelled in centimeters to meters.
+    ///
+    /// The point light, the area lights and the directional lights are moved
+    /// along so the scene is lit the same. The background is a single color
+    /// and the light probe is infinitely far away, so neither changes.
+    /// Objects shared with other worlds are copied before they are changed.
+    ///
+    /// The `caustics` and the `ao_map` hold points of the scene before it was
+    /// moved, so both are dropped and have to be built again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Point, Transformation, World};
+    ///
+    /// let mut w = World::default();
+    /// w.transform_all(Transformation::new().scale(0.01, 0.01, 0.01).build());
+    ///
+    /// assert_eq!(w.light.unwrap().position, Point::new(-0.1, 0.1, -0.1));
+    /// ```
+    pub fn transform_all(&mut self, m: Matrix) {
+        self.precomputed = false;
+        self.kd_tree = None;
+        self.caustics = None;
+        self.ao_map = None;
+
+        for object in self.objects.iter_mut() {
+            if Arc::get_mut(object).is_none() {
+                *object = Arc::from(object.clone_box());
+            }
+            let object = Arc::get_mut(object).unwrap();
+            object.set_transform(m * object.transform());
+        }
+
+        if let Some(light) = self.light.as_mut() {
+            light.position = m * light.position;
+        }
+        for light in self.area_lights.iter_mut() {
+            light.shape.set_transform(m * light.shape.transform());
+        }
+        for light in self.directional_lights.iter_mut() {
+            light.direction = (m * light.direction).normalize();
+        }
+    }
+
+    /// Light the world from the whole surface of the `shape` with
+    /// `intensity`, see [`AreaLightShape`]. The `shape` itself is not added
+    /// to the objects of the world.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::Disc, Color, SceneWarning, World};
+    ///
+    /// let mut w = World::new();
+    /// w.add_area_light(Box::new(Disc::new()), Color::new(1.0, 1.0, 1.0));
+    ///
+    /// assert_eq!(w.area_lights.len(), 1);
+    /// assert!(!w.validate().contains(&SceneWarning::NoLight));
+    /// ```
+    pub fn add_area_light(&mut self, shape: Box<dyn Shape>, intensity: Color) {
+        self.area_lights.push(AreaLightShape::new(shape, intensity));
+    }
+
+    /// Light the world with parallel rays travelling along `direction`, like
+    /// sunlight, see [`DirectionalLight`].
+    pub fn sky_light(&mut self, direction: Vector, intensity: Color) {
+        self.directional_lights
+            .push(DirectionalLight::new(direction, intensity));
+    }
+
+    /// Prepare the world for rendering and return the problems found by
+    /// [`World::validate`]. The box around every object is computed once so
+    /// `intersect_world` can skip the objects a ray misses. Transformation
+    /// matrices already keep their inverse and meshes build their bounding
+    /// volume hierarchy when they are made, so neither is redone here.
+    ///
+    /// Calling it again does nothing until an object is added or changed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{SceneWarning, World};
+    ///
+    /// let mut w = World::new();
+    ///
+    /// assert!(!w.is_precomputed());
+    /// assert_eq!(w.precompute(), vec![SceneWarning::NoLight]);
+    /// assert!(w.is_precomputed());
+    /// ```
+    pub fn precompute(&mut self) -> Vec<SceneWarning> {
+        if !self.precomputed {
+            self.object_bounds = self.objects().map(|o| o.parent_space_bounds()).collect();
+            self.precomputed = true;
+        }
+
+        self.validate()
+    }
+
+    /// Whether `precompute` was called since the objects last changed.
+    pub fn is_precomputed(&self) -> bool {
+        self.precomputed
+    }
+
+    /// Build a [`KdTree`] over the objects of the world, used by
+    /// `intersect_world` until an object is added or changed. Worth it for
+    /// scenes with many objects, where most of them are far from each ray.
+    pub fn build_kd_tree(&mut self) {
+        self.kd_tree = Some(KdTree::build(&self.objects));
+    }
+
+    /// Whether `build_kd_tree` was called since the objects last changed.
+    pub fn has_kd_tree(&self) -> bool {
+        self.kd_tree.is_some()
+    }
+
     /// Iterate over all of the objects added to the world. Intersecting each
     /// object with a ray and aggregating the intersections into a single
     /// collection. The collection is sorted.
+    ///
+    /// When the world has a [`KdTree`] only the objects near the ray are
+    /// intersected.
     pub fn intersect_world(&self, r: Ray) -> Option<Vec<Intersection>> {
+        if let Some(tree) = &self.kd_tree {
+            return tree.intersect(r);
+        }
+
         let mut xs: Vec<Intersection> = Vec::new();
-        for o in &self.objects {
+        for (index, o) in self.objects.iter().enumerate() {
+            if self.precomputed {
+                let bounds = &self.object_bounds[index];
+                if bounds.is_finite() && bounds.intersect(r).is_none() {
+                    continue;
+                }
+            }
+
             if let Some(o_xs) = o.intersect(r) {
                 for i in o_xs {
                     xs.push(i);
@@ -56,38 +392,303 @@ impl World {
         }
     }
 
+    /// Intersect every ray of the `batch` with the world, giving the same
+    /// results as calling `intersect_world` for each ray in turn. Each object
+    /// is tested against the whole batch before moving on to the next one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Point, Ray, RayBatch, Vector, World};
+    ///
+    /// let w = World::default();
+    /// let batch: RayBatch = vec![
+    ///     Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+    ///     Ray::new(Point::new(0.0, 5.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+    /// ]
+    /// .into_iter()
+    /// .collect();
+    /// let results = w.intersect_batch(&batch);
+    ///
+    /// assert_eq!(results[0].as_ref().unwrap().len(), 4);
+    /// assert!(results[1].is_none());
+    /// ```
+    pub fn intersect_batch(&self, batch: &RayBatch) -> Vec<Option<Vec<Intersection>>> {
+        if let Some(tree) = &self.kd_tree {
+            return batch.rays().map(|r| tree.intersect(r)).collect();
+        }
+
+        let rays: Vec<Ray> = batch.rays().collect();
+        let mut all_xs: Vec<Vec<Intersection>> = rays.iter().map(|_| Vec::new()).collect();
+        for (index, o) in self.objects.iter().enumerate() {
+            let bounds = self
+                .object_bounds
+                .get(index)
+                .filter(|b| self.precomputed && b.is_finite());
+
+            for (r, xs) in rays.iter().zip(all_xs.iter_mut()) {
+                if bounds.is_some_and(|b| b.intersect(*r).is_none()) {
+                    continue;
+                }
+                if let Some(o_xs) = o.intersect(*r) {
+                    xs.extend(o_xs);
+                }
+            }
+        }
+
+        all_xs
+            .into_iter()
+            .map(|mut xs| {
+                if xs.is_empty() {
+                    None
+                } else {
+                    xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                    Some(xs)
+                }
+            })
+            .collect()
+    }
+
+    /// Find the object visible at the given pixel of the `camera`. Returns the
+    /// closest object intersected by the [`Ray`] through the pixel or `None`
+    /// if the ray hits nothing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{Camera, Point, Transformation, Vector, World};
+    /// use std::f64::consts::PI;
+    ///
+    /// let w = World::default();
+    /// let mut c = Camera::new(11, 11, PI / 2.0);
+    /// c.transform = Transformation::view_transform(
+    ///     Point::new(0.0, 0.0, -5.0),
+    ///     Point::new(0.0, 0.0, 0.0),
+    ///     Vector::new(0.0, 1.0, 0.0),
+    /// );
+    ///
+    /// assert!(w.pick(&c, 5, 5).is_some());
+    /// assert!(w.pick(&c, 0, 0).is_none());
+    /// ```
+    pub fn pick(&self, camera: &Camera, screen_x: usize, screen_y: usize) -> Option<&dyn Shape> {
+        self.pick_intersection(camera, screen_x, screen_y)
+            .map(|i| i.object)
+    }
+
+    /// Find the `hit` for the given pixel of the `camera`. The returned
+    /// [`Intersection`] holds the object and the distance along the ray
+    /// through the pixel, which is enough to position something at the
+    /// point that was hit. Pixels outside of the circle of a fisheye lens
+    /// hit nothing.
+    pub fn pick_intersection(
+        &self,
+        camera: &Camera,
+        screen_x: usize,
+        screen_y: usize,
+    ) -> Option<Intersection<'_>> {
+        let r = camera.visible_ray_for_pixel(screen_x, screen_y)?;
+        self.intersect_world(r)?
+            .into_iter()
+            .filter(|i| i.t >= 0.0)
+            .min()
+    }
+
+    /// The objects at least partly inside the view of the `camera`, found with
+    /// [`Camera::frustum_cull`]. Groups and CSG shapes are returned as a whole,
+    /// and objects without bounds such as planes are always included. The
+    /// index of each object for [`World::get_object_mut`] is given by
+    /// [`Camera::frustum_cull`] in the same order.
+    pub fn find_objects_in_frustum(&self, camera: &Camera) -> Vec<&dyn Shape> {
+        camera
+            .frustum_cull(self)
+            .into_iter()
+            .map(|i| self.objects[i].as_ref())
+            .collect()
+    }
+
     /// Call the `lighting` function for the [`crate::Material`] of a `shape` intersected
     /// by a [`Ray`] to get the [`Color`] at that intersection.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world has no light source: no point `light`, no
+    /// `area_lights`, no `directional_lights` and no `light_probe`.
     pub fn shade_hit(&self, comps: &Computations, remaining: usize) -> Color {
-        let shadowed = self.is_shadow(comps.over_point);
-
         let material = self.get_object_material(comps.object);
-
-        let surface = material.lighting(
-            comps.object,
-            self.light.expect("World has no light source"),
-            comps.over_point,
-            comps.eyev,
-            comps.normalv,
-            shadowed,
-        );
-
+        let surface = self.surface_color(comps);
         let reflected = self.reflected_color(comps, remaining);
         let refracted = self.refracted_color(comps, remaining);
 
         if material.reflective > 0.0 && material.transparency > 0.0 {
-            let reflectance = comps.schlick();
+            let reflectance = comps.fresnel_reflectance();
             surface + reflected * reflectance + refracted * (1.0 - reflectance)
         } else {
             surface + reflected + refracted
         }
     }
 
+    /// The part of [`World::shade_hit`] lit by the light sources of the world,
+    /// without the reflected and refracted light.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world has no light source, like [`World::shade_hit`].
+    pub(crate) fn surface_color(&self, comps: &Computations) -> Color {
+        let material = self.get_object_material(comps.object);
+        assert!(
+            self.light.is_some()
+                || !self.area_lights.is_empty()
+                || !self.directional_lights.is_empty()
+                || self.light_probe.is_some(),
+            "World has no light source"
+        );
+
+        let mut surface = Colors::BLACK;
+        if let Some(light) = self.light {
+            surface = self.direct_lighting(
+                material,
+                comps,
+                |_| light,
+                |point| self.is_shadow_between(point, light.position),
+            );
+
+            if let Some(settings) = self.ambient_occlusion {
+                let factor = self.baked_ao(comps).unwrap_or_else(|| {
+                    ambient_occlusion(self, comps.over_point, comps.normalv, settings.samples)
+                });
+                let color = material
+                    .pattern
+                    .pattern_at_shape(comps.object, comps.over_point);
+                surface = surface - color * light.intensity * material.ambient * (1.0 - factor);
+            }
+        }
+        for light in &self.directional_lights {
+            let lit = self.direct_lighting(
+                material,
+                comps,
+                |point| light.as_point_light(point),
+                |point| self.is_blocked(point, -light.direction, f64::INFINITY),
+            );
+            surface = surface + lit;
+        }
+        for light in &self.area_lights {
+            surface = surface + self.area_lighting(light, material, comps);
+        }
+        if let Some(probe) = &self.light_probe {
+            surface = surface + self.probe_lighting(probe, material, comps);
+        }
+        if let Some(caustics) = &self.caustics {
+            surface = surface + caustics.caustic_lighting(self, comps);
+        }
+        surface
+    }
+
+    /// Light reaching the point of `comps` straight from the light made by
+    /// `light_at` for the point lit, which is dark where `shadowed`.
+    fn direct_lighting(
+        &self,
+        material: &Material,
+        comps: &Computations,
+        light_at: impl Fn(Point) -> PointLight,
+        shadowed: impl Fn(Point) -> bool,
+    ) -> Color {
+        // the far face of a two sided surface is lit by a light behind it
+        let light = light_at(comps.over_point);
+        let (point, normalv) =
+            if material.two_sided && (light.position - comps.over_point).dot(comps.normalv) < 0.0 {
+                (comps.under_point, -comps.normalv)
+            } else {
+                (comps.over_point, comps.normalv)
+            };
+
+        material.lighting(
+            comps.object,
+            light_at(point),
+            point,
+            comps.eyev,
+            normalv,
+            shadowed(point),
+        )
+    }
+
+    /// Light reaching the point of `comps` from the environment of the
+    /// `probe`, the diffuse light from the direction of the normal and the
+    /// specular light from the direction of the reflection, unless another
+    /// object is in the way.
+    fn probe_lighting(
+        &self,
+        probe: &LightProbe,
+        material: &Material,
+        comps: &Computations,
+    ) -> Color {
+        let open = |direction: Vector| {
+            if self.is_blocked(comps.over_point, direction, f64::INFINITY) {
+                Colors::BLACK
+            } else {
+                probe.sample(direction)
+            }
+        };
+        let color = material
+            .pattern
+            .pattern_at_shape(comps.object, comps.over_point);
+
+        color * open(comps.normalv) * material.diffuse
+            + open(comps.reflectv.normalize()) * material.specular
+    }
+
+    /// Light reaching the point of `comps` from the surface of the area
+    /// `light`, averaged over points picked on the surface.
+    ///
+    /// Every unblocked point lights the surface like a [`PointLight`] whose
+    /// intensity falls off with the square of the distance, weighed by the
+    /// area of the surface it stands for. The ambient light is left to the
+    /// `light` of the world.
+    fn area_lighting(
+        &self,
+        light: &AreaLightShape,
+        material: &Material,
+        comps: &Computations,
+    ) -> Color {
+        let point = comps.over_point;
+        let mut rng = StdRng::seed_from_u64(
+            point.x.to_bits().rotate_left(7)
+                ^ point.y.to_bits().rotate_left(23)
+                ^ point.z.to_bits().rotate_left(41),
+        );
+        let area = light.shape.surface_area();
+        let color = material.pattern.pattern_at_shape(comps.object, point);
+
+        let mut total = Colors::BLACK;
+        for _ in 0..light.samples {
+            let (position, normal) = light.shape.sample_surface(&mut rng);
+            let v = position - point;
+            let distance = v.magnitude();
+            let facing = -normal.dot(v / distance);
+            if facing <= 0.0 || self.is_shadow_between(point, position) {
+                continue;
+            }
+
+            let intensity = light.intensity * (facing * area / (PI * distance * distance));
+            let sample = PointLight::new(position, intensity);
+            let lit = material.lighting(
+                comps.object,
+                sample,
+                point,
+                comps.eyev,
+                comps.normalv,
+                false,
+            );
+            total = total + lit - color * intensity * material.ambient;
+        }
+
+        total * (1.0 / light.samples.max(1) as f64)
+    }
+
     /// Returns a [`Color`] for an intersection by doing the following
     ///
     /// 1. Find the [`Intersection`]s of a [`Ray`] by calling `intersect_world`.
     /// 2. Find the `hit` from the resulting intersections.
-    /// 3. Return black if there are no intersections.
+    /// 3. Return the `background` if there are no intersections.
     /// 4. `prepare_computations` on the `hit` to get the [`Computations`] for
     /// the [`Intersection`].
     /// 5. Call `shade_hit` to get the color at the `hit`.
@@ -98,9 +699,9 @@ impl World {
                     let comps = i.prepare_computations(r, &xs, Some(&self));
                     self.shade_hit(&comps, remaining)
                 }
-                None => Colors::BLACK,
+                None => self.background,
             },
-            None => Colors::BLACK,
+            None => self.background,
         }
     }
 
@@ -110,11 +711,20 @@ impl World {
     /// is considered to be in shadow, returning `true` otherwise
     /// return `false`.
     pub fn is_shadow(&self, point: Point) -> bool {
-        let v = self.light.expect("No light in world!").position - point;
+        self.is_shadow_between(point, self.light.expect("No light in world!").position)
+    }
+
+    /// Whether an object blocks the way from `point` to `target`.
+    pub(crate) fn is_shadow_between(&self, point: Point, target: Point) -> bool {
+        let v = target - point;
         let distance = v.magnitude();
-        let direction = v.normalize();
+        self.is_blocked(point, v / distance, distance)
+    }
 
-        let r = Ray::new(point, direction);
+    /// Whether an object is less than `distance` away from `point` along
+    /// the unit vector `direction`.
+    fn is_blocked(&self, point: Point, direction: Vector, distance: f64) -> bool {
+        let r = Ray::new_unit(point, direction);
         if let Some(intersections) = self.intersect_world(r) {
             if let Some(hit) = Intersection::hit(&intersections) {
                 if hit.t < distance {
@@ -157,8 +767,7 @@ impl World {
         if material.reflective == 0.0 || remaining < 1 {
             Colors::BLACK
         } else {
-            let reflect_ray = Ray::new(comps.over_point, comps.reflectv);
-            let color = self.color_at(reflect_ray, remaining - 1);
+            let color = self.color_at(comps.reflect_ray(), remaining - 1);
             color * material.reflective
         }
     }
@@ -168,17 +777,11 @@ impl World {
         if material.transparency == 0.0 || remaining == 0 {
             Colors::BLACK
         } else {
-            let n_ratio = comps.n1 / comps.n2;
-            let cos_i = comps.eyev.dot(comps.normalv);
-            let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
-
-            if sin2_t > 1.0 {
-                Colors::BLACK
-            } else {
-                let cos_t = (1.0 - sin2_t).sqrt();
-                let direction = comps.normalv * (n_ratio * cos_i - cos_t) - comps.eyev * n_ratio;
-                let refract_ray = Ray::new(comps.under_point, direction);
-                self.color_at(refract_ray, remaining - 1) * material.transparency
+            match comps.refract_ray() {
+                Some(refract_ray) => {
+                    self.color_at(refract_ray, remaining - 1) * material.transparency
+                }
+                None => Colors::BLACK,
             }
         }
     }
@@ -192,11 +795,52 @@ impl World {
         }
     }
 
+    /// Iterate over the objects added to the world, without the children of
+    /// groups.
+    pub fn objects(&self) -> impl Iterator<Item = &dyn Shape> {
+        self.objects.iter().map(|o| o.as_ref())
+    }
+
+    /// Number of objects added to the world, counting a group as a single
+    /// object.
+    pub fn count_objects(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Number of primitive shapes in the world, counting the shapes inside
+    /// groups and CSG nodes instead of the containers themselves.
+    pub fn total_primitive_count(&self) -> usize {
+        fn count(object: &dyn Shape) -> usize {
+            let children = object.children();
+            if children.is_empty() {
+                1
+            } else {
+                children.into_iter().map(count).sum()
+            }
+        }
+
+        self.objects().map(count).sum()
+    }
+
+    /// Approximate number of bytes used by the objects of the world, including
+    /// the shapes inside containers and acceleration structures like the
+    /// bounding volume hierarchy of a [`crate::shapes::Mesh`].
+    pub fn memory_estimate_bytes(&self) -> usize {
+        fn size(object: &dyn Shape) -> usize {
+            object.memory_size() + object.children().into_iter().map(size).sum::<usize>()
+        }
+
+        self.objects.capacity() * std::mem::size_of::<Arc<dyn Shape>>()
+            + self.objects().map(size).sum::<usize>()
+    }
+
     /// Returns a mutable reference to an `object` at the given index or `None`
-    /// if index is out of range.
+    /// if index is out of range or the object is shared with another owner.
     pub fn get_object_mut(&mut self, index: usize) -> Option<&mut dyn Shape> {
+        self.precomputed = false;
+        self.kd_tree = None;
         match self.objects.get_mut(index) {
-            Some(o) => Some(o.as_mut()),
+            Some(o) => Arc::get_mut(o),
             None => None,
         }
     }
@@ -243,7 +887,7 @@ impl Default for World {
         ));
 
         let mut s1 = Sphere::new();
-        s1.material.color = Color::new(0.8, 1.0, 0.6);
+        s1.material.set_color(Color::new(0.8, 1.0, 0.6));
         s1.material.diffuse = 0.7;
         s1.material.specular = 0.2;
         w.add_object(Box::new(s1));
@@ -258,7 +902,11 @@ impl Default for World {
 
 #[cfg(test)]
 mod tests {
-    use crate::{patterns::TestPattern, shapes::Group, shapes::Plane, Material, Ray, Vector};
+    use crate::{
+        patterns::TestPattern,
+        shapes::{CsgOperation, Group, Plane, CSG},
+        Material, Ray, Vector,
+    };
 
     use super::*;
 
@@ -278,7 +926,7 @@ mod tests {
     fn the_default_world() {
         let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
         let mut s1 = Sphere::new();
-        s1.material.color = Color::new(0.8, 1.0, 0.6);
+        s1.material.set_color(Color::new(0.8, 1.0, 0.6));
         s1.material.diffuse = 0.7;
         s1.material.specular = 0.2;
         let mut s2 = Sphere::new();
@@ -328,6 +976,25 @@ mod tests {
         assert_eq!(c, Color::new(0.38066, 0.47583, 0.2855));
     }
 
+    #[test]
+    fn ambient_occlusion_darkens_the_ambient_light() {
+        let mut w = World::new();
+        w.light = Some(PointLight::new(
+            Point::new(0.0, 4.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        let mut room = crate::shapes::Cube::new();
+        room.transform = Transformation::new().scale(5.0, 5.0, 5.0).build();
+        w.add_object(Box::new(room));
+        let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+
+        let open = w.color_at(r, 1);
+        w.ambient_occlusion = Some(AmbientOcclusion::new(16, 20.0));
+        let occluded = w.color_at(r, 1);
+
+        assert_eq!(open - occluded, Color::new(0.1, 0.1, 0.1));
+    }
+
     // Chapter 7 Making a Scene
     // Page 95
     #[test]
@@ -358,6 +1025,15 @@ mod tests {
         assert_eq!(c, Color::new(0.0, 0.0, 0.0));
     }
 
+    #[test]
+    fn the_color_when_a_ray_misses_is_the_background() {
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 1.0));
+        let mut w = World::new();
+        w.background = Color::new(0.2, 0.4, 0.8);
+
+        assert_eq!(w.color_at(r, 1), Color::new(0.2, 0.4, 0.8));
+    }
+
     // Chapter 7 Making a Scene
     // Page 96
     #[test]
@@ -384,7 +1060,7 @@ mod tests {
         let r = Ray::new(Point::new(0.0, 0.0, 0.75), Vector::new(0.0, 0.0, -1.0));
         let c = w.color_at(r, 1);
 
-        assert_eq!(c, inner.material().color);
+        assert_eq!(c, inner.material().solid_color().unwrap());
     }
 
     // Chapter 8 Shadows
@@ -428,6 +1104,45 @@ mod tests {
     }
 
     // Chapter 8 Shadows
+    /// A plane without ambient light, lit from above, seen from `eye_y`.
+    fn color_of_a_plane_lit_from_above(two_sided: bool, eye_y: f64) -> Color {
+        let mut w = World::new();
+        w.light = Some(PointLight::new(
+            Point::new(0.0, 10.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        let mut floor = Plane::new();
+        floor.material = Material::new().ambient(0.0).two_sided(two_sided);
+        w.add_object(Box::new(floor));
+
+        let r = Ray::new(
+            Point::new(0.0, eye_y, -1.0),
+            Vector::new(0.0, -eye_y, 1.0).normalize(),
+        );
+        w.color_at(r, 0)
+    }
+
+    #[test]
+    fn a_one_sided_plane_seen_from_below_is_not_lit() {
+        assert_eq!(color_of_a_plane_lit_from_above(false, -1.0), Colors::BLACK);
+    }
+
+    #[test]
+    fn a_two_sided_plane_seen_from_below_is_lit() {
+        let below = color_of_a_plane_lit_from_above(true, -1.0);
+
+        assert_eq!(below, Color::new(0.9, 0.9, 0.9));
+    }
+
+    #[test]
+    fn two_sided_planes_look_the_same_from_above() {
+        assert_eq!(
+            color_of_a_plane_lit_from_above(true, 1.0),
+            color_of_a_plane_lit_from_above(false, 1.0)
+        );
+        assert!(color_of_a_plane_lit_from_above(true, 1.0).red > 0.8);
+    }
+
     // Page 114
     #[test]
     fn shade_hit_is_given_an_intersection_in_shadow() {
@@ -531,6 +1246,22 @@ mod tests {
         w.color_at(r, 1);
     }
 
+    #[test]
+    fn a_ray_that_is_not_of_unit_length_reflects_and_refracts() {
+        let mut w = World::default();
+        let mut glass = Sphere::glass_sphere();
+        glass.material.reflective = 0.5;
+        glass.transform = Transformation::new().translate(0.0, 0.0, -2.0).build();
+        w.add_object(Box::new(glass));
+        let origin = Point::new(0.0, 0.0, -5.0);
+        let direction = Vector::new(0.0, 0.1, 1.0);
+
+        let long = w.color_at(Ray::new(origin, direction * 3.0), 5);
+        let unit = w.color_at(Ray::new(origin, direction.normalize()), 5);
+
+        assert_eq!(long, unit);
+    }
+
     // Chapter 11 Reflection and Refraction
     // Page 147
     #[test]
@@ -619,7 +1350,7 @@ mod tests {
 
         let mut am = Material::new();
         am.ambient = 1.0;
-        am.pattern = Some(Box::new(TestPattern::new()));
+        am.pattern = Box::new(TestPattern::new());
         w.get_object_mut(0).unwrap().set_material(am);
 
         let mut bm = Material::new();
@@ -643,6 +1374,25 @@ mod tests {
         assert_eq!(c, Color::new(0.0, 0.99888, 0.04725));
     }
 
+    #[test]
+    fn a_glass_sphere_under_water_bends_light_less() {
+        let mut w = World::new();
+        w.set_global_refractive_index(1.33);
+        w.add_object(Box::new(Sphere::glass_sphere()));
+        let r = Ray::new(Point::new(0.0, 0.5, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = w.intersect_world(r).unwrap();
+
+        let entering = xs[0].prepare_computations(r, &xs, Some(&w));
+        let leaving = xs[1].prepare_computations(r, &xs, Some(&w));
+
+        assert_eq!((entering.n1, entering.n2), (1.33, 1.5));
+        assert_eq!((leaving.n1, leaving.n2), (1.5, 1.33));
+        // Snell's law, the sine of the angle to the normal shrinks by 1.33 / 1.5
+        let refracted = entering.refract_ray().unwrap().direction;
+        let sin_t = refracted.cross(-entering.normalv).magnitude();
+        assert!(crate::float_eq(sin_t, 0.5 * 1.33 / 1.5));
+    }
+
     // Chapter 11 Reflection and Refraction
     // Page 159
     #[test]
@@ -656,7 +1406,7 @@ mod tests {
         w.add_object(Box::new(floor));
 
         let mut ball = Sphere::new();
-        ball.material.color = Color::new(1.0, 0.0, 0.0);
+        ball.material.set_color(Color::new(1.0, 0.0, 0.0));
         ball.material.ambient = 0.5;
         ball.transform = Transformation::new().translate(0.0, -3.5, -0.5).build();
         w.add_object(Box::new(ball));
@@ -689,7 +1439,7 @@ mod tests {
         w.add_object(Box::new(floor));
 
         let mut ball = Sphere::new();
-        ball.material.color = Color::new(1.0, 0.0, 0.0);
+        ball.material.set_color(Color::new(1.0, 0.0, 0.0));
         ball.material.ambient = 0.5;
         ball.transform = Transformation::new().translate(0.0, -3.5, -0.5).build();
         w.add_object(Box::new(ball));
@@ -707,20 +1457,103 @@ mod tests {
         assert_eq!(c, Color::new(0.93391, 0.69643, 0.69243));
     }
 
+    fn picking_camera() -> Camera {
+        let mut c = Camera::new(11, 11, std::f64::consts::PI / 2.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        c
+    }
+
+    #[test]
+    fn picking_a_sphere_in_the_center_of_the_view() {
+        let mut w = World::new();
+        let s = Sphere::new();
+        let s_id = s.id();
+        w.add_object(Box::new(s));
+
+        let picked = w.pick(&picking_camera(), 5, 5).expect("Nothing picked!");
+
+        assert_eq!(picked.id(), s_id);
+    }
+
+    #[test]
+    fn picking_empty_space() {
+        let mut w = World::new();
+        w.add_object(Box::new(Sphere::new()));
+
+        assert!(w.pick(&picking_camera(), 0, 0).is_none());
+    }
+
+    #[test]
+    fn picking_returns_the_front_object() {
+        let mut w = World::new();
+        let mut back = Sphere::new();
+        back.transform = Transformation::new().translate(0.0, 0.0, 3.0).build();
+        w.add_object(Box::new(back));
+        let front = Sphere::new();
+        let front_id = front.id();
+        w.add_object(Box::new(front));
+
+        let i = w
+            .pick_intersection(&picking_camera(), 5, 5)
+            .expect("Nothing picked!");
+
+        assert_eq!(i.object.id(), front_id);
+        assert_eq!(i.t, 4.0);
+    }
+
+    #[test]
+    fn finding_the_objects_seen_by_a_camera() {
+        let mut w = World::new();
+        let front = Sphere::new();
+        let front_id = front.id();
+        w.add_object(Box::new(front));
+        let mut behind = Sphere::new();
+        behind.transform = Transformation::new().translate(0.0, 0.0, -10.0).build();
+        w.add_object(Box::new(behind));
+
+        let found = w.find_objects_in_frustum(&picking_camera());
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id(), front_id);
+    }
+
+    #[test]
+    fn finding_a_csg_in_view_returns_it_whole() {
+        let mut w = World::new();
+        let mut right = Sphere::new();
+        right.transform = Transformation::new().translate(0.5, 0.0, 0.0).build();
+        let csg = CSG::new(
+            CsgOperation::Union,
+            Box::new(Sphere::new()),
+            Box::new(right),
+        );
+        let csg_id = csg.id();
+        w.add_object(Box::new(csg));
+
+        let found = w.find_objects_in_frustum(&picking_camera());
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id(), csg_id);
+    }
+
     #[test]
     fn get_material_from_top_group() {
         let mut w = World::new();
 
         let mut ball = Sphere::new();
-        ball.material.color = Color::new(1.0, 0.0, 0.0);
+        ball.material.set_color(Color::new(1.0, 0.0, 0.0));
         ball.inherit_material = true;
         let ball_id = ball.id();
 
         let mut g1 = Group::new();
-        g1.material.color = Color::new(0.0, 1.0, 0.0);
+        g1.material.set_color(Color::new(0.0, 1.0, 0.0));
 
         let mut g2 = Group::new();
-        g2.material.color = Color::new(0.0, 0.0, 1.0);
+        g2.material.set_color(Color::new(0.0, 0.0, 1.0));
         g2.inherit_material = true;
 
         g2.add_object(Box::new(ball));
@@ -730,7 +1563,7 @@ mod tests {
         let test_object = w.get_object_by_id(ball_id).unwrap();
         let m = w.get_object_material(test_object);
 
-        assert_eq!(m.color, Color::new(0.0, 1.0, 0.0));
+        assert_eq!(m.solid_color(), Some(Color::new(0.0, 1.0, 0.0)));
     }
 
     #[test]
@@ -738,15 +1571,15 @@ mod tests {
         let mut w = World::new();
 
         let mut ball = Sphere::new();
-        ball.material.color = Color::new(1.0, 0.0, 0.0);
+        ball.material.set_color(Color::new(1.0, 0.0, 0.0));
         ball.inherit_material = true;
         let ball_id = ball.id();
 
         let mut g1 = Group::new();
-        g1.material.color = Color::new(0.0, 1.0, 0.0);
+        g1.material.set_color(Color::new(0.0, 1.0, 0.0));
 
         let mut g2 = Group::new();
-        g2.material.color = Color::new(0.0, 0.0, 1.0);
+        g2.material.set_color(Color::new(0.0, 0.0, 1.0));
 
         g2.add_object(Box::new(ball));
         g1.add_object(Box::new(g2));
@@ -755,7 +1588,7 @@ mod tests {
         let test_object = w.get_object_by_id(ball_id).unwrap();
         let m = w.get_object_material(test_object);
 
-        assert_eq!(m.color, Color::new(0.0, 0.0, 1.0));
+        assert_eq!(m.solid_color(), Some(Color::new(0.0, 0.0, 1.0)));
     }
 
     #[test]
@@ -763,14 +1596,14 @@ mod tests {
         let mut w = World::new();
 
         let mut ball = Sphere::new();
-        ball.material.color = Color::new(1.0, 0.0, 0.0);
+        ball.material.set_color(Color::new(1.0, 0.0, 0.0));
         let ball_id = ball.id();
 
         let mut g1 = Group::new();
-        g1.material.color = Color::new(0.0, 1.0, 0.0);
+        g1.material.set_color(Color::new(0.0, 1.0, 0.0));
 
         let mut g2 = Group::new();
-        g2.material.color = Color::new(0.0, 0.0, 1.0);
+        g2.material.set_color(Color::new(0.0, 0.0, 1.0));
 
         g2.add_object(Box::new(ball));
         g1.add_object(Box::new(g2));
@@ -779,6 +1612,514 @@ mod tests {
         let test_object = w.get_object_by_id(ball_id).unwrap();
         let m = w.get_object_material(test_object);
 
-        assert_eq!(m.color, Color::new(1.0, 0.0, 0.0));
+        assert_eq!(m.solid_color(), Some(Color::new(1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn an_empty_world_has_no_objects() {
+        let w = World::new();
+
+        assert_eq!(w.count_objects(), 0);
+        assert_eq!(w.total_primitive_count(), 0);
+        assert_eq!(w.memory_estimate_bytes(), 0);
+    }
+
+    #[test]
+    fn counting_the_spheres_of_a_group() {
+        let mut w = World::new();
+        let mut g = Group::new();
+        for _ in 0..5 {
+            g.add_object(Box::new(Sphere::new()));
+        }
+        w.add_object(Box::new(g));
+
+        assert_eq!(w.count_objects(), 1);
+        assert_eq!(w.total_primitive_count(), 5);
+        assert!(w.memory_estimate_bytes() > 5 * std::mem::size_of::<Sphere>());
+    }
+
+    #[test]
+    fn counting_the_leaves_of_a_nested_csg_tree() {
+        use crate::shapes::{CsgOperation, CSG};
+
+        let inner = CSG::new(
+            CsgOperation::Difference,
+            Box::new(Sphere::new()),
+            Box::new(Plane::new()),
+        );
+        let mut g = Group::new();
+        g.add_object(Box::new(Sphere::new()));
+        g.add_object(Box::new(Sphere::new()));
+        let outer = CSG::new(CsgOperation::Union, Box::new(inner), Box::new(g));
+        let mut w = World::new();
+        w.add_object(Box::new(outer));
+        w.add_object(Box::new(Plane::new()));
+
+        assert_eq!(w.count_objects(), 2);
+        assert_eq!(w.total_primitive_count(), 5);
+    }
+
+    #[test]
+    fn the_memory_estimate_includes_the_mesh_hierarchy() {
+        use crate::shapes::Mesh;
+
+        let vertices: Vec<Point> = (0..30)
+            .map(|i| Point::new(i as f64, (i % 2) as f64, 0.0))
+            .collect();
+        let faces: Vec<(usize, usize, usize)> = (0..28).map(|i| (i, i + 1, i + 2)).collect();
+        let mesh = Mesh::new(vertices, Vec::new(), faces);
+        let mut w = World::new();
+        w.add_object(Box::new(mesh));
+
+        let geometry = 30 * std::mem::size_of::<Point>() + 28 * 3 * std::mem::size_of::<usize>();
+        assert!(w.memory_estimate_bytes() > std::mem::size_of::<Mesh>() + geometry);
+    }
+
+    #[test]
+    fn a_world_and_a_camera_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<World>();
+        assert_send_sync::<Camera>();
+    }
+
+    #[test]
+    fn intersecting_a_world_from_two_threads() {
+        let w = World::default();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        let ts: Vec<Vec<f64>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| {
+                    scope.spawn(|| w.intersect_world(r).unwrap().iter().map(|i| i.t).collect())
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        assert_eq!(ts[0], vec![4.0, 4.5, 5.5, 6.0]);
+        assert_eq!(ts[0], ts[1]);
+    }
+
+    #[test]
+    fn a_shared_object_can_not_be_changed_through_a_world() {
+        let s: Arc<dyn Shape> = Arc::new(Sphere::new());
+        let mut a = World::new();
+        let mut b = World::new();
+        a.add_shared_object(s.clone());
+        b.add_shared_object(s.clone());
+
+        assert!(a.get_object(0).unwrap().shape_eq(b.get_object(0).unwrap()));
+        assert!(a.get_object_mut(0).is_none());
+        drop(s);
+        drop(b);
+        assert!(a.get_object_mut(0).is_some());
+    }
+
+    #[test]
+    fn changing_an_object_of_a_deep_clone_leaves_the_original() {
+        let mut w = World::default();
+        let mut g = Group::new();
+        g.add_object(Box::new(Sphere::new()));
+        w.add_shared_object(Arc::new(g));
+        let mut copy = w.clone_deep();
+
+        copy.get_object_mut(0).unwrap().material_mut().ambient = 1.0;
+        copy.get_object_mut(2)
+            .unwrap()
+            .set_transform(Transformation::new().translate(1.0, 0.0, 0.0).build());
+
+        assert_eq!(w.get_object(0).unwrap().material().ambient, 0.1);
+        assert_eq!(w.get_object(2).unwrap().transform(), crate::IDENTITY);
+        assert!(w
+            .get_object(2)
+            .unwrap()
+            .shape_eq(copy.get_object(2).unwrap()));
+        assert_eq!(
+            w.get_object(2).unwrap().children()[0].id(),
+            copy.get_object(2).unwrap().children()[0].id()
+        );
+    }
+
+    #[test]
+    fn translating_the_world_moves_the_intersections() {
+        let mut w = World::default();
+        let r = Ray::new(Point::new(0.1, 0.2, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let shift = Vector::new(1.0, 0.0, 0.0);
+        let before: Vec<Point> = w
+            .intersect_world(r)
+            .unwrap()
+            .iter()
+            .map(|i| r.position(i.t) + shift)
+            .collect();
+        w.transform_all(Transformation::new().translate(1.0, 0.0, 0.0).build());
+        let moved = Ray::new(r.origin + shift, r.direction);
+        let after: Vec<Point> = w
+            .intersect_world(moved)
+            .unwrap()
+            .iter()
+            .map(|i| moved.position(i.t))
+            .collect();
+
+        assert_eq!(before.len(), 4);
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    fn the_light_follows_the_transform_of_the_world() {
+        let mut w = World::default();
+        let shift = Vector::new(1.0, 0.0, 0.0);
+        let points = [
+            Point::new(0.0, 10.0, 0.0),
+            Point::new(10.0, -10.0, 10.0),
+            Point::new(-20.0, 20.0, -20.0),
+            Point::new(-2.0, 2.0, -2.0),
+        ];
+        let before: Vec<bool> = points.iter().map(|p| w.is_shadow(*p)).collect();
+        w.transform_all(Transformation::new().translate(1.0, 0.0, 0.0).build());
+        let after: Vec<bool> = points.iter().map(|p| w.is_shadow(*p + shift)).collect();
+
+        assert_eq!(before, vec![false, true, false, false]);
+        assert_eq!(after, before);
+        assert_eq!(w.light.unwrap().position, Point::new(-9.0, 10.0, -10.0));
+    }
+
+    #[test]
+    fn transforming_the_world_drops_the_baked_lighting() {
+        let mut w = World::default();
+        w.ao_map = Some(w.ambient_occlusion_precompute(4));
+        w.caustics = Some(PhotonMap::build_caustics(&w, 100, 0));
+        w.transform_all(Transformation::new().translate(1.0, 0.0, 0.0).build());
+
+        assert!(w.ao_map.is_none());
+        assert!(w.caustics.is_none());
+    }
+
+    #[test]
+    fn a_deep_clone_renders_the_same_image() {
+        let mut w = World::default();
+        w.add_object(Box::new(Plane::new()));
+        w.build_kd_tree();
+        let copy = w.clone_deep();
+        let mut c = Camera::new(21, 11, std::f64::consts::PI / 2.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.5, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+
+        assert_eq!(
+            c.render(&w).canvas_to_rgb_buffer(),
+            c.render(&copy).canvas_to_rgb_buffer()
+        );
+    }
+
+    #[test]
+    fn precomputing_twice_is_safe() {
+        let mut w = World::default();
+
+        assert_eq!(w.precompute(), w.precompute());
+        assert!(w.is_precomputed());
+
+        w.add_object(Box::new(Plane::new()));
+        assert!(!w.is_precomputed());
+        w.precompute();
+        assert_eq!(w.object_bounds.len(), 3);
+    }
+
+    #[test]
+    fn precomputing_does_not_change_the_render() {
+        let mut w = World::default();
+        let mut far = Sphere::new();
+        far.transform = Transformation::new().translate(4.0, 3.0, 2.0).build();
+        w.add_object(Box::new(far));
+        w.add_object(Box::new(Plane::new()));
+        let mut c = Camera::new(21, 11, std::f64::consts::PI / 2.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.5, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+
+        let before = c.render(&w);
+        w.precompute();
+        let after = c.render(&w);
+
+        for y in 0..c.vsize {
+            for x in 0..c.hsize {
+                assert_eq!(before.pixel_at(x, y), after.pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn a_kd_tree_does_not_change_the_render() {
+        let mut w = World::default();
+        for i in 0..20 {
+            let mut s = Sphere::new();
+            s.transform = Transformation::new()
+                .scale(0.3, 0.3, 0.3)
+                .translate(i as f64 * 0.5 - 5.0, (i % 4) as f64 - 1.5, 1.0)
+                .build();
+            w.add_object(Box::new(s));
+        }
+        w.add_object(Box::new(Plane::new()));
+        let mut c = Camera::new(21, 11, std::f64::consts::PI / 2.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.5, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+
+        let before = c.render(&w);
+        w.build_kd_tree();
+        assert!(w.has_kd_tree());
+        let after = c.render(&w);
+
+        for y in 0..c.vsize {
+            for x in 0..c.hsize {
+                assert_eq!(before.pixel_at(x, y), after.pixel_at(x, y));
+            }
+        }
+
+        w.add_object(Box::new(Sphere::new()));
+        assert!(!w.has_kd_tree());
+    }
+
+    #[test]
+    fn a_deep_copy_of_a_world_with_a_kd_tree_owns_its_tree() {
+        let mut w = World::default();
+        w.build_kd_tree();
+        let copy = w.clone_deep();
+        assert!(copy.has_kd_tree());
+
+        // the tree of the copy finds the objects of the copy
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = copy.intersect_world(r).expect("No intersections!");
+        let address = |s: &dyn Shape| s as *const dyn Shape as *const u8;
+        let hit = address(Intersection::hit(&xs).unwrap().object);
+        assert!(copy.objects().any(|o| address(o) == hit));
+        assert!(!w.objects().any(|o| address(o) == hit));
+
+        // and does not keep the objects of the original shared
+        let copy = w.clone_deep();
+        w.get_object_mut(0)
+            .expect("the objects of the original are not shared")
+            .material_mut()
+            .set_color(Color::new(1.0, 0.0, 0.0));
+        assert_ne!(
+            w.get_object(0).unwrap().material(),
+            copy.get_object(0).unwrap().material()
+        );
+    }
+
+    #[test]
+    fn intersecting_a_batch_matches_intersecting_each_ray() {
+        let mut w = World::default();
+        w.add_object(Box::new(Plane::new()));
+        let batch: RayBatch = (0..16)
+            .map(|i| {
+                let angle = i as f64 * 0.4;
+                Ray::new(
+                    Point::new(angle.cos() * 5.0, 0.5, angle.sin() * 5.0),
+                    Vector::new(-angle.cos(), -0.1 * i as f64, -angle.sin()).normalize(),
+                )
+            })
+            .collect();
+
+        for precompute in [false, true] {
+            if precompute {
+                w.precompute();
+            }
+            let results = w.intersect_batch(&batch);
+
+            assert_eq!(results.len(), 16);
+            for (r, xs) in batch.rays().zip(results) {
+                let expected: Vec<f64> = w
+                    .intersect_world(r)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|i| i.t)
+                    .collect();
+                let found: Vec<f64> = xs.unwrap_or_default().iter().map(|i| i.t).collect();
+                assert_eq!(found, expected);
+            }
+        }
+    }
+
+    /// A floor lit only by a disc of `radius` facing down from `height`.
+    fn area_lit_floor(radius: f64, height: f64) -> World {
+        let mut w = World::new();
+        w.add_object(Box::new(Plane::new()));
+        let mut emitter = crate::shapes::Disc::new();
+        emitter.radius = radius;
+        emitter.transform = Transformation::new()
+            .rotate_x(PI)
+            .translate(0.0, height, 0.0)
+            .build();
+        w.add_area_light(Box::new(emitter), Color::new(1.0, 1.0, 1.0));
+        w.area_lights[0].samples = 64;
+        w
+    }
+
+    /// Brightness of the floor of `w` at `x` along the x axis.
+    fn floor_brightness(w: &World, x: f64) -> f64 {
+        let r = Ray::new(Point::new(x, 0.5, 0.0), Vector::new(0.0, -1.0, 0.0));
+        w.color_at(r, 0).red
+    }
+
+    #[test]
+    fn an_area_light_illuminates_the_floor_under_it() {
+        let w = area_lit_floor(1.0, 2.0);
+
+        assert!(floor_brightness(&w, 0.0) > 0.1);
+        // a disc facing up lights nothing below it
+        let mut w = area_lit_floor(1.0, 2.0);
+        w.area_lights[0]
+            .shape
+            .set_transform(Transformation::new().translate(0.0, 2.0, 0.0).build());
+        assert_eq!(floor_brightness(&w, 0.0), 0.0);
+    }
+
+    #[test]
+    fn the_light_of_an_area_light_falls_off_with_distance() {
+        let near = floor_brightness(&area_lit_floor(1.0, 2.0), 0.0);
+        let far = floor_brightness(&area_lit_floor(1.0, 4.0), 0.0);
+
+        assert!(far < near / 3.0);
+        assert!(floor_brightness(&area_lit_floor(1.0, 2.0), 3.0) < near);
+    }
+
+    #[test]
+    fn an_object_in_front_of_an_area_light_casts_a_soft_shadow() {
+        let mut w = area_lit_floor(1.0, 4.0);
+        let mut blocker = Sphere::new();
+        blocker.transform = Transformation::new()
+            .scale(0.5, 0.5, 0.5)
+            .translate(0.0, 2.0, 0.0)
+            .build();
+        w.add_object(Box::new(blocker));
+
+        let lit = floor_brightness(&w, 4.0);
+        let penumbra = floor_brightness(&w, 1.5);
+        let umbra = floor_brightness(&w, 0.0);
+
+        assert_eq!(umbra, 0.0);
+        assert!(penumbra > umbra);
+        assert!(penumbra < floor_brightness(&area_lit_floor(1.0, 4.0), 1.5));
+        assert!(lit > 0.0);
+    }
+
+    /// A matte floor lit only by sunlight coming down at 45° towards +x.
+    fn sunlit_floor() -> World {
+        let mut w = World::new();
+        let mut floor = Plane::new();
+        floor.material = Material::new().ambient(0.0).specular(0.0);
+        w.add_object(Box::new(floor));
+        w.sky_light(Vector::new(1.0, -1.0, 0.0), Color::new(1.0, 1.0, 1.0));
+        w
+    }
+
+    /// Color of the floor of `w` at `x` and `z`, seen from straight above.
+    fn floor_color(w: &World, x: f64, z: f64) -> Color {
+        let r = Ray::new(Point::new(x, 0.5, z), Vector::new(0.0, -1.0, 0.0));
+        w.color_at(r, 0)
+    }
+
+    #[test]
+    fn a_directional_light_shades_a_plane_by_the_cosine_of_its_angle() {
+        let w = sunlit_floor();
+        let expected = 0.9 * (PI / 4.0).cos();
+
+        assert_eq!(
+            floor_color(&w, 0.0, 0.0),
+            Color::new(expected, expected, expected)
+        );
+        assert!(w.validate().is_empty());
+    }
+
+    #[test]
+    fn an_object_casts_a_shadow_along_a_directional_light() {
+        let mut w = sunlit_floor();
+        let mut ball = Sphere::new();
+        ball.transform = Transformation::new()
+            .scale(0.5, 0.5, 0.5)
+            .translate(0.0, 2.0, 0.0)
+            .build();
+        w.add_object(Box::new(ball));
+
+        assert_eq!(floor_color(&w, 2.0, 0.0), Colors::BLACK);
+        assert_ne!(floor_color(&w, 0.0, 0.0), Colors::BLACK);
+    }
+
+    #[test]
+    fn a_directional_light_does_not_fade_with_distance() {
+        let mut w = sunlit_floor();
+        let near = floor_color(&w, 0.0, 0.0);
+        let mut ball = Sphere::new();
+        ball.transform = Transformation::new()
+            .scale(0.5, 0.5, 0.5)
+            .translate(-1000.0, 1000.0, 0.0)
+            .build();
+        w.add_object(Box::new(ball));
+
+        assert_eq!(floor_color(&w, 0.0, 0.0), Colors::BLACK);
+        assert_eq!(floor_color(&w, 5000.0, 300.0), near);
+    }
+
+    /// A sphere lit only by an environment colored by `sky` for each row and
+    /// column of a 16 by 8 image.
+    fn sphere_in_environment(sky: impl Fn(usize, usize) -> Color + Send + Sync) -> World {
+        let mut w = World::new();
+        let mut ball = Sphere::new();
+        ball.material = Material::new().specular(0.0);
+        w.add_object(Box::new(ball));
+        w.light_probe = Some(LightProbe::new(&crate::Canvas::parallel_fill(16, 8, sky)));
+        w
+    }
+
+    #[test]
+    fn a_white_environment_lights_a_sphere_the_same_from_every_side() {
+        let w = sphere_in_environment(|_, _| Color::new(1.0, 1.0, 1.0));
+        let looking_at_the_center_from = |eye: Point| {
+            let r = Ray::new(eye, (Point::new(0.0, 0.0, 0.0) - eye).normalize());
+            w.color_at(r, 0)
+        };
+
+        let front = looking_at_the_center_from(Point::new(0.0, 0.0, -5.0));
+        assert_eq!(front, Color::new(0.9, 0.9, 0.9));
+        for eye in [
+            Point::new(5.0, 0.0, 0.0),
+            Point::new(0.0, 5.0, 0.0),
+            Point::new(0.0, -5.0, 0.0),
+            Point::new(-3.0, 3.0, 3.0),
+        ] {
+            assert_eq!(looking_at_the_center_from(eye), front);
+        }
+    }
+
+    #[test]
+    fn a_bright_direction_of_the_environment_makes_a_highlight() {
+        let mut w = sphere_in_environment(|_, y| {
+            if y == 0 {
+                Color::new(5.0, 5.0, 5.0)
+            } else {
+                Colors::BLACK
+            }
+        });
+        w.get_object_mut(0).unwrap().material_mut().diffuse = 0.0;
+        w.get_object_mut(0).unwrap().material_mut().specular = 0.9;
+
+        let down = Vector::new(0.0, -1.0, 0.0);
+        let top = w.color_at(Ray::new(Point::new(0.0, 5.0, 0.0), down), 0);
+        let side = w.color_at(Ray::new(Point::new(0.8, 5.0, 0.0), down), 0);
+        let front = w.color_at(
+            Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+            0,
+        );
+
+        assert_eq!(top, Color::new(4.5, 4.5, 4.5));
+        assert_eq!(side, Colors::BLACK);
+        assert_eq!(front, Colors::BLACK);
     }
 }
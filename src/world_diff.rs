@@ -0,0 +1,274 @@
+use uuid::Uuid;
+
+use crate::{shapes::Shape, Bounds, Camera, Canvas, Matrix, World};
+
+/// Width and height in pixels of the tiles re-rendered by
+/// [`Camera::render_incremental`].
+const TILE_SIZE: usize = 16;
+
+/// The changes to the objects of a world from one state to the next, found by
+/// [`World::diff`].
+///
+/// Objects are matched by their id, so an object keeps being the same object
+/// when it moves. Only the objects added to the world are compared, not the
+/// shapes inside groups.
+#[derive(Debug, Clone)]
+pub struct WorldDiff {
+    /// Objects of the new world that are not in the old one, with their ids.
+    pub added: Vec<(Uuid, Box<dyn Shape>)>,
+    /// Ids of the objects of the old world that are not in the new one.
+    pub removed: Vec<Uuid>,
+    /// Ids of the objects whose transformation changed, with the new
+    /// transformation.
+    pub transformed: Vec<(Uuid, Matrix)>,
+    /// Boxes in world space around the changed objects, both where they were
+    /// and where they are now.
+    changed_bounds: Vec<Bounds>,
+}
+
+impl WorldDiff {
+    /// Whether no object was added, removed or moved.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.transformed.is_empty()
+    }
+}
+
+impl World {
+    /// The objects added, removed and moved to get from `self` to `new`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{shapes::Shape, Transformation, World};
+    ///
+    /// let old = World::default();
+    /// let mut new = old.clone_deep();
+    /// let m = Transformation::new().translate(1.0, 0.0, 0.0).build();
+    /// new.get_object_mut(0).unwrap().set_transform(m);
+    ///
+    /// let diff = old.diff(&new);
+    ///
+    /// assert_eq!(diff.transformed, vec![(new.get_object(0).unwrap().id(), m)]);
+    /// assert!(diff.added.is_empty() && diff.removed.is_empty());
+    /// ```
+    pub fn diff(&self, new: &World) -> WorldDiff {
+        let mut diff = WorldDiff {
+            added: Vec::new(),
+            removed: Vec::new(),
+            transformed: Vec::new(),
+            changed_bounds: Vec::new(),
+        };
+
+        for old_object in self.objects() {
+            match new.objects().find(|o| o.id() == old_object.id()) {
+                None => {
+                    diff.removed.push(old_object.id());
+                    diff.changed_bounds.push(old_object.parent_space_bounds());
+                }
+                Some(new_object) if new_object.transform() != old_object.transform() => {
+                    diff.transformed
+                        .push((new_object.id(), new_object.transform()));
+                    diff.changed_bounds.push(old_object.parent_space_bounds());
+                    diff.changed_bounds.push(new_object.parent_space_bounds());
+                }
+                Some(_) => {}
+            }
+        }
+
+        for new_object in new.objects() {
+            if !self.objects().any(|o| o.id() == new_object.id()) {
+                diff.added.push((new_object.id(), new_object.clone_box()));
+                diff.changed_bounds.push(new_object.parent_space_bounds());
+            }
+        }
+
+        diff
+    }
+}
+
+impl Camera {
+    /// Update `prev`, the image of a world before the changes of `diff`, into
+    /// the image of `world` after them, rendering again only the tiles of the
+    /// canvas the changed objects cover.
+    ///
+    /// The tiles are found from the boxes around the objects where they were
+    /// and where they are now, so shadows and reflections of the objects
+    /// falling outside of those boxes are not updated. Panoramic and fisheye
+    /// cameras, and objects without an end or behind the camera, render the
+    /// whole canvas again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prev` is not the size of the canvas of the camera.
+    pub fn render_incremental(&self, world: &World, diff: &WorldDiff, prev: &Canvas) -> Canvas {
+        assert!(
+            prev.width == self.hsize && prev.height == self.vsize,
+            "the previous image is {}x{}, not {}x{}",
+            prev.width,
+            prev.height,
+            self.hsize,
+            self.vsize
+        );
+
+        let columns = self.hsize.div_ceil(TILE_SIZE);
+        let rows = self.vsize.div_ceil(TILE_SIZE);
+        let mut dirty = vec![false; columns * rows];
+        for bounds in &diff.changed_bounds {
+            if let Some((x0, y0, x1, y1)) = self.pixel_rect(bounds) {
+                for row in y0 / TILE_SIZE..y1.div_ceil(TILE_SIZE) {
+                    for column in x0 / TILE_SIZE..x1.div_ceil(TILE_SIZE) {
+                        dirty[row * columns + column] = true;
+                    }
+                }
+            }
+        }
+
+        let mut canvas = prev.clone();
+        for row in 0..rows {
+            for column in 0..columns {
+                if !dirty[row * columns + column] {
+                    continue;
+                }
+
+                let (x, y) = (column * TILE_SIZE, row * TILE_SIZE);
+                let width = TILE_SIZE.min(self.hsize - x);
+                let height = TILE_SIZE.min(self.vsize - y);
+                let pixels = self.render_tile(world, x, y, width, height);
+                for (i, color) in pixels.into_iter().enumerate() {
+                    canvas.write_pixel(x + i % width, y + i / width, color);
+                }
+            }
+        }
+        canvas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{shapes::Sphere, Color, Point, PointLight, Transformation, Vector};
+    use std::f64::consts::PI;
+
+    fn camera() -> Camera {
+        let mut c = Camera::new(96, 96, PI / 2.0);
+        c.transform = Transformation::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        c
+    }
+
+    /// A world with a small sphere on the left and one on the right.
+    fn two_spheres() -> World {
+        let mut w = World::new();
+        w.light = Some(PointLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        for x in [-3.0, 3.0] {
+            let mut s = Sphere::new();
+            s.transform = Transformation::new()
+                .scale(0.5, 0.5, 0.5)
+                .translate(x, 0.0, 0.0)
+                .build();
+            w.add_object(Box::new(s));
+        }
+        w
+    }
+
+    fn move_object(w: &mut World, index: usize, x: f64) {
+        let m = Transformation::new()
+            .scale(0.5, 0.5, 0.5)
+            .translate(x, 0.0, 0.0)
+            .build();
+        w.get_object_mut(index).unwrap().set_transform(m);
+    }
+
+    #[test]
+    fn diffing_finds_added_removed_and_moved_objects() {
+        let old = two_spheres();
+        let mut new = old.clone_deep();
+        move_object(&mut new, 0, -2.0);
+        let mut added = Sphere::new();
+        added.transform = Transformation::new().translate(0.0, 3.0, 0.0).build();
+        let added_id = added.id();
+        new.add_object(Box::new(added));
+
+        let diff = old.diff(&new);
+
+        assert_eq!(diff.transformed.len(), 1);
+        assert_eq!(diff.transformed[0].0, old.get_object(0).unwrap().id());
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].0, added_id);
+        assert!(diff.removed.is_empty());
+
+        let back = new.diff(&old);
+        assert_eq!(back.removed, vec![added_id]);
+        assert!(back.added.is_empty());
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn only_tiles_near_a_moved_sphere_are_rendered_again() {
+        let c = camera();
+        let old = two_spheres();
+        let mut new = old.clone_deep();
+        move_object(&mut new, 0, -2.5);
+        let diff = old.diff(&new);
+
+        // mark a pixel of the tiles far from the moved sphere, so it shows
+        // whether the tile was rendered again
+        let mut prev = c.render(&old);
+        let marker = Color::new(0.0, 1.0, 0.0);
+        prev.write_pixel(90, 90, marker);
+        prev.write_pixel(5, 5, marker);
+
+        let image = c.render_incremental(&new, &diff, &prev);
+        let expected = c.render(&new);
+
+        assert_eq!(image.pixel_at(90, 90), marker);
+        assert_eq!(image.pixel_at(5, 5), marker);
+        // the sphere on the left of the world is on the left of the image
+        // too, and moved to the right by 0.5 world units, about 5 pixels
+        let mut changed = 0;
+        for y in 0..c.vsize {
+            for x in 0..c.hsize {
+                if (x, y) == (90, 90) || (x, y) == (5, 5) {
+                    continue;
+                }
+                assert_eq!(image.pixel_at(x, y), expected.pixel_at(x, y));
+                if image.pixel_at(x, y) != prev.pixel_at(x, y) {
+                    changed += 1;
+                }
+            }
+        }
+        assert!(changed > 0);
+    }
+
+    #[test]
+    fn an_unchanged_world_keeps_the_previous_image() {
+        let c = camera();
+        let w = two_spheres();
+        let mut prev = Canvas::new(c.hsize, c.vsize);
+        prev.write_pixel(48, 48, Color::new(1.0, 0.0, 0.0));
+
+        let image = c.render_incremental(&w, &w.diff(&w), &prev);
+
+        assert_eq!(image.pixel_at(48, 48), Color::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn an_object_without_an_end_renders_the_whole_canvas() {
+        let c = camera();
+        let old = two_spheres();
+        let mut new = old.clone_deep();
+        new.add_object(Box::new(crate::shapes::Plane::new()));
+        let mut prev = Canvas::new(c.hsize, c.vsize);
+        prev.write_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+
+        let image = c.render_incremental(&new, &old.diff(&new), &prev);
+
+        assert_eq!(image.pixel_at(0, 0), c.render(&new).pixel_at(0, 0));
+    }
+}
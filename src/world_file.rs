@@ -0,0 +1,379 @@
+use std::{error::Error, fmt, fs, io, path::Path, path::PathBuf};
+
+use crate::{shapes::deserialize_shape, World};
+
+/// File formats a [`World`] can be saved to, selected by the extension of
+/// the file.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// Error returned by [`World::serialize_to_file`].
+#[derive(Debug)]
+pub enum SerializeError {
+    /// The extension of the path is not one of `.json`, `.yaml`, `.yml` or
+    /// `.toml`.
+    UnsupportedFormat(PathBuf),
+    /// The world could not be converted to the format.
+    Format(String),
+    /// The file could not be written.
+    Io(io::Error),
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerializeError::UnsupportedFormat(path) => {
+                write!(f, "unsupported world file format: {}", path.display())
+            }
+            SerializeError::Format(message) => write!(f, "failed to serialize world: {}", message),
+            SerializeError::Io(e) => write!(f, "failed to write world file: {}", e),
+        }
+    }
+}
+
+impl Error for SerializeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SerializeError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Error returned by [`World::deserialize_from_file`].
+#[derive(Debug)]
+pub enum DeserializeError {
+    /// There is no file at the path.
+    FileNotFound(PathBuf),
+    /// The extension of the path is not one of `.json`, `.yaml`, `.yml` or
+    /// `.toml`.
+    UnsupportedFormat(PathBuf),
+    /// The content of the file is not a valid world. The `line` is the one
+    /// based line the parser stopped at, when the format reports it.
+    ParseError {
+        line: Option<usize>,
+        message: String,
+    },
+    /// The file could not be read.
+    Io(io::Error),
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserializeError::FileNotFound(path) => {
+                write!(f, "world file not found: {}", path.display())
+            }
+            DeserializeError::UnsupportedFormat(path) => {
+                write!(f, "unsupported world file format: {}", path.display())
+            }
+            DeserializeError::ParseError {
+                line: Some(line),
+                message,
+            } => write!(f, "failed to parse world at line {}: {}", line, message),
+            DeserializeError::ParseError {
+                line: None,
+                message,
+            } => write!(f, "failed to parse world: {}", message),
+            DeserializeError::Io(e) => write!(f, "failed to read world file: {}", e),
+        }
+    }
+}
+
+impl Error for DeserializeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DeserializeError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Remove every field set to `null` from the objects in `value`.
+fn remove_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            *map = std::mem::take(map)
+                .into_iter()
+                .filter(|(_, v)| !v.is_null())
+                .collect();
+            map.values_mut().for_each(remove_nulls);
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(remove_nulls),
+        _ => (),
+    }
+}
+
+impl World {
+    /// Save the world to the file at `path`. The format is selected by the
+    /// extension of the path, `.json`, `.yaml` (or `.yml`) or `.toml`.
+    pub fn serialize_to_file(&self, path: &Path) -> Result<(), SerializeError> {
+        let format = Format::from_path(path)
+            .ok_or_else(|| SerializeError::UnsupportedFormat(path.to_path_buf()))?;
+
+        let contents = match format {
+            Format::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| SerializeError::Format(e.to_string()))?,
+            Format::Yaml => {
+                serde_yaml::to_string(self).map_err(|e| SerializeError::Format(e.to_string()))?
+            }
+            // TOML has no null value and requires plain values to come before
+            // tables. Missing fields are read back as `None` and going through
+            // a `toml::Value` puts the keys in an order it accepts.
+            Format::Toml => {
+                let mut value = serde_json::to_value(self)
+                    .map_err(|e| SerializeError::Format(e.to_string()))?;
+                remove_nulls(&mut value);
+
+                toml::Value::try_from(value)
+                    .and_then(|v| toml::to_string_pretty(&v))
+                    .map_err(|e| SerializeError::Format(e.to_string()))?
+            }
+        };
+
+        fs::write(path, contents).map_err(SerializeError::Io)
+    }
+
+    /// Load a world from the file at `path`. The format is selected by the
+    /// extension of the path, `.json`, `.yaml` (or `.yml`) or `.toml`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::{DeserializeError, World};
+    /// use std::path::Path;
+    ///
+    /// let result = World::deserialize_from_file(Path::new("missing_world.json"));
+    ///
+    /// assert!(matches!(result, Err(DeserializeError::FileNotFound(_))));
+    /// ```
+    pub fn deserialize_from_file(path: &Path) -> Result<World, DeserializeError> {
+        let format = Format::from_path(path)
+            .ok_or_else(|| DeserializeError::UnsupportedFormat(path.to_path_buf()))?;
+
+        let contents = fs::read_to_string(path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => DeserializeError::FileNotFound(path.to_path_buf()),
+            _ => DeserializeError::Io(e),
+        })?;
+
+        let result = match format {
+            Format::Json => {
+                serde_json::from_str(&contents).map_err(|e| DeserializeError::ParseError {
+                    line: Some(e.line()),
+                    message: e.to_string(),
+                })
+            }
+            Format::Yaml => {
+                serde_yaml::from_str(&contents).map_err(|e| DeserializeError::ParseError {
+                    line: e.location().map(|l| l.line()),
+                    message: e.to_string(),
+                })
+            }
+            Format::Toml => toml::from_str(&contents).map_err(|e| DeserializeError::ParseError {
+                line: e.line_col().map(|(line, _)| line + 1),
+                message: e.to_string(),
+            }),
+        };
+
+        // objects of a type only known to the `ShapeRegistry` make the first
+        // attempt fail, its error is kept as it tells where parsing stopped
+        result.or_else(|error| {
+            let value: Option<serde_json::Value> = match format {
+                Format::Json => serde_json::from_str(&contents).ok(),
+                Format::Yaml => serde_yaml::from_str(&contents).ok(),
+                Format::Toml => toml::from_str(&contents).ok(),
+            };
+            value
+                .and_then(|v| World::from_value_with_registry(v).ok())
+                .ok_or(error)
+        })
+    }
+
+    /// Load a world from `value`, reading each of its objects with
+    /// [`deserialize_shape`] so shapes of a type registered in the
+    /// [`crate::shapes::ShapeRegistry`] are read too.
+    pub(crate) fn from_value_with_registry(
+        mut value: serde_json::Value,
+    ) -> Result<World, DeserializeError> {
+        let objects = match value.get_mut("objects") {
+            Some(serde_json::Value::Array(objects)) => std::mem::take(objects),
+            _ => Vec::new(),
+        };
+
+        let mut world: World =
+            serde_json::from_value(value).map_err(|e| DeserializeError::ParseError {
+                line: None,
+                message: e.to_string(),
+            })?;
+        for object in objects {
+            world.add_object(deserialize_shape(object)?);
+        }
+        Ok(world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        patterns::{
+            Checkers, Gradient, ImageCubeTexture, ImageTexture, Marble, Pattern, Ring, SolidColor,
+            Stripe, Voronoi, VoronoiMode, Wood,
+        },
+        shapes::Sphere,
+        Canvas, Color, Colors, Point, Transformation,
+    };
+    use std::env;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("rustic_ray_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn round_trip_through_each_format() {
+        let w = World::default();
+
+        for name in ["world.json", "world.yaml", "world.toml"] {
+            let path = temp_path(name);
+            w.serialize_to_file(&path).unwrap();
+            let de = World::deserialize_from_file(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(de.light, w.light);
+            assert_eq!(de.background, w.background);
+            for i in 0..2 {
+                let a = de.get_object(i).unwrap();
+                let b = w.get_object(i).unwrap();
+                assert_eq!(a.id(), b.id());
+                assert_eq!(a.transform(), b.transform());
+                assert_eq!(*a.material(), *b.material());
+            }
+        }
+    }
+
+    #[test]
+    fn a_missing_file_is_not_found() {
+        let path = temp_path("missing.json");
+
+        assert!(matches!(
+            World::deserialize_from_file(&path),
+            Err(DeserializeError::FileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn a_corrupted_json_file_reports_the_line() {
+        let path = temp_path("corrupted.json");
+        fs::write(&path, "{\n  \"light\": null,\n  \"objects\": [,]\n}").unwrap();
+        let result = World::deserialize_from_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(DeserializeError::ParseError { line, .. }) => assert_eq!(line, Some(3)),
+            other => panic!("expected a parse error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn an_unknown_extension_is_unsupported() {
+        let path = temp_path("world.xml");
+
+        assert!(matches!(
+            World::default().serialize_to_file(&path),
+            Err(SerializeError::UnsupportedFormat(_))
+        ));
+    }
+
+    /// One of every built in pattern, with settings differing from the
+    /// defaults.
+    fn every_pattern() -> Vec<Box<dyn Pattern>> {
+        let (a, b) = (Color::new(1.0, 0.5, 0.0), Color::new(0.0, 0.2, 0.8));
+        let mut image = Canvas::new(2, 2);
+        image.write_pixel(0, 0, a);
+        image.write_pixel(1, 1, b);
+        let mut marble = Marble::new(a, b);
+        marble.octaves = 3;
+        let mut voronoi = Voronoi::new(a, b);
+        voronoi.mode = VoronoiMode::F2MinusF1;
+        voronoi.seed = 7;
+        let scaling = Transformation::new().scale(0.5, 2.0, 1.0).build();
+
+        vec![
+            Stripe::new(a, b).transformed_with(scaling),
+            Gradient::new(a, b).transformed_with(scaling),
+            Gradient::bilinear(a, b, Colors::WHITE, Colors::BLACK).transformed_with(scaling),
+            Ring::new(a, b).transformed_with(scaling),
+            Checkers::new(a, b).transformed_with(scaling),
+            SolidColor::new(a).transformed_with(scaling),
+            marble.transformed_with(scaling),
+            Wood::new(a, b).transformed_with(scaling),
+            voronoi.transformed_with(scaling),
+            ImageTexture::new(&image).transformed_with(scaling),
+            ImageCubeTexture::new(&image, &image, &image, &image, &image, &image)
+                .transformed_with(scaling),
+            Checkers::new(a, b).tile(0.5),
+        ]
+    }
+
+    #[test]
+    fn every_pattern_survives_a_round_trip_through_a_world() {
+        let patterns = every_pattern();
+        let mut w = World::new();
+        for pattern in &patterns {
+            let mut s = Sphere::new();
+            s.material.pattern = pattern.clone();
+            w.add_object(Box::new(s));
+        }
+
+        let json = serde_json::to_string(&w).unwrap();
+        let de: World = serde_json::from_str(&json).unwrap();
+
+        for (i, pattern) in patterns.iter().enumerate() {
+            let read = &de.get_object(i).unwrap().material().pattern;
+            let (expected, found) = (
+                serde_json::to_value(pattern).unwrap(),
+                serde_json::to_value(read).unwrap(),
+            );
+
+            assert_eq!(found["type"], expected["type"]);
+            assert_eq!(found, expected);
+            for p in [Point::new(0.3, 0.6, -0.2), Point::new(-1.7, 2.2, 0.9)] {
+                assert_eq!(read.pattern_at(p), pattern.pattern_at(p));
+            }
+        }
+    }
+
+    #[test]
+    fn an_unknown_pattern_type_is_a_parse_error() {
+        let mut value = serde_json::to_value(World::default()).unwrap();
+        value["objects"][0]["material"]["pattern"]["type"] = "Perlin".into();
+        let path = temp_path("unknown_pattern.json");
+        fs::write(&path, value.to_string()).unwrap();
+        let result = World::deserialize_from_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(DeserializeError::ParseError { message, .. }) => {
+                assert!(message.contains("unknown variant `Perlin`"), "{}", message)
+            }
+            other => panic!("expected a parse error, got {:?}", other.map(|_| ())),
+        }
+    }
+}
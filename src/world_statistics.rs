@@ -0,0 +1,193 @@
+use std::fmt;
+
+use crate::{
+    shapes::{Mesh, Shape, CSG},
+    World,
+};
+
+/// A summary of how complex the scene of a [`World`] is, found by
+/// [`World::statistics`], to help understand why a render is slow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldStatistics {
+    /// Number of objects added to the world, counting a group as one.
+    pub object_count: usize,
+    /// Number of primitive shapes, see [`World::total_primitive_count`].
+    pub primitive_count: usize,
+    /// Number of light sources of every kind, including the light probe.
+    pub light_count: usize,
+    /// Whether any shape reflects, so rays bounce off of it.
+    pub has_reflective: bool,
+    /// Whether any shape is transparent, so rays pass through it.
+    pub has_refractive: bool,
+    /// Whether any shape is a [`CSG`], testing every ray against both of its
+    /// sides.
+    pub has_csg: bool,
+    /// Number of levels of the deepest hierarchy of shapes, through groups,
+    /// CSG nodes and the bounding volume hierarchy of meshes.
+    pub bvh_depth: usize,
+    /// Rough cost of tracing a ray, `primitive_count * log2(bvh_depth + 1)`.
+    pub estimated_complexity: f64,
+}
+
+/// Levels of the hierarchy from `object` down to its deepest primitive.
+fn depth(object: &dyn Shape) -> usize {
+    if let Some(mesh) = object.as_any().and_then(|a| a.downcast_ref::<Mesh>()) {
+        return 1 + mesh.bvh_depth();
+    }
+    1 + object.children().into_iter().map(depth).max().unwrap_or(0)
+}
+
+/// Record the materials and kinds of `object` and every shape it contains
+/// in `stats`.
+fn visit(object: &dyn Shape, stats: &mut WorldStatistics) {
+    let material = object.material();
+    stats.has_reflective |= material.reflective > 0.0;
+    stats.has_refractive |= material.transparency > 0.0;
+    stats.has_csg |= object
+        .as_any()
+        .is_some_and(|a| a.downcast_ref::<CSG>().is_some());
+
+    for child in object.children() {
+        visit(child, stats);
+    }
+}
+
+impl World {
+    /// Count the objects, primitives and lights of the world and note the
+    /// features that make rendering it slower.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustic_ray::World;
+    ///
+    /// let stats = World::default().statistics();
+    ///
+    /// assert_eq!(stats.primitive_count, 2);
+    /// assert_eq!(stats.light_count, 1);
+    /// assert!(!stats.has_reflective);
+    /// ```
+    pub fn statistics(&self) -> WorldStatistics {
+        let mut stats = WorldStatistics {
+            object_count: self.count_objects(),
+            primitive_count: self.total_primitive_count(),
+            light_count: self.light.iter().count()
+                + self.area_lights.len()
+                + self.directional_lights.len()
+                + self.light_probe.iter().count(),
+            has_reflective: false,
+            has_refractive: false,
+            has_csg: false,
+            bvh_depth: self.objects().map(depth).max().unwrap_or(0),
+            estimated_complexity: 0.0,
+        };
+        for object in self.objects() {
+            visit(object, &mut stats);
+        }
+        stats.estimated_complexity =
+            stats.primitive_count as f64 * (stats.bvh_depth as f64 + 1.0).log2();
+
+        stats
+    }
+}
+
+impl fmt::Display for WorldStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let yes_no = |b: bool| if b { "yes" } else { "no" };
+
+        writeln!(f, "objects:               {}", self.object_count)?;
+        writeln!(f, "primitives:            {}", self.primitive_count)?;
+        writeln!(f, "lights:                {}", self.light_count)?;
+        writeln!(f, "reflective materials:  {}", yes_no(self.has_reflective))?;
+        writeln!(f, "refractive materials:  {}", yes_no(self.has_refractive))?;
+        writeln!(f, "CSG:                   {}", yes_no(self.has_csg))?;
+        writeln!(f, "hierarchy depth:       {}", self.bvh_depth)?;
+        write!(f, "estimated complexity:  {:.1}", self.estimated_complexity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shapes::{CsgOperation, Cube, Group, Sphere},
+        Point, Vector,
+    };
+
+    #[test]
+    fn an_empty_world_has_no_complexity() {
+        let stats = World::new().statistics();
+
+        assert_eq!(
+            stats,
+            WorldStatistics {
+                object_count: 0,
+                primitive_count: 0,
+                light_count: 0,
+                has_reflective: false,
+                has_refractive: false,
+                has_csg: false,
+                bvh_depth: 0,
+                estimated_complexity: 0.0,
+            }
+        );
+    }
+
+    #[test]
+    fn the_default_world_has_two_spheres_and_a_light() {
+        let stats = World::default().statistics();
+
+        assert_eq!(stats.object_count, 2);
+        assert_eq!(stats.primitive_count, 2);
+        assert_eq!(stats.light_count, 1);
+        assert_eq!(stats.bvh_depth, 1);
+        assert_eq!(stats.estimated_complexity, 2.0);
+    }
+
+    #[test]
+    fn a_reflective_sphere_is_noted() {
+        let mut w = World::new();
+        let mut s = Sphere::new();
+        s.material.reflective = 0.5;
+        w.add_object(Box::new(s));
+
+        let stats = w.statistics();
+
+        assert!(stats.has_reflective);
+        assert!(!stats.has_refractive);
+    }
+
+    #[test]
+    fn shapes_inside_containers_are_visited() {
+        let mut w = World::new();
+        let mut g = Group::new();
+        g.add_object(Box::new(CSG::new(
+            CsgOperation::Union,
+            Box::new(Sphere::glass_sphere()),
+            Box::new(Cube::new()),
+        )));
+        w.add_object(Box::new(g));
+
+        let stats = w.statistics();
+
+        assert_eq!(stats.object_count, 1);
+        assert_eq!(stats.primitive_count, 2);
+        assert!(stats.has_csg);
+        assert!(stats.has_refractive);
+        assert_eq!(stats.bvh_depth, 3);
+    }
+
+    #[test]
+    fn the_hierarchy_of_a_mesh_counts_toward_the_depth() {
+        let vertices: Vec<Point> = (0..30).map(|i| Point::new(i as f64, 0.0, 0.0)).collect();
+        let faces = (0..10).map(|i| (3 * i, 3 * i + 1, 3 * i + 2)).collect();
+        let mesh = Mesh::new(vertices, Vec::<Vector>::new(), faces);
+        let mut w = World::new();
+        w.add_object(Box::new(mesh));
+
+        let stats = w.statistics();
+
+        assert!(stats.bvh_depth > 2);
+        assert!(stats.to_string().contains("primitives:            1"));
+    }
+}